@@ -0,0 +1,372 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// The `CodecAsType` derive implements `DecodeAsType` and scale-encode's `EncodeAsType` (and
+// `EncodeAsFields` on structs) from a single set of attributes. Only attributes which both sides
+// can honour are accepted, so that a value always encodes to something that decodes back into it.
+//
+// The attributes are translated into `decode_as_type` ones on a copy of the input, which is handed
+// to the usual `DecodeAsType` code generation. The `EncodeAsType` impls are then generated from that
+// same copy, so both sides read each name and skipped field from the very same attributes.
+
+use super::{
+    derive_with_attrs, field_name, ignore_meta_value, variant_name, FieldAttrs, TopLevelAttrs,
+};
+use alloc::string::String;
+use darling::{FromAttributes, FromMeta};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::DeriveInput;
+
+const ATTR_NAME: &str = "codec_as_type";
+
+pub fn derive(input: DeriveInput) -> TokenStream2 {
+    let (attrs, input) = match parse(input) {
+        Ok(res) => res,
+        Err(e) => return e.write_errors(),
+    };
+
+    let encode_impls = match &input.data {
+        syn::Data::Enum(details) => generate_enum_impl(&attrs, &input, details),
+        syn::Data::Struct(details) => generate_struct_impl(&attrs, &input, details),
+        syn::Data::Union(_) => {
+            return syn::Error::new(
+                input.ident.span(),
+                "Unions are not supported by the CodecAsType macro",
+            )
+            .into_compile_error()
+        }
+    };
+
+    let mut decode_attrs = match TopLevelAttrs::parse(&[]) {
+        Ok(decode_attrs) => decode_attrs,
+        Err(e) => return e.write_errors(),
+    };
+    decode_attrs.crate_path = attrs.crate_path.clone();
+    let decode_impls = derive_with_attrs(decode_attrs, input);
+
+    quote!(
+        #decode_impls
+        #encode_impls
+    )
+}
+
+struct CodecAttrs {
+    // path to the scale_decode crate, in case it's not a top level dependency.
+    crate_path: syn::Path,
+    // path to the scale_encode crate; by default, the one re-exported from scale_decode.
+    encode_crate_path: syn::Path,
+}
+
+/// Parse the attributes given at the top level
+#[derive(Debug, FromMeta)]
+struct CodecAttrsInner {
+    #[darling(default)]
+    crate_path: Option<syn::Path>,
+    #[darling(default)]
+    encode_crate_path: Option<syn::Path>,
+}
+
+/// Parse the attributes attached to some enum variant
+#[derive(Debug, FromAttributes, Default)]
+#[darling(attributes(codec_as_type))]
+struct CodecVariantAttrs {
+    #[darling(default)]
+    rename: Option<String>,
+}
+
+/// Parse the attributes attached to some field
+#[derive(Debug, FromAttributes, Default)]
+#[darling(attributes(codec_as_type))]
+struct CodecFieldAttrs {
+    #[darling(default)]
+    skip: bool,
+    #[darling(default)]
+    rename: Option<String>,
+}
+
+// Parse and check our attributes, handing back the top level ones along with a copy of the input
+// whose attributes have been translated into the `decode_as_type` ones that they correspond to.
+// Every problem found is reported, rather than just the first.
+fn parse(mut input: DeriveInput) -> darling::Result<(CodecAttrs, DeriveInput)> {
+    let mut errors = darling::Error::accumulator();
+
+    let mut crate_path = None;
+    let mut encode_crate_path = None;
+    for attr in &input.attrs {
+        if !attr.path().is_ident(ATTR_NAME) {
+            continue;
+        }
+        if let Some(parsed_attrs) = errors.handle(CodecAttrsInner::from_meta(&attr.meta)) {
+            crate_path = parsed_attrs.crate_path.or(crate_path);
+            encode_crate_path = parsed_attrs.encode_crate_path.or(encode_crate_path);
+        }
+    }
+    let crate_path: syn::Path = crate_path.unwrap_or_else(|| syn::parse_quote!(::scale_decode));
+    let encode_crate_path =
+        encode_crate_path.unwrap_or_else(|| syn::parse_quote!(#crate_path::ext::scale_encode));
+    input.attrs.clear();
+
+    for param in input.generics.type_params_mut() {
+        if let Some(attr) = param.attrs.iter().find(|attr| attr.path().is_ident(ATTR_NAME)) {
+            errors.push(
+                darling::Error::custom(
+                    "The codec_as_type attribute isn't supported on generic parameters",
+                )
+                .with_span(attr),
+            );
+        }
+        param.attrs.clear();
+    }
+
+    match &mut input.data {
+        syn::Data::Struct(details) => {
+            for field in details.fields.iter_mut() {
+                translate_field_attrs(&mut errors, field);
+            }
+        }
+        syn::Data::Enum(details) => {
+            for variant in details.variants.iter_mut() {
+                // Skipped variants could never be encoded, and so aren't supported.
+                if errors.handle(codec_skip(&variant.attrs)).unwrap_or_default() {
+                    errors.push(
+                        darling::Error::custom(
+                            "Skipped variants can't be encoded, and so aren't supported by the CodecAsType macro",
+                        )
+                        .with_span(&variant.ident),
+                    );
+                }
+                let variant_attrs =
+                    errors.handle(CodecVariantAttrs::from_attributes(&variant.attrs));
+                variant.attrs.clear();
+                if let Some(name) = variant_attrs.and_then(|attrs| attrs.rename) {
+                    variant.attrs.push(syn::parse_quote!(#[decode_as_type(rename = #name)]));
+                }
+                for field in variant.fields.iter_mut() {
+                    translate_field_attrs(&mut errors, field);
+                }
+            }
+        }
+        // An error is emitted when generating the impls.
+        syn::Data::Union(_) => {}
+    }
+
+    errors.finish()?;
+    Ok((CodecAttrs { crate_path, encode_crate_path }, input))
+}
+
+// Replace the attributes on a field with the `decode_as_type` ones that they correspond to.
+fn translate_field_attrs(errors: &mut darling::error::Accumulator, field: &mut syn::Field) {
+    let codec_skip = errors.handle(codec_skip(&field.attrs)).unwrap_or_default();
+    let field_attrs =
+        errors.handle(CodecFieldAttrs::from_attributes(&field.attrs)).unwrap_or_default();
+    field.attrs.clear();
+
+    if field_attrs.skip || codec_skip {
+        field.attrs.push(syn::parse_quote!(#[decode_as_type(skip)]));
+    }
+    if let Some(name) = field_attrs.rename {
+        if field.ident.is_none() {
+            errors.push(
+                darling::Error::custom("The rename attribute is only supported on named fields")
+                    .with_span(&field.ty),
+            );
+        }
+        field.attrs.push(syn::parse_quote!(#[decode_as_type(rename = #name)]));
+    }
+}
+
+// Is `#[codec(skip)]` given? Other `codec` attributes are for `parity-scale-codec` and describe
+// things (like compact encoding) which are already reflected in the type information, so they're
+// ignored here, but a field that it skips won't be in the type information either.
+fn codec_skip(attrs: &[syn::Attribute]) -> darling::Result<bool> {
+    let mut skip = false;
+    for attr in attrs {
+        if !attr.path().is_ident("codec") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                skip = true;
+                Ok(())
+            } else {
+                ignore_meta_value(&meta)
+            }
+        })?;
+    }
+    Ok(skip)
+}
+
+fn generate_enum_impl(
+    attrs: &CodecAttrs,
+    input: &DeriveInput,
+    details: &syn::DataEnum,
+) -> TokenStream2 {
+    let path_to_scale_decode = &attrs.crate_path;
+    let path_to_scale_encode = &attrs.encode_crate_path;
+    let path_to_type = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = handle_generics(attrs, &input.generics);
+
+    // For each variant we want to spit out a match arm.
+    let match_arms = details.variants.iter().map(|variant| {
+        let variant_ident = &variant.ident;
+        let variant_name = variant_name(variant);
+        let (matcher, composite) = fields_to_matcher_and_composite(attrs, &variant.fields);
+        quote!(
+            Self::#variant_ident #matcher => {
+                #path_to_scale_encode::Variant { name: #variant_name, fields: #composite }
+                    .encode_variant_as_type_to(
+                        __encode_as_type_type_id,
+                        __encode_as_type_types,
+                        __encode_as_type_out
+                    )
+            }
+        )
+    });
+
+    quote!(
+        impl #impl_generics #path_to_scale_encode::EncodeAsType for #path_to_type #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn encode_as_type_to<ScaleEncodeResolver: #path_to_scale_encode::TypeResolver>(
+                &self,
+                // long variable names to prevent conflict with struct field names:
+                __encode_as_type_type_id: ScaleEncodeResolver::TypeId,
+                __encode_as_type_types: &ScaleEncodeResolver,
+                __encode_as_type_out: &mut #path_to_scale_encode::Vec<u8>
+            ) -> #path_to_scale_decode::Result<(), #path_to_scale_encode::Error> {
+                match *self {
+                    #( #match_arms, )*
+                }
+            }
+        }
+    )
+}
+
+fn generate_struct_impl(
+    attrs: &CodecAttrs,
+    input: &DeriveInput,
+    details: &syn::DataStruct,
+) -> TokenStream2 {
+    let path_to_scale_decode = &attrs.crate_path;
+    let path_to_scale_encode = &attrs.encode_crate_path;
+    let path_to_type = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = handle_generics(attrs, &input.generics);
+
+    let (matcher, composite) = fields_to_matcher_and_composite(attrs, &details.fields);
+
+    quote!(
+        impl #impl_generics #path_to_scale_encode::EncodeAsType for #path_to_type #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn encode_as_type_to<ScaleEncodeResolver: #path_to_scale_encode::TypeResolver>(
+                &self,
+                // long variable names to prevent conflict with struct field names:
+                __encode_as_type_type_id: ScaleEncodeResolver::TypeId,
+                __encode_as_type_types: &ScaleEncodeResolver,
+                __encode_as_type_out: &mut #path_to_scale_encode::Vec<u8>
+            ) -> #path_to_scale_decode::Result<(), #path_to_scale_encode::Error> {
+                let #path_to_type #matcher = *self;
+                #composite.encode_composite_as_type_to(
+                    __encode_as_type_type_id,
+                    __encode_as_type_types,
+                    __encode_as_type_out
+                )
+            }
+        }
+        impl #impl_generics #path_to_scale_encode::EncodeAsFields for #path_to_type #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn encode_as_fields_to<ScaleEncodeResolver: #path_to_scale_encode::TypeResolver>(
+                &self,
+                // long variable names to prevent conflict with struct field names:
+                __encode_as_type_fields: &mut dyn #path_to_scale_encode::FieldIter<'_, ScaleEncodeResolver::TypeId>,
+                __encode_as_type_types: &ScaleEncodeResolver,
+                __encode_as_type_out: &mut #path_to_scale_encode::Vec<u8>
+            ) -> #path_to_scale_decode::Result<(), #path_to_scale_encode::Error> {
+                let #path_to_type #matcher = *self;
+                #composite.encode_composite_fields_to(
+                    __encode_as_type_fields,
+                    __encode_as_type_types,
+                    __encode_as_type_out
+                )
+            }
+        }
+    )
+}
+
+// Each type param must implement `EncodeAsType` for the encode impls, just as each must implement
+// `IntoVisitor` for the decode ones.
+fn handle_generics<'a>(
+    attrs: &CodecAttrs,
+    generics: &'a syn::Generics,
+) -> (syn::ImplGenerics<'a>, syn::TypeGenerics<'a>, syn::WhereClause) {
+    let path_to_scale_encode = &attrs.encode_crate_path;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let mut where_clause = where_clause.cloned().unwrap_or(syn::parse_quote!(where));
+    for param in generics.type_params() {
+        let ty = &param.ident;
+        where_clause.predicates.push(syn::parse_quote!(#ty: #path_to_scale_encode::EncodeAsType));
+    }
+
+    (impl_generics, ty_generics, where_clause)
+}
+
+// Generate a pattern which binds a reference to each field, and a `Composite` of the (non-skipped)
+// fields to encode, named as they would be decoded.
+fn fields_to_matcher_and_composite(
+    attrs: &CodecAttrs,
+    fields: &syn::Fields,
+) -> (TokenStream2, TokenStream2) {
+    let path_to_scale_decode = &attrs.crate_path;
+    let path_to_scale_encode = &attrs.encode_crate_path;
+
+    let bindings: alloc::vec::Vec<_> = fields
+        .iter()
+        .enumerate()
+        .map(|(idx, f)| match &f.ident {
+            Some(ident) => ident.clone(),
+            None => syn::Ident::new(&alloc::format!("_{idx}"), Span::call_site()),
+        })
+        .collect();
+    let composite_fields: alloc::vec::Vec<_> = fields
+        .iter()
+        .zip(&bindings)
+        .filter(|(f, _)| !FieldAttrs::from_attributes(&f.attrs).unwrap_or_default().skip)
+        .map(|(f, binding)| {
+            let name = match &f.ident {
+                Some(_) => {
+                    let name = field_name(f);
+                    quote!(#path_to_scale_decode::Some(#name))
+                }
+                None => quote!(#path_to_scale_decode::None),
+            };
+            quote!((#name, #path_to_scale_encode::CompositeField::new(#binding)))
+        })
+        .collect();
+
+    let matcher = match fields {
+        syn::Fields::Named(_) => quote!({ #(ref #bindings),* }),
+        syn::Fields::Unnamed(_) => quote!(( #(ref #bindings),* )),
+        syn::Fields::Unit => quote!(),
+    };
+    // The type of the fields can't be inferred if there aren't any, so we give it.
+    let composite_fields = if composite_fields.is_empty() {
+        quote!(([] as [(#path_to_scale_decode::Option<&'static str>, #path_to_scale_encode::CompositeField<_>); 0]))
+    } else {
+        quote!([#(#composite_fields),*])
+    };
+    let composite = quote!(#path_to_scale_encode::Composite::new(#composite_fields.into_iter()));
+
+    (matcher, composite)
+}
@@ -19,6 +19,8 @@
 
 extern crate alloc;
 
+mod codec_as_type;
+
 use alloc::string::ToString;
 use darling::FromAttributes;
 use proc_macro2::{Span, TokenStream as TokenStream2};
@@ -41,10 +43,24 @@ pub fn derive_macro(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     derive_with_attrs(attrs, input).into()
 }
 
+// Macro docs in main crate; don't add any docs here.
+#[proc_macro_derive(CodecAsType, attributes(codec_as_type, codec))]
+pub fn derive_codec_macro(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    codec_as_type::derive(input).into()
+}
+
 fn derive_with_attrs(attrs: TopLevelAttrs, input: DeriveInput) -> TokenStream2 {
+    if let Err(e) = check_field_attrs(&input) {
+        return e.write_errors();
+    }
     let visibility = &input.vis;
+    let constructor_impl = attrs.constructor.then(|| generate_constructor_impl(&attrs, &input));
+    let shape_impl = attrs.shape.then(|| generate_shape_impl(&attrs, &input));
+    let remote_impl =
+        attrs.remote.as_ref().map(|remote| generate_remote_impl(&attrs, remote, &input));
     // what type is the derive macro declared on?
-    match &input.data {
+    let trait_impls = match &input.data {
         syn::Data::Enum(details) => generate_enum_impl(attrs, visibility, &input, details),
         syn::Data::Struct(details) => generate_struct_impl(attrs, visibility, &input, details),
         syn::Data::Union(_) => syn::Error::new(
@@ -52,7 +68,215 @@ fn derive_with_attrs(attrs: TopLevelAttrs, input: DeriveInput) -> TokenStream2 {
             "Unions are not supported by the DecodeAsType macro",
         )
         .into_compile_error(),
+    };
+
+    quote!(
+        #trait_impls
+        #constructor_impl
+        #shape_impl
+        #remote_impl
+    )
+}
+
+// Report any problems with the attributes given on fields, which are otherwise parsed wherever
+// they're needed. Only named fields are looked up by name, so renaming an unnamed field would
+// have no effect, and is an error too.
+fn check_field_attrs(input: &DeriveInput) -> darling::Result<()> {
+    let fields: alloc::vec::Vec<&syn::Field> = match &input.data {
+        syn::Data::Struct(details) => details.fields.iter().collect(),
+        syn::Data::Enum(details) => details.variants.iter().flat_map(|v| &v.fields).collect(),
+        syn::Data::Union(_) => return Ok(()),
+    };
+    let mut errors = darling::Error::accumulator();
+    for field in fields {
+        let Some(field_attrs) = errors.handle(FieldAttrs::from_attributes(&field.attrs)) else {
+            continue;
+        };
+        if field.ident.is_none() && field_attrs.rename.is_some() {
+            errors.push(
+                darling::Error::custom("The rename attribute is only supported on named fields")
+                    .with_span(&field.ty),
+            );
+        }
     }
+    errors.finish()
+}
+
+// Generate an inherent `decode_from` method which calls `DecodeAsType::decode_as_type`.
+fn generate_constructor_impl(attrs: &TopLevelAttrs, input: &DeriveInput) -> TokenStream2 {
+    let path_to_scale_decode = &attrs.crate_path;
+    let path_to_type = &input.ident;
+    let generic_types = handle_generics(attrs, input.generics.clone());
+    let ty_generics = generic_types.ty_generics();
+    let impl_generics = generic_types.impl_generics();
+    let visitor_where_clause = generic_types.visitor_where_clause();
+
+    quote!(
+        impl #impl_generics #path_to_type #ty_generics #visitor_where_clause {
+            /// Decode SCALE encoded bytes into this type, given the type ID and type resolver
+            /// that describe the shape of the bytes. This is the same as calling
+            /// `DecodeAsType::decode_as_type`.
+            pub fn decode_from<R: #path_to_scale_decode::TypeResolver>(
+                input: &mut &[u8],
+                type_id: R::TypeId,
+                types: &R,
+            ) -> #path_to_scale_decode::Result<Self, #path_to_scale_decode::Error> {
+                <Self as #path_to_scale_decode::DecodeAsType>::decode_as_type(input, type_id, types)
+            }
+        }
+    )
+}
+
+// Generate a `From` impl which moves each field of the shadow type that we're deriving on into
+// the remote type, so that the remote type can be decoded via the shadow type.
+fn generate_remote_impl(
+    attrs: &TopLevelAttrs,
+    remote: &syn::Type,
+    input: &DeriveInput,
+) -> TokenStream2 {
+    let path_to_scale_decode = &attrs.crate_path;
+    let path_to_type = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    // Construct `Self` (or a variant of it) from the field bindings.
+    let construct = |target: TokenStream2, fields: &syn::Fields| {
+        let bindings: alloc::vec::Vec<_> = fields
+            .iter()
+            .enumerate()
+            .map(|(idx, f)| match &f.ident {
+                Some(ident) => ident.clone(),
+                None => syn::Ident::new(&alloc::format!("_{idx}"), Span::call_site()),
+            })
+            .collect();
+        match fields {
+            syn::Fields::Named(_) => {
+                (quote!({ #(#bindings),* }), quote!(#target { #(#bindings),* }))
+            }
+            syn::Fields::Unnamed(_) => {
+                (quote!(( #(#bindings),* )), quote!(#target ( #(#bindings),* )))
+            }
+            syn::Fields::Unit => (quote!(), target),
+        }
+    };
+
+    let body = match &input.data {
+        syn::Data::Struct(details) => {
+            let (pattern, value) = construct(quote!(Self), &details.fields);
+            quote!(
+                let #path_to_type #pattern = value;
+                #value
+            )
+        }
+        syn::Data::Enum(details) => {
+            let arms = details.variants.iter().map(|v| {
+                let variant_ident = &v.ident;
+                let (pattern, value) = construct(quote!(Self::#variant_ident), &v.fields);
+                quote!(#path_to_type::#variant_ident #pattern => #value)
+            });
+            quote!(
+                match value {
+                    #(#arms,)*
+                }
+            )
+        }
+        // An error is emitted when generating the visitor.
+        syn::Data::Union(_) => return TokenStream2::new(),
+    };
+
+    quote!(
+        impl #impl_generics #path_to_scale_decode::From<#path_to_type #ty_generics> for #remote #where_clause {
+            fn from(value: #path_to_type #ty_generics) -> Self {
+                #body
+            }
+        }
+    )
+}
+
+// Generate a `ShapeOf` impl describing the shape of the values that our visitor accepts, named
+// after the type so that tools displaying the shape can show where it came from.
+fn generate_shape_impl(attrs: &TopLevelAttrs, input: &DeriveInput) -> TokenStream2 {
+    let path_to_scale_decode = &attrs.crate_path;
+    let path_to_type = &input.ident;
+    let type_name = path_to_type.to_string();
+    let generic_types = handle_generics(attrs, input.generics.clone());
+    let ty_generics = generic_types.ty_generics();
+    let impl_generics = generic_types.impl_generics();
+    let shape_where_clause = generic_types.shape_where_clause();
+
+    let shape = match &input.data {
+        syn::Data::Struct(details) if attrs.transparent => {
+            match transparent_field(path_to_scale_decode, &details.fields) {
+                Ok((field_ty, _)) => quote!(#path_to_scale_decode::shape::Shape::named(
+                    #type_name,
+                    <#field_ty as #path_to_scale_decode::shape::ShapeOf>::shape()
+                )),
+                // An error is emitted when generating the visitor.
+                Err(()) => return TokenStream2::new(),
+            }
+        }
+        syn::Data::Struct(details) => {
+            let composite = composite_shape(path_to_scale_decode, attrs, &details.fields);
+            quote!(#path_to_scale_decode::shape::Shape::named(
+                #type_name,
+                #path_to_scale_decode::shape::Shape::Composite(#composite)
+            ))
+        }
+        syn::Data::Enum(details) => {
+            let variants = details
+                .variants
+                .iter()
+                .filter(|v| !VariantAttrs::from_attributes(&v.attrs).unwrap_or_default().skip)
+                .map(|v| {
+                    let variant_name = variant_name(v);
+                    let composite = composite_shape(path_to_scale_decode, attrs, &v.fields);
+                    quote!(#path_to_scale_decode::shape::VariantShape::new(#variant_name, #composite))
+                });
+            quote!(#path_to_scale_decode::shape::Shape::named(
+                #type_name,
+                #path_to_scale_decode::shape::Shape::Variant(#path_to_scale_decode::vec![#(#variants),*])
+            ))
+        }
+        // An error is emitted when generating the visitor.
+        syn::Data::Union(_) => return TokenStream2::new(),
+    };
+
+    quote!(
+        impl #impl_generics #path_to_scale_decode::shape::ShapeOf for #path_to_type #ty_generics #shape_where_clause {
+            fn shape() -> #path_to_scale_decode::shape::Shape {
+                #shape
+            }
+        }
+    )
+}
+
+// Generate a `CompositeShape` describing the (non-skipped) fields given. Unit structs and variants
+// are decoded regardless of any fields given, so any fields are allowed for them.
+fn composite_shape(
+    path_to_scale_decode: &syn::Path,
+    attrs: &TopLevelAttrs,
+    fields: &syn::Fields,
+) -> TokenStream2 {
+    let field_shapes = fields.iter().filter_map(|f| {
+        let field_attrs = FieldAttrs::from_attributes(&f.attrs).unwrap_or_default();
+        if field_attrs.skip {
+            return None;
+        }
+        let field_ty = field_attrs.as_type.as_ref().unwrap_or(&f.ty);
+        let shape_fn = quote!(<#field_ty as #path_to_scale_decode::shape::ShapeOf>::shape);
+        Some(match &f.ident {
+            Some(_) => {
+                let field_name = field_name(f);
+                quote!(#path_to_scale_decode::shape::FieldShape::named(#field_name, #shape_fn))
+            }
+            None => quote!(#path_to_scale_decode::shape::FieldShape::unnamed(#shape_fn)),
+        })
+    });
+    let allow_extra_fields = attrs.allow_extra_fields || matches!(fields, syn::Fields::Unit);
+
+    quote!(#path_to_scale_decode::shape::CompositeShape {
+        fields: #path_to_scale_decode::vec![#(#field_shapes),*],
+        allow_extra_fields: #allow_extra_fields,
+    })
 }
 
 fn generate_enum_impl(
@@ -61,9 +285,22 @@ fn generate_enum_impl(
     input: &DeriveInput,
     details: &syn::DataEnum,
 ) -> TokenStream2 {
+    if attrs.transparent {
+        return syn::Error::new(
+            input.ident.span(),
+            "The transparent attribute is only supported on structs with a single field",
+        )
+        .into_compile_error();
+    }
     let path_to_scale_decode = &attrs.crate_path;
     let path_to_type: syn::Path = input.ident.clone().into();
-    let variant_names = details.variants.iter().map(|v| v.ident.to_string());
+    let expecting = attrs.expecting_call();
+    // Variants marked as skipped are never decoded into, so we ignore them entirely.
+    let variants = details.variants.iter().filter(|v| {
+        let variant_attrs = VariantAttrs::from_attributes(&v.attrs).unwrap_or_default();
+        !variant_attrs.skip
+    });
+    let variant_names = variants.clone().map(variant_name);
 
     let generic_types = handle_generics(&attrs, input.generics.clone());
     let ty_generics = generic_types.ty_generics();
@@ -75,83 +312,254 @@ fn generate_enum_impl(
     let type_resolver_ident = generic_types.type_resolver_ident();
 
     // determine what the body of our visitor functions will be based on the type of enum fields
-    // that we're trying to generate output for.
-    let variant_ifs = details.variants.iter().map(|variant| {
-        let variant_ident = &variant.ident;
-        let variant_name = variant_ident.to_string();
-
-        let visit_one_variant_body = match &variant.fields {
-            syn::Fields::Named(fields) => {
-                let (
-                    field_count,
-                    field_composite_keyvals,
-                    field_tuple_keyvals
-                ) = named_field_keyvals(path_to_scale_decode, fields);
-
-                quote!{
-                    let fields = value.fields();
-                    return if fields.has_unnamed_fields() {
-                        if fields.remaining() != #field_count {
-                            return Err(#path_to_scale_decode::Error::new(#path_to_scale_decode::error::ErrorKind::WrongLength {
-                                actual_len: fields.remaining(),
-                                expected_len: #field_count
-                            }));
+    // that we're trying to generate output for. Variants are found by matching on their name,
+    // which is much quicker than comparing against each name in turn for enums with many variants.
+    let variant_arms = variants.clone().map(|variant| {
+        let variant_name = variant_name(variant);
+        let visit_one_variant_body = variant_fields_body(
+            path_to_scale_decode,
+            &path_to_type,
+            &expecting,
+            attrs.allow_extra_fields,
+            variant,
+        );
+
+        quote! {
+            #variant_name => {
+                let fields = value.fields();
+                #visit_one_variant_body
+            }
+        }
+    });
+
+    let expected_variant_names = variant_names.clone();
+    let visit_variant_body = validate_container_body(
+        &attrs,
+        quote! {
+            match value.name() {
+                #( #variant_arms )*
+                _ => {}
+            }
+            #path_to_scale_decode::Err(#path_to_scale_decode::Error::cannot_find_variant(
+                value.name(),
+                value.index(),
+                vec![#(#expected_variant_names),*]
+            )#expecting)
+        },
+    );
+    let visit_variant_body = limit_depth_body(&attrs, visit_variant_body);
+
+    // If asked to, enums whose variants have no fields can also be decoded from a bare index.
+    // The indexes are only worked out (and checked) if they're needed, since other enums may
+    // have discriminants that don't fit in a u8.
+    let indexes = if attrs.from_index || attrs.try_from_u8 {
+        match fieldless_enum_indexes(details) {
+            Ok(indexes) => indexes,
+            Err(e) => return e.into_compile_error(),
+        }
+    } else {
+        None
+    };
+    let index_impls = match (attrs.from_index, &indexes) {
+        (false, _) => None,
+        (true, Some(indexes)) => Some(fieldless_enum_index_impls(&attrs, &path_to_type, indexes)),
+        (true, None) => {
+            return syn::Error::new(
+                input.ident.span(),
+                "The from_index attribute is only supported on enums whose variants have no fields",
+            )
+            .into_compile_error()
+        }
+    };
+    let try_from_u8_impl = match (attrs.try_from_u8, &indexes) {
+        (false, _) => None,
+        (true, Some(indexes)) => Some(try_from_u8_impl(&attrs, input, indexes)),
+        (true, None) => return syn::Error::new(
+            input.ident.span(),
+            "The try_from_u8 attribute is only supported on enums whose variants have no fields",
+        )
+        .into_compile_error(),
+    };
+
+    // If asked to, we also decode composites which name the variant in one field and hold its
+    // fields in another. The fields are handed to a separate visitor which knows the variant name.
+    let (tagged_composite, tagged_content_visitor) = match &attrs.tag_and_content {
+        None => (None, None),
+        Some((tag, content)) => {
+            let tagged_variant_arms = variants.clone().map(|variant| {
+                let variant_name = variant_name(variant);
+                let visit_one_variant_body = variant_fields_body(
+                    path_to_scale_decode,
+                    &path_to_type,
+                    &expecting,
+                    attrs.allow_extra_fields,
+                    variant,
+                );
+                quote! {
+                    #variant_name => {
+                        #visit_one_variant_body
+                    }
+                }
+            });
+            let unit_variant_arms =
+                variants.clone().filter(|v| matches!(v.fields, syn::Fields::Unit)).map(|variant| {
+                    let variant_ident = &variant.ident;
+                    let variant_name = variant_name(variant);
+                    quote! {
+                        #variant_name => return #path_to_scale_decode::Ok(#path_to_type::#variant_ident),
+                    }
+                });
+            let variant_names = variant_names.clone();
+
+            let tagged_composite = quote! {
+                if value.fields().iter().any(|f| f.name == #path_to_scale_decode::Some(#tag)) {
+                    let mut variant_name = #path_to_scale_decode::None;
+                    let mut content = #path_to_scale_decode::None;
+                    for field in &mut *value {
+                        let field = field?;
+                        if field.name() == #path_to_scale_decode::Some(#tag) {
+                            let name: #path_to_scale_decode::String =
+                                field.decode_as_type().map_err(|e| e.at_field(#tag))?;
+                            variant_name = #path_to_scale_decode::Some(name);
+                        } else if field.name() == #path_to_scale_decode::Some(#content) {
+                            content = #path_to_scale_decode::Some(field);
                         }
-                        let vals = fields;
-                        Ok(#path_to_type::#variant_ident { #(#field_tuple_keyvals),* })
-                    } else {
-                        let vals: #path_to_scale_decode::BTreeMap<Option<&str>, _> = fields
-                            .map(|res| res.map(|item| (item.name(), item)))
-                            .collect::<Result<_, _>>()?;
-                        Ok(#path_to_type::#variant_ident { #(#field_composite_keyvals),* })
                     }
+                    let variant_name = variant_name.expect("tag field was found above; please file a bug report");
+
+                    let #path_to_scale_decode::Some(content) = content else {
+                        // Unit variants don't need any content:
+                        match variant_name.as_str() {
+                            #(#unit_variant_arms)*
+                            _ => {}
+                        }
+                        return #path_to_scale_decode::Err(#path_to_scale_decode::Error::new(
+                            #path_to_scale_decode::error::ErrorKind::CannotFindField { name: #content.to_string() }
+                        )#expecting);
+                    };
+                    let visitor: TaggedContentVisitor #visitor_ty_generics =
+                        TaggedContentVisitor(variant_name, #path_to_scale_decode::PhantomData);
+                    return content.decode_with_visitor(visitor).map_err(|e| e.at_field(#content));
                 }
-            },
-            syn::Fields::Unnamed(fields) => {
-                let (
-                    field_count,
-                    field_vals
-                ) = unnamed_field_vals(path_to_scale_decode, fields);
-
-                quote!{
-                    let fields = value.fields();
-                    if fields.remaining() != #field_count {
-                        return Err(#path_to_scale_decode::Error::new(#path_to_scale_decode::error::ErrorKind::WrongLength {
-                            actual_len: fields.remaining(),
-                            expected_len: #field_count
-                        }));
+            };
+            let visit_tagged_content_body = validate_container_body(
+                &attrs,
+                quote! {
+                    let fields = value;
+                    match self.0.as_str() {
+                        #( #tagged_variant_arms )*
+                        _ => {}
+                    }
+                    #path_to_scale_decode::Err(#path_to_scale_decode::Error::cannot_find_variant_name(
+                        self.0,
+                        vec![#(#variant_names),*]
+                    )#expecting)
+                },
+            );
+            let visit_tagged_content_body = limit_depth_body(&attrs, visit_tagged_content_body);
+            let tagged_content_visitor = quote! {
+                #visibility struct TaggedContentVisitor #visitor_impl_generics (
+                    #path_to_scale_decode::String,
+                    #path_to_scale_decode::PhantomData<#visitor_phantomdata_type>
+                );
+
+                impl #visitor_impl_generics #path_to_scale_decode::Visitor for TaggedContentVisitor #visitor_ty_generics #visitor_where_clause {
+                    type Error = #path_to_scale_decode::Error;
+                    type Value<'scale, 'info> = #path_to_type #ty_generics;
+                    type TypeResolver = #type_resolver_ident;
+
+                    fn visit_composite<'scale, 'info>(
+                        self,
+                        value: &mut #path_to_scale_decode::visitor::types::Composite<'scale, 'info, Self::TypeResolver>,
+                        _type_id: <Self::TypeResolver as #path_to_scale_decode::TypeResolver>::TypeId,
+                    ) -> #path_to_scale_decode::Result<Self::Value<'scale, 'info>, Self::Error> {
+                        #visit_tagged_content_body
                     }
-                    let vals = fields;
-                    return Ok(#path_to_type::#variant_ident ( #(#field_vals),* ))
                 }
-            },
-            syn::Fields::Unit => {
-                quote!{
-                    return Ok(#path_to_type::#variant_ident)
+            };
+            (Some(tagged_composite), Some(tagged_content_visitor))
+        }
+    };
+
+    let depth_counter = depth_counter(&attrs);
+
+    // If asked to, we also decode a set of fields into the enum by treating them as the fields
+    // of the given variant.
+    let decode_as_fields_impl = match &attrs.fields_from_variant {
+        None => None,
+        Some(name) => {
+            let Some(variant) = variants.clone().find(|v| v.ident == name.as_str()) else {
+                return syn::Error::new(
+                    input.ident.span(),
+                    format!("The fields_from_variant attribute must name a variant which isn't skipped, but '{name}' was given"),
+                )
+                .into_compile_error();
+            };
+            let visit_fields_body = variant_fields_body(
+                path_to_scale_decode,
+                &path_to_type,
+                &expecting,
+                attrs.allow_extra_fields,
+                variant,
+            );
+            let visit_fields_body = validate_container_body(
+                &attrs,
+                quote! {
+                    let fields = value;
+                    #visit_fields_body
+                },
+            );
+            let visit_fields_body = limit_depth_body(&attrs, visit_fields_body);
+
+            Some(quote! {
+                #visibility struct FieldsVisitor #visitor_impl_generics (
+                    #path_to_scale_decode::PhantomData<#visitor_phantomdata_type>
+                );
+
+                impl #visitor_impl_generics #path_to_scale_decode::Visitor for FieldsVisitor #visitor_ty_generics #visitor_where_clause {
+                    type Error = #path_to_scale_decode::Error;
+                    type Value<'scale, 'info> = #path_to_type #ty_generics;
+                    type TypeResolver = #type_resolver_ident;
+
+                    fn visit_composite<'scale, 'info>(
+                        self,
+                        value: &mut #path_to_scale_decode::visitor::types::Composite<'scale, 'info, Self::TypeResolver>,
+                        _type_id: <Self::TypeResolver as #path_to_scale_decode::TypeResolver>::TypeId,
+                    ) -> #path_to_scale_decode::Result<Self::Value<'scale, 'info>, Self::Error> {
+                        #visit_fields_body
+                    }
                 }
-            },
-        };
 
-        quote!{
-            if value.name() == #variant_name {
-                #visit_one_variant_body
-            }
+                impl #impl_generics #path_to_scale_decode::DecodeAsFields for #path_to_type #ty_generics #visitor_where_clause  {
+                    fn decode_as_fields<'info, #type_resolver_ident: #path_to_scale_decode::TypeResolver>(
+                        input: &mut &[u8],
+                        fields: &mut dyn #path_to_scale_decode::FieldIter<'info, #type_resolver_ident::TypeId>,
+                        types: &'info #type_resolver_ident
+                    ) -> #path_to_scale_decode::Result<Self, #path_to_scale_decode::Error>
+                    {
+                        let visitor: FieldsVisitor #visitor_ty_generics = FieldsVisitor(#path_to_scale_decode::PhantomData);
+                        #path_to_scale_decode::visitor::decode_fields_with_visitor(input, fields, types, visitor)
+                    }
+                }
+            })
         }
-    });
+    };
 
     quote!(
         const _: () = {
             #visibility struct Visitor #visitor_impl_generics (
-                ::core::marker::PhantomData<#visitor_phantomdata_type>
+                #path_to_scale_decode::PhantomData<#visitor_phantomdata_type>
             );
 
             use #path_to_scale_decode::vec;
             use #path_to_scale_decode::ToString;
 
+            #depth_counter
+
             impl #impl_generics #path_to_scale_decode::IntoVisitor for #path_to_type #ty_generics #visitor_where_clause {
                 type AnyVisitor<#type_resolver_ident: #path_to_scale_decode::TypeResolver> = Visitor #visitor_ty_generics;
                 fn into_visitor<#type_resolver_ident: #path_to_scale_decode::TypeResolver>() -> Self::AnyVisitor<#type_resolver_ident> {
-                    Visitor(::core::marker::PhantomData)
+                    Visitor(#path_to_scale_decode::PhantomData)
                 }
             }
 
@@ -164,21 +572,16 @@ fn generate_enum_impl(
                     self,
                     value: &mut #path_to_scale_decode::visitor::types::Variant<'scale, 'info, Self::TypeResolver>,
                     type_id: <Self::TypeResolver as #path_to_scale_decode::TypeResolver>::TypeId,
-                ) -> Result<Self::Value<'scale, 'info>, Self::Error> {
-                    #(
-                        #variant_ifs
-                    )*
-                    Err(#path_to_scale_decode::Error::new(#path_to_scale_decode::error::ErrorKind::CannotFindVariant {
-                        got: value.name().to_string(),
-                        expected: vec![#(#variant_names),*]
-                    }))
+                ) -> #path_to_scale_decode::Result<Self::Value<'scale, 'info>, Self::Error> {
+                    #visit_variant_body
                 }
                 // Allow an enum to be decoded through nested 1-field composites and tuples:
                 fn visit_composite<'scale, 'info>(
                     self,
                     value: &mut #path_to_scale_decode::visitor::types::Composite<'scale, 'info, Self::TypeResolver>,
                     _type_id: <Self::TypeResolver as #path_to_scale_decode::TypeResolver>::TypeId,
-                ) -> Result<Self::Value<'scale, 'info>, Self::Error> {
+                ) -> #path_to_scale_decode::Result<Self::Value<'scale, 'info>, Self::Error> {
+                    #tagged_composite
                     if value.remaining() != 1 {
                         return self.visit_unexpected(#path_to_scale_decode::visitor::Unexpected::Composite);
                     }
@@ -188,25 +591,268 @@ fn generate_enum_impl(
                     self,
                     value: &mut #path_to_scale_decode::visitor::types::Tuple<'scale, 'info, Self::TypeResolver>,
                     _type_id: <Self::TypeResolver as #path_to_scale_decode::TypeResolver>::TypeId,
-                ) -> Result<Self::Value<'scale, 'info>, Self::Error> {
+                ) -> #path_to_scale_decode::Result<Self::Value<'scale, 'info>, Self::Error> {
                     if value.remaining() != 1 {
                         return self.visit_unexpected(#path_to_scale_decode::visitor::Unexpected::Tuple);
                     }
                     value.decode_item(self).unwrap()
                 }
+                #index_impls
             }
+
+            #tagged_content_visitor
+            #decode_as_fields_impl
+            #try_from_u8_impl
         };
     )
 }
 
+// If none of the (non-skipped) variants of the enum have fields, return the index of each of
+// them. The index of each variant is given by an `index` attribute, or else an explicit
+// discriminant, or else its position, just as the index of each variant is given when it's SCALE
+// encoded.
+fn fieldless_enum_indexes(
+    details: &syn::DataEnum,
+) -> syn::Result<Option<alloc::vec::Vec<(u8, &syn::Variant)>>> {
+    let mut indexes = alloc::vec::Vec::new();
+    for (pos, variant) in details.variants.iter().enumerate() {
+        let variant_attrs = VariantAttrs::from_attributes(&variant.attrs).unwrap_or_default();
+        if variant_attrs.skip {
+            continue;
+        }
+        if !matches!(variant.fields, syn::Fields::Unit) {
+            return Ok(None);
+        }
+        let discriminant = match &variant.discriminant {
+            Some((_, syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(n), .. }))) => {
+                Some(n.base10_parse::<u8>()?)
+            }
+            _ => None,
+        };
+        let Some(index) = variant_attrs.index.or(discriminant).or(u8::try_from(pos).ok()) else {
+            return Err(syn::Error::new(
+                variant.ident.span(),
+                "Variants beyond the 256th must be given an index that fits in a u8",
+            ));
+        };
+        if indexes.iter().any(|(i, _)| *i == index) {
+            return Err(syn::Error::new(
+                variant.ident.span(),
+                format!("More than one variant has the index {index}"),
+            ));
+        }
+        indexes.push((index, variant));
+    }
+    if indexes.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(indexes))
+}
+
+// Generate visitor methods which decode a fieldless enum from the index of one of its variants.
+fn fieldless_enum_index_impls(
+    attrs: &TopLevelAttrs,
+    path_to_type: &syn::Path,
+    indexes: &[(u8, &syn::Variant)],
+) -> TokenStream2 {
+    let path_to_scale_decode = &attrs.crate_path;
+    let expecting = attrs.expecting_call();
+
+    let variant_indexes = indexes.iter().map(|(i, _)| i);
+    let variant_idents = indexes.iter().map(|(_, variant)| &variant.ident);
+    let variant_names = indexes.iter().map(|(_, variant)| variant_name(variant));
+    let visit_u8_body = validate_container_body(
+        attrs,
+        quote! {
+            match value {
+                #( #variant_indexes => #path_to_scale_decode::Ok(#path_to_type::#variant_idents), )*
+                _ => #path_to_scale_decode::Err(#path_to_scale_decode::Error::cannot_find_variant(
+                    value.to_string(),
+                    value,
+                    vec![#(#variant_names),*]
+                )#expecting),
+            }
+        },
+    );
+    let visit_larger_int = |name: syn::Ident, ty: syn::Ident| {
+        quote! {
+            fn #name<'scale, 'info>(
+                self,
+                value: #ty,
+                type_id: <Self::TypeResolver as #path_to_scale_decode::TypeResolver>::TypeId,
+            ) -> #path_to_scale_decode::Result<Self::Value<'scale, 'info>, Self::Error> {
+                let value: u8 = #path_to_scale_decode::TryFrom::try_from(value).map_err(|_| {
+                    #path_to_scale_decode::Error::new(#path_to_scale_decode::error::ErrorKind::NumberOutOfRange {
+                        value: value.to_string()
+                    })#expecting
+                })?;
+                self.visit_u8(value, type_id)
+            }
+        }
+    };
+    let visit_u16 = visit_larger_int(syn::parse_quote!(visit_u16), syn::parse_quote!(u16));
+    let visit_u32 = visit_larger_int(syn::parse_quote!(visit_u32), syn::parse_quote!(u32));
+
+    quote! {
+        // Allow a fieldless enum to be decoded from the index of one of its variants:
+        fn visit_u8<'scale, 'info>(
+            self,
+            value: u8,
+            _type_id: <Self::TypeResolver as #path_to_scale_decode::TypeResolver>::TypeId,
+        ) -> #path_to_scale_decode::Result<Self::Value<'scale, 'info>, Self::Error> {
+            #visit_u8_body
+        }
+        #visit_u16
+        #visit_u32
+    }
+}
+
+// Generate a `TryFrom<u8>` impl which converts the index of a variant into that variant.
+fn try_from_u8_impl(
+    attrs: &TopLevelAttrs,
+    input: &DeriveInput,
+    indexes: &[(u8, &syn::Variant)],
+) -> TokenStream2 {
+    let path_to_scale_decode = &attrs.crate_path;
+    let path_to_type = &input.ident;
+    let expecting = attrs.expecting_call();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let variant_indexes = indexes.iter().map(|(i, _)| i);
+    let variant_idents = indexes.iter().map(|(_, variant)| &variant.ident);
+    let variant_names = indexes.iter().map(|(_, variant)| variant_name(variant));
+
+    quote! {
+        impl #impl_generics #path_to_scale_decode::TryFrom<u8> for #path_to_type #ty_generics #where_clause {
+            type Error = #path_to_scale_decode::Error;
+            fn try_from(value: u8) -> #path_to_scale_decode::Result<Self, Self::Error> {
+                match value {
+                    #( #variant_indexes => #path_to_scale_decode::Ok(#path_to_type::#variant_idents), )*
+                    _ => #path_to_scale_decode::Err(#path_to_scale_decode::Error::cannot_find_variant(
+                        value.to_string(),
+                        value,
+                        vec![#(#variant_names),*]
+                    )#expecting),
+                }
+            }
+        }
+    }
+}
+
+// Generate the body of a function which decodes the fields of the given variant, found in some
+// `fields` composite, into that variant. The body always returns.
+fn variant_fields_body(
+    path_to_scale_decode: &syn::Path,
+    path_to_type: &syn::Path,
+    expecting: &Option<TokenStream2>,
+    allow_extra_fields: bool,
+    variant: &syn::Variant,
+) -> TokenStream2 {
+    let variant_ident = &variant.ident;
+    let variant_attrs = VariantAttrs::from_attributes(&variant.attrs).unwrap_or_default();
+
+    if variant_attrs.reorder_fields && !matches!(variant.fields, syn::Fields::Named(_)) {
+        return syn::Error::new(
+            variant_ident.span(),
+            "The reorder_fields attribute is only supported on struct variants",
+        )
+        .into_compile_error();
+    }
+
+    match &variant.fields {
+        // Always look fields up by name, ignoring their order and any extra fields.
+        syn::Fields::Named(fields) if variant_attrs.reorder_fields => {
+            let (_, field_composite_keyvals, _) =
+                named_field_keyvals(path_to_scale_decode, expecting, fields);
+            let collect_fields = collect_named_fields(path_to_scale_decode, quote!(fields), fields);
+
+            quote! {
+                let vals = #collect_fields;
+                return #path_to_scale_decode::Ok(#path_to_type::#variant_ident { #(#field_composite_keyvals),* })
+            }
+        }
+        syn::Fields::Named(fields) => {
+            let (field_count, field_composite_keyvals, field_tuple_keyvals) =
+                named_field_keyvals(path_to_scale_decode, expecting, fields);
+            let collect_fields = collect_named_fields(path_to_scale_decode, quote!(fields), fields);
+
+            let mismatch_err = field_mismatch_error(
+                path_to_scale_decode,
+                expecting,
+                fields,
+                composite_field_names(quote!(fields)),
+            );
+
+            let wrong_field_count =
+                wrong_field_count(quote!(fields), field_count, allow_extra_fields);
+
+            quote! {
+                return if fields.has_unnamed_fields() {
+                    if #wrong_field_count {
+                        return #path_to_scale_decode::Err(#mismatch_err);
+                    }
+                    let vals = fields;
+                    #path_to_scale_decode::Ok(#path_to_type::#variant_ident { #(#field_tuple_keyvals),* })
+                } else {
+                    let vals = #collect_fields;
+                    #path_to_scale_decode::Ok(#path_to_type::#variant_ident { #(#field_composite_keyvals),* })
+                }
+            }
+        }
+        syn::Fields::Unnamed(fields) => {
+            let (field_count, field_vals) = unnamed_field_vals(path_to_scale_decode, fields);
+            let wrong_field_count =
+                wrong_field_count(quote!(fields), field_count, allow_extra_fields);
+
+            quote! {
+                if #wrong_field_count {
+                    return #path_to_scale_decode::Err(#path_to_scale_decode::Error::new(#path_to_scale_decode::error::ErrorKind::WrongLength {
+                        actual_len: fields.remaining(),
+                        expected_len: #field_count
+                    })#expecting);
+                }
+                let vals = fields;
+                return #path_to_scale_decode::Ok(#path_to_type::#variant_ident ( #(#field_vals),* ))
+            }
+        }
+        syn::Fields::Unit => {
+            quote! {
+                return #path_to_scale_decode::Ok(#path_to_type::#variant_ident)
+            }
+        }
+    }
+}
+
 fn generate_struct_impl(
     attrs: TopLevelAttrs,
     visibility: &syn::Visibility,
     input: &DeriveInput,
     details: &syn::DataStruct,
 ) -> TokenStream2 {
+    if attrs.fields_from_variant.is_some() {
+        return syn::Error::new(
+            input.ident.span(),
+            "The fields_from_variant attribute is only supported on enums",
+        )
+        .into_compile_error();
+    }
+    if attrs.tag_and_content.is_some() {
+        return syn::Error::new(
+            input.ident.span(),
+            "The tag and content attributes are only supported on enums",
+        )
+        .into_compile_error();
+    }
+    if attrs.try_from_u8 {
+        return syn::Error::new(
+            input.ident.span(),
+            "The try_from_u8 attribute is only supported on enums",
+        )
+        .into_compile_error();
+    }
     let path_to_scale_decode = &attrs.crate_path;
     let path_to_type: syn::Path = input.ident.clone().into();
+    let expecting = attrs.expecting_call();
 
     let generic_types = handle_generics(&attrs, input.generics.clone());
     let ty_generics = generic_types.ty_generics();
@@ -219,77 +865,148 @@ fn generate_struct_impl(
 
     // determine what the body of our visitor functions will be based on the type of struct
     // that we're trying to generate output for.
+    // The tuple body also works for composites (both have the methods we use), so we use it
+    // directly rather than going via `Composite::as_tuple()`, to make sure that the composite
+    // is moved past exactly the bytes that we decode.
     let (visit_composite_body, visit_tuple_body) = match &details.fields {
         syn::Fields::Named(fields) => {
             let (field_count, field_composite_keyvals, field_tuple_keyvals) =
-                named_field_keyvals(path_to_scale_decode, fields);
-
-            (
+                named_field_keyvals(path_to_scale_decode, &expecting, fields);
+            let collect_fields = collect_named_fields(path_to_scale_decode, quote!(value), fields);
+
+            let field_tuple_keyvals: alloc::vec::Vec<_> = field_tuple_keyvals.collect();
+            // Where we can, we report the names of the fields that we were given.
+            let wrong_field_count =
+                wrong_field_count(quote!(value), field_count, attrs.allow_extra_fields);
+            let visit_tuple_body = |found_names: TokenStream2| {
+                let mismatch_err =
+                    field_mismatch_error(path_to_scale_decode, &expecting, fields, found_names);
                 quote! {
-                    if value.has_unnamed_fields() {
-                       return self.visit_tuple(&mut value.as_tuple(), type_id)
+                    if #wrong_field_count {
+                        return #path_to_scale_decode::Err(#mismatch_err);
                     }
 
-                    let vals: #path_to_scale_decode::BTreeMap<Option<&str>, _> =
-                        value.map(|res| res.map(|item| (item.name(), item))).collect::<Result<_, _>>()?;
+                    let vals = value;
 
-                    Ok(#path_to_type { #(#field_composite_keyvals),* })
-                },
+                    #path_to_scale_decode::Ok(#path_to_type { #(#field_tuple_keyvals),* })
+                }
+            };
+            let visit_composite_as_tuple_body =
+                visit_tuple_body(composite_field_names(quote!(value)));
+
+            (
                 quote! {
-                    if value.remaining() != #field_count {
-                        return Err(#path_to_scale_decode::Error::new(#path_to_scale_decode::error::ErrorKind::WrongLength { actual_len: value.remaining(), expected_len: #field_count }));
+                    if value.has_unnamed_fields() {
+                       return { #visit_composite_as_tuple_body }
                     }
 
-                    let vals = value;
+                    let vals = #collect_fields;
 
-                    Ok(#path_to_type { #(#field_tuple_keyvals),* })
+                    #path_to_scale_decode::Ok(#path_to_type { #(#field_composite_keyvals),* })
                 },
+                visit_tuple_body(quote!(vec![#path_to_scale_decode::None; value.remaining()])),
             )
         }
         syn::Fields::Unnamed(fields) => {
             let (field_count, field_vals) = unnamed_field_vals(path_to_scale_decode, fields);
+            let wrong_field_count =
+                wrong_field_count(quote!(value), field_count, attrs.allow_extra_fields);
 
-            (
-                quote! {
-                    self.visit_tuple(&mut value.as_tuple(), type_id)
-                },
-                quote! {
-                    if value.remaining() != #field_count {
-                        return Err(#path_to_scale_decode::Error::new(#path_to_scale_decode::error::ErrorKind::WrongLength { actual_len: value.remaining(), expected_len: #field_count }));
-                    }
+            let visit_tuple_body = quote! {
+                if #wrong_field_count {
+                    return #path_to_scale_decode::Err(#path_to_scale_decode::Error::new(#path_to_scale_decode::error::ErrorKind::WrongLength { actual_len: value.remaining(), expected_len: #field_count })#expecting);
+                }
 
-                    let vals = value;
+                let vals = value;
 
-                    Ok(#path_to_type ( #( #field_vals ),* ))
-                },
-            )
+                #path_to_scale_decode::Ok(#path_to_type ( #( #field_vals ),* ))
+            };
+
+            (visit_tuple_body.clone(), visit_tuple_body)
         }
-        syn::Fields::Unit => (
-            quote! {
-                self.visit_tuple(&mut value.as_tuple(), type_id)
-            },
-            quote! {
-                if value.remaining() > 0 {
-                    return Err(#path_to_scale_decode::Error::new(#path_to_scale_decode::error::ErrorKind::WrongLength { actual_len: value.remaining(), expected_len: 0 }));
+        syn::Fields::Unit => {
+            let wrong_field_count = wrong_field_count(quote!(value), 0, attrs.allow_extra_fields);
+            let visit_tuple_body = quote! {
+                if #wrong_field_count {
+                    return #path_to_scale_decode::Err(#path_to_scale_decode::Error::new(#path_to_scale_decode::error::ErrorKind::WrongLength { actual_len: value.remaining(), expected_len: 0 })#expecting);
                 }
-                Ok(#path_to_type)
-            },
-        ),
+                #path_to_scale_decode::Ok(#path_to_type)
+            };
+
+            (visit_tuple_body.clone(), visit_tuple_body)
+        }
+    };
+
+    let visit_composite_body =
+        limit_depth_body(&attrs, validate_container_body(&attrs, visit_composite_body));
+    let visit_tuple_body =
+        limit_depth_body(&attrs, validate_container_body(&attrs, visit_tuple_body));
+
+    let depth_counter = depth_counter(&attrs);
+
+    // If transparent, we decode exactly as the single field would, and wrap the result.
+    let transparent_impl = if attrs.transparent {
+        let validate_transparent = attrs.validate.as_ref().map(|validate| {
+            let validate = call_validate(path_to_scale_decode, validate, &expecting);
+            quote!(.and_then(|val| { #validate?; #path_to_scale_decode::Ok(val) }))
+        });
+        match transparent_field(path_to_scale_decode, &details.fields) {
+            Ok((field_ty, construct)) => {
+                let decode = quote! {
+                    #path_to_scale_decode::visitor::decode_with_visitor(
+                        input,
+                        type_id,
+                        types,
+                        <#field_ty as #path_to_scale_decode::IntoVisitor>::into_visitor::<#type_resolver_ident>(),
+                    )
+                    .and_then(|val| #path_to_scale_decode::Ok(#path_to_type #construct))
+                    #validate_transparent
+                };
+                // The depth guard is held (and so this level of nesting is entered) until the
+                // field has been decoded.
+                let decode = match enter_depth(&attrs) {
+                    Some(enter_depth) => quote!(#enter_depth.and_then(|_depth_guard| #decode)),
+                    None => decode,
+                };
+                Some(quote! {
+                    fn unchecked_decode_as_type<'scale, 'info>(
+                        self,
+                        input: &mut &'scale [u8],
+                        type_id: <Self::TypeResolver as #path_to_scale_decode::TypeResolver>::TypeId,
+                        types: &'info Self::TypeResolver,
+                    ) -> #path_to_scale_decode::visitor::DecodeAsTypeResult<Self, #path_to_scale_decode::Result<Self::Value<'scale, 'info>, Self::Error>> {
+                        let res = #decode;
+                        #path_to_scale_decode::visitor::DecodeAsTypeResult::Decoded(res)
+                    }
+                })
+            }
+            Err(()) => {
+                return syn::Error::new(
+                    input.ident.span(),
+                    "The transparent attribute is only supported on structs with a single (non-skipped) field",
+                )
+                .into_compile_error()
+            }
+        }
+    } else {
+        None
     };
 
     quote!(
         const _: () = {
             #visibility struct Visitor #visitor_impl_generics (
-                ::core::marker::PhantomData<#visitor_phantomdata_type>
+                #path_to_scale_decode::PhantomData<#visitor_phantomdata_type>
             );
 
             use #path_to_scale_decode::vec;
             use #path_to_scale_decode::ToString;
 
+            #depth_counter
+
             impl #impl_generics #path_to_scale_decode::IntoVisitor for #path_to_type #ty_generics #visitor_where_clause {
                 type AnyVisitor<#type_resolver_ident: #path_to_scale_decode::TypeResolver> = Visitor #visitor_ty_generics;
                 fn into_visitor<#type_resolver_ident: #path_to_scale_decode::TypeResolver>() -> Self::AnyVisitor<#type_resolver_ident> {
-                    Visitor(::core::marker::PhantomData)
+                    Visitor(#path_to_scale_decode::PhantomData)
                 }
             }
 
@@ -298,18 +1015,20 @@ fn generate_struct_impl(
                 type Value<'scale, 'info> = #path_to_type #ty_generics;
                 type TypeResolver = #type_resolver_ident;
 
+                #transparent_impl
+
                 fn visit_composite<'scale, 'info>(
                     self,
                     value: &mut #path_to_scale_decode::visitor::types::Composite<'scale, 'info, Self::TypeResolver>,
-                    type_id: <Self::TypeResolver as #path_to_scale_decode::TypeResolver>::TypeId,
-                ) -> Result<Self::Value<'scale, 'info>, Self::Error> {
+                    _type_id: <Self::TypeResolver as #path_to_scale_decode::TypeResolver>::TypeId,
+                ) -> #path_to_scale_decode::Result<Self::Value<'scale, 'info>, Self::Error> {
                     #visit_composite_body
                 }
                 fn visit_tuple<'scale, 'info>(
                     self,
                     value: &mut #path_to_scale_decode::visitor::types::Tuple<'scale, 'info, Self::TypeResolver>,
-                    type_id: <Self::TypeResolver as #path_to_scale_decode::TypeResolver>::TypeId,
-                ) -> Result<Self::Value<'scale, 'info>, Self::Error> {
+                    _type_id: <Self::TypeResolver as #path_to_scale_decode::TypeResolver>::TypeId,
+                ) -> #path_to_scale_decode::Result<Self::Value<'scale, 'info>, Self::Error> {
                     #visit_tuple_body
                 }
             }
@@ -319,17 +1038,9 @@ fn generate_struct_impl(
                     input: &mut &[u8],
                     fields: &mut dyn #path_to_scale_decode::FieldIter<'info, R::TypeId>,
                     types: &'info R
-                ) -> Result<Self, #path_to_scale_decode::Error>
+                ) -> #path_to_scale_decode::Result<Self, #path_to_scale_decode::Error>
                 {
-                    let mut composite = #path_to_scale_decode::visitor::types::Composite::new(core::iter::empty(), input, fields, types, false);
-                    use #path_to_scale_decode::{ Visitor, IntoVisitor };
-                    let val = <#path_to_type #ty_generics>::into_visitor().visit_composite(&mut composite, Default::default());
-
-                    // Consume any remaining bytes and update input:
-                    composite.skip_decoding()?;
-                    *input = composite.bytes_from_undecoded();
-
-                    val.map_err(From::from)
+                    #path_to_scale_decode::decode_composite_as_fields::<Self, R>(input, fields, types)
                 }
             }
         };
@@ -339,20 +1050,28 @@ fn generate_struct_impl(
 // Given some named fields, generate impls like `field_name: get_field_value()` for each field. Do this for the composite and tuple impls.
 fn named_field_keyvals<'f>(
     path_to_scale_decode: &'f syn::Path,
+    expecting: &'f Option<TokenStream2>,
     fields: &'f syn::FieldsNamed,
 ) -> (usize, impl Iterator<Item = TokenStream2> + 'f, impl Iterator<Item = TokenStream2> + 'f) {
     let field_keyval_impls = fields.named.iter().map(move |f| {
         let field_attrs = FieldAttrs::from_attributes(&f.attrs).unwrap_or_default();
         let field_ident = f.ident.as_ref().expect("named field has ident");
-        let field_name = field_ident.to_string();
+        let field_name = field_name(f);
         let skip_field = field_attrs.skip;
+        let is_compact = field_attrs.compact;
+        let decode_val = decode_field_val(
+            path_to_scale_decode,
+            &f.ty,
+            &field_attrs,
+            quote!(.at_field(#field_name)),
+        );
 
         // If a field is skipped, we expect it to have a Default impl to use to populate it instead.
         if skip_field {
             return (
                 false,
-                quote!(#field_ident: ::core::default::Default::default()),
-                quote!(#field_ident: ::core::default::Default::default())
+                quote!(#field_ident: #path_to_scale_decode::Default::default()),
+                quote!(#field_ident: #path_to_scale_decode::Default::default())
             )
         }
 
@@ -362,15 +1081,15 @@ fn named_field_keyvals<'f>(
             // For turning named fields in scale typeinfo into named fields on struct like type:
             quote!(#field_ident: {
                 let val = vals
-                    .get(&Some(#field_name))
-                    .ok_or_else(|| #path_to_scale_decode::Error::new(#path_to_scale_decode::error::ErrorKind::CannotFindField { name: #field_name.to_string() }))?
+                    .get(&#path_to_scale_decode::Some(#field_name))
+                    .ok_or_else(|| #path_to_scale_decode::Error::new(#path_to_scale_decode::error::ErrorKind::CannotFindField { name: #field_name.to_string() })#expecting)?
                     .clone();
-                val.decode_as_type().map_err(|e| e.at_field(#field_name))?
+                #decode_val
             }),
             // For turning named fields in scale typeinfo into unnamed fields on tuple like type:
             quote!(#field_ident: {
-                let val = vals.next().expect("field count should have been checked already on tuple type; please file a bug report")?;
-                val.decode_as_type().map_err(|e| e.at_field(#field_name))?
+                let val = vals.next_maybe_compact(#is_compact).expect("field count should have been checked already on tuple type; please file a bug report")?;
+                #decode_val
             })
         )
     });
@@ -383,18 +1102,99 @@ fn named_field_keyvals<'f>(
     (field_count, field_composite_keyvals, field_tuple_keyvals)
 }
 
+// Generate an expression which is true if the number of fields remaining in some composite or
+// tuple doesn't line up with the number of fields that we expect. If extra fields are allowed,
+// there need only be at least as many fields as we expect; any others are skipped over.
+fn wrong_field_count(
+    composite: TokenStream2,
+    field_count: usize,
+    allow_extra_fields: bool,
+) -> TokenStream2 {
+    if allow_extra_fields {
+        quote!(#composite.remaining() < #field_count)
+    } else {
+        quote!(#composite.remaining() != #field_count)
+    }
+}
+
+// Generate an error for when the number of fields given doesn't line up with the named fields
+// that we're decoding into. `found_names` should evaluate to the (optional) names of the fields given.
+fn field_mismatch_error(
+    path_to_scale_decode: &syn::Path,
+    expecting: &Option<TokenStream2>,
+    fields: &syn::FieldsNamed,
+    found_names: TokenStream2,
+) -> TokenStream2 {
+    let expected_names = fields
+        .named
+        .iter()
+        .filter(|f| !FieldAttrs::from_attributes(&f.attrs).unwrap_or_default().skip)
+        .map(field_name);
+
+    quote! {
+        #path_to_scale_decode::Error::new(#path_to_scale_decode::error::ErrorKind::FieldMismatch {
+            expected: vec![#(#expected_names),*],
+            found: #found_names,
+        })#expecting
+    }
+}
+
+// Generate an expression which evaluates to the (optional) names of all of the fields in some composite.
+fn composite_field_names(composite: TokenStream2) -> TokenStream2 {
+    quote!(#composite.fields().iter().map(|f| f.name.map(|n| n.to_string())).collect())
+}
+
+// Generate an expression which collects the fields in some composite type into a map from field
+// name to field, treating any fields we've marked as compact as compact encoded.
+fn collect_named_fields(
+    path_to_scale_decode: &syn::Path,
+    composite: TokenStream2,
+    fields: &syn::FieldsNamed,
+) -> TokenStream2 {
+    let compact_field_names: alloc::vec::Vec<_> = fields
+        .named
+        .iter()
+        .filter(|f| FieldAttrs::from_attributes(&f.attrs).unwrap_or_default().compact)
+        .map(field_name)
+        .collect();
+
+    // If no fields are marked as compact, we can just iterate over them.
+    if compact_field_names.is_empty() {
+        return quote! {
+            #composite
+                .map(|res| res.map(|item| (item.name(), item)))
+                .collect::<#path_to_scale_decode::Result<#path_to_scale_decode::BTreeMap<#path_to_scale_decode::Option<&str>, _>, _>>()?
+        };
+    }
+
+    quote! {{
+        let mut vals = #path_to_scale_decode::BTreeMap::<#path_to_scale_decode::Option<&str>, _>::new();
+        loop {
+            let name = #composite.peek_name();
+            let is_compact = matches!(name, #(#path_to_scale_decode::Some(#compact_field_names))|*);
+            match #composite.next_maybe_compact(is_compact) {
+                #path_to_scale_decode::Some(item) => vals.insert(name, item?),
+                #path_to_scale_decode::None => break vals,
+            };
+        }
+    }}
+}
+
 // Given some unnamed fields, generate impls like `get_field_value()` for each field. Do this for a tuple style impl.
 fn unnamed_field_vals<'f>(
-    _path_to_scale_decode: &'f syn::Path,
+    path_to_scale_decode: &'f syn::Path,
     fields: &'f syn::FieldsUnnamed,
 ) -> (usize, impl Iterator<Item = TokenStream2> + 'f) {
-    let field_val_impls = fields.unnamed.iter().enumerate().map(|(idx, f)| {
+    let field_val_impls = fields.unnamed.iter().enumerate().map(move |(idx, f)| {
         let field_attrs = FieldAttrs::from_attributes(&f.attrs).unwrap_or_default();
         let skip_field = field_attrs.skip;
+        let is_compact = field_attrs.compact;
+        let decode_val =
+            decode_field_val(path_to_scale_decode, &f.ty, &field_attrs, quote!(.at_idx(#idx)));
 
         // If a field is skipped, we expect it to have a Default impl to use to populate it instead.
         if skip_field {
-            return (false, quote!(::core::default::Default::default()));
+            return (false, quote!(#path_to_scale_decode::Default::default()));
         }
 
         (
@@ -402,8 +1202,8 @@ fn unnamed_field_vals<'f>(
             true,
             // For turning unnamed fields in scale typeinfo into unnamed fields on tuple like type:
             quote!({
-                let val = vals.next().expect("field count should have been checked already on tuple type; please file a bug report")?;
-                val.decode_as_type().map_err(|e| e.at_idx(#idx))?
+                let val = vals.next_maybe_compact(#is_compact).expect("field count should have been checked already on tuple type; please file a bug report")?;
+                #decode_val
             }),
         )
     });
@@ -415,14 +1215,188 @@ fn unnamed_field_vals<'f>(
     (field_count, field_vals)
 }
 
-fn handle_generics(attrs: &TopLevelAttrs, generics: syn::Generics) -> GenericTypes {
+// Generate an expression which decodes `val` (some field handed back from a composite or tuple)
+// into the field type. If the field should be decoded as some other type, then we decode it as
+// that first, and then convert it into the field type via `TryFrom`. If the field should be
+// validated, the validation function is then called on the decoded value.
+fn decode_field_val(
+    path_to_scale_decode: &syn::Path,
+    field_ty: &syn::Type,
+    field_attrs: &FieldAttrs,
+    add_context: TokenStream2,
+) -> TokenStream2 {
+    let decode_val = match &field_attrs.as_type {
+        None => quote!(val.decode_as_type().map_err(|e| e #add_context)?),
+        Some(as_type) => {
+            let convert = convert_from_as_type(path_to_scale_decode, field_ty, as_type);
+            quote!({
+                let val: #as_type = val.decode_as_type().map_err(|e| e #add_context)?;
+                #convert.map_err(|e| e #add_context)?
+            })
+        }
+    };
+    match &field_attrs.validate {
+        None => decode_val,
+        Some(validate) => {
+            let validate = call_validate(path_to_scale_decode, validate, &None);
+            quote!({
+                let val: #field_ty = #decode_val;
+                #validate.map_err(|e| e #add_context)?;
+                val
+            })
+        }
+    }
+}
+
+// Generate an expression which hands a reference to `val` to the given validation function, and
+// converts any error that it returns into a custom error.
+fn call_validate(
+    path_to_scale_decode: &syn::Path,
+    validate: &syn::Path,
+    expecting: &Option<TokenStream2>,
+) -> TokenStream2 {
+    quote!(
+        #validate(&val)
+            .map_err(|e| #path_to_scale_decode::Error::custom_error(e)#expecting)
+    )
+}
+
+// Wrap the body of some visitor method, which returns the decoded container or an error, so that
+// a decoded container is handed to the validation function given in the top level attributes
+// (if any) before being returned.
+fn validate_container_body(attrs: &TopLevelAttrs, body: TokenStream2) -> TokenStream2 {
+    let Some(validate) = &attrs.validate else {
+        return body;
+    };
+    let path_to_scale_decode = &attrs.crate_path;
+    let validate = call_validate(path_to_scale_decode, validate, &attrs.expecting_call());
+    quote! {
+        // The body may return early, so it's wrapped in a closure to catch the result.
+        let res: #path_to_scale_decode::Result<Self::Value<'scale, 'info>, Self::Error> = (|| { #body })();
+        let val = res?;
+        #validate?;
+        #path_to_scale_decode::Ok(val)
+    }
+}
+
+// Wrap the body of some visitor method which decodes the fields of the container, so that the
+// method errors if the container is nested inside itself more deeply than the top level
+// attributes allow (if they set a limit). The static counter is declared by `depth_counter()`.
+fn limit_depth_body(attrs: &TopLevelAttrs, body: TokenStream2) -> TokenStream2 {
+    let Some(enter_depth) = enter_depth(attrs) else {
+        return body;
+    };
+    quote! {
+        // The guard leaves this level of nesting again when the method returns.
+        let _depth_guard = #enter_depth?;
+        #body
+    }
+}
+
+// Generate an expression which enters one more level of nesting of the container, returning a
+// guard or an error, if the top level attributes set a limit on how deeply it can be nested.
+fn enter_depth(attrs: &TopLevelAttrs) -> Option<TokenStream2> {
+    let max_depth = attrs.max_depth?;
+    Some(match attrs.expecting_call() {
+        Some(expecting) => quote!(DECODE_DEPTH.enter(#max_depth).map_err(|e| e #expecting)),
+        None => quote!(DECODE_DEPTH.enter(#max_depth)),
+    })
+}
+
+// Declare the static counter used to track how deeply nested the container currently is, if the
+// top level attributes set a limit.
+fn depth_counter(attrs: &TopLevelAttrs) -> Option<TokenStream2> {
+    let path_to_scale_decode = &attrs.crate_path;
+    attrs.max_depth.map(|_| {
+        quote! {
+            static DECODE_DEPTH: #path_to_scale_decode::recursion::DepthCounter =
+                #path_to_scale_decode::recursion::DepthCounter::new();
+        }
+    })
+}
+
+// Generate an expression which converts `val`, of type `as_type`, into the field type.
+fn convert_from_as_type(
+    path_to_scale_decode: &syn::Path,
+    field_ty: &syn::Type,
+    as_type: &syn::Type,
+) -> TokenStream2 {
+    quote!(
+        <#field_ty as #path_to_scale_decode::TryFrom<#as_type>>::try_from(val)
+            .map_err(|e| #path_to_scale_decode::Error::custom_error(e))
+    )
+}
+
+// Given the fields of a transparent struct, return the type that the single non-skipped field is
+// decoded as, and the tokens needed to construct the struct given a `val` of this type. If the
+// field needs converting from the type that it's decoded as, this will use `?`.
+fn transparent_field(
+    path_to_scale_decode: &syn::Path,
+    fields: &syn::Fields,
+) -> Result<(syn::Type, TokenStream2), ()> {
+    let is_skipped =
+        |f: &syn::Field| FieldAttrs::from_attributes(&f.attrs).unwrap_or_default().skip;
+
+    let mut non_skipped = fields.iter().filter(|f| !is_skipped(f));
+    let (Some(field), None) = (non_skipped.next(), non_skipped.next()) else {
+        return Err(());
+    };
+    let field_attrs = FieldAttrs::from_attributes(&field.attrs).unwrap_or_default();
+    let as_type = field_attrs.as_type;
+
+    let field_vals = fields.iter().map(|f| {
+        let val = if is_skipped(f) {
+            quote!(#path_to_scale_decode::Default::default())
+        } else if let Some(as_type) = &as_type {
+            let convert = convert_from_as_type(path_to_scale_decode, &f.ty, as_type);
+            quote!(#convert?)
+        } else {
+            quote!(val)
+        };
+        let val = match (&field_attrs.validate, is_skipped(f)) {
+            (Some(validate), false) => {
+                let field_ty = &f.ty;
+                let validate = call_validate(path_to_scale_decode, validate, &None);
+                quote!({
+                    let val: #field_ty = #val;
+                    #validate?;
+                    val
+                })
+            }
+            _ => val,
+        };
+        match &f.ident {
+            Some(ident) => quote!(#ident: #val),
+            None => val,
+        }
+    });
+    let construct = match fields {
+        syn::Fields::Named(_) => quote!({ #(#field_vals),* }),
+        _ => quote!(( #(#field_vals),* )),
+    };
+
+    Ok((as_type.unwrap_or_else(|| field.ty.clone()), construct))
+}
+
+fn handle_generics(attrs: &TopLevelAttrs, mut generics: syn::Generics) -> GenericTypes {
     let path_to_crate = &attrs.crate_path;
 
+    // Note which type params shouldn't be given default bounds, and then remove our attributes
+    // from them, since they aren't valid anywhere except on the type that the derive is on.
+    let mut bound_skipped = alloc::vec::Vec::new();
+    for param in generics.type_params_mut() {
+        let param_attrs = TypeParamAttrs::from_attributes(&param.attrs).unwrap_or_default();
+        if param_attrs.bound_skip {
+            bound_skipped.push(param.ident.clone());
+        }
+        param.attrs.retain(|attr| !attr.path().is_ident(ATTR_NAME));
+    }
+
     let type_resolver_ident =
         syn::Ident::new(GenericTypes::TYPE_RESOLVER_IDENT_STR, Span::call_site());
 
-    // Where clause to use on Visitor/IntoVisitor
-    let visitor_where_clause = {
+    // Build a where clause which bounds each type param by the given trait.
+    let where_clause_with_bound = |bound: syn::Path| {
         let (_, _, where_clause) = generics.split_for_impl();
         let mut where_clause = where_clause.cloned().unwrap_or(syn::parse_quote!(where));
         if let Some(where_predicates) = &attrs.trait_bounds {
@@ -430,14 +1404,26 @@ fn handle_generics(attrs: &TopLevelAttrs, generics: syn::Generics) -> GenericTyp
             where_clause.predicates.extend(where_predicates.clone());
         } else {
             // else, append our default bounds to each parameter to ensure that it all lines up with our generated impls and such:
+            // (const params need no bounds; anything they're used in, like arrays, is bounded via its type params).
+            // Params marked with `bound_skip` are left alone.
             for param in generics.type_params() {
                 let ty = &param.ident;
-                where_clause.predicates.push(syn::parse_quote!(#ty: #path_to_crate::IntoVisitor));
+                if bound_skipped.contains(ty) {
+                    continue;
+                }
+                where_clause.predicates.push(syn::parse_quote!(#ty: #bound));
             }
         }
         where_clause
     };
 
+    // Where clause to use on Visitor/IntoVisitor
+    let visitor_where_clause =
+        where_clause_with_bound(syn::parse_quote!(#path_to_crate::IntoVisitor));
+    // Where clause to use on ShapeOf
+    let shape_where_clause =
+        where_clause_with_bound(syn::parse_quote!(#path_to_crate::shape::ShapeOf));
+
     // (A, B, C, ScaleDecodeTypeResolver) style PhantomData type to use in Visitor struct.
     let visitor_phantomdata_type = {
         let tys = generics.params.iter().filter_map::<syn::Type, _>(|p| match p {
@@ -478,6 +1464,7 @@ fn handle_generics(attrs: &TopLevelAttrs, generics: syn::Generics) -> GenericTyp
         visitor_generics,
         visitor_phantomdata_type,
         visitor_where_clause,
+        shape_where_clause,
     }
 }
 
@@ -486,6 +1473,7 @@ struct GenericTypes {
     type_generics: syn::Generics,
     visitor_generics: syn::Generics,
     visitor_where_clause: syn::WhereClause,
+    shape_where_clause: syn::WhereClause,
     visitor_phantomdata_type: syn::Type,
 }
 
@@ -503,6 +1491,9 @@ impl GenericTypes {
     pub fn visitor_where_clause(&self) -> &syn::WhereClause {
         &self.visitor_where_clause
     }
+    pub fn shape_where_clause(&self) -> &syn::WhereClause {
+        &self.shape_where_clause
+    }
     pub fn visitor_ty_generics(&self) -> syn::TypeGenerics<'_> {
         let (_, ty_generics, _) = self.visitor_generics.split_for_impl();
         ty_generics
@@ -524,6 +1515,31 @@ struct TopLevelAttrs {
     crate_path: syn::Path,
     // allow custom trait bounds to be used instead of the defaults.
     trait_bounds: Option<Punctuated<syn::WherePredicate, syn::Token!(,)>>,
+    // a description of the type, to attach to any errors we generate.
+    expecting: Option<String>,
+    // decode the struct exactly as its single field would be decoded.
+    transparent: bool,
+    // implement DecodeAsFields on an enum by decoding the fields into this variant.
+    fields_from_variant: Option<String>,
+    // generate an inherent `decode_from` constructor, so the trait needn't be imported.
+    constructor: bool,
+    // also decode an enum from a composite whose `tag` field holds the variant name, and whose
+    // `content` field holds the fields of that variant.
+    tag_and_content: Option<(String, String)>,
+    // skip over any extra fields beyond those we expect, rather than erroring.
+    allow_extra_fields: bool,
+    // a function to call on the decoded value, which can return an error if it is invalid.
+    validate: Option<syn::Path>,
+    // error if decoding this type is nested inside itself more than this many levels deep.
+    max_depth: Option<usize>,
+    // generate a `ShapeOf` impl describing the values that this type can be decoded from.
+    shape: bool,
+    // generate a `From` impl converting this shadow type into the given remote type.
+    remote: Option<syn::Type>,
+    // also decode a fieldless enum from a bare number giving the index of a variant.
+    from_index: bool,
+    // generate a `TryFrom<u8>` impl converting the index of a variant into a fieldless enum.
+    try_from_u8: bool,
 }
 
 impl TopLevelAttrs {
@@ -536,10 +1552,50 @@ impl TopLevelAttrs {
             crate_path: Option<syn::Path>,
             #[darling(default)]
             trait_bounds: Option<Punctuated<syn::WherePredicate, syn::Token!(,)>>,
+            #[darling(default)]
+            expecting: Option<String>,
+            #[darling(default)]
+            transparent: bool,
+            #[darling(default)]
+            fields_from_variant: Option<String>,
+            #[darling(default)]
+            constructor: bool,
+            #[darling(default)]
+            tag: Option<String>,
+            #[darling(default)]
+            content: Option<String>,
+            #[darling(default)]
+            allow_extra_fields: bool,
+            #[darling(default)]
+            validate: Option<syn::Path>,
+            #[darling(default)]
+            max_depth: Option<usize>,
+            #[darling(default)]
+            shape: bool,
+            #[darling(default)]
+            remote: Option<syn::Type>,
+            #[darling(default)]
+            from_index: bool,
+            #[darling(default)]
+            try_from_u8: bool,
         }
 
-        let mut res =
-            TopLevelAttrs { crate_path: syn::parse_quote!(::scale_decode), trait_bounds: None };
+        let mut res = TopLevelAttrs {
+            crate_path: syn::parse_quote!(::scale_decode),
+            trait_bounds: None,
+            expecting: None,
+            transparent: false,
+            fields_from_variant: None,
+            constructor: false,
+            tag_and_content: None,
+            allow_extra_fields: false,
+            validate: None,
+            max_depth: None,
+            shape: false,
+            remote: None,
+            from_index: false,
+            try_from_u8: false,
+        };
 
         // look at each top level attr. parse any for decode_as_type.
         for attr in attrs {
@@ -553,16 +1609,160 @@ impl TopLevelAttrs {
             if let Some(crate_path) = parsed_attrs.crate_path {
                 res.crate_path = crate_path;
             }
+            if let Some(expecting) = parsed_attrs.expecting {
+                res.expecting = Some(expecting);
+            }
+            if parsed_attrs.transparent {
+                res.transparent = true;
+            }
+            if let Some(variant) = parsed_attrs.fields_from_variant {
+                res.fields_from_variant = Some(variant);
+            }
+            if parsed_attrs.constructor {
+                res.constructor = true;
+            }
+            if parsed_attrs.allow_extra_fields {
+                res.allow_extra_fields = true;
+            }
+            if let Some(validate) = parsed_attrs.validate {
+                res.validate = Some(validate);
+            }
+            if let Some(max_depth) = parsed_attrs.max_depth {
+                res.max_depth = Some(max_depth);
+            }
+            if parsed_attrs.shape {
+                res.shape = true;
+            }
+            if let Some(remote) = parsed_attrs.remote {
+                res.remote = Some(remote);
+            }
+            if parsed_attrs.from_index {
+                res.from_index = true;
+            }
+            if parsed_attrs.try_from_u8 {
+                res.try_from_u8 = true;
+            }
+            match (parsed_attrs.tag, parsed_attrs.content) {
+                (Some(tag), Some(content)) => res.tag_and_content = Some((tag, content)),
+                (None, None) => {}
+                _ => {
+                    return Err(darling::Error::custom(
+                        "The tag and content attributes must be given together",
+                    )
+                    .with_span(meta))
+                }
+            }
         }
 
         Ok(res)
     }
+
+    // A method call to append to any errors we construct, to describe what we were decoding.
+    fn expecting_call(&self) -> Option<TokenStream2> {
+        self.expecting.as_ref().map(|expecting| quote!(.expecting(#expecting)))
+    }
 }
 
-/// Parse the attributes attached to some field
+/// Parse the attributes attached to some enum variant
 #[derive(Debug, FromAttributes, Default)]
 #[darling(attributes(decode_as_type, codec))]
-struct FieldAttrs {
+struct VariantAttrs {
     #[darling(default)]
     skip: bool,
+    #[darling(default)]
+    reorder_fields: bool,
+    #[darling(default)]
+    index: Option<u8>,
+    #[darling(default)]
+    rename: Option<String>,
+}
+
+/// Parse the attributes attached to some field
+#[derive(Debug, Default)]
+struct FieldAttrs {
+    skip: bool,
+    compact: bool,
+    as_type: Option<syn::Type>,
+    validate: Option<syn::Path>,
+    rename: Option<String>,
+}
+
+// Darling can't parse `as = "..."` because `as` is a keyword, so we parse these by hand, erroring
+// on anything unknown just as darling would. Only `skip` is read from `codec` attributes; any
+// others are for `parity-scale-codec` (and things like `compact` are already reflected in the
+// type information), so they're ignored.
+impl FromAttributes for FieldAttrs {
+    fn from_attributes(attrs: &[syn::Attribute]) -> darling::Result<Self> {
+        let mut field_attrs = FieldAttrs::default();
+        for attr in attrs {
+            if attr.path().is_ident("codec") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("skip") {
+                        field_attrs.skip = true;
+                        Ok(())
+                    } else {
+                        ignore_meta_value(&meta)
+                    }
+                })?;
+                continue;
+            }
+            if !attr.path().is_ident(ATTR_NAME) {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    field_attrs.skip = true;
+                } else if meta.path.is_ident("compact") {
+                    field_attrs.compact = true;
+                } else if meta.path.is_ident("as") {
+                    let ty: syn::LitStr = meta.value()?.parse()?;
+                    field_attrs.as_type = Some(ty.parse()?);
+                } else if meta.path.is_ident("validate") {
+                    let path: syn::LitStr = meta.value()?.parse()?;
+                    field_attrs.validate = Some(path.parse()?);
+                } else if meta.path.is_ident("rename") {
+                    let name: syn::LitStr = meta.value()?.parse()?;
+                    field_attrs.rename = Some(name.value());
+                } else {
+                    return Err(meta.error("unknown field attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(field_attrs)
+    }
+}
+
+// Move past the value given to some attribute that we don't use, like `= "..."` or `(...)`.
+fn ignore_meta_value(meta: &syn::meta::ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<syn::Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.input.parse::<proc_macro2::TokenTree>()?;
+    }
+    Ok(())
+}
+
+// The name that a variant is decoded from; its own name unless it's been renamed.
+fn variant_name(variant: &syn::Variant) -> String {
+    VariantAttrs::from_attributes(&variant.attrs)
+        .unwrap_or_default()
+        .rename
+        .unwrap_or_else(|| variant.ident.to_string())
+}
+
+// The name that a named field is decoded from; its own name unless it's been renamed.
+fn field_name(field: &syn::Field) -> String {
+    FieldAttrs::from_attributes(&field.attrs)
+        .unwrap_or_default()
+        .rename
+        .unwrap_or_else(|| field.ident.as_ref().expect("named field has ident").to_string())
+}
+
+/// Parse the attributes attached to some generic type parameter
+#[derive(Debug, FromAttributes, Default)]
+#[darling(attributes(decode_as_type))]
+struct TypeParamAttrs {
+    #[darling(default)]
+    bound_skip: bool,
 }
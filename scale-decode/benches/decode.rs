@@ -0,0 +1,199 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use codec::Encode;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use scale_decode::{visitor::cache::CachedResolver, visitor::IgnoreVisitor, DecodeAsType};
+use scale_info::{PortableRegistry, TypeInfo};
+
+// Some types which look roughly like the things we'd decode from a runtime.
+
+#[derive(Encode, TypeInfo, DecodeAsType)]
+struct AccountInfo {
+    nonce: u32,
+    consumers: u32,
+    providers: u32,
+    sufficients: u32,
+    data: AccountData,
+}
+
+#[derive(Encode, TypeInfo, DecodeAsType)]
+struct AccountData {
+    free: u128,
+    reserved: u128,
+    frozen: u128,
+    flags: u128,
+}
+
+#[derive(Encode, TypeInfo, DecodeAsType)]
+enum Event {
+    Transfer { from: [u8; 32], to: [u8; 32], amount: u128 },
+    Remarked { sender: [u8; 32], hash: [u8; 32] },
+    ExtrinsicSuccess { weight: Weight, class: u8, pays_fee: bool },
+    Code(Vec<u8>),
+}
+
+#[derive(Encode, TypeInfo, DecodeAsType)]
+struct Weight {
+    #[codec(compact)]
+    ref_time: u64,
+    #[codec(compact)]
+    proof_size: u64,
+}
+
+#[derive(Encode, TypeInfo, DecodeAsType)]
+struct EventRecord {
+    phase: Option<u32>,
+    event: Event,
+    topics: Vec<[u8; 32]>,
+}
+
+// Values nested a few levels deep, to exercise decoding through composites.
+#[derive(Encode, TypeInfo, DecodeAsType)]
+struct Nested {
+    name: String,
+    inner: Option<Box<Nested>>,
+    values: Vec<(u8, bool)>,
+}
+
+// An enum with as many variants as a smallish runtime call, each of which is looked up by name.
+macro_rules! many_variants {
+    ($($variant:ident)*) => {
+        #[derive(Encode, TypeInfo, DecodeAsType)]
+        enum Call {
+            $($variant { value: u32 },)*
+        }
+
+        fn calls() -> Vec<Call> {
+            let calls: Vec<fn(u32) -> Call> = vec![$(|value| Call::$variant { value }),*];
+            (0..100).map(|n| calls[n * 7 % calls.len()](n as u32)).collect()
+        }
+    };
+}
+
+many_variants!(
+    System Scheduler Preimage Babe Timestamp Indices Balances TransactionPayment Authorship
+    Staking Offences Historical Session Grandpa ImOnline AuthorityDiscovery Treasury
+    ConvictionVoting Referenda Whitelist Claims Vesting Utility Identity Proxy Multisig Bounties
+    ChildBounties ElectionProviderMultiPhase VoterList NominationPools FastUnstake ParaInclusion
+    ParaInherent Paras Initializer Hrmp ParasDisputes ParasSlashing Registrar Slots Auctions
+    Crowdloan XcmPallet MessageQueue AssetRate Beefy Mmr BeefyMmrLeaf
+);
+
+fn make_type<T: TypeInfo + 'static>() -> (u32, PortableRegistry) {
+    let m = scale_info::MetaType::new::<T>();
+    let mut types = scale_info::Registry::new();
+    let id = types.register_type(&m);
+    (id.id, types.into())
+}
+
+fn account_infos() -> Vec<AccountInfo> {
+    (0..100)
+        .map(|n| AccountInfo {
+            nonce: n,
+            consumers: 1,
+            providers: 1,
+            sufficients: 0,
+            data: AccountData {
+                free: n as u128 * 1_000_000,
+                reserved: 0,
+                frozen: 0,
+                flags: 1 << 127,
+            },
+        })
+        .collect()
+}
+
+fn events() -> Vec<EventRecord> {
+    (0..100)
+        .map(|n| {
+            let event = match n % 4 {
+                0 => Event::Transfer { from: [1; 32], to: [2; 32], amount: n as u128 },
+                1 => Event::Remarked { sender: [3; 32], hash: [4; 32] },
+                2 => Event::ExtrinsicSuccess {
+                    weight: Weight { ref_time: n * 1000, proof_size: n },
+                    class: 0,
+                    pays_fee: true,
+                },
+                _ => Event::Code(vec![0; 256]),
+            };
+            EventRecord { phase: Some(n as u32), event, topics: vec![[5; 32]; (n % 3) as usize] }
+        })
+        .collect()
+}
+
+fn nested(depth: usize) -> Nested {
+    (0..depth).fold(Nested { name: "leaf".into(), inner: None, values: vec![] }, |inner, n| {
+        Nested {
+            name: format!("level {n}"),
+            inner: Some(Box::new(inner)),
+            values: vec![(n as u8, true); 4],
+        }
+    })
+}
+
+fn bench_type<T: Encode + TypeInfo + DecodeAsType + 'static>(
+    c: &mut Criterion,
+    name: &str,
+    value: T,
+) {
+    let (type_id, types) = make_type::<T>();
+    let encoded = value.encode();
+    let cached = CachedResolver::new(types.clone(), [type_id]).unwrap();
+
+    let mut group = c.benchmark_group(name);
+    group.bench_function("decode_as_type", |b| {
+        b.iter(|| T::decode_as_type(&mut &*encoded, type_id, &types).unwrap())
+    });
+    group.bench_function("decode_as_type_cached", |b| {
+        b.iter(|| T::decode_as_type(&mut &*encoded, type_id, &cached).unwrap())
+    });
+    group.bench_function("ignore", |b| {
+        b.iter(|| {
+            scale_decode::visitor::decode_with_visitor(
+                &mut &*encoded,
+                type_id,
+                &types,
+                IgnoreVisitor::new(),
+            )
+            .unwrap()
+        })
+    });
+    group.bench_function("encoded_size_of", |b| {
+        b.iter(|| scale_decode::visitor::encoded_size_of(&encoded, type_id, &types).unwrap())
+    });
+    group.bench_function("ignore_cached", |b| {
+        b.iter(|| {
+            scale_decode::visitor::decode_with_visitor(
+                &mut &*encoded,
+                type_id,
+                &cached,
+                IgnoreVisitor::new(),
+            )
+            .unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn benchmarks(c: &mut Criterion) {
+    bench_type(c, "account_infos", black_box(account_infos()));
+    bench_type(c, "events", black_box(events()));
+    bench_type(c, "nested_composites", black_box(nested(64)));
+    bench_type(c, "many_variants", black_box(calls()));
+}
+
+criterion_group!(benches, benchmarks);
+criterion_main!(benches);
@@ -105,10 +105,11 @@ impl<R: TypeResolver> Visitor for FooVisitor<R> {
             Ok(Foo::Empty)
         } else {
             // The variant name doesn't match; we can't decode!
-            Err(Error::new(ErrorKind::CannotFindVariant {
-                got: value.name().to_string(),
-                expected: vec!["Bar", "Wibble", "Empty"],
-            }))
+            Err(Error::cannot_find_variant(
+                value.name(),
+                value.index(),
+                vec!["Bar", "Wibble", "Empty"],
+            ))
         }
     }
 }
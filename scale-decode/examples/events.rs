@@ -0,0 +1,158 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use codec::Encode;
+use scale_decode::{
+    events::{decode_events, Phase},
+    visitor::{types::Composite, DecodeError, TypeIdFor, Unexpected},
+    DecodeAsType, TypeResolver, Visitor,
+};
+use std::marker::PhantomData;
+
+// Some types describing the events in a block. If you're working with a Substrate based node,
+// these will be described by the metadata that you can obtain from it.
+#[derive(Encode, scale_info::TypeInfo)]
+enum RuntimeEvent {
+    System(SystemEvent),
+    Balances(BalancesEvent),
+}
+#[derive(Encode, scale_info::TypeInfo)]
+enum SystemEvent {
+    ExtrinsicSuccess { weight: u64 },
+}
+#[derive(Encode, scale_info::TypeInfo)]
+enum BalancesEvent {
+    Transfer { from: [u8; 32], to: [u8; 32], amount: u128 },
+}
+#[allow(dead_code)]
+#[derive(Encode, scale_info::TypeInfo)]
+enum SystemPhase {
+    ApplyExtrinsic(u32),
+    Finalization,
+    Initialization,
+}
+#[derive(Encode, scale_info::TypeInfo)]
+struct EventRecord {
+    phase: SystemPhase,
+    event: RuntimeEvent,
+    topics: Vec<[u8; 32]>,
+}
+
+// The event that we're interested in. Events are decoded as fields, so the field names here
+// need to line up with the event, but the types just need to be compatible.
+#[derive(DecodeAsType, Debug)]
+struct Transfer {
+    amount: u64,
+}
+
+// For everything else, a custom visitor which summarises each field. Numbers are handed back
+// as they are, and anything else is summarised by the kind of value that it is.
+struct SummaryVisitor<R>(PhantomData<R>);
+
+impl<R: TypeResolver> Visitor for SummaryVisitor<R> {
+    type Value<'scale, 'resolver> = String;
+    type Error = DecodeError;
+    type TypeResolver = R;
+
+    fn visit_unexpected<'scale, 'resolver>(
+        self,
+        unexpected: Unexpected,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        Ok(format!("<{unexpected}>"))
+    }
+    fn visit_u64<'scale, 'resolver>(
+        self,
+        value: u64,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        Ok(value.to_string())
+    }
+    fn visit_u128<'scale, 'resolver>(
+        self,
+        value: u128,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        Ok(value.to_string())
+    }
+}
+
+// Summarise each of the fields of some event using our visitor.
+fn summarise<R: TypeResolver>(mut fields: Composite<'_, '_, R>) -> Result<String, DecodeError> {
+    let mut summary = Vec::new();
+    for field in fields.by_ref() {
+        let field = field?;
+        let value = field.decode_with_visitor(SummaryVisitor(PhantomData))?;
+        summary.push(format!("{}: {value}", field.name().unwrap_or("_")));
+    }
+    Ok(summary.join(", "))
+}
+
+fn main() {
+    let (type_id, types) = make_type::<Vec<EventRecord>>();
+
+    // The SCALE encoded events that we'd find in storage:
+    let records = vec![
+        EventRecord {
+            phase: SystemPhase::ApplyExtrinsic(0),
+            event: RuntimeEvent::Balances(BalancesEvent::Transfer {
+                from: [1; 32],
+                to: [2; 32],
+                amount: 1000,
+            }),
+            topics: vec![],
+        },
+        EventRecord {
+            phase: SystemPhase::ApplyExtrinsic(0),
+            event: RuntimeEvent::System(SystemEvent::ExtrinsicSuccess { weight: 123 }),
+            topics: vec![[3; 32]],
+        },
+    ];
+    let bytes = records.encode();
+
+    // Events are decoded one at a time, and their fields are only decoded if we ask:
+    let mut transfers = Vec::new();
+    let mut summaries = Vec::new();
+    for event in decode_events(&bytes, type_id, &types).unwrap() {
+        let event = event.unwrap();
+        assert_eq!(event.phase, Phase::ApplyExtrinsic(0));
+
+        if (event.pallet_name, event.event_name) == ("Balances", "Transfer") {
+            transfers.push(event.fields.decode_as_fields::<Transfer>().unwrap());
+        }
+
+        let fields = summarise(event.fields.as_composite()).unwrap();
+        summaries.push(format!("{}.{} {{ {fields} }}", event.pallet_name, event.event_name));
+    }
+
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].amount, 1000);
+    assert_eq!(
+        summaries,
+        [
+            "Balances.Transfer { from: <array>, to: <array>, amount: 1000 }",
+            "System.ExtrinsicSuccess { weight: 123 }",
+        ]
+    );
+}
+
+// Normally we'd have a type registry to hand already, but if not, we can build our own:
+fn make_type<T: scale_info::TypeInfo + 'static>() -> (u32, scale_info::PortableRegistry) {
+    let m = scale_info::MetaType::new::<T>();
+    let mut types = scale_info::Registry::new();
+    let id = types.register_type(&m);
+    let portable_registry: scale_info::PortableRegistry = types.into();
+
+    (id.id, portable_registry)
+}
@@ -0,0 +1,145 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decode arbitrary bytes against a handful of types which, between them, cover every path
+//! through the decoder. Decoding is expected to fail often, but it must never panic.
+
+#![no_main]
+
+use bitvec::{
+    order::{Lsb0, Msb0},
+    vec::BitVec,
+};
+use codec::Compact;
+use libfuzzer_sys::fuzz_target;
+use scale_decode::{
+    visitor::{
+        decode_with_visitor, encoded_size_of,
+        types::{Array, BitSequence, Composite, Sequence, Str, Tuple, Variant},
+        DecodeError, IgnoreVisitor, TypeIdFor, Unexpected, Visitor,
+    },
+    DecodeAsType,
+};
+use scale_info::{PortableRegistry, TypeInfo};
+use std::marker::PhantomData;
+
+#[derive(TypeInfo, DecodeAsType)]
+#[allow(dead_code)]
+enum Everything {
+    Numbers(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128),
+    Compacts(Compact<u8>, Compact<u16>, Compact<u32>, Compact<u64>, Compact<u128>),
+    Text { c: char, s: String },
+    Nested(Option<Option<Option<Box<Everything>>>>),
+    Collections([u16; 3], Vec<(bool, String)>),
+    Bits(BitVec<u8, Lsb0>, BitVec<u16, Msb0>, BitVec<u32, Lsb0>, BitVec<u64, Msb0>),
+    Big(primitive_types::U256, primitive_types::H256),
+}
+
+fn make_type<T: TypeInfo + 'static>() -> (u32, PortableRegistry) {
+    let m = scale_info::MetaType::new::<T>();
+    let mut types = scale_info::Registry::new();
+    let id = types.register_type(&m);
+    (id.id, types.into())
+}
+
+// Decode every part of a value, so that each visit path is exercised.
+struct VisitAll<R>(PhantomData<R>);
+
+impl<R: scale_decode::TypeResolver> Visitor for VisitAll<R> {
+    type Value<'scale, 'resolver> = ();
+    type Error = DecodeError;
+    type TypeResolver = R;
+
+    fn visit_unexpected<'scale, 'resolver>(
+        self,
+        _unexpected: Unexpected,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        Ok(())
+    }
+    fn visit_sequence<'scale, 'resolver>(
+        self,
+        value: &mut Sequence<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        while let Some(res) = value.decode_item(VisitAll(PhantomData)) {
+            res?;
+        }
+        Ok(())
+    }
+    fn visit_array<'scale, 'resolver>(
+        self,
+        value: &mut Array<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        while let Some(res) = value.decode_item(VisitAll(PhantomData)) {
+            res?;
+        }
+        Ok(())
+    }
+    fn visit_composite<'scale, 'resolver>(
+        self,
+        value: &mut Composite<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        while let Some(res) = value.decode_item(VisitAll(PhantomData)) {
+            res?;
+        }
+        Ok(())
+    }
+    fn visit_tuple<'scale, 'resolver>(
+        self,
+        value: &mut Tuple<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        while let Some(res) = value.decode_item(VisitAll(PhantomData)) {
+            res?;
+        }
+        Ok(())
+    }
+    fn visit_variant<'scale, 'resolver>(
+        self,
+        value: &mut Variant<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        self.visit_composite(value.fields(), _type_id)
+    }
+    fn visit_str<'scale, 'resolver>(
+        self,
+        value: &mut Str<'scale>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        value.as_str()?;
+        Ok(())
+    }
+    fn visit_bitsequence<'scale, 'resolver>(
+        self,
+        value: &mut BitSequence<'scale>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        for bit in value.decode()? {
+            bit?;
+        }
+        Ok(())
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let (id, types) = make_type::<Everything>();
+
+    let _ = decode_with_visitor(&mut &*data, id, &types, VisitAll(PhantomData));
+    let _ = decode_with_visitor(&mut &*data, id, &types, IgnoreVisitor::new());
+    let _ = encoded_size_of(data, id, &types);
+    let _ = Everything::decode_as_type(&mut &*data, id, &types);
+});
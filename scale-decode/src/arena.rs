@@ -0,0 +1,309 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decode values whose allocations live in a [`Bump`] arena, rather than each being allocated
+//! separately. When lots of values are decoded and then thrown away together (for instance, all
+//! of the events in a block), this cuts down on the work done by the allocator, since the whole
+//! arena can be reset at once when the values are no longer needed.
+//!
+//! [`DecodeInArena`] is implemented for anything that implements [`IntoVisitor`] (whose
+//! allocations are made as normal), as well as for `bumpalo` vecs, strings and boxes, and
+//! `&'arena str` and `&'arena [T]`, which are allocated in the arena. Sequences are allocated
+//! with the exact capacity needed to hold their items.
+//!
+//! ```rust
+//! use bumpalo::{collections::Vec as BumpVec, Bump};
+//! use codec::Encode;
+//! use scale_decode::arena::DecodeInArena;
+//!
+//! let mut types = scale_info::Registry::new();
+//! let type_id = types.register_type(&scale_info::MetaType::new::<Vec<String>>()).id;
+//! let types: scale_info::PortableRegistry = types.into();
+//!
+//! let arena = Bump::new();
+//! let encoded = vec!["a".to_string(), "b".to_string()].encode();
+//! let decoded =
+//!     <BumpVec<&str>>::decode_in_arena(&mut &*encoded, type_id, &types, &arena).unwrap();
+//!
+//! assert_eq!(&decoded[..], &["a", "b"]);
+//! ```
+
+use crate::{
+    error::Error,
+    visitor::{
+        decode_with_visitor,
+        types::{Array, Composite, Sequence, Str, Tuple},
+        DecodeAsTypeResult, DecodeItemIterator, TypeIdFor, Unexpected, Visitor,
+    },
+    IntoVisitor,
+};
+use bumpalo::{boxed::Box, collections::String, collections::Vec, Bump};
+use core::marker::PhantomData;
+use scale_type_resolver::TypeResolver;
+
+/// This is implemented for types which can be decoded given some arena to allocate into. Values
+/// can be decoded by calling [`DecodeInArena::decode_in_arena()`].
+pub trait DecodeInArena<'arena>: Sized {
+    /// The visitor type used to decode SCALE encoded bytes to `Self`.
+    type AnyVisitor<R: TypeResolver>: for<'scale, 'resolver> Visitor<
+        Value<'scale, 'resolver> = Self,
+        Error = Error,
+        TypeResolver = R,
+    >;
+    /// A means of obtaining this visitor, given the arena to allocate values into.
+    fn into_arena_visitor<R: TypeResolver>(arena: &'arena Bump) -> Self::AnyVisitor<R>;
+
+    /// Given some input bytes, a `type_id`, and type registry, attempt to decode the bytes into
+    /// `Self`, making any allocations that we can in the arena given.
+    fn decode_in_arena<R: TypeResolver>(
+        input: &mut &[u8],
+        type_id: R::TypeId,
+        types: &R,
+        arena: &'arena Bump,
+    ) -> Result<Self, Error> {
+        decode_with_visitor(input, type_id, types, Self::into_arena_visitor(arena))
+    }
+}
+
+impl<'arena, T: IntoVisitor> DecodeInArena<'arena> for T {
+    type AnyVisitor<R: TypeResolver> = T::AnyVisitor<R>;
+    fn into_arena_visitor<R: TypeResolver>(_arena: &'arena Bump) -> Self::AnyVisitor<R> {
+        T::into_visitor()
+    }
+}
+
+/// The [`Visitor`] used to decode values into some arena.
+pub struct ArenaVisitor<'arena, T, R> {
+    arena: &'arena Bump,
+    _marker: PhantomData<(T, R)>,
+}
+
+impl<'arena, T, R> ArenaVisitor<'arena, T, R> {
+    fn new(arena: &'arena Bump) -> Self {
+        ArenaVisitor { arena, _marker: PhantomData }
+    }
+}
+
+// Implement `DecodeInArena` for some type whose `ArenaVisitor` implements `Visitor`, given the
+// type parameter of any items that are decoded into the arena too.
+macro_rules! impl_decode_in_arena {
+    ($ty:ty $(, $param:ident)?) => {
+        impl<'arena $(, $param: DecodeInArena<'arena>)?> DecodeInArena<'arena> for $ty {
+            type AnyVisitor<R: TypeResolver> = ArenaVisitor<'arena, $ty, R>;
+            fn into_arena_visitor<R: TypeResolver>(arena: &'arena Bump) -> Self::AnyVisitor<R> {
+                ArenaVisitor::new(arena)
+            }
+        }
+    };
+}
+
+// Ignore single-field tuples/composites and visit the single field inside instead.
+macro_rules! visit_single_field_composite_tuple_impls {
+    () => {
+        fn visit_composite<'scale, 'resolver>(
+            self,
+            value: &mut Composite<'scale, 'resolver, R>,
+            _type_id: TypeIdFor<Self>,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            if value.remaining() != 1 {
+                return self.visit_unexpected(Unexpected::Composite);
+            }
+            value.decode_item(self).unwrap()
+        }
+        fn visit_tuple<'scale, 'resolver>(
+            self,
+            value: &mut Tuple<'scale, 'resolver, R>,
+            _type_id: TypeIdFor<Self>,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            if value.remaining() != 1 {
+                return self.visit_unexpected(Unexpected::Tuple);
+            }
+            value.decode_item(self).unwrap()
+        }
+    };
+}
+
+// Decode the items of some sequence or array into a vec which is allocated in the arena.
+fn decode_items_in_arena<'arena, 'scale, 'resolver, T, R, I>(
+    items: &mut I,
+    len: usize,
+    arena: &'arena Bump,
+) -> Result<Vec<'arena, T>, Error>
+where
+    T: DecodeInArena<'arena>,
+    R: TypeResolver,
+    I: DecodeItemIterator<'scale, 'resolver, R>,
+{
+    let mut vec = Vec::with_capacity_in(len, arena);
+    while let Some(item) = items.decode_item(T::into_arena_visitor::<R>(arena)) {
+        vec.push(item?);
+    }
+    Ok(vec)
+}
+
+impl<'arena, T: DecodeInArena<'arena>, R: TypeResolver> Visitor
+    for ArenaVisitor<'arena, Vec<'arena, T>, R>
+{
+    type Value<'scale, 'resolver> = Vec<'arena, T>;
+    type Error = Error;
+    type TypeResolver = R;
+
+    fn visit_sequence<'scale, 'resolver>(
+        self,
+        value: &mut Sequence<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let len = value.remaining();
+        decode_items_in_arena(value, len, self.arena)
+    }
+    fn visit_array<'scale, 'resolver>(
+        self,
+        value: &mut Array<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let len = value.remaining();
+        decode_items_in_arena(value, len, self.arena)
+    }
+    visit_single_field_composite_tuple_impls!();
+}
+impl_decode_in_arena!(Vec<'arena, T>, T);
+
+impl<'arena, T: DecodeInArena<'arena>, R: TypeResolver> Visitor
+    for ArenaVisitor<'arena, &'arena [T], R>
+{
+    type Value<'scale, 'resolver> = &'arena [T];
+    type Error = Error;
+    type TypeResolver = R;
+
+    fn unchecked_decode_as_type<'scale, 'resolver>(
+        self,
+        input: &mut &'scale [u8],
+        type_id: TypeIdFor<Self>,
+        types: &'resolver Self::TypeResolver,
+    ) -> DecodeAsTypeResult<Self, Result<Self::Value<'scale, 'resolver>, Self::Error>> {
+        let res = Vec::<T>::decode_in_arena(input, type_id, types, self.arena);
+        DecodeAsTypeResult::Decoded(res.map(Vec::into_bump_slice))
+    }
+}
+impl_decode_in_arena!(&'arena [T], T);
+
+impl<'arena, R: TypeResolver> Visitor for ArenaVisitor<'arena, String<'arena>, R> {
+    type Value<'scale, 'resolver> = String<'arena>;
+    type Error = Error;
+    type TypeResolver = R;
+
+    fn visit_str<'scale, 'resolver>(
+        self,
+        value: &mut Str<'scale>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        Ok(String::from_str_in(value.as_str()?, self.arena))
+    }
+    visit_single_field_composite_tuple_impls!();
+}
+impl_decode_in_arena!(String<'arena>);
+
+impl<'arena, R: TypeResolver> Visitor for ArenaVisitor<'arena, &'arena str, R> {
+    type Value<'scale, 'resolver> = &'arena str;
+    type Error = Error;
+    type TypeResolver = R;
+
+    fn visit_str<'scale, 'resolver>(
+        self,
+        value: &mut Str<'scale>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        Ok(self.arena.alloc_str(value.as_str()?))
+    }
+    visit_single_field_composite_tuple_impls!();
+}
+impl_decode_in_arena!(&'arena str);
+
+impl<'arena, T: DecodeInArena<'arena>, R: TypeResolver> Visitor
+    for ArenaVisitor<'arena, Box<'arena, T>, R>
+{
+    type Value<'scale, 'resolver> = Box<'arena, T>;
+    type Error = Error;
+    type TypeResolver = R;
+
+    fn unchecked_decode_as_type<'scale, 'resolver>(
+        self,
+        input: &mut &'scale [u8],
+        type_id: TypeIdFor<Self>,
+        types: &'resolver Self::TypeResolver,
+    ) -> DecodeAsTypeResult<Self, Result<Self::Value<'scale, 'resolver>, Self::Error>> {
+        let res = T::decode_in_arena(input, type_id, types, self.arena);
+        DecodeAsTypeResult::Decoded(res.map(|val| Box::new_in(val, self.arena)))
+    }
+}
+impl_decode_in_arena!(Box<'arena, T>, T);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::vec;
+    use codec::Encode;
+    use scale_info::PortableRegistry;
+
+    fn make_type<T: scale_info::TypeInfo + 'static>() -> (u32, PortableRegistry) {
+        let m = scale_info::MetaType::new::<T>();
+        let mut types = scale_info::Registry::new();
+        let id = types.register_type(&m);
+        (id.id, types.into())
+    }
+
+    #[test]
+    fn decodes_nested_containers_into_arena() {
+        let (type_id, types) =
+            make_type::<alloc::vec::Vec<alloc::vec::Vec<alloc::string::String>>>();
+        let encoded =
+            vec![vec!["a".to_string()], vec![], vec!["b".to_string(), "c".to_string()]].encode();
+
+        let arena = Bump::new();
+        let decoded =
+            <Vec<&[String]>>::decode_in_arena(&mut &*encoded, type_id, &types, &arena).unwrap();
+
+        assert_eq!(decoded.capacity(), 3);
+        assert_eq!(decoded[0], ["a"]);
+        assert!(decoded[1].is_empty());
+        assert_eq!(decoded[2], ["b", "c"]);
+    }
+
+    #[test]
+    fn decodes_through_single_field_wrappers() {
+        #[derive(Encode, scale_info::TypeInfo)]
+        struct Wrapper {
+            name: alloc::string::String,
+        }
+
+        let (type_id, types) = make_type::<Wrapper>();
+        let encoded = Wrapper { name: "hello".to_string() }.encode();
+
+        let arena = Bump::new();
+        let decoded =
+            <Box<&str>>::decode_in_arena(&mut &*encoded, type_id, &types, &arena).unwrap();
+        assert_eq!(*decoded, "hello");
+    }
+
+    #[test]
+    fn errors_on_the_wrong_shape() {
+        let (type_id, types) = make_type::<(u8, u8)>();
+        let encoded = (1u8, 2u8).encode();
+
+        let arena = Bump::new();
+        assert!(<String>::decode_in_arena(&mut &*encoded, type_id, &types, &arena).is_err());
+    }
+}
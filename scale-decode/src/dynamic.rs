@@ -0,0 +1,417 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The [`Visitor`] trait relies on generic associated types and generic methods, and so it can't
+//! be used as a trait object. This module provides an object safe alternative, [`DynVisitor`],
+//! which is handed owned [`DynValue`]s rather than borrowing from the input bytes or the type
+//! resolver. This makes it suitable for plugging in decoding logic that is only known at runtime,
+//! for instance when exposing decoding to other languages.
+//!
+//! A [`DynVisitor`] is called once for each value, children first. Containers are handed the
+//! values that the visitor returned for each of their items.
+//!
+//! ```rust
+//! use codec::Encode;
+//! use scale_decode::dynamic::{decode_with_dyn_visitor, DynValue, DynVisitor};
+//! use scale_decode::Error;
+//! use scale_info::{PortableRegistry, TypeInfo};
+//!
+//! // Sum up every number we find.
+//! struct Sum;
+//! impl DynVisitor for Sum {
+//!     type Value = u64;
+//!     fn visit(&mut self, value: DynValue<u64>) -> Result<u64, Error> {
+//!         Ok(match value {
+//!             DynValue::U8(n) => n as u64,
+//!             DynValue::U32(n) => n as u64,
+//!             DynValue::Sequence(vals) | DynValue::Tuple(vals) => vals.into_iter().sum(),
+//!             _ => 0,
+//!         })
+//!     }
+//! }
+//!
+//! let m = scale_info::MetaType::new::<(u8, Vec<u32>)>();
+//! let mut types = scale_info::Registry::new();
+//! let type_id = types.register_type(&m).id;
+//! let types: PortableRegistry = types.into();
+//!
+//! let encoded = (1u8, vec![2u32, 3]).encode();
+//! let visitor: &mut dyn DynVisitor<Value = u64> = &mut Sum;
+//! let sum = decode_with_dyn_visitor(&mut &*encoded, type_id, &types, visitor).unwrap();
+//! assert_eq!(sum, 6);
+//! ```
+
+use crate::{
+    error::{Error, ErrorKind},
+    visitor::{
+        decode_with_visitor,
+        types::{Array, BitSequence, Composite, Sequence, Str, Tuple, Variant},
+        CompactLocation, DecodeItemIterator, TypeIdFor, Visitor,
+    },
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::marker::PhantomData;
+use scale_bits::{Bits, Format};
+use scale_type_resolver::TypeResolver;
+
+/// Decode some bytes, whose structure is given by the type ID and type resolver provided, using
+/// the [`DynVisitor`] provided. The provided pointer to the bytes is moved forwards past the
+/// decoded value.
+pub fn decode_with_dyn_visitor<R: TypeResolver, V>(
+    input: &mut &[u8],
+    type_id: R::TypeId,
+    types: &R,
+    visitor: &mut dyn DynVisitor<Value = V>,
+) -> Result<V, Error> {
+    decode_with_visitor(input, type_id, types, DynVisitorAdapter::new(visitor))
+}
+
+/// An object safe visitor, which is handed each value that is decoded as a [`DynValue`], and
+/// returns some [`DynVisitor::Value`] to represent it. Use [`DynVisitorAdapter`] to turn one of
+/// these into a [`Visitor`].
+pub trait DynVisitor {
+    /// The type of value that this visitor returns for each value that is decoded.
+    type Value;
+    /// Called for each value that is decoded. Any items or fields in the value have already been
+    /// handed to this visitor, and it is given back whatever was returned for each of them.
+    fn visit(&mut self, value: DynValue<Self::Value>) -> Result<Self::Value, Error>;
+}
+
+impl<T: DynVisitor + ?Sized> DynVisitor for &mut T {
+    type Value = T::Value;
+    fn visit(&mut self, value: DynValue<Self::Value>) -> Result<Self::Value, Error> {
+        (**self).visit(value)
+    }
+}
+
+impl<T: DynVisitor + ?Sized> DynVisitor for alloc::boxed::Box<T> {
+    type Value = T::Value;
+    fn visit(&mut self, value: DynValue<Self::Value>) -> Result<Self::Value, Error> {
+        (**self).visit(value)
+    }
+}
+
+/// An owned value handed to a [`DynVisitor`]. Items and fields in containers are represented by
+/// whatever the visitor returned for them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DynValue<V> {
+    /// A boolean.
+    Bool(bool),
+    /// A char.
+    Char(char),
+    /// A `u8`.
+    U8(u8),
+    /// A `u16`.
+    U16(u16),
+    /// A `u32`.
+    U32(u32),
+    /// A `u64`.
+    U64(u64),
+    /// A `u128`.
+    U128(u128),
+    /// A `u256`, as little endian bytes.
+    U256([u8; 32]),
+    /// An `i8`.
+    I8(i8),
+    /// An `i16`.
+    I16(i16),
+    /// An `i32`.
+    I32(i32),
+    /// An `i64`.
+    I64(i64),
+    /// An `i128`.
+    I128(i128),
+    /// An `i256`, as little endian bytes.
+    I256([u8; 32]),
+    /// An `f32`.
+    #[cfg(feature = "float")]
+    F32(f32),
+    /// An `f64`.
+    #[cfg(feature = "float")]
+    F64(f64),
+    /// A compact encoded unsigned number of any size.
+    Compact(u128),
+    /// A string.
+    Str(String),
+    /// A sequence of bits, along with the format that they were encoded with.
+    BitSequence(Bits, Format),
+    /// A sequence of values, which was prefixed with its length when encoded.
+    Sequence(Vec<V>),
+    /// A fixed length array of values.
+    Array(Vec<V>),
+    /// A tuple of values.
+    Tuple(Vec<V>),
+    /// A composite type, along with the name of each field if it has one.
+    Composite(Vec<(Option<String>, V)>),
+    /// A variant, along with the name of each of its fields if it has one.
+    Variant {
+        /// The index of the variant.
+        index: u8,
+        /// The name of the variant.
+        name: String,
+        /// The fields of the variant.
+        fields: Vec<(Option<String>, V)>,
+    },
+}
+
+/// A [`Visitor`] which hands each value that it decodes to some [`DynVisitor`].
+pub struct DynVisitorAdapter<'v, V, R> {
+    visitor: &'v mut dyn DynVisitor<Value = V>,
+    marker: PhantomData<R>,
+}
+
+impl<'v, V, R> DynVisitorAdapter<'v, V, R> {
+    /// Construct a new [`DynVisitorAdapter`] which hands values to the given [`DynVisitor`].
+    pub fn new(visitor: &'v mut dyn DynVisitor<Value = V>) -> Self {
+        DynVisitorAdapter { visitor, marker: PhantomData }
+    }
+    fn reborrow(&mut self) -> DynVisitorAdapter<'_, V, R> {
+        DynVisitorAdapter { visitor: &mut *self.visitor, marker: PhantomData }
+    }
+}
+
+macro_rules! visit_number_fn_impl {
+    ($name:ident : $ty:ty => $variant:ident) => {
+        fn $name<'scale, 'resolver>(
+            self,
+            value: $ty,
+            _type_id: TypeIdFor<Self>,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            self.visitor.visit(DynValue::$variant(value))
+        }
+    };
+}
+
+macro_rules! visit_compact_fn_impl {
+    ($name:ident : $ty:ty) => {
+        fn $name<'scale, 'resolver>(
+            self,
+            value: $ty,
+            _location: CompactLocation,
+            _type_id: TypeIdFor<Self>,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            self.visitor.visit(DynValue::Compact(value.into()))
+        }
+    };
+}
+
+impl<'v, V, R: TypeResolver> DynVisitorAdapter<'v, V, R> {
+    // Decode each item in some container.
+    fn decode_items<'scale, 'resolver, D>(&mut self, items: &mut D) -> Result<Vec<V>, Error>
+    where
+        D: DecodeItemIterator<'scale, 'resolver, R>,
+    {
+        let mut idx = 0;
+        let mut vals = Vec::new();
+        while let Some(val) = items.decode_item(self.reborrow()) {
+            vals.push(val.map_err(|e| e.at_idx(idx))?);
+            idx += 1;
+        }
+        Ok(vals)
+    }
+
+    // Decode each field in some composite, along with its name.
+    fn decode_fields(
+        &mut self,
+        fields: &mut Composite<'_, '_, R>,
+    ) -> Result<Vec<(Option<String>, V)>, Error> {
+        let mut vals = Vec::new();
+        for (idx, field) in fields.by_ref().enumerate() {
+            let field = field?;
+            let name = field.name();
+            let val = field.decode_with_visitor(self.reborrow()).map_err(|e| match name {
+                Some(name) => e.at_field(name.to_string()),
+                None => e.at_idx(idx),
+            })?;
+            vals.push((name.map(ToString::to_string), val));
+        }
+        Ok(vals)
+    }
+}
+
+impl<'v, V, R: TypeResolver> Visitor for DynVisitorAdapter<'v, V, R> {
+    type Value<'scale, 'resolver> = V;
+    type Error = Error;
+    type TypeResolver = R;
+
+    visit_number_fn_impl!(visit_bool: bool => Bool);
+    visit_number_fn_impl!(visit_char: char => Char);
+    visit_number_fn_impl!(visit_u8: u8 => U8);
+    visit_number_fn_impl!(visit_u16: u16 => U16);
+    visit_number_fn_impl!(visit_u32: u32 => U32);
+    visit_number_fn_impl!(visit_u64: u64 => U64);
+    visit_number_fn_impl!(visit_u128: u128 => U128);
+    visit_number_fn_impl!(visit_i8: i8 => I8);
+    visit_number_fn_impl!(visit_i16: i16 => I16);
+    visit_number_fn_impl!(visit_i32: i32 => I32);
+    visit_number_fn_impl!(visit_i64: i64 => I64);
+    visit_number_fn_impl!(visit_i128: i128 => I128);
+    #[cfg(feature = "float")]
+    visit_number_fn_impl!(visit_f32: f32 => F32);
+    #[cfg(feature = "float")]
+    visit_number_fn_impl!(visit_f64: f64 => F64);
+    visit_compact_fn_impl!(visit_compact_u8: u8);
+    visit_compact_fn_impl!(visit_compact_u16: u16);
+    visit_compact_fn_impl!(visit_compact_u32: u32);
+    visit_compact_fn_impl!(visit_compact_u64: u64);
+    visit_compact_fn_impl!(visit_compact_u128: u128);
+    fn visit_u256<'resolver>(
+        self,
+        value: &[u8; 32],
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'_, 'resolver>, Self::Error> {
+        self.visitor.visit(DynValue::U256(*value))
+    }
+    fn visit_i256<'resolver>(
+        self,
+        value: &[u8; 32],
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'_, 'resolver>, Self::Error> {
+        self.visitor.visit(DynValue::I256(*value))
+    }
+    fn visit_str<'scale, 'resolver>(
+        self,
+        value: &mut Str<'scale>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        self.visitor.visit(DynValue::Str(value.as_str()?.to_string()))
+    }
+    fn visit_bitsequence<'scale, 'resolver>(
+        self,
+        value: &mut BitSequence<'scale>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let bits = value
+            .decode()?
+            .collect::<Result<Bits, _>>()
+            .map_err(|e| Error::new(ErrorKind::VisitorDecodeError(e.into())))?;
+        self.visitor.visit(DynValue::BitSequence(bits, value.format()))
+    }
+    fn visit_sequence<'scale, 'resolver>(
+        mut self,
+        value: &mut Sequence<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let vals = self.decode_items(value)?;
+        self.visitor.visit(DynValue::Sequence(vals))
+    }
+    fn visit_array<'scale, 'resolver>(
+        mut self,
+        value: &mut Array<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let vals = self.decode_items(value)?;
+        self.visitor.visit(DynValue::Array(vals))
+    }
+    fn visit_tuple<'scale, 'resolver>(
+        mut self,
+        value: &mut Tuple<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let vals = self.decode_items(value)?;
+        self.visitor.visit(DynValue::Tuple(vals))
+    }
+    fn visit_composite<'scale, 'resolver>(
+        mut self,
+        value: &mut Composite<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let fields = self.decode_fields(value)?;
+        self.visitor.visit(DynValue::Composite(fields))
+    }
+    fn visit_variant<'scale, 'resolver>(
+        mut self,
+        value: &mut Variant<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let name = value.name();
+        let fields =
+            self.decode_fields(value.fields()).map_err(|e| e.at_variant(name.to_string()))?;
+        self.visitor.visit(DynValue::Variant {
+            index: value.index(),
+            name: name.to_string(),
+            fields,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::{boxed::Box, format, vec};
+    use codec::{Compact, Encode};
+    use scale_info::TypeInfo;
+
+    fn make_type<T: TypeInfo + 'static>() -> (u32, scale_info::PortableRegistry) {
+        let m = scale_info::MetaType::new::<T>();
+        let mut types = scale_info::Registry::new();
+        let id = types.register_type(&m);
+        (id.id, types.into())
+    }
+
+    // Render values into a string, as some dynamic language binding might build up its own
+    // representation of each value.
+    struct Render;
+    impl DynVisitor for Render {
+        type Value = String;
+        fn visit(&mut self, value: DynValue<String>) -> Result<String, Error> {
+            Ok(match value {
+                DynValue::Bool(b) => format!("{b}"),
+                DynValue::U8(n) => format!("{n}"),
+                DynValue::Compact(n) => format!("{n}"),
+                DynValue::Str(s) => format!("{s:?}"),
+                DynValue::Sequence(vals) => format!("[{}]", vals.join(", ")),
+                DynValue::Tuple(vals) => format!("({})", vals.join(", ")),
+                DynValue::Variant { name, fields, .. } => {
+                    let fields: Vec<_> = fields
+                        .into_iter()
+                        .map(|(n, v)| format!("{}: {v}", n.unwrap_or_default()))
+                        .collect();
+                    format!("{name} {{ {} }}", fields.join(", "))
+                }
+                _ => return Err(Error::custom_str("unsupported value")),
+            })
+        }
+    }
+
+    #[test]
+    fn dyn_visitors_are_handed_nested_values() {
+        #[derive(Encode, TypeInfo)]
+        enum Foo {
+            Bar { a: Compact<u32>, b: (bool, Vec<u8>) },
+        }
+        let (type_id, types) = make_type::<Foo>();
+        let encoded = Foo::Bar { a: Compact(7), b: (true, vec![1, 2]) }.encode();
+
+        let mut visitor: Box<dyn DynVisitor<Value = String>> = Box::new(Render);
+        let cursor = &mut &*encoded;
+        let rendered = decode_with_dyn_visitor(cursor, type_id, &types, &mut visitor).unwrap();
+        assert_eq!(rendered, "Bar { a: 7, b: (true, [1, 2]) }");
+        assert!(cursor.is_empty());
+    }
+
+    #[test]
+    fn dyn_visitor_errors_are_given_context() {
+        let (type_id, types) = make_type::<(bool, Vec<u32>)>();
+        let encoded = (true, vec![1u32]).encode();
+
+        let err = decode_with_dyn_visitor(&mut &*encoded, type_id, &types, &mut Render)
+            .expect_err("u32s are not supported");
+        assert_eq!(err.context().path().to_string(), "[0].[1]");
+    }
+}
@@ -16,14 +16,40 @@
 //! This module provides a [`Context`] type, which tracks the path
 //! that we're attempting to encode to aid in error reporting.
 
-use alloc::{borrow::Cow, vec::Vec};
+use alloc::{borrow::Cow, boxed::Box};
+#[cfg(feature = "type-id-path")]
+use alloc::{format, string::String, vec::Vec};
+use smallvec::SmallVec;
+
+// How many locations are stored inline before the path spills onto the heap. Each location
+// takes 32 bytes, and any more than this would push `Error` over the size at which clippy starts
+// warning about large `Result` types.
+const INLINE_LOCATIONS: usize = 1;
+
+// When the path first spills onto the heap, reserve room for this many locations, so that
+// errors in deeply nested types don't reallocate at every level.
+const SPILLED_LOCATIONS: usize = 4;
 
 /// A cheaply clonable opaque context which allows us to track the current
 /// location into a type that we're trying to encode, to aid in
 /// error reporting.
+///
+/// The first location is stored inline, so errors that are given a single location
+/// don't allocate at all.
 #[derive(Clone, Default, Debug)]
 pub struct Context {
-    path: Vec<Location>,
+    path: SmallVec<[Location; INLINE_LOCATIONS]>,
+    // Rarely set, so boxed to keep the context (and thus every error) small.
+    extra: Option<Box<Extra>>,
+}
+
+// Details which are only sometimes given to an error.
+#[derive(Clone, Default, Debug)]
+struct Extra {
+    expected: Option<Cow<'static, str>>,
+    // The type IDs that we were decoding when the error occurred, innermost first.
+    #[cfg(feature = "type-id-path")]
+    type_ids: Vec<String>,
 }
 
 impl Context {
@@ -31,18 +57,60 @@ impl Context {
     pub fn new() -> Context {
         Default::default()
     }
+    /// Construct a new, empty context with room for at least `capacity` locations
+    /// before any further allocation is needed.
+    pub fn with_capacity(capacity: usize) -> Context {
+        Context { path: SmallVec::with_capacity(capacity), extra: None }
+    }
+    /// Reserve room for at least `additional` more locations.
+    pub fn reserve(&mut self, additional: usize) {
+        self.path.reserve(additional);
+    }
+    /// Remove all locations and any expected description, keeping any allocated
+    /// capacity so that the context can be reused for another error.
+    pub fn clear(&mut self) {
+        self.path.clear();
+        self.extra = None;
+    }
     /// Return a new context with the given location appended.
     pub fn push(&mut self, loc: Location) {
+        if self.path.len() == INLINE_LOCATIONS && !self.path.spilled() {
+            self.path.reserve(SPILLED_LOCATIONS - INLINE_LOCATIONS);
+        }
         self.path.push(loc);
     }
     /// Return the current path.
     pub fn path(&self) -> Path<'_> {
         Path(Cow::Borrowed(&self.path))
     }
+    /// Set a human friendly description of the thing we were trying to decode into.
+    pub fn set_expected(&mut self, expected: impl Into<Cow<'static, str>>) {
+        self.extra.get_or_insert_with(Default::default).expected = Some(expected.into());
+    }
+    /// Return a human friendly description of the thing we were trying to decode
+    /// into, if one was given.
+    pub fn expected(&self) -> Option<&str> {
+        self.extra.as_ref()?.expected.as_deref()
+    }
+    /// Note the ID of a type that we were decoding. Type IDs are stored using their
+    /// [`core::fmt::Debug`] representation, so that this doesn't depend on the type resolver.
+    #[cfg(feature = "type-id-path")]
+    pub fn push_type_id(&mut self, type_id: impl core::fmt::Debug) {
+        self.extra.get_or_insert_with(Default::default).type_ids.push(format!("{type_id:?}"));
+    }
+    /// Return the IDs of the types that we were decoding when the error occurred, innermost
+    /// first like the locations in the [`Context::path()`]. An ID is noted each time a value is
+    /// decoded via [`crate::DecodeAsType`], which includes each field of a derived type. For a
+    /// [`scale_info::PortableRegistry`](https://docs.rs/scale-info) these are the `u32` IDs of
+    /// the types in the registry.
+    #[cfg(feature = "type-id-path")]
+    pub fn type_ids(&self) -> impl Iterator<Item = &str> {
+        self.extra.iter().flat_map(|extra| extra.type_ids.iter()).map(|id| &**id)
+    }
 }
 
 /// The current path that we're trying to encode.
-pub struct Path<'a>(Cow<'a, Vec<Location>>);
+pub struct Path<'a>(Cow<'a, [Location]>);
 
 impl<'a> Path<'a> {
     /// Cheaply convert the path to an owned version.
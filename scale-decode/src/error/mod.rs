@@ -18,8 +18,10 @@ mod context;
 
 pub use context::{Context, Location};
 
-use crate::visitor::DecodeError;
-use alloc::{borrow::Cow, boxed::Box, string::String, vec::Vec};
+use crate::visitor::{DecodeError, InvalidBitSequence};
+#[cfg(feature = "boxed-custom-errors")]
+use alloc::boxed::Box;
+use alloc::{borrow::Cow, string::String, vec::Vec};
 use core::fmt::Display;
 
 /// An error produced while attempting to decode some type.
@@ -36,25 +38,92 @@ impl Error {
     pub fn new(kind: ErrorKind) -> Error {
         Error { context: Context::new(), kind }
     }
+    /// Construct a new error given an error kind, with room for `capacity` locations to be added
+    /// via [`Error::at()`] and friends before any allocation is needed. This is useful when an
+    /// error is expected to be propagated up through deeply nested types.
+    pub fn with_capacity(kind: ErrorKind, capacity: usize) -> Error {
+        Error { context: Context::with_capacity(capacity), kind }
+    }
+    /// Construct a new error given an error kind and some existing context. Together with
+    /// [`Error::into_parts()`] and [`Context::clear()`], this allows the allocation behind a
+    /// context to be reused when lots of errors are expected, for instance when probing some
+    /// bytes against a number of candidate types.
+    pub fn with_context(kind: ErrorKind, context: Context) -> Error {
+        Error { context, kind }
+    }
+    /// Break this error into its kind and context.
+    pub fn into_parts(self) -> (ErrorKind, Context) {
+        (self.kind, self.context)
+    }
     /// Construct a new, custom error.
+    #[cfg(feature = "boxed-custom-errors")]
     pub fn custom(error: impl core::error::Error + Send + Sync + 'static) -> Error {
         Error::new(ErrorKind::Custom(Box::new(error)))
     }
-    /// Construct a custom error from a static string.
+    /// Construct a custom error from some other error. With the `boxed-custom-errors` feature,
+    /// this is the same as [`Error::custom()`]. Without it, only the message of the error is kept,
+    /// as with [`Error::custom_string()`].
+    pub fn custom_error(error: impl core::error::Error + Send + Sync + 'static) -> Error {
+        #[cfg(feature = "boxed-custom-errors")]
+        {
+            Error::custom(error)
+        }
+        #[cfg(not(feature = "boxed-custom-errors"))]
+        {
+            use alloc::string::ToString;
+            Error::custom_string(error.to_string())
+        }
+    }
+    /// Construct a custom error from a message and some error code. Unlike [`Error::custom()`],
+    /// this doesn't box anything, and is always available.
+    pub fn custom_message(message: impl Into<Cow<'static, str>>, code: u32) -> Error {
+        Error::new(ErrorKind::CustomMessage { message: message.into(), code })
+    }
+    /// Construct a custom error from a static string. Without the `boxed-custom-errors` feature,
+    /// this is an [`ErrorKind::CustomMessage`] with a code of 0.
     pub fn custom_str(error: &'static str) -> Error {
-        #[derive(Debug, thiserror::Error)]
-        #[error("{0}")]
-        pub struct StrError(pub &'static str);
+        #[cfg(feature = "boxed-custom-errors")]
+        {
+            #[derive(Debug, thiserror::Error)]
+            #[error("{0}")]
+            pub struct StrError(pub &'static str);
 
-        Error::new(ErrorKind::Custom(Box::new(StrError(error))))
+            Error::new(ErrorKind::Custom(Box::new(StrError(error))))
+        }
+        #[cfg(not(feature = "boxed-custom-errors"))]
+        Error::custom_message(error, 0)
     }
-    /// Construct a custom error from an owned string.
+    /// Construct a custom error from an owned string. Without the `boxed-custom-errors` feature,
+    /// this is an [`ErrorKind::CustomMessage`] with a code of 0.
     pub fn custom_string(error: String) -> Error {
-        #[derive(Debug, thiserror::Error)]
-        #[error("{0}")]
-        pub struct StringError(String);
+        #[cfg(feature = "boxed-custom-errors")]
+        {
+            #[derive(Debug, thiserror::Error)]
+            #[error("{0}")]
+            pub struct StringError(String);
 
-        Error::new(ErrorKind::Custom(Box::new(StringError(error))))
+            Error::new(ErrorKind::Custom(Box::new(StringError(error))))
+        }
+        #[cfg(not(feature = "boxed-custom-errors"))]
+        Error::custom_message(error, 0)
+    }
+    /// Construct an [`ErrorKind::CannotFindVariant`] error, suggesting whichever expected variant
+    /// name is closest to the one that we were given.
+    pub fn cannot_find_variant(
+        got: impl Into<String>,
+        index: u8,
+        expected: Vec<&'static str>,
+    ) -> Error {
+        let got = got.into();
+        let suggestion = closest_name(&got, &expected);
+        Error::new(ErrorKind::CannotFindVariant { got, index, expected, suggestion })
+    }
+    /// Construct an [`ErrorKind::CannotFindVariantName`] error, suggesting whichever expected
+    /// variant name is closest to the one that we were given.
+    pub fn cannot_find_variant_name(got: impl Into<String>, expected: Vec<&'static str>) -> Error {
+        let got = got.into();
+        let suggestion = closest_name(&got, &expected);
+        Error::new(ErrorKind::CannotFindVariantName { got, expected, suggestion })
     }
     /// Retrieve more information about what went wrong.
     pub fn kind(&self) -> &ErrorKind {
@@ -67,22 +136,36 @@ impl Error {
     /// Give some context to the error.
     pub fn at(mut self, loc: Location) -> Self {
         self.context.push(loc);
-        Error { context: self.context, kind: self.kind }
+        self
     }
     /// Note which sequence index the error occurred in.
     pub fn at_idx(mut self, idx: usize) -> Self {
         self.context.push(Location::idx(idx));
-        Error { context: self.context, kind: self.kind }
+        self
     }
     /// Note which field the error occurred in.
     pub fn at_field(mut self, field: impl Into<Cow<'static, str>>) -> Self {
         self.context.push(Location::field(field));
-        Error { context: self.context, kind: self.kind }
+        self
     }
     /// Note which variant the error occurred in.
     pub fn at_variant(mut self, variant: impl Into<Cow<'static, str>>) -> Self {
         self.context.push(Location::variant(variant));
-        Error { context: self.context, kind: self.kind }
+        self
+    }
+    /// Note the ID of the type that we were decoding when the error occurred. This is done
+    /// automatically each time a value is decoded via [`crate::DecodeAsType`], so that
+    /// [`Context::type_ids()`] can be used to find the types in the resolver which led to the error.
+    #[cfg(feature = "type-id-path")]
+    pub fn at_type_id(mut self, type_id: impl core::fmt::Debug) -> Self {
+        self.context.push_type_id(type_id);
+        self
+    }
+    /// Note what we were trying to decode into when the error occurred, for instance
+    /// "an XCM v3 MultiLocation". This is shown alongside the error.
+    pub fn expecting(mut self, expected: impl Into<Cow<'static, str>>) -> Self {
+        self.context.set_expected(expected);
+        self
     }
 }
 
@@ -90,13 +173,27 @@ impl Display for Error {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let path = self.context.path();
         let kind = &self.kind;
-        write!(f, "Error at {path}: {kind}")
+        match self.context.expected() {
+            Some(expected) => write!(f, "Error at {path} (expecting {expected}): {kind}"),
+            None => write!(f, "Error at {path}: {kind}"),
+        }
     }
 }
 
 impl From<DecodeError> for Error {
     fn from(err: DecodeError) -> Error {
-        Error::new(err.into())
+        match err {
+            DecodeError::InvalidBitSequence(details) => {
+                Error::new(ErrorKind::InvalidBitSequence(details))
+            }
+            err => Error::new(err.into()),
+        }
+    }
+}
+
+impl From<core::convert::Infallible> for Error {
+    fn from(err: core::convert::Infallible) -> Error {
+        match err {}
     }
 }
 
@@ -120,13 +217,37 @@ pub enum ErrorKind {
         /// A string representation of the numeric value that was out of range.
         value: String,
     },
-    /// We cannot find the variant we're trying to decode from in the target type.
-    #[error("Cannot find variant {got}; expects one of {expected:?}")]
+    /// We cannot decode a value of zero into a non-zero number type.
+    #[error("Cannot decode a value of zero into a non-zero number type")]
+    ZeroValueForNonZeroType,
+    /// We cannot find the variant we're trying to decode from in the target type. See
+    /// [`Error::cannot_find_variant()`].
+    #[error(
+        "Cannot find variant {got} (index {index}); expects one of {expected:?}{}",
+        did_you_mean(.suggestion)
+    )]
     CannotFindVariant {
         /// The variant that we are given back from the encoded bytes.
         got: String,
+        /// The index of the variant that we are given back from the encoded bytes.
+        index: u8,
         /// The possible variants that we can decode into.
         expected: Vec<&'static str>,
+        /// The expected variant whose name is closest to the one we were given, if any is
+        /// close enough to be a likely match.
+        suggestion: Option<&'static str>,
+    },
+    /// Like [`ErrorKind::CannotFindVariant`], but for when the variant was only given by name,
+    /// for instance in a tagged composite. See [`Error::cannot_find_variant_name()`].
+    #[error("Cannot find variant {got}; expects one of {expected:?}{}", did_you_mean(.suggestion))]
+    CannotFindVariantName {
+        /// The name of the variant that we were given.
+        got: String,
+        /// The possible variants that we can decode into.
+        expected: Vec<&'static str>,
+        /// The expected variant whose name is closest to the one we were given, if any is
+        /// close enough to be a likely match.
+        suggestion: Option<&'static str>,
     },
     /// The types line up, but the expected length of the target type is different from the length of the input value.
     #[error("Cannot decode from type; expected length {expected_len} but got length {actual_len}")]
@@ -136,13 +257,214 @@ pub enum ErrorKind {
         /// Length fo the type we're trying to decode into
         expected_len: usize,
     },
+    /// The number of fields given doesn't line up with the named fields of the type we're
+    /// decoding into, so we can't decode them by position.
+    #[error("Cannot decode {} fields {found:?} into fields {expected:?}", found.len())]
+    FieldMismatch {
+        /// The names of the fields we are trying to decode into.
+        expected: Vec<&'static str>,
+        /// The names of the fields we are given (or `None` for each unnamed field).
+        found: Vec<Option<String>>,
+    },
     /// Cannot find a field that we need to decode to our target type
     #[error("Field {name} does not exist in our encoded data")]
     CannotFindField {
         /// Name of the field which was not provided.
         name: String,
     },
+    /// Cannot parse a field name into the key type of the map we're decoding into.
+    #[error("Cannot parse field name {key} into a map key: {reason}")]
+    CannotParseKey {
+        /// The field name which could not be parsed.
+        key: String,
+        /// Why the field name could not be parsed.
+        reason: String,
+    },
+    /// An extrinsic has a version that we don't know how to decode.
+    #[error("Cannot decode extrinsic with version {version}")]
+    UnsupportedExtrinsicVersion {
+        /// The version of the extrinsic.
+        version: u8,
+    },
+    /// Some pallet or item could not be found in the metadata handed to `crate::metadata`.
+    #[error("Cannot find {item} {name} in the metadata")]
+    CannotFindMetadataItem {
+        /// What we were looking for, for instance "pallet" or "storage entry".
+        item: &'static str,
+        /// The name of the thing we were looking for.
+        name: String,
+    },
+    /// A bit sequence could not be decoded, for instance because there weren't enough bytes to
+    /// hold the number of bits that it declared.
+    #[error("{0}")]
+    InvalidBitSequence(InvalidBitSequence),
+    /// A value was nested inside itself more deeply than allowed. See the `recursion` module,
+    /// which needs the `std` feature.
+    #[error("Cannot decode a value nested more than {max_depth} levels deep")]
+    MaxDepthExceeded {
+        /// The maximum depth that the value is allowed to be nested to.
+        max_depth: usize,
+    },
+    /// Decoding succeeded, but not all of the input bytes were used.
+    #[error("Decoding succeeded but {count} input bytes were left over")]
+    LeftoverBytes {
+        /// The number of bytes left over.
+        count: usize,
+    },
     /// A custom error.
+    #[cfg(feature = "boxed-custom-errors")]
     #[error("Custom error: {0}")]
     Custom(Box<dyn core::error::Error + Send + Sync + 'static>),
+    /// A custom error which is described by a message and some error code, and so doesn't need
+    /// to be boxed. See [`Error::custom_message()`].
+    #[error("Custom error: {message} (code {code})")]
+    CustomMessage {
+        /// A description of the error.
+        message: Cow<'static, str>,
+        /// Some code which identifies the error.
+        code: u32,
+    },
+}
+
+fn did_you_mean(suggestion: &Option<&'static str>) -> String {
+    match suggestion {
+        Some(name) => alloc::format!(" (did you mean {name}?)"),
+        None => String::new(),
+    }
+}
+
+// Find the name which is the fewest edits away from the one given, so long as it's close enough
+// to plausibly be a typo. Exact matches aren't suggested; the name isn't the problem there.
+fn closest_name(got: &str, names: &[&'static str]) -> Option<&'static str> {
+    let max_distance = (got.chars().count() / 3).max(1);
+    names
+        .iter()
+        .map(|name| (edit_distance(got, name), *name))
+        .filter(|(distance, _)| *distance > 0 && *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name)
+}
+
+// The Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut curr = alloc::vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(a_char != *b_char);
+            curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::{string::ToString, vec};
+
+    #[test]
+    fn errors_stay_small() {
+        // Keep this below the point at which clippy's `result_large_err` lint fires.
+        assert!(core::mem::size_of::<Error>() < 128);
+    }
+
+    #[test]
+    fn error_contexts_can_be_reused() {
+        let err = Error::with_capacity(ErrorKind::ZeroValueForNonZeroType, 8)
+            .at_idx(2)
+            .at_field("b")
+            .at_variant("A")
+            .expecting("a thing");
+        assert_eq!(
+            err.to_string(),
+            "Error at [2].b.(A) (expecting a thing): Cannot decode a value of zero into a non-zero number type"
+        );
+
+        let (_kind, mut context) = err.into_parts();
+        context.clear();
+        assert_eq!(context.path().locations().count(), 0);
+        assert_eq!(context.expected(), None);
+
+        let err = Error::with_context(ErrorKind::ZeroValueForNonZeroType, context).at_field("c");
+        assert_eq!(err.context().path().to_string(), "c");
+        assert_eq!(err.context().expected(), None);
+    }
+
+    #[cfg(all(feature = "type-id-path", feature = "derive"))]
+    #[test]
+    fn errors_note_the_type_ids_decoded() {
+        use crate::DecodeAsType;
+        use codec::Encode;
+
+        #[allow(dead_code)]
+        #[derive(DecodeAsType, Debug)]
+        #[decode_as_type(crate_path = "crate")]
+        struct Outer {
+            a: u8,
+            inner: Inner,
+        }
+        #[allow(dead_code)]
+        #[derive(DecodeAsType, Debug)]
+        #[decode_as_type(crate_path = "crate")]
+        struct Inner {
+            b: u16,
+            c: bool,
+        }
+
+        let m = scale_info::MetaType::new::<(u8, (u16, u8))>();
+        let mut types = scale_info::Registry::new();
+        let type_id = types.register_type(&m).id;
+        let types: scale_info::PortableRegistry = types.into();
+        let encoded = (1u8, (2u16, 3u8)).encode();
+
+        // Fails to decode the final u8 into a bool:
+        let err = Outer::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert_eq!(err.context().path().to_string(), "c.inner");
+
+        // The innermost type (the u8) comes first, like the path:
+        let type_ids: Vec<u32> = err.context().type_ids().map(|id| id.parse().unwrap()).collect();
+        let is_tuple: Vec<_> = type_ids
+            .iter()
+            .map(|&id| matches!(types.resolve(id).unwrap().type_def, scale_info::TypeDef::Tuple(_)))
+            .collect();
+        assert_eq!(is_tuple, [false, true, true]);
+        assert_eq!(type_ids[2], type_id);
+    }
+
+    #[test]
+    fn custom_messages_are_not_boxed() {
+        let err = Error::custom_message("bad value", 42).at_field("foo");
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::CustomMessage { message, code: 42 } if message == "bad value"
+        ));
+        assert_eq!(err.kind().to_string(), "Custom error: bad value (code 42)");
+
+        let err = Error::custom_str("oops");
+        #[cfg(feature = "boxed-custom-errors")]
+        assert!(matches!(err.kind(), ErrorKind::Custom(e) if e.to_string() == "oops"));
+        #[cfg(not(feature = "boxed-custom-errors"))]
+        assert!(matches!(err.kind(), ErrorKind::CustomMessage { code: 0, .. }));
+    }
+
+    #[test]
+    fn cannot_find_variant_suggests_close_names() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+
+        let err = Error::cannot_find_variant("Exclded", 1, vec!["Included", "Excluded"]);
+        assert_eq!(
+            err.kind().to_string(),
+            "Cannot find variant Exclded (index 1); expects one of [\"Included\", \"Excluded\"] (did you mean Excluded?)"
+        );
+
+        // Nothing is suggested if no name is close, or if the name already matches:
+        for got in ["Foo", "Some"] {
+            let err = Error::cannot_find_variant(got, 0, vec!["Some", "None"]);
+            assert!(matches!(err.kind(), ErrorKind::CannotFindVariant { suggestion: None, .. }));
+        }
+    }
 }
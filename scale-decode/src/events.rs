@@ -0,0 +1,521 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decode Substrate events. The events in a block are stored as a `Vec<EventRecord<E, H>>`, where
+//! each record is made up of the [`Phase`] that the event was emitted in, the event itself, and
+//! some topics (hashes of type `H`). The event `E` is an outer variant naming the pallet, whose
+//! single field is an inner variant naming the event, whose fields are the event data.
+//!
+//! [`decode_events()`] hands back an iterator which decodes each record as it's asked for. The
+//! fields of each event are not decoded, but are handed back as [`EventFields`], which can be
+//! decoded into any [`DecodeAsFields`] type or visited one at a time.
+//!
+//! ```rust
+//! use codec::Encode;
+//! use scale_decode::events::{decode_events, Phase};
+//! use scale_decode::DecodeAsType;
+//! use scale_info::{PortableRegistry, TypeInfo};
+//!
+//! #[derive(Encode, TypeInfo)]
+//! enum RuntimeEvent {
+//!     Balances(BalancesEvent),
+//! }
+//! #[derive(Encode, TypeInfo)]
+//! enum BalancesEvent {
+//!     Transfer { from: [u8; 32], to: [u8; 32], amount: u128 },
+//! }
+//! #[derive(Encode, TypeInfo)]
+//! enum SystemPhase {
+//!     ApplyExtrinsic(u32),
+//!     Finalization,
+//!     Initialization,
+//! }
+//! #[derive(Encode, TypeInfo)]
+//! struct EventRecord {
+//!     phase: SystemPhase,
+//!     event: RuntimeEvent,
+//!     topics: Vec<[u8; 32]>,
+//! }
+//!
+//! // What we'd like to decode the event fields into:
+//! #[derive(DecodeAsType)]
+//! struct Transfer {
+//!     amount: u64,
+//! }
+//!
+//! let mut types = scale_info::Registry::new();
+//! let type_id = types.register_type(&scale_info::MetaType::new::<Vec<EventRecord>>()).id;
+//! let types: PortableRegistry = types.into();
+//!
+//! let event = BalancesEvent::Transfer { from: [1; 32], to: [2; 32], amount: 1000 };
+//! let records = vec![EventRecord {
+//!     phase: SystemPhase::ApplyExtrinsic(1),
+//!     event: RuntimeEvent::Balances(event),
+//!     topics: vec![[3; 32]],
+//! }];
+//! let encoded = records.encode();
+//!
+//! for event in decode_events(&encoded, type_id, &types).unwrap() {
+//!     let event = event.unwrap();
+//!     assert_eq!(event.phase, Phase::ApplyExtrinsic(1));
+//!     assert_eq!((event.pallet_name, event.event_name), ("Balances", "Transfer"));
+//!     assert_eq!(event.topics, [&[3; 32]]);
+//!
+//!     let transfer: Transfer = event.fields.decode_as_fields().unwrap();
+//!     assert_eq!(transfer.amount, 1000);
+//! }
+//! ```
+
+use crate::{
+    error::{Error, ErrorKind},
+    visitor::{
+        cache::{self, Shape},
+        decode_with_visitor,
+        types::{Composite, Sequence, Variant},
+        DecodeError, TypeIdFor, Unexpected, Visitor,
+    },
+    DecodeAsFields, Field,
+};
+use alloc::{string::ToString, vec, vec::Vec};
+use codec::{Compact, Decode};
+use core::marker::PhantomData;
+use scale_type_resolver::TypeResolver;
+
+/// Decode the compact encoded length at the start of some SCALE encoded events, returning an
+/// iterator which decodes each event record in turn. The type ID given is that of the whole
+/// `Vec<EventRecord<E, H>>`.
+pub fn decode_events<'scale, 'resolver, R: TypeResolver>(
+    bytes: &'scale [u8],
+    type_id: R::TypeId,
+    types: &'resolver R,
+) -> Result<Events<'scale, 'resolver, R>, Error> {
+    let record_type_id = match cache::resolve_owned_shape(type_id, types)? {
+        Shape::Sequence { inner, .. } => inner,
+        _ => return Err(DecodeError::Unexpected(Unexpected::Sequence).into()),
+    };
+
+    let input = &mut &*bytes;
+    let len = Compact::<u32>::decode(input).map_err(DecodeError::from)?.0 as usize;
+    Ok(Events { bytes: input, len, idx: 0, record_type_id, types })
+}
+
+/// An iterator over some events, decoding each [`EventRecord`] as it's asked for. See
+/// [`decode_events()`]. If a record fails to decode, the error is handed back and iteration
+/// stops, since we no longer know where the next record begins.
+pub struct Events<'scale, 'resolver, R: TypeResolver> {
+    bytes: &'scale [u8],
+    len: usize,
+    idx: usize,
+    record_type_id: R::TypeId,
+    types: &'resolver R,
+}
+
+impl<'scale, 'resolver, R: TypeResolver> Events<'scale, 'resolver, R> {
+    /// The total number of events.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Are there no events at all?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// The number of events which have yet to be decoded.
+    pub fn remaining(&self) -> usize {
+        self.len - self.idx
+    }
+    /// The bytes which have yet to be decoded.
+    pub fn bytes_from_undecoded(&self) -> &'scale [u8] {
+        self.bytes
+    }
+}
+
+impl<'scale, 'resolver, R: TypeResolver> Iterator for Events<'scale, 'resolver, R> {
+    type Item = Result<EventRecord<'scale, 'resolver, R>, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.idx == self.len {
+            return None;
+        }
+        let idx = self.idx;
+        let res = decode_with_visitor(
+            &mut self.bytes,
+            self.record_type_id.clone(),
+            self.types,
+            RecordVisitor(PhantomData),
+        );
+        // Stop after an error, since we don't know where the next record would start.
+        self.idx = if res.is_ok() { idx + 1 } else { self.len };
+        let types = self.types;
+        Some(res.map_err(|e| e.at_idx(idx)).map(|record| EventRecord {
+            phase: record.phase,
+            pallet_name: record.event.pallet_name,
+            pallet_index: record.event.pallet_index,
+            event_name: record.event.event_name,
+            event_index: record.event.event_index,
+            fields: EventFields { bytes: record.event.bytes, fields: record.event.fields, types },
+            topics: record.topics,
+        }))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining()))
+    }
+}
+
+/// A single decoded event record. See [`decode_events()`].
+pub struct EventRecord<'scale, 'resolver, R: TypeResolver> {
+    /// The phase of block execution that the event was emitted in.
+    pub phase: Phase,
+    /// The name of the pallet that emitted the event.
+    pub pallet_name: &'resolver str,
+    /// The index of the pallet that emitted the event.
+    pub pallet_index: u8,
+    /// The name of the event.
+    pub event_name: &'resolver str,
+    /// The index of the event.
+    pub event_index: u8,
+    /// The fields of the event, which have yet to be decoded.
+    pub fields: EventFields<'scale, 'resolver, R>,
+    /// The bytes of each of the topics of the event.
+    pub topics: Vec<&'scale [u8]>,
+}
+
+/// The phase of block execution that some event was emitted in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// Applying the extrinsic at the given index.
+    ApplyExtrinsic(u32),
+    /// Finalizing the block.
+    Finalization,
+    /// Initializing the block.
+    Initialization,
+}
+
+/// The fields of some event, which can be decoded into a [`DecodeAsFields`] type, or visited one
+/// at a time via [`EventFields::as_composite()`].
+pub struct EventFields<'scale, 'resolver, R: TypeResolver> {
+    bytes: &'scale [u8],
+    fields: Vec<Field<'resolver, R::TypeId>>,
+    types: &'resolver R,
+}
+
+impl<'scale, 'resolver, R: TypeResolver> EventFields<'scale, 'resolver, R> {
+    /// The bytes of all of the fields.
+    pub fn bytes(&self) -> &'scale [u8] {
+        self.bytes
+    }
+    /// The names (if any) and type IDs of the fields.
+    pub fn fields(&self) -> &[Field<'resolver, R::TypeId>] {
+        &self.fields
+    }
+    /// Decode the fields into some type.
+    pub fn decode_as_fields<T: DecodeAsFields>(&self) -> Result<T, Error> {
+        T::decode_as_fields(&mut &*self.bytes, &mut self.fields.iter().cloned(), self.types)
+    }
+    /// Hand back a [`Composite`] which can be iterated over to get each field in turn, or handed
+    /// to some [`Visitor`].
+    pub fn as_composite(&self) -> Composite<'scale, 'resolver, R> {
+        let fields = &mut self.fields.iter().cloned();
+        Composite::new(core::iter::empty(), self.bytes, fields, self.types, false)
+    }
+}
+
+// The details of a decoded event record.
+struct Record<'scale, 'resolver, TypeId> {
+    phase: Phase,
+    event: Event<'scale, 'resolver, TypeId>,
+    topics: Vec<&'scale [u8]>,
+}
+
+// Decode the phase, event and topics of an event record.
+struct RecordVisitor<R>(PhantomData<R>);
+
+impl<R: TypeResolver> Visitor for RecordVisitor<R> {
+    type Value<'scale, 'resolver> = Record<'scale, 'resolver, R::TypeId>;
+    type Error = Error;
+    type TypeResolver = R;
+
+    fn visit_composite<'scale, 'resolver>(
+        self,
+        value: &mut Composite<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        if value.remaining() != 3 {
+            return Err(Error::new(ErrorKind::WrongLength {
+                actual_len: value.remaining(),
+                expected_len: 3,
+            }));
+        }
+
+        let phase = value
+            .decode_item(PhaseVisitor(PhantomData))
+            .expect("three fields remain; qed")
+            .map_err(|e| e.at_field("phase"))?;
+        let event = value
+            .decode_item(EventVisitor(PhantomData))
+            .expect("two fields remain; qed")
+            .map_err(|e| e.at_field("event"))?;
+        let topics = value
+            .decode_item(TopicsVisitor(PhantomData))
+            .expect("one field remains; qed")
+            .map_err(|e| e.at_field("topics"))?;
+
+        Ok(Record { phase, event, topics })
+    }
+}
+
+// Decode the phase of an event record, from any variant with the expected names.
+struct PhaseVisitor<R>(PhantomData<R>);
+
+impl<R: TypeResolver> Visitor for PhaseVisitor<R> {
+    type Value<'scale, 'resolver> = Phase;
+    type Error = Error;
+    type TypeResolver = R;
+
+    fn visit_variant<'scale, 'resolver>(
+        self,
+        value: &mut Variant<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        match value.name() {
+            "ApplyExtrinsic" => {
+                let fields = value.fields();
+                let (idx,) = <(u32,)>::decode_as_fields(
+                    &mut fields.bytes_from_undecoded(),
+                    &mut fields.fields().iter().cloned(),
+                    fields.types(),
+                )
+                .map_err(|e| e.at_variant("ApplyExtrinsic"))?;
+                Ok(Phase::ApplyExtrinsic(idx))
+            }
+            "Finalization" => Ok(Phase::Finalization),
+            "Initialization" => Ok(Phase::Initialization),
+            name => Err(Error::cannot_find_variant(
+                name,
+                value.index(),
+                vec!["ApplyExtrinsic", "Finalization", "Initialization"],
+            )),
+        }
+    }
+}
+
+// The details of a decoded event, whose fields have yet to be decoded.
+struct Event<'scale, 'resolver, TypeId> {
+    pallet_name: &'resolver str,
+    pallet_index: u8,
+    event_name: &'resolver str,
+    event_index: u8,
+    bytes: &'scale [u8],
+    fields: Vec<Field<'resolver, TypeId>>,
+}
+
+// Decode the outer variant naming the pallet, and then the inner variant naming the event.
+struct EventVisitor<R>(PhantomData<R>);
+
+impl<R: TypeResolver> Visitor for EventVisitor<R> {
+    type Value<'scale, 'resolver> = Event<'scale, 'resolver, R::TypeId>;
+    type Error = Error;
+    type TypeResolver = R;
+
+    fn visit_variant<'scale, 'resolver>(
+        self,
+        value: &mut Variant<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let pallet_name = value.name();
+        let fields = value.fields();
+        if fields.remaining() != 1 {
+            return Err(Error::new(ErrorKind::WrongLength {
+                actual_len: fields.remaining(),
+                expected_len: 1,
+            })
+            .at_variant(pallet_name.to_string()));
+        }
+        let mut event = fields
+            .decode_item(EventFieldsVisitor(PhantomData))
+            .expect("one field remains; qed")
+            .map_err(|e| e.at_variant(pallet_name.to_string()))?;
+
+        event.pallet_name = pallet_name;
+        event.pallet_index = value.index();
+        Ok(event)
+    }
+}
+
+// Decode the inner variant naming the event, handing back its fields without decoding them. The
+// pallet details are filled in by the `EventVisitor`.
+struct EventFieldsVisitor<R>(PhantomData<R>);
+
+impl<R: TypeResolver> Visitor for EventFieldsVisitor<R> {
+    type Value<'scale, 'resolver> = Event<'scale, 'resolver, R::TypeId>;
+    type Error = Error;
+    type TypeResolver = R;
+
+    fn visit_variant<'scale, 'resolver>(
+        self,
+        value: &mut Variant<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let event_name = value.name();
+        let event_index = value.index();
+        let fields = value.fields();
+        let start = fields.bytes_from_undecoded();
+        fields.skip_decoding().map_err(|e| Error::from(e).at_variant(event_name.to_string()))?;
+        let bytes = &start[..start.len() - fields.bytes_from_undecoded().len()];
+
+        Ok(Event {
+            pallet_name: "",
+            pallet_index: 0,
+            event_name,
+            event_index,
+            bytes,
+            fields: fields.fields().to_vec(),
+        })
+    }
+}
+
+// Hand back the bytes of each of the topics of an event record.
+struct TopicsVisitor<R>(PhantomData<R>);
+
+impl<R: TypeResolver> Visitor for TopicsVisitor<R> {
+    type Value<'scale, 'resolver> = Vec<&'scale [u8]>;
+    type Error = Error;
+    type TypeResolver = R;
+
+    fn visit_sequence<'scale, 'resolver>(
+        self,
+        value: &mut Sequence<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let mut topics = Vec::with_capacity(value.remaining());
+        for (idx, item) in value.enumerate() {
+            topics.push(item.map_err(|e| Error::from(e).at_idx(idx))?.bytes());
+        }
+        Ok(topics)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use codec::Encode;
+    use scale_info::{PortableRegistry, TypeInfo};
+
+    #[derive(Encode, TypeInfo)]
+    enum RuntimeEvent {
+        #[codec(index = 0)]
+        System(SystemEvent),
+        #[codec(index = 10)]
+        Balances(BalancesEvent),
+    }
+    #[derive(Encode, TypeInfo)]
+    enum SystemEvent {
+        ExtrinsicSuccess { weight: u64 },
+    }
+    #[derive(Encode, TypeInfo)]
+    enum BalancesEvent {
+        #[codec(index = 2)]
+        Transfer([u8; 4], [u8; 4], u128),
+    }
+    #[derive(Encode, TypeInfo)]
+    enum SystemPhase {
+        ApplyExtrinsic(u32),
+        Finalization,
+        Initialization,
+    }
+    #[derive(Encode, TypeInfo)]
+    struct EventRecord {
+        phase: SystemPhase,
+        event: RuntimeEvent,
+        topics: Vec<[u8; 32]>,
+    }
+
+    fn make_type<T: TypeInfo + 'static>() -> (u32, PortableRegistry) {
+        let m = scale_info::MetaType::new::<T>();
+        let mut types = scale_info::Registry::new();
+        let id = types.register_type(&m);
+        (id.id, types.into())
+    }
+
+    fn records() -> Vec<EventRecord> {
+        vec![
+            EventRecord {
+                phase: SystemPhase::Initialization,
+                event: RuntimeEvent::System(SystemEvent::ExtrinsicSuccess { weight: 100 }),
+                topics: vec![],
+            },
+            EventRecord {
+                phase: SystemPhase::ApplyExtrinsic(3),
+                event: RuntimeEvent::Balances(BalancesEvent::Transfer([1; 4], [2; 4], 5000)),
+                topics: vec![[7; 32], [8; 32]],
+            },
+            EventRecord {
+                phase: SystemPhase::Finalization,
+                event: RuntimeEvent::System(SystemEvent::ExtrinsicSuccess { weight: 200 }),
+                topics: vec![],
+            },
+        ]
+    }
+
+    #[test]
+    fn events_are_decoded() {
+        let (type_id, types) = make_type::<Vec<EventRecord>>();
+        let encoded = records().encode();
+
+        let events = decode_events(&encoded, type_id, &types).unwrap();
+        assert_eq!(events.len(), 3);
+        let events: Vec<_> = events.collect::<Result<_, _>>().unwrap();
+
+        let phases: Vec<_> = events.iter().map(|e| e.phase).collect();
+        assert_eq!(phases, [Phase::Initialization, Phase::ApplyExtrinsic(3), Phase::Finalization]);
+
+        let transfer = &events[1];
+        assert_eq!((transfer.pallet_name, transfer.pallet_index), ("Balances", 10));
+        assert_eq!((transfer.event_name, transfer.event_index), ("Transfer", 2));
+        assert_eq!(transfer.topics, [&[7; 32], &[8; 32]]);
+        assert_eq!(transfer.fields.bytes(), ([1u8; 4], [2u8; 4], 5000u128).encode());
+        let (from, to, amount) =
+            transfer.fields.decode_as_fields::<([u8; 4], [u8; 4], u64)>().unwrap();
+        assert_eq!((from, to, amount), ([1; 4], [2; 4], 5000));
+
+        // Fields can also be visited one at a time:
+        let weights: Vec<u64> = [&events[0], &events[2]]
+            .iter()
+            .map(|e| {
+                let field = e.fields.as_composite().next().unwrap().unwrap();
+                assert_eq!(field.name(), Some("weight"));
+                field.decode_as_type().unwrap()
+            })
+            .collect();
+        assert_eq!(weights, [100, 200]);
+    }
+
+    #[test]
+    fn iteration_stops_at_the_first_error() {
+        let (type_id, types) = make_type::<Vec<EventRecord>>();
+        let mut encoded = records().encode();
+        // Make the pallet index of the second event (after its 5 byte phase) invalid:
+        let second = 1 + records()[0].encode().len() + 5;
+        encoded[second] = 99;
+
+        let mut events = decode_events(&encoded, type_id, &types).unwrap();
+        assert!(events.next().unwrap().is_ok());
+        let err = events.next().unwrap().err().unwrap();
+        assert_eq!(err.context().path().to_string(), "event.[1]");
+        assert!(events.next().is_none());
+
+        // Only sequences of event records can be decoded:
+        let (type_id, types) = make_type::<EventRecord>();
+        assert!(decode_events(&encoded, type_id, &types).is_err());
+    }
+}
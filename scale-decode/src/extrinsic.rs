@@ -0,0 +1,333 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decode Substrate extrinsics. An extrinsic is made up of a compact encoded length, a version
+//! byte which also denotes whether the extrinsic is signed, the address, signature and signed
+//! extension data if it is signed, and then the call. The call is an outer variant naming the
+//! pallet, whose single field is an inner variant naming the call, whose fields are the call
+//! arguments. These arguments can be decoded into any [`DecodeAsFields`] type.
+//!
+//! Only version 4 extrinsics are supported at present.
+//!
+//! ```rust
+//! use codec::{Compact, Encode};
+//! use scale_decode::extrinsic::{decode_extrinsic, ExtrinsicTypeIds};
+//! use scale_decode::DecodeAsType;
+//! use scale_info::{PortableRegistry, TypeInfo};
+//!
+//! #[derive(Encode, TypeInfo)]
+//! enum RuntimeCall {
+//!     Balances(BalancesCall),
+//! }
+//! #[derive(Encode, TypeInfo)]
+//! enum BalancesCall {
+//!     #[allow(non_camel_case_types)]
+//!     transfer { dest: [u8; 32], value: u128 },
+//! }
+//!
+//! // What we'd like to decode the call arguments into:
+//! #[derive(DecodeAsType)]
+//! struct Transfer {
+//!     dest: [u8; 32],
+//!     value: u64,
+//! }
+//!
+//! let mut types = scale_info::Registry::new();
+//! let mut register = |m| types.register_type(&m).id;
+//! let type_ids = ExtrinsicTypeIds {
+//!     address: register(scale_info::MetaType::new::<[u8; 32]>()),
+//!     signature: register(scale_info::MetaType::new::<[u8; 64]>()),
+//!     extra: register(scale_info::MetaType::new::<(Compact<u64>, u8)>()),
+//!     call: register(scale_info::MetaType::new::<RuntimeCall>()),
+//! };
+//! let types: PortableRegistry = types.into();
+//!
+//! // An unsigned extrinsic:
+//! let call = RuntimeCall::Balances(BalancesCall::transfer { dest: [1; 32], value: 1000 });
+//! let extrinsic = (4u8, call).encode().encode();
+//!
+//! let decoded = decode_extrinsic::<Transfer, _>(&extrinsic, &type_ids, &types).unwrap();
+//! assert_eq!(decoded.pallet_name, "Balances");
+//! assert_eq!(decoded.call_name, "transfer");
+//! assert!(decoded.signature.is_none());
+//! assert_eq!(decoded.call.value, 1000);
+//! ```
+
+use crate::{
+    error::{Error, ErrorKind},
+    visitor::{
+        decode_with_visitor, encoded_size_of, types::Variant, DecodeError, TypeIdFor, Visitor,
+    },
+    DecodeAsFields,
+};
+use alloc::string::ToString;
+use codec::{Compact, Decode};
+use core::marker::PhantomData;
+use scale_type_resolver::TypeResolver;
+
+/// The type IDs needed to decode extrinsics. These are usually the type parameters of the
+/// `UncheckedExtrinsic` type described in some metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtrinsicTypeIds<TypeId> {
+    /// The type of the address of a signed extrinsic.
+    pub address: TypeId,
+    /// The type of the signature of a signed extrinsic.
+    pub signature: TypeId,
+    /// The type of the signed extension data of a signed extrinsic.
+    pub extra: TypeId,
+    /// The type of the call.
+    pub call: TypeId,
+}
+
+/// A decoded extrinsic. See [`decode_extrinsic()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Extrinsic<'scale, 'resolver, T> {
+    /// The version of the extrinsic.
+    pub version: u8,
+    /// The address, signature and signed extension data, if the extrinsic is signed.
+    pub signature: Option<ExtrinsicSignature<'scale>>,
+    /// The name of the pallet that the call belongs to.
+    pub pallet_name: &'resolver str,
+    /// The index of the pallet that the call belongs to.
+    pub pallet_index: u8,
+    /// The name of the call.
+    pub call_name: &'resolver str,
+    /// The index of the call.
+    pub call_index: u8,
+    /// The arguments of the call.
+    pub call: T,
+}
+
+/// The SCALE encoded bytes of the address, signature and signed extension data of a signed
+/// extrinsic. These can be decoded using the type IDs in [`ExtrinsicTypeIds`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtrinsicSignature<'scale> {
+    /// The bytes of the address.
+    pub address: &'scale [u8],
+    /// The bytes of the signature.
+    pub signature: &'scale [u8],
+    /// The bytes of the signed extension data.
+    pub extra: &'scale [u8],
+}
+
+// The version of extrinsics that we know how to decode.
+const SUPPORTED_VERSION: u8 = 4;
+// The bit in the version byte which is set if the extrinsic is signed.
+const SIGNED_BIT: u8 = 0b1000_0000;
+
+/// Decode some extrinsic bytes, starting with the compact encoded length, decoding the call
+/// arguments into `T`. The length must correspond to the number of bytes which follow it.
+pub fn decode_extrinsic<'scale, 'resolver, T: DecodeAsFields, R: TypeResolver>(
+    bytes: &'scale [u8],
+    type_ids: &ExtrinsicTypeIds<R::TypeId>,
+    types: &'resolver R,
+) -> Result<Extrinsic<'scale, 'resolver, T>, Error> {
+    let input = &mut &*bytes;
+    let len = Compact::<u32>::decode(input).map_err(DecodeError::from)?.0 as usize;
+    if input.len() != len {
+        return Err(Error::new(ErrorKind::WrongLength {
+            actual_len: input.len(),
+            expected_len: len,
+        }));
+    }
+
+    let version_byte = u8::decode(input).map_err(DecodeError::from)?;
+    let version = version_byte & !SIGNED_BIT;
+    if version != SUPPORTED_VERSION {
+        return Err(Error::new(ErrorKind::UnsupportedExtrinsicVersion { version }));
+    }
+
+    let signature = if version_byte & SIGNED_BIT != 0 {
+        let address =
+            take_value(input, &type_ids.address, types).map_err(|e| e.at_field("address"))?;
+        let signature =
+            take_value(input, &type_ids.signature, types).map_err(|e| e.at_field("signature"))?;
+        let extra = take_value(input, &type_ids.extra, types).map_err(|e| e.at_field("extra"))?;
+        Some(ExtrinsicSignature { address, signature, extra })
+    } else {
+        None
+    };
+
+    let call = decode_with_visitor(input, type_ids.call.clone(), types, CallVisitor(PhantomData))
+        .map_err(|e| e.at_field("call"))?;
+
+    Ok(Extrinsic {
+        version,
+        signature,
+        pallet_name: call.pallet_name,
+        pallet_index: call.pallet_index,
+        call_name: call.call_name,
+        call_index: call.call_index,
+        call: call.call,
+    })
+}
+
+// Move the input past the value with the given type, returning the bytes of the value.
+fn take_value<'scale, R: TypeResolver>(
+    input: &mut &'scale [u8],
+    type_id: &R::TypeId,
+    types: &R,
+) -> Result<&'scale [u8], Error> {
+    let len = encoded_size_of(input, type_id.clone(), types)?;
+    let (value, rest) = input.split_at(len);
+    *input = rest;
+    Ok(value)
+}
+
+// The details of a decoded call.
+struct Call<'resolver, T> {
+    pallet_name: &'resolver str,
+    pallet_index: u8,
+    call_name: &'resolver str,
+    call_index: u8,
+    call: T,
+}
+
+// Decode the outer variant naming the pallet, and then the inner variant naming the call.
+struct CallVisitor<T, R>(PhantomData<(T, R)>);
+
+impl<T: DecodeAsFields, R: TypeResolver> Visitor for CallVisitor<T, R> {
+    type Value<'scale, 'resolver> = Call<'resolver, T>;
+    type Error = Error;
+    type TypeResolver = R;
+
+    fn visit_variant<'scale, 'resolver>(
+        self,
+        value: &mut Variant<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let pallet_name = value.name();
+        let fields = value.fields();
+        if fields.remaining() != 1 {
+            return Err(Error::new(ErrorKind::WrongLength {
+                actual_len: fields.remaining(),
+                expected_len: 1,
+            })
+            .at_variant(pallet_name.to_string()));
+        }
+        let (call_name, call_index, call) = fields
+            .decode_item(ArgsVisitor(PhantomData))
+            .expect("one field remains; qed")
+            .map_err(|e| e.at_variant(pallet_name.to_string()))?;
+
+        Ok(Call { pallet_name, pallet_index: value.index(), call_name, call_index, call })
+    }
+}
+
+// Decode the inner variant naming the call, and its fields into `T`.
+struct ArgsVisitor<T, R>(PhantomData<(T, R)>);
+
+impl<T: DecodeAsFields, R: TypeResolver> Visitor for ArgsVisitor<T, R> {
+    type Value<'scale, 'resolver> = (&'resolver str, u8, T);
+    type Error = Error;
+    type TypeResolver = R;
+
+    fn visit_variant<'scale, 'resolver>(
+        self,
+        value: &mut Variant<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let call_name = value.name();
+        let fields = value.fields();
+        let input = &mut fields.bytes_from_undecoded();
+        let call = T::decode_as_fields(input, &mut fields.fields().iter().cloned(), fields.types())
+            .map_err(|e| e.at_variant(call_name.to_string()))?;
+        Ok((call_name, value.index(), call))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::{vec, vec::Vec};
+    use codec::Encode;
+    use scale_info::TypeInfo;
+
+    #[derive(Encode, TypeInfo)]
+    enum RuntimeCall {
+        #[codec(index = 5)]
+        System(SystemCall),
+    }
+    #[derive(Encode, TypeInfo)]
+    #[allow(non_camel_case_types)]
+    enum SystemCall {
+        #[codec(index = 2)]
+        remark { remark: Vec<u8> },
+    }
+
+    fn type_ids() -> (ExtrinsicTypeIds<u32>, scale_info::PortableRegistry) {
+        let mut types = scale_info::Registry::new();
+        let mut register = |m| types.register_type(&m).id;
+        let type_ids = ExtrinsicTypeIds {
+            address: register(scale_info::MetaType::new::<[u8; 32]>()),
+            signature: register(scale_info::MetaType::new::<(u8, [u8; 64])>()),
+            extra: register(scale_info::MetaType::new::<(Compact<u64>, Compact<u128>)>()),
+            call: register(scale_info::MetaType::new::<RuntimeCall>()),
+        };
+        (type_ids, types.into())
+    }
+
+    #[test]
+    fn signed_extrinsics_are_decoded() {
+        let (type_ids, types) = type_ids();
+
+        let address = [1u8; 32].encode();
+        let signature = (0u8, [2u8; 64]).encode();
+        let extra = (Compact(3u64), Compact(1000u128)).encode();
+        let call = RuntimeCall::System(SystemCall::remark { remark: vec![1, 2, 3] }).encode();
+        let extrinsic = [&[SIGNED_BIT | 4][..], &address, &signature, &extra, &call].concat();
+        let bytes = extrinsic.encode();
+
+        let decoded = decode_extrinsic::<(Vec<u8>,), _>(&bytes, &type_ids, &types).unwrap();
+        assert_eq!(
+            decoded,
+            Extrinsic {
+                version: 4,
+                signature: Some(ExtrinsicSignature {
+                    address: &address,
+                    signature: &signature,
+                    extra: &extra
+                }),
+                pallet_name: "System",
+                pallet_index: 5,
+                call_name: "remark",
+                call_index: 2,
+                call: (vec![1, 2, 3],),
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_extrinsics_are_rejected() {
+        let (type_ids, types) = type_ids();
+        let call = RuntimeCall::System(SystemCall::remark { remark: vec![1, 2, 3] });
+
+        // The length prefix doesn't line up:
+        let mut bytes = (4u8, &call).encode().encode();
+        bytes.push(0);
+        let err = decode_extrinsic::<(Vec<u8>,), _>(&bytes, &type_ids, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::WrongLength { .. }));
+
+        // Unsupported version:
+        let bytes = (5u8, &call).encode().encode();
+        let err = decode_extrinsic::<(Vec<u8>,), _>(&bytes, &type_ids, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::UnsupportedExtrinsicVersion { version: 5 }));
+
+        // The call arguments don't line up with the target type:
+        let bytes = (4u8, &call).encode().encode();
+        let err = decode_extrinsic::<(bool,), _>(&bytes, &type_ids, &types).unwrap_err();
+        assert_eq!(err.context().path().to_string(), "[0].(remark).(System).call");
+    }
+}
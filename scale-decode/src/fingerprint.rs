@@ -0,0 +1,414 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compute a [`TypeFingerprint`] describing the structure of some type, so that values can be
+//! tagged with the shape that they were decoded under.
+
+use crate::{visitor::DecodeError, DecodeAsType, Error};
+use alloc::{format, string::ToString, vec::Vec};
+use scale_type_resolver::{
+    BitsOrderFormat, BitsStoreFormat, FieldIter, PathIter, Primitive, ResolvedTypeVisitor,
+    TypeResolver, UnhandledKind, VariantIter,
+};
+
+/// A stable hash of the structure of some type. Two types which resolve to the same shape (the same
+/// field and variant names, variant indexes, primitives and so on) will have the same fingerprint,
+/// regardless of the type IDs or type paths used to describe them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TypeFingerprint(u64);
+
+impl TypeFingerprint {
+    /// Return the fingerprint as a `u64`.
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl core::fmt::Display for TypeFingerprint {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// Compute the [`TypeFingerprint`] of the type given by `type_id`. Type IDs are compared with each
+/// other in order to spot recursive types.
+pub fn fingerprint<R: TypeResolver>(
+    type_id: R::TypeId,
+    types: &R,
+) -> Result<TypeFingerprint, DecodeError>
+where
+    R::TypeId: PartialEq,
+{
+    let mut hasher = Hasher::new();
+    let mut stack = Vec::new();
+    fingerprint_type(type_id, types, &mut hasher, &mut stack)?;
+    Ok(TypeFingerprint(hasher.finish()))
+}
+
+/// Decode some bytes into `T` via [`DecodeAsType`], and also return the [`TypeFingerprint`] of
+/// the type that the bytes were decoded from.
+pub fn decode_with_fingerprint<T: DecodeAsType, R: TypeResolver>(
+    input: &mut &[u8],
+    type_id: R::TypeId,
+    types: &R,
+) -> Result<(T, TypeFingerprint), Error>
+where
+    R::TypeId: PartialEq,
+{
+    let fingerprint = fingerprint(type_id.clone(), types)?;
+    let value = T::decode_as_type(input, type_id, types)?;
+    Ok((value, fingerprint))
+}
+
+// Tags written to the hasher to distinguish the different shapes from each other.
+const TAG_COMPOSITE: u8 = 0;
+const TAG_VARIANT: u8 = 1;
+const TAG_SEQUENCE: u8 = 2;
+const TAG_ARRAY: u8 = 3;
+const TAG_TUPLE: u8 = 4;
+const TAG_PRIMITIVE: u8 = 5;
+const TAG_COMPACT: u8 = 6;
+const TAG_BIT_SEQUENCE: u8 = 7;
+const TAG_RECURSIVE: u8 = 8;
+const TAG_NAMED: u8 = 9;
+const TAG_UNNAMED: u8 = 10;
+
+fn fingerprint_type<R: TypeResolver>(
+    type_id: R::TypeId,
+    types: &R,
+    hasher: &mut Hasher,
+    stack: &mut Vec<R::TypeId>,
+) -> Result<(), DecodeError>
+where
+    R::TypeId: PartialEq,
+{
+    // When we find a recursive type, we hash how far back up the stack it points instead of
+    // following it.
+    if let Some(pos) = stack.iter().position(|id| *id == type_id) {
+        hasher.write_u8(TAG_RECURSIVE);
+        hasher.write_usize(stack.len() - pos);
+        return Ok(());
+    }
+
+    stack.push(type_id.clone());
+    let visitor = FingerprintVisitor { type_id: type_id.clone(), types, hasher, stack };
+    let res = types
+        .resolve_type(type_id, visitor)
+        .map_err(|e| DecodeError::TypeResolvingError(e.to_string()))?;
+    stack.pop();
+    res
+}
+
+struct FingerprintVisitor<'a, R: TypeResolver> {
+    type_id: R::TypeId,
+    types: &'a R,
+    hasher: &'a mut Hasher,
+    stack: &'a mut Vec<R::TypeId>,
+}
+
+impl<'a, R: TypeResolver> FingerprintVisitor<'a, R>
+where
+    R::TypeId: PartialEq,
+{
+    fn fields<'resolver>(
+        &mut self,
+        fields: &mut dyn FieldIter<'resolver, R::TypeId>,
+    ) -> Result<(), DecodeError> {
+        self.hasher.write_usize(fields.len());
+        for field in fields {
+            match field.name {
+                Some(name) => {
+                    self.hasher.write_u8(TAG_NAMED);
+                    self.hasher.write_str(name);
+                }
+                None => self.hasher.write_u8(TAG_UNNAMED),
+            }
+            fingerprint_type(field.id, self.types, self.hasher, self.stack)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, 'resolver, R: TypeResolver> ResolvedTypeVisitor<'resolver> for FingerprintVisitor<'a, R>
+where
+    R::TypeId: PartialEq,
+{
+    type TypeId = R::TypeId;
+    type Value = Result<(), DecodeError>;
+
+    fn visit_unhandled(self, kind: UnhandledKind) -> Self::Value {
+        let type_id = self.type_id;
+        Err(DecodeError::TypeIdNotFound(format!(
+            "Kind {kind:?} (type ID {type_id:?}) has not been properly handled"
+        )))
+    }
+
+    fn visit_not_found(self) -> Self::Value {
+        let type_id = self.type_id;
+        Err(DecodeError::TypeIdNotFound(format!("{type_id:?}")))
+    }
+
+    fn visit_composite<Path, Fields>(mut self, _path: Path, mut fields: Fields) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+        Fields: FieldIter<'resolver, Self::TypeId>,
+    {
+        self.hasher.write_u8(TAG_COMPOSITE);
+        self.fields(&mut fields)
+    }
+
+    fn visit_variant<Path, Fields, Var>(mut self, _path: Path, variants: Var) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+        Fields: FieldIter<'resolver, Self::TypeId>,
+        Var: VariantIter<'resolver, Fields>,
+    {
+        self.hasher.write_u8(TAG_VARIANT);
+        self.hasher.write_usize(variants.len());
+        for mut variant in variants {
+            self.hasher.write_u8(variant.index);
+            self.hasher.write_str(variant.name);
+            self.fields(&mut variant.fields)?;
+        }
+        Ok(())
+    }
+
+    fn visit_sequence<Path>(self, _path: Path, inner_type_id: Self::TypeId) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+    {
+        self.hasher.write_u8(TAG_SEQUENCE);
+        fingerprint_type(inner_type_id, self.types, self.hasher, self.stack)
+    }
+
+    fn visit_array(self, inner_type_id: Self::TypeId, len: usize) -> Self::Value {
+        self.hasher.write_u8(TAG_ARRAY);
+        self.hasher.write_usize(len);
+        fingerprint_type(inner_type_id, self.types, self.hasher, self.stack)
+    }
+
+    fn visit_tuple<TypeIds>(self, type_ids: TypeIds) -> Self::Value
+    where
+        TypeIds: ExactSizeIterator<Item = Self::TypeId>,
+    {
+        self.hasher.write_u8(TAG_TUPLE);
+        self.hasher.write_usize(type_ids.len());
+        for type_id in type_ids {
+            fingerprint_type(type_id, self.types, self.hasher, self.stack)?;
+        }
+        Ok(())
+    }
+
+    fn visit_primitive(self, primitive: Primitive) -> Self::Value {
+        let primitive_tag = match primitive {
+            Primitive::Bool => 0,
+            Primitive::Char => 1,
+            Primitive::Str => 2,
+            Primitive::U8 => 3,
+            Primitive::U16 => 4,
+            Primitive::U32 => 5,
+            Primitive::U64 => 6,
+            Primitive::U128 => 7,
+            Primitive::U256 => 8,
+            Primitive::I8 => 9,
+            Primitive::I16 => 10,
+            Primitive::I32 => 11,
+            Primitive::I64 => 12,
+            Primitive::I128 => 13,
+            Primitive::I256 => 14,
+        };
+        self.hasher.write_u8(TAG_PRIMITIVE);
+        self.hasher.write_u8(primitive_tag);
+        Ok(())
+    }
+
+    fn visit_compact(self, inner_type_id: Self::TypeId) -> Self::Value {
+        self.hasher.write_u8(TAG_COMPACT);
+        fingerprint_type(inner_type_id, self.types, self.hasher, self.stack)
+    }
+
+    fn visit_bit_sequence(
+        self,
+        store_format: BitsStoreFormat,
+        order_format: BitsOrderFormat,
+    ) -> Self::Value {
+        let store_tag = match store_format {
+            BitsStoreFormat::U8 => 0,
+            BitsStoreFormat::U16 => 1,
+            BitsStoreFormat::U32 => 2,
+            BitsStoreFormat::U64 => 3,
+        };
+        let order_tag = match order_format {
+            BitsOrderFormat::Lsb0 => 0,
+            BitsOrderFormat::Msb0 => 1,
+        };
+        self.hasher.write_u8(TAG_BIT_SEQUENCE);
+        self.hasher.write_u8(store_tag);
+        self.hasher.write_u8(order_tag);
+        Ok(())
+    }
+}
+
+/// A 64 bit FNV-1a hasher. We don't use `core::hash::Hasher` impls because we need
+/// the output to be stable across platforms and releases.
+struct Hasher(u64);
+
+impl Hasher {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    fn new() -> Self {
+        Hasher(Self::OFFSET_BASIS)
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= *b as u64;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+    fn write_u8(&mut self, n: u8) {
+        self.write(&[n]);
+    }
+    fn write_usize(&mut self, n: usize) {
+        self.write(&(n as u64).to_le_bytes());
+    }
+    fn write_str(&mut self, s: &str) {
+        self.write_usize(s.len());
+        self.write(s.as_bytes());
+    }
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::{boxed::Box, string::String};
+    use codec::Encode;
+    use scale_info::PortableRegistry;
+
+    fn make_type<T: scale_info::TypeInfo + 'static>() -> (u32, PortableRegistry) {
+        let m = scale_info::MetaType::new::<T>();
+        let mut types = scale_info::Registry::new();
+        let id = types.register_type(&m);
+        let portable_registry: PortableRegistry = types.into();
+
+        (id.id, portable_registry)
+    }
+
+    fn fingerprint_of<T: scale_info::TypeInfo + 'static>() -> TypeFingerprint {
+        let (id, types) = make_type::<T>();
+        fingerprint(id, &types).expect("can fingerprint type")
+    }
+
+    #[test]
+    fn same_shape_has_same_fingerprint() {
+        #[derive(scale_info::TypeInfo)]
+        struct Foo {
+            _a: u8,
+            _b: (bool, String),
+        }
+        #[derive(scale_info::TypeInfo)]
+        struct Bar {
+            _a: u8,
+            _b: (bool, String),
+        }
+
+        assert_eq!(fingerprint_of::<Foo>(), fingerprint_of::<Bar>());
+        assert_eq!(fingerprint_of::<Vec<u64>>(), fingerprint_of::<Vec<u64>>());
+    }
+
+    #[test]
+    fn different_shape_has_different_fingerprint() {
+        #[derive(scale_info::TypeInfo)]
+        struct Foo {
+            _a: u8,
+        }
+        #[derive(scale_info::TypeInfo)]
+        struct Bar {
+            _b: u8,
+        }
+        #[derive(scale_info::TypeInfo)]
+        #[allow(dead_code)]
+        struct Wibble(u8);
+        #[derive(scale_info::TypeInfo)]
+        enum Enum1 {
+            _A(u8),
+        }
+        #[derive(scale_info::TypeInfo)]
+        enum Enum2 {
+            #[codec(index = 1)]
+            _A(u8),
+        }
+
+        assert_ne!(fingerprint_of::<Foo>(), fingerprint_of::<Bar>());
+        assert_ne!(fingerprint_of::<Foo>(), fingerprint_of::<Wibble>());
+        assert_ne!(fingerprint_of::<Enum1>(), fingerprint_of::<Enum2>());
+        assert_ne!(fingerprint_of::<[u8; 3]>(), fingerprint_of::<[u8; 4]>());
+        assert_ne!(fingerprint_of::<Vec<u8>>(), fingerprint_of::<Vec<u16>>());
+        assert_ne!(fingerprint_of::<(u8, u16)>(), fingerprint_of::<(u16, u8)>());
+    }
+
+    #[test]
+    fn recursive_types_can_be_fingerprinted() {
+        #[derive(scale_info::TypeInfo)]
+        #[allow(dead_code)]
+        enum List {
+            Nil,
+            Cons(u8, Box<List>),
+        }
+
+        #[derive(scale_info::TypeInfo)]
+        #[allow(dead_code)]
+        enum OtherList {
+            Nil,
+            Cons(u8, Box<OtherList>),
+        }
+
+        #[derive(scale_info::TypeInfo)]
+        #[allow(dead_code)]
+        enum Tree {
+            Leaf(u8),
+            Node(Box<Tree>, Box<Tree>),
+        }
+
+        // The same recursive shape has the same fingerprint, however often we ask.
+        let list = fingerprint_of::<List>();
+        assert_eq!(list, fingerprint_of::<List>());
+        assert_eq!(list, fingerprint_of::<OtherList>());
+
+        // Different recursive shapes, or a recursive and non recursive shape, differ.
+        assert_ne!(list, fingerprint_of::<Tree>());
+        assert_ne!(list, fingerprint_of::<Option<(u8, Box<u8>)>>());
+        assert_ne!(fingerprint_of::<Vec<List>>(), fingerprint_of::<Vec<Tree>>());
+
+        // Nested uses of a recursive type point at the right level of the stack.
+        assert_ne!(fingerprint_of::<(List, List)>(), fingerprint_of::<(List, Tree)>());
+    }
+
+    #[test]
+    fn decode_with_fingerprint_works() {
+        let (id, types) = make_type::<(u8, bool)>();
+        let encoded = (123u8, true).encode();
+        let input = &mut &*encoded;
+
+        let (val, fp) = decode_with_fingerprint::<(u64, bool), _>(input, id, &types).unwrap();
+
+        assert_eq!(val, (123, true));
+        assert_eq!(fp, fingerprint(id, &types).unwrap());
+        assert_eq!(input.len(), 0, "all bytes should be consumed");
+    }
+}
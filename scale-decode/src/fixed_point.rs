@@ -0,0 +1,429 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Decode fixed point types, such as `FixedU128` or `Perbill`, into an `f64` or a
+//! [`rust_decimal::Decimal`].
+//!
+//! Fixed point types are encoded as plain integers; the number that they represent depends on the
+//! type itself. So, rather than looking at the bytes, we look at the path of each type that we
+//! decode and use a [`FixedPointTypes`] mapping to know how to scale the underlying integer.
+//!
+//! ```rust
+//! use codec::Encode;
+//! use scale_decode::fixed_point::{decode_fixed_point, FixedPointTypes};
+//! use scale_info::{PortableRegistry, TypeInfo};
+//!
+//! // A type that looks like the one from sp_arithmetic:
+//! #[derive(Encode, TypeInfo)]
+//! struct Perbill(u32);
+//!
+//! let m = scale_info::MetaType::new::<Perbill>();
+//! let mut types = scale_info::Registry::new();
+//! let type_id = types.register_type(&m).id;
+//! let types: PortableRegistry = types.into();
+//!
+//! let encoded = Perbill(250_000_000).encode();
+//! let value: f64 =
+//!     decode_fixed_point(&mut &*encoded, type_id, &types, &FixedPointTypes::substrate()).unwrap();
+//! assert_eq!(value, 0.25);
+//! ```
+
+use crate::{
+    error::{Error, ErrorKind},
+    visitor::{decode_with_visitor, types::Composite, types::Tuple, Unexpected, Visitor},
+};
+use alloc::{borrow::Cow, format, vec::Vec};
+use core::marker::PhantomData;
+use rust_decimal::Decimal;
+use scale_type_resolver::TypeResolver;
+
+/// How the integer stored in a fixed point type maps to the number that it represents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scaling {
+    /// The stored integer is divided by `10^n`; ie there are `n` decimal places.
+    Decimals(u32),
+    /// The stored integer is divided by the given number.
+    Divisor(u128),
+}
+
+impl Scaling {
+    /// The number that the stored integer is divided by, if it fits in a `u128`.
+    pub fn divisor(&self) -> Option<u128> {
+        match *self {
+            Scaling::Decimals(n) => 10u128.checked_pow(n),
+            Scaling::Divisor(d) => Some(d),
+        }
+    }
+}
+
+/// A mapping from type paths to the [`Scaling`] that should be applied to them.
+#[derive(Debug, Clone, Default)]
+pub struct FixedPointTypes {
+    types: Vec<(Cow<'static, str>, Scaling)>,
+    fallback: Option<Scaling>,
+}
+
+impl FixedPointTypes {
+    /// An empty mapping, which won't recognise any types.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A mapping which recognises the fixed point and "per thing" types from `sp-arithmetic`,
+    /// by name.
+    pub fn substrate() -> Self {
+        Self::new()
+            .with_type("Percent", Scaling::Decimals(2))
+            .with_type("PerU16", Scaling::Divisor(u16::MAX as u128))
+            .with_type("Permill", Scaling::Decimals(6))
+            .with_type("Perbill", Scaling::Decimals(9))
+            .with_type("Perquintill", Scaling::Decimals(18))
+            .with_type("FixedI64", Scaling::Decimals(9))
+            .with_type("FixedU64", Scaling::Decimals(9))
+            .with_type("FixedI128", Scaling::Decimals(18))
+            .with_type("FixedU128", Scaling::Decimals(18))
+    }
+
+    /// Recognise a type and decode it using the given [`Scaling`]. The path can either be a full
+    /// type path like `"sp_arithmetic::per_things::Perbill"`, or just the type name, eg
+    /// `"Perbill"`, in which case any type with this name will be recognised. Later entries take
+    /// precedence over earlier ones.
+    pub fn with_type(mut self, path: impl Into<Cow<'static, str>>, scaling: Scaling) -> Self {
+        self.types.push((path.into(), scaling));
+        self
+    }
+
+    /// Decode plain numbers which aren't wrapped in a recognised type using the given [`Scaling`].
+    /// Without this, such numbers will fail to decode.
+    pub fn with_fallback(mut self, scaling: Scaling) -> Self {
+        self.fallback = Some(scaling);
+        self
+    }
+
+    /// Return the [`Scaling`] to use for a type with the given path, if one is known.
+    pub fn scaling_for<'a>(&self, path: impl IntoIterator<Item = &'a str>) -> Option<Scaling> {
+        let path: Vec<&str> = path.into_iter().collect();
+        let name = path.last()?;
+        let full_path = path.join("::");
+
+        self.types.iter().rev().find_map(|(p, scaling)| {
+            let is_match = if p.contains("::") { *p == full_path } else { p == name };
+            is_match.then_some(*scaling)
+        })
+    }
+}
+
+/// The raw integer stored in some fixed point type, and the [`Scaling`] that should be applied to
+/// it in order to get the number that it represents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedPoint {
+    /// Is the number negative?
+    pub negative: bool,
+    /// The absolute value of the stored integer.
+    pub magnitude: u128,
+    /// How to scale the stored integer.
+    pub scaling: Scaling,
+}
+
+impl FixedPoint {
+    fn out_of_range(&self) -> Error {
+        let sign = if self.negative { "-" } else { "" };
+        Error::new(ErrorKind::NumberOutOfRange { value: format!("{sign}{}", self.magnitude) })
+    }
+}
+
+/// Types which a [`FixedPoint`] value can be converted into.
+pub trait FromFixedPoint: Sized {
+    /// Convert the given [`FixedPoint`] into `Self`.
+    fn from_fixed_point(value: FixedPoint) -> Result<Self, Error>;
+}
+
+impl FromFixedPoint for FixedPoint {
+    fn from_fixed_point(value: FixedPoint) -> Result<Self, Error> {
+        Ok(value)
+    }
+}
+
+impl FromFixedPoint for f64 {
+    fn from_fixed_point(value: FixedPoint) -> Result<Self, Error> {
+        let divisor = value.scaling.divisor().ok_or_else(|| value.out_of_range())?;
+        if divisor == 0 {
+            return Err(Error::custom_str("cannot scale a fixed point number by a divisor of 0"));
+        }
+        let n = value.magnitude as f64 / divisor as f64;
+        Ok(if value.negative { -n } else { n })
+    }
+}
+
+impl FromFixedPoint for Decimal {
+    fn from_fixed_point(value: FixedPoint) -> Result<Self, Error> {
+        let magnitude = i128::try_from(value.magnitude).map_err(|_| value.out_of_range())?;
+        let mut n = match value.scaling {
+            Scaling::Decimals(scale) => Decimal::try_from_i128_with_scale(magnitude, scale)
+                .map_err(|_| value.out_of_range())?,
+            Scaling::Divisor(divisor) => {
+                let divisor = i128::try_from(divisor)
+                    .ok()
+                    .and_then(|d| Decimal::try_from_i128_with_scale(d, 0).ok())
+                    .ok_or_else(|| value.out_of_range())?;
+                Decimal::try_from_i128_with_scale(magnitude, 0)
+                    .ok()
+                    .and_then(|m| m.checked_div(divisor))
+                    .ok_or_else(|| value.out_of_range())?
+            }
+        };
+        n.set_sign_negative(value.negative);
+        Ok(n)
+    }
+}
+
+/// Decode some bytes into `T`, using the given [`FixedPointTypes`] to work out how the
+/// underlying integer should be scaled.
+pub fn decode_fixed_point<T: FromFixedPoint, R: TypeResolver>(
+    input: &mut &[u8],
+    type_id: R::TypeId,
+    types: &R,
+    fixed_point_types: &FixedPointTypes,
+) -> Result<T, Error> {
+    decode_with_visitor(input, type_id, types, FixedPointVisitor::new(fixed_point_types))
+}
+
+/// A [`Visitor`] which decodes recognised fixed point types into `T`. Composites and tuples
+/// which aren't recognised, but contain a single field, are decoded through.
+pub struct FixedPointVisitor<'a, T, R> {
+    fixed_point_types: &'a FixedPointTypes,
+    _marker: PhantomData<(T, R)>,
+}
+
+impl<'a, T, R> FixedPointVisitor<'a, T, R> {
+    /// Construct a new [`FixedPointVisitor`].
+    pub fn new(fixed_point_types: &'a FixedPointTypes) -> Self {
+        FixedPointVisitor { fixed_point_types, _marker: PhantomData }
+    }
+}
+
+// Turn any number into the [`FixedPoint`] that it represents, given some scaling.
+macro_rules! to_fixed_point {
+    (unsigned $value:expr, $scaling:expr) => {
+        FixedPoint { negative: false, magnitude: $value as u128, scaling: $scaling }
+    };
+    (signed $value:expr, $scaling:expr) => {
+        FixedPoint {
+            negative: $value < 0,
+            magnitude: $value.unsigned_abs() as u128,
+            scaling: $scaling,
+        }
+    };
+}
+
+// Visit numbers which aren't wrapped in a recognised fixed point type.
+macro_rules! visit_number_fn_impl {
+    ($name:ident : $ty:ty, $sign:ident) => {
+        fn $name<'scale, 'resolver>(
+            self,
+            value: $ty,
+            _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            let Some(scaling) = self.fixed_point_types.fallback else {
+                return Err(Error::custom_str(
+                    "cannot decode a number which isn't wrapped in a known fixed point type",
+                ));
+            };
+            T::from_fixed_point(to_fixed_point!($sign value, scaling))
+        }
+    };
+}
+
+impl<T: FromFixedPoint, R: TypeResolver> Visitor for FixedPointVisitor<'_, T, R> {
+    type Error = Error;
+    type Value<'scale, 'resolver> = T;
+    type TypeResolver = R;
+
+    fn visit_composite<'scale, 'resolver>(
+        self,
+        value: &mut Composite<'scale, 'resolver, R>,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        if value.remaining() != 1 {
+            return self.visit_unexpected(Unexpected::Composite);
+        }
+        match self.fixed_point_types.scaling_for(value.path()) {
+            Some(scaling) => {
+                let number = value.decode_item(RawNumberVisitor(PhantomData)).unwrap()?;
+                T::from_fixed_point(FixedPoint { scaling, ..number })
+            }
+            None => value.decode_item(self).unwrap(),
+        }
+    }
+
+    fn visit_tuple<'scale, 'resolver>(
+        self,
+        value: &mut Tuple<'scale, 'resolver, R>,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        if value.remaining() != 1 {
+            return self.visit_unexpected(Unexpected::Tuple);
+        }
+        value.decode_item(self).unwrap()
+    }
+
+    visit_number_fn_impl!(visit_u8: u8, unsigned);
+    visit_number_fn_impl!(visit_u16: u16, unsigned);
+    visit_number_fn_impl!(visit_u32: u32, unsigned);
+    visit_number_fn_impl!(visit_u64: u64, unsigned);
+    visit_number_fn_impl!(visit_u128: u128, unsigned);
+    visit_number_fn_impl!(visit_i8: i8, signed);
+    visit_number_fn_impl!(visit_i16: i16, signed);
+    visit_number_fn_impl!(visit_i32: i32, signed);
+    visit_number_fn_impl!(visit_i64: i64, signed);
+    visit_number_fn_impl!(visit_i128: i128, signed);
+}
+
+// Decode the integer stored inside a recognised fixed point type. The scaling is filled in
+// by the caller.
+struct RawNumberVisitor<R>(PhantomData<R>);
+
+macro_rules! visit_raw_number_fn_impl {
+    ($name:ident : $ty:ty, $sign:ident) => {
+        fn $name<'scale, 'resolver>(
+            self,
+            value: $ty,
+            _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            Ok(to_fixed_point!($sign value, Scaling::Decimals(0)))
+        }
+    };
+}
+
+impl<R: TypeResolver> Visitor for RawNumberVisitor<R> {
+    type Error = Error;
+    type Value<'scale, 'resolver> = FixedPoint;
+    type TypeResolver = R;
+
+    visit_raw_number_fn_impl!(visit_u8: u8, unsigned);
+    visit_raw_number_fn_impl!(visit_u16: u16, unsigned);
+    visit_raw_number_fn_impl!(visit_u32: u32, unsigned);
+    visit_raw_number_fn_impl!(visit_u64: u64, unsigned);
+    visit_raw_number_fn_impl!(visit_u128: u128, unsigned);
+    visit_raw_number_fn_impl!(visit_i8: i8, signed);
+    visit_raw_number_fn_impl!(visit_i16: i16, signed);
+    visit_raw_number_fn_impl!(visit_i32: i32, signed);
+    visit_raw_number_fn_impl!(visit_i64: i64, signed);
+    visit_raw_number_fn_impl!(visit_i128: i128, signed);
+    crate::impls::visit_single_field_composite_tuple_impls!(R);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::String;
+    use codec::Encode;
+    use scale_info::{PortableRegistry, TypeInfo};
+
+    fn make_type<T: TypeInfo + 'static>() -> (u32, PortableRegistry) {
+        let m = scale_info::MetaType::new::<T>();
+        let mut types = scale_info::Registry::new();
+        let id = types.register_type(&m);
+        let portable_registry: PortableRegistry = types.into();
+
+        (id.id, portable_registry)
+    }
+
+    fn decode<T: FromFixedPoint, V: Encode + TypeInfo + 'static>(
+        value: V,
+        fixed_point_types: &FixedPointTypes,
+    ) -> Result<T, Error> {
+        let (type_id, types) = make_type::<V>();
+        let encoded = value.encode();
+        decode_fixed_point(&mut &*encoded, type_id, &types, fixed_point_types)
+    }
+
+    #[derive(Encode, TypeInfo)]
+    struct Perbill(u32);
+    #[derive(Encode, TypeInfo)]
+    struct FixedI128(i128);
+    #[derive(Encode, TypeInfo)]
+    struct PerU16(u16);
+
+    #[test]
+    fn decodes_substrate_types_to_f64() {
+        let types = FixedPointTypes::substrate();
+        assert_eq!(decode::<f64, _>(Perbill(500_000_000), &types).unwrap(), 0.5);
+        assert_eq!(decode::<f64, _>(FixedI128(-1_500_000_000_000_000_000), &types).unwrap(), -1.5);
+        assert_eq!(decode::<f64, _>(PerU16(u16::MAX), &types).unwrap(), 1.0);
+    }
+
+    #[test]
+    fn decodes_substrate_types_to_decimal() {
+        let types = FixedPointTypes::substrate();
+        assert_eq!(
+            decode::<Decimal, _>(Perbill(123_456_789), &types).unwrap(),
+            Decimal::new(123_456_789, 9)
+        );
+        assert_eq!(
+            decode::<Decimal, _>(FixedI128(-2_250_000_000_000_000_000), &types).unwrap(),
+            Decimal::new(-225, 2)
+        );
+    }
+
+    #[test]
+    fn decodes_through_wrapper_types() {
+        #[derive(Encode, TypeInfo)]
+        struct Wrapper {
+            inner: (Perbill,),
+        }
+
+        let types = FixedPointTypes::substrate();
+        assert_eq!(
+            decode::<f64, _>(Wrapper { inner: (Perbill(1_000_000_000),) }, &types).unwrap(),
+            1.0
+        );
+    }
+
+    #[test]
+    fn custom_mappings_and_fallback() {
+        #[derive(Encode, TypeInfo)]
+        struct Price(u64);
+
+        // Unknown types and plain numbers can't be decoded by default:
+        let types = FixedPointTypes::substrate();
+        assert!(decode::<f64, _>(Price(1), &types).is_err());
+        assert!(decode::<f64, _>(1u64, &types).is_err());
+
+        // Types can be recognised by name or full path, and a fallback can be given:
+        let types = FixedPointTypes::new()
+            .with_type("Price", Scaling::Decimals(2))
+            .with_fallback(Scaling::Divisor(4));
+        assert_eq!(decode::<f64, _>(Price(150), &types).unwrap(), 1.5);
+        assert_eq!(decode::<f64, _>(6u8, &types).unwrap(), 1.5);
+        assert_eq!(decode::<f64, _>(-6i32, &types).unwrap(), -1.5);
+
+        let path = String::from("scale_decode::fixed_point::test::Perbill");
+        let types = FixedPointTypes::substrate().with_type(path, Scaling::Decimals(1));
+        assert_eq!(decode::<f64, _>(Perbill(5), &types).unwrap(), 0.5);
+    }
+
+    #[test]
+    fn out_of_range_values_error() {
+        #[derive(Encode, TypeInfo)]
+        struct FixedU128(u128);
+
+        let types = FixedPointTypes::substrate();
+        let err = decode::<Decimal, _>(FixedU128(u128::MAX), &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+
+        // f64 can represent this (albeit imprecisely):
+        assert!(decode::<f64, _>(FixedU128(u128::MAX), &types).is_ok());
+    }
+}
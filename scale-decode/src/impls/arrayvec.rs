@@ -0,0 +1,49 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use super::{visit_single_field_composite_tuple_impls, BasicVisitor};
+use crate::{
+    error::{Error, ErrorKind},
+    visitor::types::Str,
+    visitor::Visitor,
+    IntoVisitor,
+};
+use arrayvec::ArrayString;
+use scale_type_resolver::TypeResolver;
+
+impl<const N: usize, R: TypeResolver> Visitor for BasicVisitor<ArrayString<N>, R> {
+    type Error = Error;
+    type Value<'scale, 'resolver> = ArrayString<N>;
+    type TypeResolver = R;
+
+    fn visit_str<'scale, 'resolver>(
+        self,
+        value: &mut Str<'scale>,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let s = value.as_str()?;
+        // Strings longer than the capacity of the ArrayString can't be decoded into it:
+        ArrayString::from(s).map_err(|_e| {
+            Error::new(ErrorKind::WrongLength { actual_len: s.len(), expected_len: N })
+        })
+    }
+    visit_single_field_composite_tuple_impls!(R);
+}
+
+impl<const N: usize> IntoVisitor for ArrayString<N> {
+    type AnyVisitor<R: TypeResolver> = BasicVisitor<ArrayString<N>, R>;
+    fn into_visitor<R: TypeResolver>() -> Self::AnyVisitor<R> {
+        BasicVisitor { _marker: core::marker::PhantomData }
+    }
+}
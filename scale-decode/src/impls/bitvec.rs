@@ -0,0 +1,50 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{visit_single_field_composite_tuple_impls, BasicVisitor};
+use crate::{
+    error::{Error, ErrorKind},
+    visitor::{types::BitSequence, Visitor},
+    IntoVisitor,
+};
+use bitvec::{order::BitOrder, store::BitStore, vec::BitVec};
+use scale_type_resolver::TypeResolver;
+
+// The bits are decoded according to the store and order formats given in the type information,
+// and then collected into whichever store and order type we're decoding into.
+impl<T: BitStore, O: BitOrder, R: TypeResolver> Visitor for BasicVisitor<BitVec<T, O>, R> {
+    type Error = Error;
+    type Value<'scale, 'resolver> = BitVec<T, O>;
+    type TypeResolver = R;
+
+    fn visit_bitsequence<'scale, 'resolver>(
+        self,
+        value: &mut BitSequence<'scale>,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        value
+            .decode()?
+            .collect::<Result<BitVec<T, O>, _>>()
+            .map_err(|e| Error::new(ErrorKind::VisitorDecodeError(e.into())))
+    }
+    visit_single_field_composite_tuple_impls!(R);
+}
+
+impl<T: BitStore, O: BitOrder> IntoVisitor for BitVec<T, O> {
+    type AnyVisitor<R: TypeResolver> = BasicVisitor<BitVec<T, O>, R>;
+    fn into_visitor<R: TypeResolver>() -> Self::AnyVisitor<R> {
+        BasicVisitor { _marker: core::marker::PhantomData }
+    }
+}
@@ -0,0 +1,47 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::timestamp::{FromTimestamp, TimestampUnit, TimestampVisitor};
+use crate::IntoVisitor;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use scale_type_resolver::TypeResolver;
+
+impl FromTimestamp for DateTime<Utc> {
+    fn from_timestamp(value: i64, unit: TimestampUnit) -> Option<Self> {
+        match unit {
+            TimestampUnit::Seconds => DateTime::from_timestamp(value, 0),
+            TimestampUnit::Millis => DateTime::from_timestamp_millis(value),
+        }
+    }
+}
+
+impl IntoVisitor for DateTime<Utc> {
+    type AnyVisitor<R: TypeResolver> = TimestampVisitor<DateTime<Utc>, R>;
+    fn into_visitor<R: TypeResolver>() -> Self::AnyVisitor<R> {
+        TimestampVisitor::new(TimestampUnit::Millis)
+    }
+}
+
+impl FromTimestamp for NaiveDateTime {
+    fn from_timestamp(value: i64, unit: TimestampUnit) -> Option<Self> {
+        <DateTime<Utc> as FromTimestamp>::from_timestamp(value, unit).map(|d| d.naive_utc())
+    }
+}
+
+impl IntoVisitor for NaiveDateTime {
+    type AnyVisitor<R: TypeResolver> = TimestampVisitor<NaiveDateTime, R>;
+    fn into_visitor<R: TypeResolver>() -> Self::AnyVisitor<R> {
+        TimestampVisitor::new(TimestampUnit::Millis)
+    }
+}
@@ -0,0 +1,161 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{decode_single_variant_field, visit_single_field_composite_tuple_impls, BasicVisitor};
+use crate::{
+    error::Error,
+    visitor::{types::Variant, Visitor},
+    IntoVisitor,
+};
+use alloc::vec;
+use core::marker::PhantomData;
+use either::Either;
+use scale_type_resolver::{
+    FieldIter, PathIter, ResolvedTypeVisitor, TypeResolver, UnhandledKind, VariantIter,
+};
+use smallvec::SmallVec;
+
+impl<L: IntoVisitor, Rt: IntoVisitor, R: TypeResolver> Visitor for BasicVisitor<Either<L, Rt>, R> {
+    type Error = Error;
+    type Value<'scale, 'resolver> = Either<L, Rt>;
+    type TypeResolver = R;
+
+    fn visit_variant<'scale, 'resolver>(
+        self,
+        value: &mut Variant<'scale, 'resolver, R>,
+        type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let num_fields = value.fields().remaining();
+        let is_left = match value.name() {
+            "Left" => Some(true),
+            "Right" => Some(false),
+            // Otherwise, the first of any two variants is "Left" and the second is "Right".
+            _ => variant_position(value.types(), type_id, value.index()).map(|pos| pos == 0),
+        };
+
+        match (is_left, num_fields) {
+            (Some(true), 1) => decode_single_variant_field(value).map(Either::Left),
+            (Some(false), 1) => decode_single_variant_field(value).map(Either::Right),
+            _ => {
+                Err(Error::cannot_find_variant(value.name(), value.index(), vec!["Left", "Right"]))
+            }
+        }
+    }
+    visit_single_field_composite_tuple_impls!(R);
+}
+
+impl<L: IntoVisitor, Rt: IntoVisitor> IntoVisitor for Either<L, Rt> {
+    type AnyVisitor<R: TypeResolver> = BasicVisitor<Either<L, Rt>, R>;
+    fn into_visitor<R: TypeResolver>() -> Self::AnyVisitor<R> {
+        BasicVisitor { _marker: core::marker::PhantomData }
+    }
+}
+
+// The position of the variant with the given index, if the type has exactly two variants.
+fn variant_position<R: TypeResolver>(types: &R, type_id: R::TypeId, index: u8) -> Option<usize> {
+    types.resolve_type(type_id, VariantPositionVisitor(index, PhantomData)).ok().flatten()
+}
+
+struct VariantPositionVisitor<TypeId>(u8, PhantomData<TypeId>);
+
+impl<'resolver, TypeId: scale_type_resolver::TypeId + 'static> ResolvedTypeVisitor<'resolver>
+    for VariantPositionVisitor<TypeId>
+{
+    type TypeId = TypeId;
+    type Value = Option<usize>;
+
+    fn visit_unhandled(self, _kind: UnhandledKind) -> Self::Value {
+        None
+    }
+    fn visit_variant<Path, Fields, Var>(self, _path: Path, variants: Var) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+        Fields: FieldIter<'resolver, Self::TypeId>,
+        Var: VariantIter<'resolver, Fields>,
+    {
+        let indexes: SmallVec<[u8; 2]> = variants.map(|v| v.index).collect();
+        if indexes.len() != 2 {
+            return None;
+        }
+        indexes.iter().position(|i| *i == self.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::DecodeAsType;
+    use alloc::string::{String, ToString};
+    use codec::Encode;
+    use scale_info::TypeInfo;
+
+    fn decode_as<T: Encode + TypeInfo + 'static, Out: DecodeAsType>(
+        value: T,
+    ) -> Result<Out, Error> {
+        let m = scale_info::MetaType::new::<T>();
+        let mut types = scale_info::Registry::new();
+        let type_id = types.register_type(&m).id;
+        let types: scale_info::PortableRegistry = types.into();
+
+        Out::decode_as_type(&mut &*value.encode(), type_id, &types)
+    }
+
+    #[test]
+    fn either_decodes_from_left_and_right_variants() {
+        #[derive(Encode, TypeInfo)]
+        enum Named {
+            #[codec(index = 5)]
+            Right(String),
+            Middle,
+            Left(u8),
+        }
+
+        let left: Either<u64, String> = decode_as(Named::Left(1)).unwrap();
+        assert_eq!(left, Either::Left(1));
+        let right: Either<u64, String> = decode_as(Named::Right("hi".to_string())).unwrap();
+        assert_eq!(right, Either::Right("hi".to_string()));
+        assert!(decode_as::<_, Either<u64, String>>(Named::Middle).is_err());
+    }
+
+    #[test]
+    fn either_decodes_from_any_two_variant_enum_by_position() {
+        #[derive(Encode, TypeInfo)]
+        enum Foo {
+            #[codec(index = 3)]
+            Number(u8),
+            #[codec(index = 1)]
+            Text(String),
+        }
+
+        let left: Either<u64, String> = decode_as(Foo::Number(1)).unwrap();
+        assert_eq!(left, Either::Left(1));
+        let right: Either<u64, String> = decode_as(Foo::Text("hi".to_string())).unwrap();
+        assert_eq!(right, Either::Right("hi".to_string()));
+        let left: Either<u64, String> = decode_as(Ok::<_, String>(2u8)).unwrap();
+        assert_eq!(left, Either::Left(2));
+
+        // Three variants is too many to decode by position:
+        #[derive(Encode, TypeInfo)]
+        enum Three {
+            A(u8),
+            B(u8),
+            C(u8),
+        }
+        let err = decode_as::<_, Either<u64, u64>>(Three::A(1)).unwrap_err();
+        assert!(err.to_string().contains("Cannot find variant A"), "{err}");
+        assert!(decode_as::<_, Either<u64, u64>>(Three::B(1)).is_err());
+        assert!(decode_as::<_, Either<u64, u64>>(Three::C(1)).is_err());
+    }
+}
@@ -0,0 +1,87 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{visit_single_field_composite_tuple_impls, BasicVisitor};
+use crate::{
+    error::{Error, ErrorKind},
+    visitor::Visitor,
+    IntoVisitor,
+};
+use alloc::string::ToString;
+use scale_type_resolver::TypeResolver;
+
+impl<R: TypeResolver> Visitor for BasicVisitor<f64, R> {
+    type Error = Error;
+    type Value<'scale, 'resolver> = f64;
+    type TypeResolver = R;
+
+    fn visit_f32<'scale, 'resolver>(
+        self,
+        value: f32,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        Ok(value.into())
+    }
+    fn visit_f64<'scale, 'resolver>(
+        self,
+        value: f64,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        Ok(value)
+    }
+    visit_single_field_composite_tuple_impls!(R);
+}
+
+// An f64 is only decoded into an f32 if it can be represented exactly.
+impl<R: TypeResolver> Visitor for BasicVisitor<f32, R> {
+    type Error = Error;
+    type Value<'scale, 'resolver> = f32;
+    type TypeResolver = R;
+
+    fn visit_f32<'scale, 'resolver>(
+        self,
+        value: f32,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        Ok(value)
+    }
+    fn visit_f64<'scale, 'resolver>(
+        self,
+        value: f64,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let n = value as f32;
+        if f64::from(n) == value || value.is_nan() {
+            Ok(n)
+        } else {
+            Err(Error::new(ErrorKind::NumberOutOfRange { value: value.to_string() }))
+        }
+    }
+    visit_single_field_composite_tuple_impls!(R);
+}
+
+impl IntoVisitor for f64 {
+    type AnyVisitor<R: TypeResolver> = BasicVisitor<f64, R>;
+    fn into_visitor<R: TypeResolver>() -> Self::AnyVisitor<R> {
+        BasicVisitor { _marker: core::marker::PhantomData }
+    }
+}
+
+impl IntoVisitor for f32 {
+    type AnyVisitor<R: TypeResolver> = BasicVisitor<f32, R>;
+    fn into_visitor<R: TypeResolver>() -> Self::AnyVisitor<R> {
+        BasicVisitor { _marker: core::marker::PhantomData }
+    }
+}
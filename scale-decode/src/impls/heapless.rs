@@ -0,0 +1,148 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{decode_items_using, visit_single_field_composite_tuple_impls, BasicVisitor};
+use crate::{
+    error::{Error, ErrorKind},
+    visitor::{
+        types::{Array, Sequence, Str},
+        DecodeItemIterator, Visitor,
+    },
+    IntoVisitor,
+};
+use heapless::{String, Vec};
+use scale_type_resolver::TypeResolver;
+
+// Decode each item into a heapless::Vec, so long as there aren't more items than will fit.
+fn decode_items_into_vec<'scale, 'resolver, T, R, D, const N: usize>(
+    items: &mut D,
+    actual_len: usize,
+) -> Result<Vec<T, N>, Error>
+where
+    T: IntoVisitor,
+    R: TypeResolver,
+    D: DecodeItemIterator<'scale, 'resolver, R>,
+{
+    if actual_len > N {
+        return Err(Error::new(ErrorKind::WrongLength { actual_len, expected_len: N }));
+    }
+    let mut vec = Vec::new();
+    for item in decode_items_using::<_, _, T>(items) {
+        // We checked the length above, so this won't fail.
+        let _ = vec.push(item?);
+    }
+    Ok(vec)
+}
+
+impl<T: IntoVisitor, const N: usize, R: TypeResolver> Visitor for BasicVisitor<Vec<T, N>, R> {
+    type Error = Error;
+    type Value<'scale, 'resolver> = Vec<T, N>;
+    type TypeResolver = R;
+
+    fn visit_sequence<'scale, 'resolver>(
+        self,
+        value: &mut Sequence<'scale, 'resolver, R>,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let actual_len = value.remaining();
+        decode_items_into_vec(value, actual_len)
+    }
+    fn visit_array<'scale, 'resolver>(
+        self,
+        value: &mut Array<'scale, 'resolver, R>,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let actual_len = value.remaining();
+        decode_items_into_vec(value, actual_len)
+    }
+    visit_single_field_composite_tuple_impls!(R);
+}
+
+impl<T: IntoVisitor, const N: usize> IntoVisitor for Vec<T, N> {
+    type AnyVisitor<R: TypeResolver> = BasicVisitor<Vec<T, N>, R>;
+    fn into_visitor<R: TypeResolver>() -> Self::AnyVisitor<R> {
+        BasicVisitor { _marker: core::marker::PhantomData }
+    }
+}
+
+impl<const N: usize, R: TypeResolver> Visitor for BasicVisitor<String<N>, R> {
+    type Error = Error;
+    type Value<'scale, 'resolver> = String<N>;
+    type TypeResolver = R;
+
+    fn visit_str<'scale, 'resolver>(
+        self,
+        value: &mut Str<'scale>,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let s = value.as_str()?;
+        // Strings longer than the capacity of the heapless::String can't be decoded into it:
+        let mut string = String::new();
+        string.push_str(s).map_err(|_e| {
+            Error::new(ErrorKind::WrongLength { actual_len: s.len(), expected_len: N })
+        })?;
+        Ok(string)
+    }
+    visit_single_field_composite_tuple_impls!(R);
+}
+
+impl<const N: usize> IntoVisitor for String<N> {
+    type AnyVisitor<R: TypeResolver> = BasicVisitor<String<N>, R>;
+    fn into_visitor<R: TypeResolver>() -> Self::AnyVisitor<R> {
+        BasicVisitor { _marker: core::marker::PhantomData }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::DecodeAsType;
+    use alloc::{string::ToString, vec};
+    use codec::Encode;
+    use scale_info::TypeInfo;
+
+    fn decode_as<T: Encode + TypeInfo + 'static, Out: DecodeAsType>(
+        value: T,
+    ) -> Result<Out, Error> {
+        let m = scale_info::MetaType::new::<T>();
+        let mut types = scale_info::Registry::new();
+        let type_id = types.register_type(&m).id;
+        let types: scale_info::PortableRegistry = types.into();
+
+        Out::decode_as_type(&mut &*value.encode(), type_id, &types)
+    }
+
+    #[test]
+    fn heapless_vecs_decode_up_to_their_capacity() {
+        let vec: Vec<u16, 3> = decode_as(vec![1u8, 2, 3]).unwrap();
+        assert_eq!(&*vec, &[1, 2, 3]);
+        let vec: Vec<u16, 4> = decode_as([1u8, 2]).unwrap();
+        assert_eq!(&*vec, &[1, 2]);
+
+        let err = decode_as::<_, Vec<u16, 2>>(vec![1u8, 2, 3]).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::WrongLength { actual_len: 3, expected_len: 2 }));
+        let err = decode_as::<_, Vec<u8, 2>>(vec![1u16, 300]).unwrap_err();
+        assert_eq!(err.context().path().to_string(), "[1]");
+    }
+
+    #[test]
+    fn heapless_strings_decode_up_to_their_capacity() {
+        let s: String<5> = decode_as("hello".to_string()).unwrap();
+        assert_eq!(s.as_str(), "hello");
+
+        let err = decode_as::<_, String<4>>("hello".to_string()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::WrongLength { actual_len: 5, expected_len: 4 }));
+    }
+}
@@ -13,15 +13,44 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "arrayvec")]
+mod arrayvec;
+#[cfg(feature = "bitvec")]
+mod bitvec;
+#[cfg(feature = "bytes")]
+mod bytes;
+#[cfg(feature = "chrono")]
+mod chrono;
+#[cfg(feature = "either")]
+mod either;
+#[cfg(feature = "float")]
+mod float;
+#[cfg(feature = "heapless")]
+mod heapless;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "net")]
+mod net;
 #[cfg(feature = "primitive-types")]
 mod primitive_types;
+#[cfg(feature = "smol_str")]
+mod smol_str;
+#[cfg(feature = "substrate-types")]
+mod substrate;
+#[cfg(feature = "time")]
+mod time;
+#[cfg(feature = "uuid")]
+mod uuid;
+mod value;
 
 use crate::{
     error::{Error, ErrorKind},
     visitor::{
-        self, decode_with_visitor, types::*, DecodeAsTypeResult, DecodeItemIterator, Visitor,
+        self, decode_with_visitor, types::*, CompactLocation, DecodeAsTypeResult,
+        DecodeItemIterator, Visitor,
     },
-    DecodeAsFields, FieldIter, IntoVisitor,
+    DecodeAsFields, DecodeVia, FieldIter, Ignored, IntoVisitor, MaybeCompact, PrefixTuple,
+    ShapeOption, ShapeResult,
 };
 use alloc::{
     borrow::{Cow, ToOwned},
@@ -35,12 +64,13 @@ use alloc::{
 };
 use codec::Compact;
 use core::num::{
-    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroU128, NonZeroU16,
-    NonZeroU32, NonZeroU64, NonZeroU8,
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize, Saturating, Wrapping,
 };
 use core::{
     marker::PhantomData,
-    ops::{Range, RangeInclusive},
+    ops::{Bound, Range, RangeFrom, RangeInclusive, RangeTo, RangeToInclusive},
+    str::FromStr,
     time::Duration,
 };
 use scale_bits::Bits;
@@ -89,6 +119,9 @@ macro_rules! visit_single_field_composite_tuple_impls {
         }
     };
 }
+// Make the above macros usable from the feature gated submodules, too.
+#[allow(unused_imports)]
+pub(crate) use {impl_into_visitor, visit_single_field_composite_tuple_impls};
 
 impl<R: TypeResolver> Visitor for BasicVisitor<char, R> {
     type Error = Error;
@@ -188,6 +221,21 @@ impl<T, R: TypeResolver> Visitor for BasicVisitor<PhantomData<T>, R> {
 }
 impl_into_visitor!(PhantomData<T>);
 
+impl<R: TypeResolver> Visitor for BasicVisitor<Ignored, R> {
+    type Error = Error;
+    type Value<'scale, 'resolver> = Ignored;
+    type TypeResolver = R;
+
+    // Whatever the value is, skip over it. Any bytes we don't consume are skipped for us.
+    fn visit_unexpected<'scale, 'resolver>(
+        self,
+        _unexpected: visitor::Unexpected,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        Ok(Ignored)
+    }
+}
+impl_into_visitor!(Ignored);
+
 // Generate impls to encode things based on some other type. We do this by implementing
 // `IntoVisitor` and using the `AndThen` combinator to map from an existing one to the desired output.
 macro_rules! impl_into_visitor_like {
@@ -223,12 +271,70 @@ impl_into_visitor_like!(Compact<T> as T: |res| Compact(res));
 impl_into_visitor_like!(Arc<T> as T: |res| Arc::new(res));
 impl_into_visitor_like!(Rc<T> as T: |res| Rc::new(res));
 impl_into_visitor_like!(Box<T> as T: |res| Box::new(res));
-impl_into_visitor_like!(Duration as (u64, u32): |res: (u64,u32)| Duration::from_secs(res.0) + Duration::from_nanos(res.1 as u64));
 impl_into_visitor_like!(Range<T> as (T, T): |res: (T,T)| res.0..res.1);
 impl_into_visitor_like!(RangeInclusive<T> as (T, T): |res: (T,T)| res.0..=res.1);
+impl_into_visitor_like!(RangeFrom<T> as T: |res| res..);
+impl_into_visitor_like!(RangeTo<T> as T: |res| ..res);
+impl_into_visitor_like!(RangeToInclusive<T> as T: |res| ..=res);
+impl_into_visitor_like!(Wrapping<T> as T: |res| Wrapping(res));
+impl_into_visitor_like!(Saturating<T> as T: |res| Saturating(res));
+
+// Durations are decoded from a `(u64, u32)` shape of seconds and nanoseconds, as they are encoded,
+// or alternately from a single unsigned integer of milliseconds.
+macro_rules! visit_duration_millis_fn_impl {
+    ($name:ident : $ty:ty) => {
+        fn $name<'scale, 'resolver>(
+            self,
+            value: $ty,
+            _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            let millis = u64::try_from(value).map_err(|_| {
+                Error::new(ErrorKind::NumberOutOfRange { value: value.to_string() })
+            })?;
+            Ok(Duration::from_millis(millis))
+        }
+    };
+}
+impl<R: TypeResolver> Visitor for BasicVisitor<Duration, R> {
+    type Error = Error;
+    type Value<'scale, 'resolver> = Duration;
+    type TypeResolver = R;
+
+    visit_duration_millis_fn_impl!(visit_u8: u8);
+    visit_duration_millis_fn_impl!(visit_u16: u16);
+    visit_duration_millis_fn_impl!(visit_u32: u32);
+    visit_duration_millis_fn_impl!(visit_u64: u64);
+    visit_duration_millis_fn_impl!(visit_u128: u128);
+
+    fn visit_composite<'scale, 'resolver>(
+        self,
+        value: &mut Composite<'scale, 'resolver, R>,
+        type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        if value.remaining() == 1 {
+            return value.decode_item(self).unwrap();
+        }
+        let (secs, nanos) = <(u64, u32)>::into_visitor().visit_composite(value, type_id)?;
+        Ok(Duration::from_secs(secs) + Duration::from_nanos(nanos as u64))
+    }
+    fn visit_tuple<'scale, 'resolver>(
+        self,
+        value: &mut Tuple<'scale, 'resolver, R>,
+        type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        if value.remaining() == 1 {
+            return value.decode_item(self).unwrap();
+        }
+        let (secs, nanos) = <(u64, u32)>::into_visitor().visit_tuple(value, type_id)?;
+        Ok(Duration::from_secs(secs) + Duration::from_nanos(nanos as u64))
+    }
+}
+impl_into_visitor!(Duration);
 
 // A custom implementation for `Cow` because it's rather tricky; the visitor we want is whatever the
-// `ToOwned` value for the Cow is, and Cow's have specific constraints, too.
+// `ToOwned` value for the Cow is, and Cow's have specific constraints, too. This covers the likes of
+// `Cow<str>` (decoding into a `String`) and `Cow<[T]>` (decoding into a `Vec<T>`) without needing
+// `str` or `[T]` themselves to implement `IntoVisitor`.
 impl<'a, T, R> Visitor for BasicVisitor<Cow<'a, T>, R>
 where
     T: 'a + ToOwned + ?Sized,
@@ -302,16 +408,21 @@ impl_decode_seq_via_collect!(LinkedList<T>);
 impl_decode_seq_via_collect!(BinaryHeap<T> where T: Ord);
 impl_decode_seq_via_collect!(BTreeSet<T> where T: Ord);
 
-// For arrays of fixed lengths, we decode to a vec first and then try to turn that into the fixed size array.
-// Like vecs, we can decode from tuples, sequences or arrays if the types line up ok.
+// For arrays of fixed lengths, we check the length and then decode each item into place, so that
+// no allocation is needed. Like vecs, we can decode from tuples, sequences or arrays if the types
+// line up ok.
 macro_rules! array_method_impl {
     ($value:ident, [$t:ident; $n:ident]) => {{
-        let val = decode_items_using::<_, _, $t>($value).collect::<Result<Vec<$t>, _>>()?;
-        let actual_len = val.len();
-        let arr = val
-            .try_into()
-            .map_err(|_e| Error::new(ErrorKind::WrongLength { actual_len, expected_len: $n }))?;
-        Ok(arr)
+        let actual_len = $value.remaining();
+        if actual_len != $n {
+            return Err(Error::new(ErrorKind::WrongLength { actual_len, expected_len: $n }));
+        }
+        let mut items = decode_items_using::<_, _, $t>($value);
+        let mut vals: [Option<$t>; $n] = core::array::from_fn(|_| None);
+        for (val, item) in vals.iter_mut().zip(&mut items) {
+            *val = Some(item?);
+        }
+        Ok(vals.map(|val| val.expect("length checked above, so every item is decoded")))
     }};
 }
 impl<const N: usize, T: IntoVisitor, R: TypeResolver> Visitor for BasicVisitor<[T; N], R> {
@@ -343,9 +454,17 @@ impl<const N: usize, T: IntoVisitor> IntoVisitor for [T; N] {
     }
 }
 
-impl<T: IntoVisitor, R: TypeResolver> Visitor for BasicVisitor<BTreeMap<String, T>, R> {
+// Maps can be decoded from named composites, in which case the field names are parsed into keys,
+// or from a sequence of key/value pairs, which is how maps are SCALE encoded.
+impl<K, T, R> Visitor for BasicVisitor<BTreeMap<K, T>, R>
+where
+    K: IntoVisitor + FromStr + Ord,
+    K::Err: core::fmt::Display,
+    T: IntoVisitor,
+    R: TypeResolver,
+{
     type Error = Error;
-    type Value<'scale, 'resolver> = BTreeMap<String, T>;
+    type Value<'scale, 'resolver> = BTreeMap<K, T>;
     type TypeResolver = R;
 
     fn visit_composite<'scale, 'resolver>(
@@ -353,24 +472,55 @@ impl<T: IntoVisitor, R: TypeResolver> Visitor for BasicVisitor<BTreeMap<String,
         value: &mut Composite<'scale, 'resolver, R>,
         _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
     ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        // A single unnamed field (eg the sequence of pairs in a BTreeMap) is decoded through.
+        if value.remaining() == 1 && value.has_unnamed_fields() {
+            return value.decode_item(self).unwrap();
+        }
         let mut map = BTreeMap::new();
         while value.remaining() > 0 {
             // Get the name. If no name, skip over the corresponding value.
-            let Some(key) = value.peek_name() else {
+            let Some(name) = value.peek_name() else {
                 value.decode_item(crate::visitor::IgnoreVisitor::<R>::new()).transpose()?;
                 continue;
             };
-            // Decode the value now that we have a valid name.
+            // Parse the name into a key.
+            let key = K::from_str(name).map_err(|e| {
+                Error::new(ErrorKind::CannotParseKey {
+                    key: name.to_owned(),
+                    reason: e.to_string(),
+                })
+                .at_field(name.to_owned())
+            })?;
+            // Decode the value now that we have a valid key.
             let Some(val) = value.decode_item(T::into_visitor::<R>()) else { break };
             // Save to the map.
-            let val = val.map_err(|e| e.at_field(key.to_owned()))?;
-            map.insert(key.to_owned(), val);
+            let val = val.map_err(|e| e.at_field(name.to_owned()))?;
+            map.insert(key, val);
         }
         Ok(map)
     }
+    fn visit_sequence<'scale, 'resolver>(
+        self,
+        value: &mut Sequence<'scale, 'resolver, R>,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        decode_items_using::<_, _, (K, T)>(value).collect()
+    }
+    fn visit_array<'scale, 'resolver>(
+        self,
+        value: &mut Array<'scale, 'resolver, R>,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        decode_items_using::<_, _, (K, T)>(value).collect()
+    }
 }
-impl<T: IntoVisitor> IntoVisitor for BTreeMap<String, T> {
-    type AnyVisitor<R: TypeResolver> = BasicVisitor<BTreeMap<String, T>, R>;
+impl<K, T> IntoVisitor for BTreeMap<K, T>
+where
+    K: IntoVisitor + FromStr + Ord,
+    K::Err: core::fmt::Display,
+    T: IntoVisitor,
+{
+    type AnyVisitor<R: TypeResolver> = BasicVisitor<BTreeMap<K, T>, R>;
     fn into_visitor<R: TypeResolver>() -> Self::AnyVisitor<R> {
         BasicVisitor { _marker: core::marker::PhantomData }
     }
@@ -397,10 +547,7 @@ impl<T: IntoVisitor, R: TypeResolver> Visitor for BasicVisitor<Option<T>, R> {
         } else if value.name() == "None" && value.fields().remaining() == 0 {
             Ok(None)
         } else {
-            Err(Error::new(ErrorKind::CannotFindVariant {
-                got: value.name().to_string(),
-                expected: vec!["Some", "None"],
-            }))
+            Err(Error::cannot_find_variant(value.name(), value.index(), vec!["Some", "None"]))
         }
     }
     visit_single_field_composite_tuple_impls!(R);
@@ -434,16 +581,177 @@ impl<T: IntoVisitor, E: IntoVisitor, R: TypeResolver> Visitor for BasicVisitor<R
                 .expect("checked for 1 field already so should be ok");
             Ok(Err(val))
         } else {
-            Err(Error::new(ErrorKind::CannotFindVariant {
-                got: value.name().to_string(),
-                expected: vec!["Ok", "Err"],
-            }))
+            Err(Error::cannot_find_variant(value.name(), value.index(), vec!["Ok", "Err"]))
         }
     }
     visit_single_field_composite_tuple_impls!(R);
 }
 impl_into_visitor!(Result<T, E> where T: IntoVisitor, E: IntoVisitor);
 
+// Decode the single field of some variant, having already checked that there is one.
+fn decode_single_variant_field<T: IntoVisitor, R: TypeResolver>(
+    value: &mut Variant<'_, '_, R>,
+) -> Result<T, Error> {
+    let name = value.name();
+    let val = value
+        .fields()
+        .decode_item(T::into_visitor::<R>())
+        .transpose()
+        .map_err(|e| e.at_variant(name.to_string()))?
+        .expect("checked for 1 field already so should be ok");
+    Ok(val)
+}
+
+impl<T: IntoVisitor, R: TypeResolver> Visitor for BasicVisitor<Bound<T>, R> {
+    type Error = Error;
+    type Value<'scale, 'resolver> = Bound<T>;
+    type TypeResolver = R;
+
+    fn visit_variant<'scale, 'resolver>(
+        self,
+        value: &mut Variant<'scale, 'resolver, R>,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        match (value.name(), value.fields().remaining()) {
+            ("Included", 1) => decode_single_variant_field(value).map(Bound::Included),
+            ("Excluded", 1) => decode_single_variant_field(value).map(Bound::Excluded),
+            ("Unbounded", 0) => Ok(Bound::Unbounded),
+            (name, _) => Err(Error::cannot_find_variant(
+                name,
+                value.index(),
+                vec!["Included", "Excluded", "Unbounded"],
+            )),
+        }
+    }
+    visit_single_field_composite_tuple_impls!(R);
+}
+impl_into_visitor!(Bound<T> where T: IntoVisitor);
+
+impl<T: IntoVisitor, R: TypeResolver> Visitor for BasicVisitor<ShapeOption<T>, R> {
+    type Error = Error;
+    type Value<'scale, 'resolver> = ShapeOption<T>;
+    type TypeResolver = R;
+
+    fn visit_variant<'scale, 'resolver>(
+        self,
+        value: &mut Variant<'scale, 'resolver, R>,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        match (value.index(), value.fields().remaining()) {
+            (0, 0) => Ok(ShapeOption(None)),
+            (1, 1) => decode_single_variant_field(value).map(|v| ShapeOption(Some(v))),
+            _ => Err(Error::cannot_find_variant(
+                value.name(),
+                value.index(),
+                vec!["<index 0 with no fields>", "<index 1 with one field>"],
+            )),
+        }
+    }
+    visit_single_field_composite_tuple_impls!(R);
+}
+impl_into_visitor!(ShapeOption<T> where T: IntoVisitor);
+
+impl<T: IntoVisitor, E: IntoVisitor, R: TypeResolver> Visitor
+    for BasicVisitor<ShapeResult<T, E>, R>
+{
+    type Error = Error;
+    type Value<'scale, 'resolver> = ShapeResult<T, E>;
+    type TypeResolver = R;
+
+    fn visit_variant<'scale, 'resolver>(
+        self,
+        value: &mut Variant<'scale, 'resolver, R>,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        match (value.index(), value.fields().remaining()) {
+            (0, 1) => decode_single_variant_field(value).map(|v| ShapeResult(Ok(v))),
+            (1, 1) => decode_single_variant_field(value).map(|e| ShapeResult(Err(e))),
+            _ => Err(Error::cannot_find_variant(
+                value.name(),
+                value.index(),
+                vec!["<index 0 with one field>", "<index 1 with one field>"],
+            )),
+        }
+    }
+    visit_single_field_composite_tuple_impls!(R);
+}
+impl_into_visitor!(ShapeResult<T, E> where T: IntoVisitor, E: IntoVisitor);
+
+impl<Src, Dst, R> Visitor for BasicVisitor<DecodeVia<Src, Dst>, R>
+where
+    Src: IntoVisitor,
+    Dst: TryFrom<Src>,
+    <Dst as TryFrom<Src>>::Error: core::error::Error + Send + Sync + 'static,
+    R: TypeResolver,
+{
+    type Error = Error;
+    type Value<'scale, 'resolver> = DecodeVia<Src, Dst>;
+    type TypeResolver = R;
+
+    fn unchecked_decode_as_type<'scale, 'resolver>(
+        self,
+        input: &mut &'scale [u8],
+        type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+        types: &'resolver Self::TypeResolver,
+    ) -> DecodeAsTypeResult<Self, Result<Self::Value<'scale, 'resolver>, Self::Error>> {
+        let visitor = visitor::TryFromVisitor::<Src, Dst, R>::new();
+        let res = decode_with_visitor(input, type_id, types, visitor).map(DecodeVia::new);
+        DecodeAsTypeResult::Decoded(res)
+    }
+}
+impl_into_visitor!(
+    DecodeVia<Src, Dst>
+    where
+        Src: IntoVisitor,
+        Dst: TryFrom<Src>,
+        <Dst as TryFrom<Src>>::Error: core::error::Error + Send + Sync + 'static,
+);
+
+// Hand numbers to the visitor for `T`, noting whether they were compact encoded.
+macro_rules! visit_maybe_compact_fn_impl {
+    ($name:ident : $ty:ty) => {
+        fn $name<'scale, 'resolver>(
+            self,
+            value: $ty,
+            type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            let value = T::into_visitor::<R>().$name(value, type_id)?;
+            Ok(MaybeCompact { value, was_compact: false })
+        }
+    };
+    ($name:ident : $ty:ty, $compact_name:ident) => {
+        visit_maybe_compact_fn_impl!($name: $ty);
+        fn $compact_name<'scale, 'resolver>(
+            self,
+            value: $ty,
+            location: CompactLocation,
+            type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            let value = T::into_visitor::<R>().$compact_name(value, location, type_id)?;
+            Ok(MaybeCompact { value, was_compact: true })
+        }
+    };
+}
+
+impl<T: IntoVisitor, R: TypeResolver> Visitor for BasicVisitor<MaybeCompact<T>, R> {
+    type Error = Error;
+    type Value<'scale, 'resolver> = MaybeCompact<T>;
+    type TypeResolver = R;
+
+    visit_maybe_compact_fn_impl!(visit_u8: u8, visit_compact_u8);
+    visit_maybe_compact_fn_impl!(visit_u16: u16, visit_compact_u16);
+    visit_maybe_compact_fn_impl!(visit_u32: u32, visit_compact_u32);
+    visit_maybe_compact_fn_impl!(visit_u64: u64, visit_compact_u64);
+    visit_maybe_compact_fn_impl!(visit_u128: u128, visit_compact_u128);
+    visit_maybe_compact_fn_impl!(visit_i8: i8);
+    visit_maybe_compact_fn_impl!(visit_i16: i16);
+    visit_maybe_compact_fn_impl!(visit_i32: i32);
+    visit_maybe_compact_fn_impl!(visit_i64: i64);
+    visit_maybe_compact_fn_impl!(visit_i128: i128);
+    visit_single_field_composite_tuple_impls!(R);
+}
+impl_into_visitor!(MaybeCompact<T> where T: IntoVisitor);
+
 // Impl Visitor/DecodeAsType for all primitive number types
 macro_rules! visit_number_fn_impl {
     ($name:ident : $ty:ty where |$res:ident| $expr:expr) => {
@@ -496,16 +804,59 @@ visit_number_impl!(i32 where |res| res.try_into().ok());
 visit_number_impl!(i64 where |res| res.try_into().ok());
 visit_number_impl!(i128 where |res| res.try_into().ok());
 visit_number_impl!(isize where |res| res.try_into().ok());
-visit_number_impl!(NonZeroU8 where |res| res.try_into().ok().and_then(NonZeroU8::new));
-visit_number_impl!(NonZeroU16 where |res| res.try_into().ok().and_then(NonZeroU16::new));
-visit_number_impl!(NonZeroU32 where |res| res.try_into().ok().and_then(NonZeroU32::new));
-visit_number_impl!(NonZeroU64 where |res| res.try_into().ok().and_then(NonZeroU64::new));
-visit_number_impl!(NonZeroU128 where |res| res.try_into().ok().and_then(NonZeroU128::new));
-visit_number_impl!(NonZeroI8 where |res| res.try_into().ok().and_then(NonZeroI8::new));
-visit_number_impl!(NonZeroI16 where |res| res.try_into().ok().and_then(NonZeroI16::new));
-visit_number_impl!(NonZeroI32 where |res| res.try_into().ok().and_then(NonZeroI32::new));
-visit_number_impl!(NonZeroI64 where |res| res.try_into().ok().and_then(NonZeroI64::new));
-visit_number_impl!(NonZeroI128 where |res| res.try_into().ok().and_then(NonZeroI128::new));
+
+// Impl Visitor/DecodeAsType for the non-zero number types. Zero values are reported separately
+// from values which are out of range of the underlying number type.
+macro_rules! visit_nonzero_fn_impl {
+    ($name:ident : $ty:ty => $target:ident($inner:ty)) => {
+        #[allow(clippy::unnecessary_fallible_conversions, clippy::useless_conversion)]
+        fn $name<'scale, 'resolver>(
+            self,
+            value: $ty,
+            _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            let n: $inner = value.try_into().map_err(|_| {
+                Error::new(ErrorKind::NumberOutOfRange { value: value.to_string() })
+            })?;
+            $target::new(n).ok_or_else(|| Error::new(ErrorKind::ZeroValueForNonZeroType))
+        }
+    };
+}
+macro_rules! visit_nonzero_impl {
+    ($target:ident($inner:ty)) => {
+        impl<R: TypeResolver> Visitor for BasicVisitor<$target, R> {
+            type Error = Error;
+            type Value<'scale, 'resolver> = $target;
+            type TypeResolver = R;
+
+            visit_nonzero_fn_impl!(visit_u8: u8 => $target($inner));
+            visit_nonzero_fn_impl!(visit_u16: u16 => $target($inner));
+            visit_nonzero_fn_impl!(visit_u32: u32 => $target($inner));
+            visit_nonzero_fn_impl!(visit_u64: u64 => $target($inner));
+            visit_nonzero_fn_impl!(visit_u128: u128 => $target($inner));
+            visit_nonzero_fn_impl!(visit_i8: i8 => $target($inner));
+            visit_nonzero_fn_impl!(visit_i16: i16 => $target($inner));
+            visit_nonzero_fn_impl!(visit_i32: i32 => $target($inner));
+            visit_nonzero_fn_impl!(visit_i64: i64 => $target($inner));
+            visit_nonzero_fn_impl!(visit_i128: i128 => $target($inner));
+
+            visit_single_field_composite_tuple_impls!(R);
+        }
+        impl_into_visitor!($target);
+    };
+}
+visit_nonzero_impl!(NonZeroU8(u8));
+visit_nonzero_impl!(NonZeroU16(u16));
+visit_nonzero_impl!(NonZeroU32(u32));
+visit_nonzero_impl!(NonZeroU64(u64));
+visit_nonzero_impl!(NonZeroU128(u128));
+visit_nonzero_impl!(NonZeroUsize(usize));
+visit_nonzero_impl!(NonZeroI8(i8));
+visit_nonzero_impl!(NonZeroI16(i16));
+visit_nonzero_impl!(NonZeroI32(i32));
+visit_nonzero_impl!(NonZeroI64(i64));
+visit_nonzero_impl!(NonZeroI128(i128));
+visit_nonzero_impl!(NonZeroIsize(isize));
 
 macro_rules! count_idents {
     ($t:ident $($rest:ident)*) => {
@@ -526,11 +877,31 @@ macro_rules! tuple_method_impl {
                 expected_len: EXPECTED_LEN
             }))
         }
+        decode_tuple_items!(($($t,)*), $value)
+    }}
+}
+
+// Decode tuple types from the first items of any matching type, leaving the rest to be skipped.
+macro_rules! prefix_tuple_method_impl {
+    (($($t:ident,)*), $value:ident) => {{
+        const EXPECTED_LEN: usize = count_idents!($($t)*);
+        if $value.remaining() < EXPECTED_LEN {
+            return Err(Error::new(ErrorKind::WrongLength {
+                actual_len: $value.remaining(),
+                expected_len: EXPECTED_LEN
+            }))
+        }
+        decode_tuple_items!(($($t,)*), $value).map(PrefixTuple)
+    }}
+}
 
+// Decode the next items into a tuple, once we know that there are enough of them.
+macro_rules! decode_tuple_items {
+    (($($t:ident,)*), $value:ident) => {{
         #[allow(unused)]
         let mut idx = 0;
 
-        Ok((
+        Ok::<_, Error>((
             $(
                 #[allow(unused_assignments)]
                 {
@@ -621,6 +992,70 @@ macro_rules! impl_decode_tuple {
             ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
                 tuple_method_impl!(($($t,)*), value)
             }
+            // Arrays and sequences of the right length can be decoded into a tuple, so long as
+            // each tuple entry can be decoded from the item type.
+            fn visit_array<'scale, 'resolver>(
+                self,
+                value: &mut Array<'scale, 'resolver, Resolver>,
+                _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                tuple_method_impl!(($($t,)*), value)
+            }
+            fn visit_sequence<'scale, 'resolver>(
+                self,
+                value: &mut Sequence<'scale, 'resolver, Resolver>,
+                _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                tuple_method_impl!(($($t,)*), value)
+            }
+        }
+
+        // Prefixes of composites, tuples, arrays and sequences can be decoded into a PrefixTuple:
+        impl <Resolver, $($t),* > Visitor for BasicVisitor<PrefixTuple<($($t,)*)>, Resolver>
+        where
+            Resolver: TypeResolver,
+            $($t: IntoVisitor,)*
+        {
+            type Value<'scale, 'resolver> = PrefixTuple<($($t,)*)>;
+            type Error = Error;
+            type TypeResolver = Resolver;
+
+            fn visit_composite<'scale, 'resolver>(
+                self,
+                value: &mut Composite<'scale, 'resolver, Resolver>,
+                _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                prefix_tuple_method_impl!(($($t,)*), value)
+            }
+            fn visit_tuple<'scale, 'resolver>(
+                self,
+                value: &mut Tuple<'scale, 'resolver, Resolver>,
+                _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                prefix_tuple_method_impl!(($($t,)*), value)
+            }
+            fn visit_array<'scale, 'resolver>(
+                self,
+                value: &mut Array<'scale, 'resolver, Resolver>,
+                _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                prefix_tuple_method_impl!(($($t,)*), value)
+            }
+            fn visit_sequence<'scale, 'resolver>(
+                self,
+                value: &mut Sequence<'scale, 'resolver, Resolver>,
+                _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                prefix_tuple_method_impl!(($($t,)*), value)
+            }
+        }
+        impl < $($t),* > IntoVisitor for PrefixTuple<($($t,)*)>
+        where $( $t: IntoVisitor, )*
+        {
+            type AnyVisitor<Resolver: TypeResolver> = BasicVisitor<PrefixTuple<($($t,)*)>, Resolver>;
+            fn into_visitor<Resolver: TypeResolver>() -> Self::AnyVisitor<Resolver> {
+                BasicVisitor { _marker: core::marker::PhantomData }
+            }
         }
 
         // We can turn this tuple into a visitor which knows how to decode it:
@@ -633,22 +1068,12 @@ macro_rules! impl_decode_tuple {
             }
         }
 
-        // We can decode given a list of fields (just delegate to the visitor impl:
+        // We can decode given a list of fields (just delegate to the visitor impl):
         impl < $($t),* > DecodeAsFields for ($($t,)*)
         where $( $t: IntoVisitor, )*
         {
             fn decode_as_fields<'resolver, Resolver: TypeResolver>(input: &mut &[u8], fields: &mut dyn FieldIter<'resolver, Resolver::TypeId>, types: &'resolver Resolver) -> Result<Self, Error> {
-                let mut composite = crate::visitor::types::Composite::new(core::iter::empty(), input, fields, types, false);
-
-                // [jsdw] TODO: Passing a "default type ID" to a visitor just to satisfy the signature is
-                // a bit hideous (and requires `TypeId: Default`). Can we re-work this to avoid?
-                let val = <($($t,)*)>::into_visitor().visit_composite(&mut composite, Default::default());
-
-                // Skip over bytes that we decoded:
-                composite.skip_decoding()?;
-                *input = composite.bytes_from_undecoded();
-
-                val
+                crate::decode_composite_as_fields(input, fields, types)
             }
         }
     }
@@ -675,7 +1100,60 @@ impl_decode_tuple!(A B C D E F G H I J K L M N O P Q);
 impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R);
 impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S);
 impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T);
-// ^ Note: We make sure to support as many as parity-scale-codec's impls do.
+// ^ Note: We make sure to support as many as parity-scale-codec's impls do by default. Larger
+// tuples are supported behind the `tuples-32` and `tuples-64` features.
+#[cfg(feature = "tuples-32")]
+mod tuples_32 {
+    use super::*;
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1);
+}
+#[cfg(feature = "tuples-64")]
+mod tuples_64 {
+    use super::*;
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1 Q1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1 Q1 R1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1 Q1 R1 S1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1 Q1 R1 S1 T1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1 Q1 R1 S1 T1 U1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1 Q1 R1 S1 T1 U1 V1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1 Q1 R1 S1 T1 U1 V1 W1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1 Q1 R1 S1 T1 U1 V1 W1 X1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1 Q1 R1 S1 T1 U1 V1 W1 X1 Y1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1 Q1 R1 S1 T1 U1 V1 W1 X1 Y1 Z1);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1 Q1 R1 S1 T1 U1 V1 W1 X1 Y1 Z1 A2);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1 Q1 R1 S1 T1 U1 V1 W1 X1 Y1 Z1 A2 B2);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1 Q1 R1 S1 T1 U1 V1 W1 X1 Y1 Z1 A2 B2 C2);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1 Q1 R1 S1 T1 U1 V1 W1 X1 Y1 Z1 A2 B2 C2 D2);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1 Q1 R1 S1 T1 U1 V1 W1 X1 Y1 Z1 A2 B2 C2 D2 E2);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1 Q1 R1 S1 T1 U1 V1 W1 X1 Y1 Z1 A2 B2 C2 D2 E2 F2);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1 Q1 R1 S1 T1 U1 V1 W1 X1 Y1 Z1 A2 B2 C2 D2 E2 F2 G2);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1 Q1 R1 S1 T1 U1 V1 W1 X1 Y1 Z1 A2 B2 C2 D2 E2 F2 G2 H2);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1 Q1 R1 S1 T1 U1 V1 W1 X1 Y1 Z1 A2 B2 C2 D2 E2 F2 G2 H2 I2);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1 Q1 R1 S1 T1 U1 V1 W1 X1 Y1 Z1 A2 B2 C2 D2 E2 F2 G2 H2 I2 J2);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1 Q1 R1 S1 T1 U1 V1 W1 X1 Y1 Z1 A2 B2 C2 D2 E2 F2 G2 H2 I2 J2 K2);
+    impl_decode_tuple!(A B C D E F G H I J K L M N O P Q R S T U V W X Y Z A1 B1 C1 D1 E1 F1 G1 H1 I1 J1 K1 L1 M1 N1 O1 P1 Q1 R1 S1 T1 U1 V1 W1 X1 Y1 Z1 A2 B2 C2 D2 E2 F2 G2 H2 I2 J2 K2 L2);
+}
 
 /// This takes anything that can decode a stream if items and return an iterator over them.
 fn decode_items_using<'a, 'scale, 'resolver, R, D, T>(
@@ -804,45 +1282,176 @@ mod test {
         assert_encode_decode_to(&true, &Cow::Borrowed(&true));
     }
 
+    #[test]
+    fn decode_numeric_wrappers() {
+        assert_encode_decode_to(&123u8, &Wrapping(123u64));
+        assert_encode_decode_to(&Compact(123u64), &Wrapping(123u16));
+        assert_encode_decode_to(&-123i32, &Saturating(-123i64));
+        assert_encode_decode_to(&(123u32,), &Saturating(123u32));
+
+        // The inner number must still fit; values aren't wrapped or saturated when decoding:
+        let (type_id, types) = make_type::<u16>();
+        let encoded = 300u16.encode();
+        assert!(Wrapping::<u8>::decode_as_type(&mut &*encoded, type_id, &types).is_err());
+        assert!(Saturating::<u8>::decode_as_type(&mut &*encoded, type_id, &types).is_err());
+    }
+
     #[test]
     fn decode_duration() {
         assert_encode_decode_with::<(u64, u32), _>(&Duration::from_millis(12345));
+        assert_encode_decode_to(&12345u64, &Duration::from_millis(12345));
+        assert_encode_decode_to(&Compact(12345u64), &Duration::from_millis(12345));
+        assert_encode_decode_to(&(12345u32,), &Duration::from_millis(12345));
     }
 
     #[test]
     fn decode_ranges() {
         assert_encode_decode(&(1..10));
         assert_encode_decode(&(1..=10));
+
+        // Half open ranges are decoded from their single bound:
+        assert_encode_decode_to(&1u8, &(1u64..));
+        assert_encode_decode_to(&10u8, &(..10u64));
+        assert_encode_decode_to(&10u8, &(..=10u64));
     }
 
     #[test]
-    fn decode_basic_numbers() {
-        fn decode_all_types(n: u128) {
-            assert_encode_decode_to(&n, &(n as u8));
-            assert_encode_decode_to(&n, &(n as u16));
-            assert_encode_decode_to(&n, &(n as u32));
-            assert_encode_decode_to(&n, &(n as u64));
-            assert_encode_decode_to(&n, &n);
-
-            assert_encode_decode_to(&n, &(n as i8));
-            assert_encode_decode_to(&n, &(n as i16));
-            assert_encode_decode_to(&n, &(n as i32));
-            assert_encode_decode_to(&n, &(n as i64));
-            assert_encode_decode_to(&n, &(n as i128));
+    fn decode_bounds() {
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum Bound<T> {
+            Included(T),
+            Excluded(T),
+            Unbounded,
         }
 
-        decode_all_types(0);
-        decode_all_types(1);
-        decode_all_types(127);
+        assert_encode_decode_to(&Bound::Included(1u8), &core::ops::Bound::Included(1u64));
+        assert_encode_decode_to(&Bound::Excluded(1u8), &core::ops::Bound::Excluded(1u64));
+        assert_encode_decode_to(&Bound::<u8>::Unbounded, &core::ops::Bound::<u64>::Unbounded);
     }
 
     #[test]
-    fn decode_cows() {
+    fn decode_maybe_compact() {
+        assert_encode_decode_to(&123u8, &MaybeCompact { value: 123u64, was_compact: false });
+        assert_encode_decode_to(
+            &Compact(123u32),
+            &MaybeCompact { value: 123u64, was_compact: true },
+        );
+        assert_encode_decode_to(&-1i32, &MaybeCompact { value: -1i64, was_compact: false });
+        // Compact-ness is preserved for compact encoded fields too:
+        assert_encode_decode_to(
+            &(Compact(1u64), 2u16),
+            &(
+                MaybeCompact { value: 1u64, was_compact: true },
+                MaybeCompact { value: 2u64, was_compact: false },
+            ),
+        );
+
+        // Values re-encode exactly as they were given:
+        #[derive(Encode, scale_info::TypeInfo)]
+        struct Foo {
+            #[codec(compact)]
+            a: u128,
+            b: u128,
+        }
+        #[derive(DecodeAsType, Encode)]
+        #[decode_as_type(crate_path = "crate")]
+        struct FooMaybe {
+            a: MaybeCompact<u128>,
+            b: MaybeCompact<u128>,
+        }
+
+        let (type_id, types) = make_type::<Foo>();
+        let encoded = Foo { a: 1, b: 2 }.encode();
+        let decoded = FooMaybe::decode_as_type(&mut &*encoded, type_id, &types).unwrap();
+        assert!(decoded.a.was_compact && !decoded.b.was_compact);
+        assert_eq!(decoded.encode(), encoded);
+    }
+
+    #[test]
+    fn decode_options_and_results_by_shape() {
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum Maybe {
+            Nothing,
+            Just(u8),
+        }
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum Outcome {
+            Good(u8),
+            Bad(String),
+        }
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum WrongShape {
+            A(u8),
+            B,
+        }
+
+        assert_encode_decode_to(&Maybe::Nothing, &ShapeOption::<u64>(None));
+        assert_encode_decode_to(&Maybe::Just(1), &ShapeOption(Some(1u64)));
+        assert_encode_decode_to(&Some(1u8), &ShapeOption(Some(1u64)));
+        assert_encode_decode_to(&Outcome::Good(1), &ShapeResult::<u64, String>(Ok(1)));
+        assert_encode_decode_to(
+            &Outcome::Bad("hi".to_string()),
+            &ShapeResult::<u64, String>(Err("hi".to_string())),
+        );
+
+        let (type_id, types) = make_type::<WrongShape>();
+        for (value, name) in [(WrongShape::A(1), "A"), (WrongShape::B, "B")] {
+            let encoded = value.encode();
+            let err =
+                ShapeOption::<u8>::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+            assert!(matches!(err.kind(), ErrorKind::CannotFindVariant { got, .. } if got == name));
+        }
+    }
+
+    #[test]
+    fn decode_basic_numbers() {
+        fn decode_all_types(n: u128) {
+            assert_encode_decode_to(&n, &(n as u8));
+            assert_encode_decode_to(&n, &(n as u16));
+            assert_encode_decode_to(&n, &(n as u32));
+            assert_encode_decode_to(&n, &(n as u64));
+            assert_encode_decode_to(&n, &n);
+
+            assert_encode_decode_to(&n, &(n as i8));
+            assert_encode_decode_to(&n, &(n as i16));
+            assert_encode_decode_to(&n, &(n as i32));
+            assert_encode_decode_to(&n, &(n as i64));
+            assert_encode_decode_to(&n, &(n as i128));
+        }
+
+        decode_all_types(0);
+        decode_all_types(1);
+        decode_all_types(127);
+    }
+
+    #[test]
+    fn decode_nonzero_numbers() {
+        assert_encode_decode_to(&5u8, &NonZeroU64::new(5).unwrap());
+        assert_encode_decode_to(&5u64, &NonZeroUsize::new(5).unwrap());
+        assert_encode_decode_to(&-5i32, &NonZeroIsize::new(-5).unwrap());
+        assert_encode_decode_to(&Compact(5u32), &NonZeroU8::new(5).unwrap());
+
+        let (type_id, types) = make_type::<Compact<u64>>();
+
+        let encoded = Compact(0u64).encode();
+        let err = NonZeroU8::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::ZeroValueForNonZeroType));
+
+        let encoded = Compact(256u64).encode();
+        let err = NonZeroU8::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+    }
+
+    #[test]
+    fn decode_cows() {
         let a = "hello";
         assert_encode_decode_to(&a, &Cow::<'_, str>::Borrowed(a));
         // Decoding a Cow means being able to jump into the inner composite type
         // (Cow's are a one-field composite type in TypeInfo by the looks of it).
         assert_encode_decode(&Cow::<'_, str>::Borrowed(a));
+        assert_encode_decode_to(&vec![1u8, 2], &Cow::<'_, [u8]>::Borrowed(&[1, 2]));
+        assert_encode_decode_to(&vec![1u8, 2], &Cow::<'static, [u64]>::Owned(vec![1, 2]));
+        assert_encode_decode(&Cow::<'_, [u16]>::Borrowed(&[1, 2, 3]));
     }
 
     #[test]
@@ -930,6 +1539,65 @@ mod test {
         assert_encode_decode_to(&Foo { a: 1u8, b: 2u16, c: true }, &(1u8, 2u8, true));
     }
 
+    #[test]
+    fn decode_arrays_and_sequences_to_tuples() {
+        assert_encode_decode_to(&[1u8, 2, 3], &(1u8, 2u8, 3u8));
+        assert_encode_decode_to(&vec![1u8, 2, 3], &(1u16, 2u32, 3u64));
+        assert_encode_decode_to(&vec!["a", "b"], &("a".to_string(), Cow::Borrowed("b")));
+        assert_encode_decode_to(&Vec::<u8>::new(), &());
+
+        // The length must line up, and each item must decode into the tuple entry:
+        let (type_id, types) = make_type::<Vec<u8>>();
+        let encoded = vec![1u8, 2].encode();
+        let err = <(u8, u8, u8)>::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::WrongLength { actual_len: 2, expected_len: 3 }));
+        let err = <(u8, bool)>::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert_eq!(err.context().path().to_string(), "[1]");
+    }
+
+    #[test]
+    fn decode_prefix_tuples() {
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        struct Foo {
+            a: u8,
+            b: String,
+            c: bool,
+        }
+
+        // Any remaining items are skipped over:
+        assert_encode_decode_to(&(1u8, 2u16, true), &PrefixTuple((1u8, 2u16)));
+        assert_encode_decode_to(&(1u8, 2u16), &PrefixTuple((1u8, 2u16)));
+        assert_encode_decode_to(
+            &Foo { a: 1, b: "hi".to_string(), c: true },
+            &PrefixTuple((1u64, "hi".to_string())),
+        );
+        assert_encode_decode_to(&[1u8, 2, 3], &PrefixTuple((1u8,)));
+        assert_encode_decode_to(&vec![1u8, 2, 3], &PrefixTuple(()));
+
+        // But there must be enough items:
+        let (type_id, types) = make_type::<(u8, u16)>();
+        let encoded = (1u8, 2u16).encode();
+        let err = PrefixTuple::<(u8, u16, bool)>::decode_as_type(&mut &*encoded, type_id, &types)
+            .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::WrongLength { actual_len: 2, expected_len: 3 }));
+    }
+
+    #[test]
+    fn decode_sequences_and_arrays_to_fixed_size_arrays() {
+        assert_encode_decode_to(&vec![1u8, 2, 3], &[1u8, 2, 3]);
+        assert_encode_decode_to(&[1u8, 2, 3], &[1u64, 2, 3]);
+        assert_encode_decode_to(&[[1u8; 2]; 2], &[[1u16; 2]; 2]);
+        assert_encode_decode_to(&vec!["a".to_string()], &["a".to_string()]);
+
+        // The length must line up, and each item must decode into the array:
+        let (type_id, types) = make_type::<Vec<u16>>();
+        let encoded = vec![1u16, 300].encode();
+        let err = <[u8; 3]>::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::WrongLength { actual_len: 2, expected_len: 3 }));
+        let err = <[u8; 2]>::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert_eq!(err.context().path().to_string(), "[1]");
+    }
+
     #[test]
     fn decode_composites_to_tuples() {
         #[derive(codec::Encode, scale_info::TypeInfo)]
@@ -945,6 +1613,41 @@ mod test {
         assert_encode_decode_to(&input, &(true, (1u64, 3u64)));
     }
 
+    #[cfg(feature = "tuples-32")]
+    #[test]
+    fn decode_large_tuples() {
+        use crate::resolver::{InMemoryResolver, Primitive};
+        use crate::DecodeAsFields;
+
+        #[rustfmt::skip]
+        type Big = (
+            u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8,
+            u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u64,
+        );
+
+        let mut builder = InMemoryResolver::builder();
+        let u8_id = builder.add_primitive(Primitive::U8);
+        let tuple_id = builder.add_tuple((0..32).map(|_| u8_id));
+        let composite_id = builder.add_composite(["Foo"], (0..32).map(|_| Field::unnamed(u8_id)));
+        let types = builder.build();
+
+        let bytes: Vec<u8> = (0..32).collect();
+
+        let big = Big::decode_as_type(&mut &*bytes, tuple_id, &types).unwrap();
+        assert_eq!((big.0, big.15, big.31), (0, 15, 31));
+
+        let big = Big::decode_as_type(&mut &*bytes, composite_id, &types).unwrap();
+        assert_eq!((big.0, big.15, big.31), (0, 15, 31));
+
+        let mut fields = (0..32).map(|_| Field::unnamed(u8_id));
+        let big = Big::decode_as_fields(&mut &*bytes, &mut fields, &types).unwrap();
+        assert_eq!((big.0, big.15, big.31), (0, 15, 31));
+
+        // The length must still match:
+        let err = Big::decode_as_type(&mut &bytes[..31], tuple_id, &types);
+        assert!(err.is_err());
+    }
+
     #[test]
     fn decode_compacts() {
         assert_encode_decode(&Compact(126u64));
@@ -996,6 +1699,343 @@ mod test {
         try_decode_hxxx([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]);
     }
 
+    #[test]
+    #[cfg(feature = "primitive-types")]
+    fn decode_uxxx() {
+        use ::primitive_types::{U128, U256, U512};
+
+        // A type which is described as a primitive u256 rather than a composite:
+        #[derive(codec::Encode)]
+        struct PrimitiveU256(U256);
+        impl scale_info::TypeInfo for PrimitiveU256 {
+            type Identity = Self;
+            fn type_info() -> scale_info::Type {
+                scale_info::TypeDefPrimitive::U256.into()
+            }
+        }
+
+        // From the composite of [u64; N] limbs that these types are described by:
+        assert_encode_decode(&U128::from(123456u64));
+        assert_encode_decode(&U256::MAX);
+        assert_encode_decode(&U512::MAX);
+        assert_encode_decode_to(&U128::MAX, &U256::from(u128::MAX));
+
+        // From the primitive u256 type:
+        let n = U256::from(u128::MAX) * 1000;
+        assert_encode_decode_to(&PrimitiveU256(n), &n);
+        assert_encode_decode_to(&PrimitiveU256(n), &U512::from(n));
+        assert_encode_decode_to(&PrimitiveU256(123u64.into()), &U128::from(123u64));
+
+        // From regular numbers:
+        assert_encode_decode_to(&123u8, &U256::from(123u64));
+        assert_encode_decode_to(&u128::MAX, &U128::MAX);
+        assert_encode_decode_to(&123i64, &U512::from(123u64));
+
+        // Values that don't fit will fail:
+        let (type_id, types) = make_type::<PrimitiveU256>();
+        let encoded = PrimitiveU256(n).encode();
+        let err = U128::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+
+        let (type_id, types) = make_type::<i32>();
+        let encoded = (-1i32).encode();
+        let err = U256::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+    }
+
+    #[test]
+    #[cfg(feature = "smol_str")]
+    fn decode_smol_str() {
+        use ::smol_str::SmolStr;
+
+        assert_encode_decode_to(&"hello".to_string(), &SmolStr::new("hello"));
+        assert_encode_decode_to(&("hello",), &SmolStr::new("hello"));
+    }
+
+    #[test]
+    #[cfg(feature = "bitvec")]
+    fn decode_bitvec() {
+        use ::bitvec::{
+            order::{Lsb0, Msb0},
+            vec::BitVec,
+        };
+
+        let bits = [true, false, false, true, false, true, true, true, false];
+        let lsb0_u8: BitVec<u8, Lsb0> = bits.iter().collect();
+        let msb0_u32: BitVec<u32, Msb0> = bits.iter().collect();
+        let lsb0_u64: BitVec<u64, Lsb0> = bits.iter().collect();
+
+        assert_encode_decode(&lsb0_u8);
+        assert_encode_decode(&msb0_u32);
+        // The store and order types needn't match those that were encoded:
+        assert_encode_decode_to(&lsb0_u8, &msb0_u32);
+        assert_encode_decode_to(&msb0_u32, &lsb0_u64);
+        assert_encode_decode_to(&Bits::from_iter(bits), &lsb0_u8);
+    }
+
+    #[test]
+    #[cfg(feature = "float")]
+    fn decode_floats() {
+        use crate::resolver::{InMemoryResolver, Primitive};
+        use crate::Field;
+
+        let mut builder = InMemoryResolver::builder();
+        let u32_id = builder.add_primitive(Primitive::U32);
+        let u64_id = builder.add_primitive(Primitive::U64);
+        let f32_id = builder.add_composite(["f32"], [Field::unnamed(u32_id)]);
+        let f64_id = builder.add_composite(["f64"], [Field::unnamed(u64_id)]);
+        let wrapper_id = builder.add_struct("Wrapper", [("a", f32_id), ("b", u32_id)]);
+        let types = builder.build();
+
+        let decode = |bytes: &[u8], type_id| -> Result<(f32, f64), Error> {
+            let cursor = &mut &*bytes;
+            let a = f32::decode_as_type(&mut &*bytes, type_id, &types);
+            let b = f64::decode_as_type(cursor, type_id, &types)?;
+            assert!(cursor.is_empty(), "all bytes should be consumed");
+            Ok((a?, b))
+        };
+
+        assert_eq!(decode(&1.5f32.to_bits().encode(), f32_id).unwrap(), (1.5, 1.5));
+        assert_eq!(decode(&(-2.25f64).to_bits().encode(), f64_id).unwrap(), (-2.25, -2.25));
+        // An f64 which can't be represented exactly as an f32 won't decode into one:
+        let err =
+            f32::decode_as_type(&mut &*0.1f64.to_bits().encode(), f64_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+
+        // Floats can be nested in other types, and skipped over:
+        let encoded = (0.5f32.to_bits(), 7u32).encode();
+        let (a, b) = <(f32, u32)>::decode_as_type(&mut &*encoded, wrapper_id, &types).unwrap();
+        assert_eq!((a, b), (0.5, 7));
+        assert_eq!(crate::visitor::encoded_size_of(&encoded, wrapper_id, &types).unwrap(), 8);
+    }
+
+    #[test]
+    #[cfg(feature = "bytes")]
+    fn decode_bytes() {
+        use ::bytes::Bytes;
+
+        assert_encode_decode_to(&vec![1u8, 2, 3], &Bytes::from_static(&[1, 2, 3]));
+        assert_encode_decode_to(&[1u8, 2, 3], &Bytes::from_static(&[1, 2, 3]));
+    }
+
+    #[test]
+    #[cfg(feature = "arrayvec")]
+    fn decode_array_string() {
+        use ::arrayvec::ArrayString;
+
+        assert_encode_decode_to(&"hello".to_string(), &ArrayString::<5>::from("hello").unwrap());
+        assert_encode_decode_to(&"hi".to_string(), &ArrayString::<16>::from("hi").unwrap());
+
+        // Strings which are too long for the ArrayString will fail to decode:
+        let (type_id, types) = make_type::<String>();
+        let encoded = "hello".to_string().encode();
+        let err = ArrayString::<4>::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::WrongLength { actual_len: 5, expected_len: 4 }));
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn decode_chrono_timestamps() {
+        use ::chrono::{DateTime, NaiveDateTime, Utc};
+
+        let date = DateTime::<Utc>::from_timestamp_millis(1_700_000_000_123).unwrap();
+        assert_encode_decode_to(&1_700_000_000_123u64, &date);
+        assert_encode_decode_to(&Compact(1_700_000_000_123u64), &date);
+        assert_encode_decode_to(&Compact(1_700_000_000_123u64), &date.naive_utc());
+
+        // Timestamps which are out of range will fail to decode:
+        let (type_id, types) = make_type::<u64>();
+        let encoded = u64::MAX.encode();
+        let err = NaiveDateTime::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+    }
+
+    #[test]
+    #[cfg(feature = "time")]
+    fn decode_time_timestamps() {
+        use ::time::OffsetDateTime;
+
+        let date = OffsetDateTime::from_unix_timestamp_nanos(1_700_000_000_123_000_000).unwrap();
+        assert_encode_decode_to(&1_700_000_000_123u64, &date);
+        assert_encode_decode_to(&Compact(1_700_000_000_123u64), &date);
+    }
+
+    #[test]
+    #[cfg(feature = "uuid")]
+    fn decode_uuid() {
+        let bytes = [7u8; 16];
+        assert_encode_decode_to(&bytes, &::uuid::Uuid::from_bytes(bytes));
+    }
+
+    #[test]
+    #[cfg(feature = "net")]
+    fn decode_ip_and_socket_addrs() {
+        use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum MyIpAddr {
+            V4([u8; 4]),
+            V6([u8; 16]),
+        }
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        struct MySocketAddrV4 {
+            ip: u32,
+            port: u16,
+        }
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        #[allow(dead_code)]
+        enum MySocketAddr {
+            V4(MySocketAddrV4),
+            V6([u8; 16], u16),
+        }
+
+        let v4 = Ipv4Addr::new(127, 0, 0, 1);
+        let v6 = Ipv6Addr::LOCALHOST;
+
+        assert_encode_decode_to(&u32::from(v4), &v4);
+        assert_encode_decode_to(&v4.octets(), &v4);
+        assert_encode_decode_to(&v6.octets(), &v6);
+        assert_encode_decode_to(&MyIpAddr::V4(v4.octets()), &IpAddr::V4(v4));
+        assert_encode_decode_to(&MyIpAddr::V6(v6.octets()), &IpAddr::V6(v6));
+        assert_encode_decode_to(
+            &MySocketAddrV4 { ip: u32::from(v4), port: 8080 },
+            &SocketAddrV4::new(v4, 8080),
+        );
+        assert_encode_decode_to(
+            &MySocketAddr::V4(MySocketAddrV4 { ip: u32::from(v4), port: 8080 }),
+            &SocketAddr::V4(SocketAddrV4::new(v4, 8080)),
+        );
+        assert_encode_decode_to(
+            &MySocketAddr::V6(v6.octets(), 443),
+            &SocketAddr::V6(SocketAddrV6::new(v6, 443, 0, 0)),
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "substrate-types")]
+    fn decode_per_things() {
+        use ::sp_arithmetic::{Perbill, Percent, Permill};
+
+        assert_encode_decode(&Perbill::from_parts(123_456));
+        assert_encode_decode(&Permill::from_parts(1_000_000));
+        assert_encode_decode(&Percent::from_parts(50));
+        assert_encode_decode_to(&123_456u32, &Perbill::from_parts(123_456));
+        assert_encode_decode_to(&(50u8,), &Percent::from_parts(50));
+
+        // Values greater than the accuracy of the type will fail to decode:
+        let (type_id, types) = make_type::<u32>();
+        let encoded = 1_000_001u32.encode();
+        let err = Permill::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+    }
+
+    #[test]
+    #[cfg(feature = "substrate-types")]
+    fn decode_account_id_and_multi_address() {
+        use crate::substrate::{AccountId32, MultiAddress};
+
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        #[allow(dead_code)]
+        enum MyMultiAddress {
+            Id([u8; 32]),
+            Index(#[codec(compact)] u32),
+            Raw(Vec<u8>),
+            Address32([u8; 32]),
+            Address20([u8; 20]),
+        }
+
+        assert_encode_decode_to(&[7u8; 32], &AccountId32([7; 32]));
+        assert_encode_decode_to(
+            &MyMultiAddress::Id([1; 32]),
+            &MultiAddress::<AccountId32, ()>::Id(AccountId32([1; 32])),
+        );
+        assert_encode_decode_to(
+            &MyMultiAddress::Index(123),
+            &MultiAddress::<AccountId32, u32>::Index(123),
+        );
+        assert_encode_decode_to(
+            &MyMultiAddress::Raw(vec![1, 2, 3]),
+            &MultiAddress::<AccountId32, ()>::Raw(vec![1, 2, 3]),
+        );
+        assert_encode_decode_to(
+            &MyMultiAddress::Address20([2; 20]),
+            &MultiAddress::<AccountId32, ()>::Address20([2; 20]),
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "substrate-types")]
+    fn decode_era() {
+        use crate::substrate::Era;
+        use scale_info::build::{Fields, Variants};
+
+        // Eras are described as an "Immortal" variant followed by "Mortal1" to "Mortal255"
+        // variants which each contain a single u8.
+        struct EraType;
+
+        impl scale_info::TypeInfo for EraType {
+            type Identity = Self;
+            fn type_info() -> scale_info::Type {
+                let variants = (1..=255u8).fold(
+                    Variants::new().variant("Immortal", |v| v.index(0)),
+                    |variants, idx| {
+                        let name: &'static str =
+                            alloc::boxed::Box::leak(alloc::format!("Mortal{idx}").into_boxed_str());
+                        variants.variant(name, |v| {
+                            v.index(idx).fields(Fields::unnamed().field(|f| f.ty::<u8>()))
+                        })
+                    },
+                );
+                scale_info::Type::builder()
+                    .path(scale_info::Path::new("Era", "test"))
+                    .variant(variants)
+            }
+        }
+
+        let decode = |bytes: &[u8]| {
+            let (type_id, types) = make_type::<EraType>();
+            Era::decode_as_type(&mut &*bytes, type_id, &types)
+        };
+
+        assert_eq!(decode(&[0]).unwrap(), Era::Immortal);
+        assert_eq!(decode(&[0x15, 0x03]).unwrap(), Era::Mortal(64, 49));
+        // A period of 2 isn't valid:
+        assert!(decode(&[0x10, 0x00]).is_err());
+    }
+
+    #[test]
+    fn decode_btreemaps() {
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        struct Foo {
+            hello: u8,
+            world: u16,
+        }
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        struct Numbered {
+            _1: u8,
+            _2: u8,
+        }
+
+        // Field names become keys:
+        assert_encode_decode_to(
+            &Foo { hello: 1, world: 2 },
+            &BTreeMap::from([("hello".to_string(), 1u64), ("world".to_string(), 2u64)]),
+        );
+
+        // Maps can also be decoded from sequences of pairs:
+        let pairs = vec![(1u32, true), (3u32, false)];
+        assert_encode_decode_to(&pairs, &BTreeMap::from([(1u64, true), (3u64, false)]));
+        let map = BTreeMap::from([(1u8, "a".to_string()), (2u8, "b".to_string())]);
+        assert_encode_decode(&map);
+
+        // Field names which can't be parsed into the key type will fail to decode:
+        let (type_id, types) = make_type::<Numbered>();
+        let encoded = Numbered { _1: 1, _2: 2 }.encode();
+        let err = BTreeMap::<u8, u8>::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert_eq!(err.context().path().to_string(), "_1");
+        assert!(matches!(err.kind(), ErrorKind::CannotParseKey { key, .. } if key == "_1"));
+    }
+
     #[test]
     fn decoding_can_skip_named_struct_fields() {
         #[derive(DecodeAsType, PartialEq, Debug)]
@@ -1059,6 +2099,10 @@ mod test {
                 // the codec attr should work too:
                 #[codec(skip)]
                 another_field_to_skip: String,
+                // and other codec attrs shouldn't get in the way:
+                #[codec(encoded_as = "u64", compact)]
+                #[decode_as_type(skip)]
+                yet_another_field_to_skip: u32,
                 value: u16,
             },
             UnnamedField(bool, #[decode_as_type(skip)] usize, String),
@@ -1076,6 +2120,7 @@ mod test {
                 some_field: 123,
                 some_field_to_skip: false,
                 another_field_to_skip: String::new(),
+                yet_another_field_to_skip: 0,
                 value: 456,
             },
         );
@@ -1086,31 +2131,1316 @@ mod test {
     }
 
     #[test]
-    fn decode_as_fields_works() {
-        use core::fmt::Debug;
+    fn decode_ignored() {
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum Foo {
+            A(u8),
+            B { c: String },
+        }
 
-        #[derive(DecodeAsType, codec::Encode, PartialEq, Debug, scale_info::TypeInfo)]
-        #[decode_as_type(crate_path = "crate")]
-        struct Foo {
-            some_field: u8,
-            value: u16,
+        // Anything can be decoded into Ignored, and the bytes are consumed:
+        assert_encode_decode_to(&123u8, &Ignored);
+        assert_encode_decode_to(&"hello".to_string(), &Ignored);
+        assert_encode_decode_to(&vec![(1u8, true), (2, false)], &Ignored);
+        assert_encode_decode_to(&Foo::B { c: "hi".to_string() }, &Ignored);
+
+        // Handy for discarding fields we don't care about:
+        assert_encode_decode_to(&(Foo::A(1), 2u8, Foo::A(3)), &(Ignored, 2u8, Ignored));
+    }
+
+    #[test]
+    fn decode_as_type_exact_errors_on_leftover_bytes() {
+        let (type_id, types) = make_type::<u16>();
+
+        let encoded = 123u16.encode();
+        assert_eq!(u16::decode_as_type_exact(&encoded, type_id, &types).unwrap(), 123);
+
+        // Non-strict decoding is fine with the leftover bytes, but exact decoding isn't:
+        let encoded = (123u16, 1u8, 2u8).encode();
+        assert_eq!(u16::decode_as_type(&mut &*encoded, type_id, &types).unwrap(), 123);
+        let err = u16::decode_as_type_exact(&encoded, type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::LeftoverBytes { count: 2 }));
+    }
+
+    #[test]
+    fn decode_as_fields_exact_errors_on_leftover_bytes() {
+        let (type_id, types) = make_type::<u16>();
+        let fields = || [Field::named(type_id, "a"), Field::named(type_id, "b")].into_iter();
+
+        let encoded = (1u16, 2u16).encode();
+        let decoded = <(u16, u16)>::decode_as_fields_exact(&encoded, &mut fields(), &types);
+        assert_eq!(decoded.unwrap(), (1, 2));
+
+        let encoded = (1u16, 2u16, 3u8).encode();
+        let err =
+            <(u16, u16)>::decode_as_fields_exact(&encoded, &mut fields(), &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::LeftoverBytes { count: 1 }));
+    }
+
+    #[test]
+    fn decode_via_try_from() {
+        let (type_id, types) = make_type::<u64>();
+
+        let decoded =
+            DecodeVia::<u64, u8>::decode_as_type(&mut &*200u64.encode(), type_id, &types).unwrap();
+        assert_eq!(decoded.into_inner(), 200);
+
+        // Fails to convert:
+        let err = DecodeVia::<u64, u8>::decode_as_type(&mut &*300u64.encode(), type_id, &types)
+            .unwrap_err();
+        #[cfg(feature = "boxed-custom-errors")]
+        assert!(matches!(err.kind(), ErrorKind::Custom(_)));
+        #[cfg(not(feature = "boxed-custom-errors"))]
+        assert!(matches!(err.kind(), ErrorKind::CustomMessage { .. }));
+
+        // Fails to decode the source type in the first place:
+        let (str_id, types) = make_type::<String>();
+        let err =
+            DecodeVia::<u64, u8>::decode_as_type(&mut &*"hi".encode(), str_id, &types).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::VisitorDecodeError(visitor::DecodeError::Unexpected(_))
+        ));
+    }
+
+    #[test]
+    fn derive_expecting_is_attached_to_errors() {
+        #[derive(DecodeAsType, Debug)]
+        #[decode_as_type(crate_path = "crate", expecting = "a thing")]
+        #[allow(dead_code)]
+        enum Thing {
+            A(u8),
         }
 
-        assert_encode_decode_as_fields(Foo { some_field: 123, value: 456 });
+        #[derive(DecodeAsType, Debug)]
+        #[decode_as_type(crate_path = "crate", expecting = "a pair")]
+        #[allow(dead_code)]
+        struct Pair(u8, u8);
 
-        #[derive(DecodeAsType, codec::Encode, PartialEq, Debug, scale_info::TypeInfo)]
-        #[decode_as_type(crate_path = "crate")]
-        struct Foo2(String, bool, u64);
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum Other {
+            B(u8),
+        }
 
-        assert_encode_decode_as_fields(Foo2("hello".to_string(), true, 12345));
+        let (type_id, types) = make_type::<Other>();
+        let encoded = Other::B(1).encode();
+        let err = Thing::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::CannotFindVariant { .. }));
+        assert_eq!(err.context().expected(), Some("a thing"));
+        assert!(err.to_string().contains("(expecting a thing)"));
+
+        let (type_id, types) = make_type::<(u8, u8, u8)>();
+        let encoded = (1u8, 2u8, 3u8).encode();
+        let err = Pair::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::WrongLength { .. }));
+        assert_eq!(err.context().expected(), Some("a pair"));
+
+        // Errors from inside fields are left alone:
+        let (type_id, types) = make_type::<(u8, bool)>();
+        let encoded = (1u8, true).encode();
+        let err = Pair::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert_eq!(err.context().expected(), None);
+    }
 
-        #[derive(DecodeAsType, codec::Encode, PartialEq, Debug, scale_info::TypeInfo)]
+    #[test]
+    fn derive_transparent_works() {
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate", transparent)]
+        struct Wrapper(u32);
+
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate", transparent)]
+        struct Named<T> {
+            #[decode_as_type(skip)]
+            marker: core::marker::PhantomData<T>,
+            inner: Vec<T>,
+        }
+
+        // Decodes from primitives, which the non-transparent derive would not:
+        assert_encode_decode_to(&123u8, &Wrapper(123));
+        assert_encode_decode_to(&codec::Compact(123u64), &Wrapper(123));
+        assert_encode_decode_to(&(123u16,), &Wrapper(123));
+        assert_encode_decode_to(
+            &vec![1u8, 2, 3],
+            &Named { marker: core::marker::PhantomData, inner: vec![1u64, 2, 3] },
+        );
+
+        // Errors are exactly as they'd be for the inner type:
+        let (type_id, types) = make_type::<u64>();
+        let encoded = u64::MAX.encode();
+        let err = Wrapper::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+    }
+
+    #[test]
+    fn derive_tagged_enums_work() {
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate", tag = "kind", content = "data")]
+        enum Target {
+            A,
+            B(u8, bool),
+            C { x: u32 },
+        }
+
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        struct Tagged<T> {
+            kind: String,
+            data: T,
+        }
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        struct TagOnly {
+            kind: String,
+        }
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        struct BData(u8, bool);
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        struct CData {
+            x: u64,
+        }
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        struct Reordered {
+            data: CData,
+            kind: String,
+        }
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum Source {
+            B(u8, bool),
+        }
+
+        let tagged = |kind: &str| kind.to_string();
+        assert_encode_decode_to(&TagOnly { kind: tagged("A") }, &Target::A);
+        assert_encode_decode_to(
+            &Tagged { kind: tagged("B"), data: BData(1, true) },
+            &Target::B(1, true),
+        );
+        assert_encode_decode_to(
+            &Reordered { data: CData { x: 123 }, kind: tagged("C") },
+            &Target::C { x: 123 },
+        );
+        // Plain variants can still be decoded:
+        assert_encode_decode_to(&Source::B(1, true), &Target::B(1, true));
+
+        // Unknown variant names are reported:
+        let (type_id, types) = make_type::<Tagged<BData>>();
+        let encoded = Tagged { kind: tagged("Bb"), data: BData(1, true) }.encode();
+        let err = Target::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert_eq!(err.context().path().to_string(), "data");
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::CannotFindVariantName { got, suggestion: Some("B"), .. } if got == "Bb"
+        ));
+
+        // Variants with fields need the content field:
+        let (type_id, types) = make_type::<TagOnly>();
+        let encoded = TagOnly { kind: tagged("C") }.encode();
+        let err = Target::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::CannotFindField { name } if name == "data"));
+    }
+
+    #[test]
+    fn derive_as_attribute_works() {
+        #[derive(Debug, PartialEq)]
+        struct Balance(u32);
+        #[derive(Debug, thiserror::Error)]
+        #[error("balance too large")]
+        struct TooLarge;
+        impl TryFrom<u64> for Balance {
+            type Error = TooLarge;
+            fn try_from(value: u64) -> Result<Self, Self::Error> {
+                u32::try_from(value).map(Balance).map_err(|_| TooLarge)
+            }
+        }
+        #[derive(Debug, PartialEq)]
+        struct Name(String);
+        impl From<String> for Name {
+            fn from(value: String) -> Self {
+                Name(value)
+            }
+        }
+
+        #[derive(DecodeAsType, Debug, PartialEq)]
         #[decode_as_type(crate_path = "crate")]
-        struct Foo3;
+        struct Account {
+            #[decode_as_type(as = "u64")]
+            balance: Balance,
+            #[decode_as_type(as = "String")]
+            name: Name,
+        }
 
-        assert_encode_decode_as_fields(Foo3);
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate")]
+        struct Unnamed(#[decode_as_type(as = "u64")] Balance, bool);
 
-        // Tuples should work, too:
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate")]
+        enum Event {
+            Transfer(#[decode_as_type(as = "u64")] Balance),
+            Named {
+                #[decode_as_type(as = "u64")]
+                amount: Balance,
+            },
+        }
+
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate", transparent)]
+        struct Wrapper(#[decode_as_type(as = "u64")] Balance);
+
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        struct AccountIn {
+            name: String,
+            balance: u64,
+        }
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum EventIn {
+            Transfer(u64),
+            Named { amount: u128 },
+        }
+
+        assert_encode_decode_to(
+            &AccountIn { name: "Alice".into(), balance: 100 },
+            &Account { balance: Balance(100), name: Name("Alice".into()) },
+        );
+        assert_encode_decode_to(&(100u8, true), &Unnamed(Balance(100), true));
+        assert_encode_decode_to(&EventIn::Transfer(100), &Event::Transfer(Balance(100)));
+        assert_encode_decode_to(
+            &EventIn::Named { amount: 100 },
+            &Event::Named { amount: Balance(100) },
+        );
+        assert_encode_decode_to(&100u16, &Wrapper(Balance(100)));
+
+        // Errors from the conversion are returned, with the location of the field:
+        let (type_id, types) = make_type::<AccountIn>();
+        let encoded = AccountIn { name: "Bob".into(), balance: u64::MAX }.encode();
+        let err = Account::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        // (without boxed-custom-errors, the message is followed by an error code)
+        assert!(err.to_string().starts_with("Error at balance: Custom error: balance too large"));
+        // With boxed-custom-errors, the original error is kept:
+        #[cfg(feature = "boxed-custom-errors")]
+        assert!(
+            matches!(err.kind(), ErrorKind::Custom(e) if e.downcast_ref::<TooLarge>().is_some())
+        );
+
+        let (type_id, types) = make_type::<u64>();
+        let encoded = u64::MAX.encode();
+        let err = Wrapper::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert!(err.kind().to_string().starts_with("Custom error: balance too large"));
+    }
+
+    #[test]
+    fn derive_fieldless_enums_decode_from_indexes() {
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate", from_index)]
+        enum Status {
+            Pending,
+            #[decode_as_type(index = 5)]
+            Done,
+            #[codec(index = 7)]
+            Failed,
+            Cancelled = 10,
+        }
+
+        assert_encode_decode_to(&0u8, &Status::Pending);
+        assert_encode_decode_to(&5u16, &Status::Done);
+        assert_encode_decode_to(&7u32, &Status::Failed);
+        assert_encode_decode_to(&codec::Compact(10u32), &Status::Cancelled);
+
+        // Variants can still be decoded by name:
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum StatusIn {
+            Done,
+        }
+        assert_encode_decode_to(&StatusIn::Done, &Status::Done);
+
+        let (type_id, types) = make_type::<u32>();
+        let err = Status::decode_as_type(&mut &*3u32.encode(), type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::CannotFindVariant { index: 3, .. }));
+        let err = Status::decode_as_type(&mut &*256u32.encode(), type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+    }
+
+    #[test]
+    fn derive_fieldless_enums_dont_decode_from_indexes_by_default() {
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate")]
+        enum Status {
+            Pending,
+            Done,
+        }
+
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate")]
+        enum Opt {
+            #[allow(dead_code)]
+            Some(u8),
+            None,
+        }
+
+        for (type_id, types) in [make_type::<u8>(), make_type::<u16>(), make_type::<u32>()] {
+            assert!(Status::decode_as_type(&mut &*1u32.encode(), type_id, &types).is_err());
+            assert!(Opt::decode_as_type(&mut &*1u32.encode(), type_id, &types).is_err());
+        }
+
+        // Variants can still be decoded by name:
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum StatusIn {
+            Done,
+        }
+        assert_encode_decode_to(&StatusIn::Done, &Status::Done);
+    }
+
+    #[test]
+    fn derive_try_from_u8_attribute_works() {
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate", try_from_u8, expecting = "a status")]
+        enum Status {
+            Pending,
+            #[codec(index = 7)]
+            Failed,
+            #[decode_as_type(skip)]
+            #[allow(dead_code)]
+            Unknown,
+        }
+
+        assert_eq!(Status::try_from(0).unwrap(), Status::Pending);
+        assert_eq!(Status::try_from(7).unwrap(), Status::Failed);
+
+        // Skipped variants are never converted into:
+        let err = Status::try_from(2).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::CannotFindVariant { index: 2, .. }));
+        assert_eq!(err.context().expected(), Some("a status"));
+    }
+
+    #[test]
+    fn derive_fieldless_enums_with_large_discriminants_work() {
+        // Indexes are only worked out when asked for, so discriminants needn't fit in a u8:
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate")]
+        #[repr(u16)]
+        enum Big {
+            A = 1000,
+            B = 1001,
+        }
+
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum BigIn {
+            B,
+        }
+        assert_encode_decode_to(&BigIn::B, &Big::B);
+        assert_eq!(Big::A as u16, 1000);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn derive_max_depth_attribute_works() {
+        #[derive(DecodeAsType, codec::Encode, scale_info::TypeInfo, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate", max_depth = 3)]
+        enum Expr {
+            Leaf(u32),
+            Node(Box<Expr>, Box<Expr>),
+        }
+
+        #[derive(DecodeAsType, codec::Encode, scale_info::TypeInfo, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate", transparent, max_depth = 2)]
+        struct Tree(Vec<Tree>);
+
+        fn nested_expr(depth: usize) -> Expr {
+            match depth {
+                1 => Expr::Leaf(1),
+                n => Expr::Node(Box::new(nested_expr(n - 1)), Box::new(Expr::Leaf(2))),
+            }
+        }
+
+        assert_encode_decode_to(&nested_expr(3), &nested_expr(3));
+
+        // One level deeper is too deep:
+        let (type_id, types) = make_type::<Expr>();
+        let encoded = nested_expr(4).encode();
+        let err = Expr::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::MaxDepthExceeded { max_depth: 3 }));
+
+        // Each level is left again afterwards, even after errors:
+        assert_encode_decode_to(&nested_expr(3), &nested_expr(3));
+
+        // Transparent structs are limited in the same way:
+        let tree = Tree(vec![Tree(vec![]), Tree(vec![])]);
+        assert_encode_decode_to(&tree, &tree);
+
+        let (type_id, types) = make_type::<Tree>();
+        let encoded = Tree(vec![Tree(vec![Tree(vec![])])]).encode();
+        let err = Tree::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::MaxDepthExceeded { max_depth: 2 }));
+        // Values decoded at the same time on other threads don't count towards the depth:
+        let (type_id, types) = make_type::<Expr>();
+        let encoded = nested_expr(3).encode();
+        std::thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    for _ in 0..1000 {
+                        Expr::decode_as_type(&mut &*encoded, type_id, &types).unwrap();
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn derive_shape_attribute_works() {
+        use crate::shape::{check_decodable, CompatibilityErrorKind};
+
+        #[derive(scale_info::TypeInfo)]
+        #[allow(dead_code)]
+        enum Event {
+            Transfer { from: [u8; 32], to: [u8; 32], amount: u128 },
+            Burn(u64),
+            Other,
+        }
+
+        #[derive(DecodeAsType)]
+        #[decode_as_type(crate_path = "crate", shape)]
+        #[allow(dead_code)]
+        enum MyEvent<T> {
+            Transfer {
+                to: [u8; 32],
+                amount: T,
+            },
+            Burn(#[decode_as_type(as = "u64")] u128),
+            Other,
+            #[decode_as_type(skip)]
+            Unknown(String),
+        }
+
+        #[derive(DecodeAsType)]
+        #[decode_as_type(crate_path = "crate", shape)]
+        #[allow(dead_code)]
+        enum OldEvent {
+            Transfer { to: [u8; 32], amount: u128 },
+            Burn(u64),
+        }
+
+        #[derive(DecodeAsType)]
+        #[decode_as_type(crate_path = "crate", shape, transparent)]
+        #[allow(dead_code)]
+        struct Events(Vec<MyEvent<u128>>);
+
+        let (type_id, types) = make_type::<Event>();
+        assert!(check_decodable::<MyEvent<u128>, _>(type_id, &types).is_ok());
+
+        let err = check_decodable::<MyEvent<String>, _>(type_id, &types).unwrap_err();
+        assert_eq!(err.context().path().to_string(), "amount.(Transfer)");
+
+        let err = check_decodable::<OldEvent, _>(type_id, &types).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            CompatibilityErrorKind::CannotFindVariant { name, .. } if name == "Other"
+        ));
+
+        let (type_id, types) = make_type::<Vec<Event>>();
+        assert!(check_decodable::<Events, _>(type_id, &types).is_ok());
+
+        // The shapes are named after the types and can be displayed:
+        assert_eq!(
+            crate::shape::Shape::of::<Events>().to_string(),
+            "Events = [enum MyEvent { Transfer { to: [number; 32], amount: number }, Burn(number), Other }]"
+        );
+    }
+    #[test]
+    fn derive_remote_attribute_works() {
+        // Stand in for some types in another crate, which don't implement `DecodeAsType`.
+        mod other_crate {
+            #[derive(Debug, PartialEq)]
+            pub struct Point<T> {
+                pub x: T,
+                pub y: T,
+            }
+            #[derive(Debug, PartialEq)]
+            pub enum Shape {
+                Circle(Point<u32>, u32),
+                Line { from: Point<u32>, to: Point<u32> },
+                Empty,
+            }
+        }
+
+        #[derive(DecodeAsType)]
+        #[decode_as_type(crate_path = "crate", remote = "other_crate::Point<T>")]
+        struct PointDef<T> {
+            x: T,
+            y: T,
+        }
+
+        #[derive(DecodeAsType)]
+        #[decode_as_type(crate_path = "crate", remote = "other_crate::Shape")]
+        #[allow(dead_code)]
+        enum ShapeDef {
+            Circle(#[decode_as_type(as = "PointDef<u32>")] other_crate::Point<u32>, u32),
+            Line {
+                #[decode_as_type(as = "PointDef<u32>")]
+                from: other_crate::Point<u32>,
+                #[decode_as_type(as = "PointDef<u32>")]
+                to: other_crate::Point<u32>,
+            },
+            Empty,
+        }
+
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        struct Point {
+            x: u8,
+            y: u8,
+        }
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum Shape {
+            Circle(Point, u16),
+            Line { from: Point, to: Point },
+        }
+
+        let (type_id, types) = make_type::<Shape>();
+
+        let encoded = Shape::Circle(Point { x: 1, y: 2 }, 3).encode();
+        let decoded = DecodeVia::<ShapeDef, other_crate::Shape>::decode_as_type(
+            &mut &*encoded,
+            type_id,
+            &types,
+        )
+        .unwrap()
+        .into_inner();
+        assert_eq!(decoded, other_crate::Shape::Circle(other_crate::Point { x: 1, y: 2 }, 3));
+
+        let encoded = Shape::Line { from: Point { x: 1, y: 2 }, to: Point { x: 3, y: 4 } }.encode();
+        let decoded: other_crate::Shape =
+            ShapeDef::decode_as_type(&mut &*encoded, type_id, &types).unwrap().into();
+        assert_eq!(
+            decoded,
+            other_crate::Shape::Line {
+                from: other_crate::Point { x: 1, y: 2 },
+                to: other_crate::Point { x: 3, y: 4 }
+            }
+        );
+    }
+
+    #[test]
+    fn derive_validate_attribute_works() {
+        #[derive(Debug, PartialEq, thiserror::Error)]
+        enum Invalid {
+            #[error("{0} items is too many")]
+            TooMany(usize),
+            #[error("start must not be after end")]
+            Unordered,
+            #[error("no items")]
+            Empty,
+        }
+        fn at_most_two<T>(items: &[T]) -> Result<(), Invalid> {
+            match items.len() {
+                0..=2 => Ok(()),
+                n => Err(Invalid::TooMany(n)),
+            }
+        }
+        fn ordered(range: &Range) -> Result<(), Invalid> {
+            if range.start <= range.end {
+                Ok(())
+            } else {
+                Err(Invalid::Unordered)
+            }
+        }
+        fn not_empty(event: &Event) -> Result<(), Invalid> {
+            match event {
+                Event::Items(items) if items.is_empty() => Err(Invalid::Empty),
+                _ => Ok(()),
+            }
+        }
+
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate", validate = "ordered")]
+        struct Range {
+            start: u8,
+            end: u8,
+            #[decode_as_type(validate = "at_most_two")]
+            tags: Vec<u8>,
+        }
+
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate", validate = "not_empty")]
+        enum Event {
+            Items(#[decode_as_type(validate = "at_most_two")] Vec<u8>),
+        }
+
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate", transparent)]
+        struct Tags(#[decode_as_type(validate = "at_most_two")] Vec<u8>);
+
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        struct RangeIn {
+            start: u8,
+            end: u8,
+            tags: Vec<u8>,
+        }
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum EventIn {
+            Items(Vec<u8>),
+        }
+
+        // Valid values decode as normal:
+        assert_encode_decode_to(
+            &RangeIn { start: 1, end: 2, tags: vec![1] },
+            &Range { start: 1, end: 2, tags: vec![1] },
+        );
+        assert_encode_decode_to(&(1u8, 2u8, vec![1u8]), &Range { start: 1, end: 2, tags: vec![1] });
+        assert_encode_decode_to(&EventIn::Items(vec![1]), &Event::Items(vec![1]));
+        assert_encode_decode_to(&vec![1u8, 2], &Tags(vec![1, 2]));
+
+        fn decode_err<A, B>(a: A) -> String
+        where
+            A: Encode + scale_info::TypeInfo + 'static,
+            B: DecodeAsType + core::fmt::Debug,
+        {
+            let (type_id, types) = make_type::<A>();
+            let err = B::decode_as_type(&mut &*a.encode(), type_id, &types).unwrap_err();
+            // Without boxed-custom-errors, messages are followed by an error code.
+            err.to_string().trim_end_matches(" (code 0)").to_string()
+        }
+
+        // Invalid fields are reported along with their location:
+        assert_eq!(
+            decode_err::<_, Range>(RangeIn { start: 1, end: 2, tags: vec![1, 2, 3] }),
+            "Error at tags: Custom error: 3 items is too many"
+        );
+        assert_eq!(
+            decode_err::<_, Event>(EventIn::Items(vec![1, 2, 3])),
+            "Error at [0]: Custom error: 3 items is too many"
+        );
+        assert_eq!(
+            decode_err::<_, Tags>(vec![1u8, 2, 3]),
+            "Error at : Custom error: 3 items is too many"
+        );
+        // Invalid containers are reported too:
+        assert_eq!(
+            decode_err::<_, Range>(RangeIn { start: 2, end: 1, tags: vec![] }),
+            "Error at : Custom error: start must not be after end"
+        );
+        assert_eq!(
+            decode_err::<_, Range>((2u8, 1u8, Vec::<u8>::new())),
+            "Error at : Custom error: start must not be after end"
+        );
+        assert_eq!(
+            decode_err::<_, Event>(EventIn::Items(vec![])),
+            "Error at : Custom error: no items"
+        );
+
+        // With boxed-custom-errors, the error returned by the validation function is kept:
+        #[cfg(feature = "boxed-custom-errors")]
+        {
+            let (type_id, types) = make_type::<RangeIn>();
+            let encoded = RangeIn { start: 1, end: 2, tags: vec![1, 2, 3] }.encode();
+            let err = Range::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+            let ErrorKind::Custom(e) = err.kind() else { panic!("expected a custom error") };
+            assert_eq!(e.downcast_ref::<Invalid>(), Some(&Invalid::TooMany(3)));
+        }
+    }
+
+    #[test]
+    fn derive_allow_extra_fields_works() {
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate", allow_extra_fields)]
+        struct Unnamed(u8, bool);
+
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate", allow_extra_fields)]
+        struct Named {
+            a: u8,
+            b: bool,
+        }
+
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate", allow_extra_fields)]
+        enum Event {
+            Transfer(u64),
+            Named { amount: u64 },
+        }
+
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate")]
+        struct Strict(u8, bool);
+
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum NewerEvent {
+            Transfer(u64, String),
+            Named(u64, bool),
+        }
+
+        // Extra fields are skipped over, and all of the input is consumed:
+        let newer = (1u8, true, "extra".to_string());
+        assert_encode_decode_to(&newer, &Unnamed(1, true));
+        assert_encode_decode_to(&newer, &Named { a: 1, b: true });
+        assert_encode_decode_to(&NewerEvent::Transfer(100, "extra".into()), &Event::Transfer(100));
+        assert_encode_decode_to(&NewerEvent::Named(100, true), &Event::Named { amount: 100 });
+
+        // Too few fields is still an error:
+        let (type_id, types) = make_type::<(u8,)>();
+        let encoded = (1u8,).encode();
+        let err = Unnamed::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::WrongLength { actual_len: 1, expected_len: 2 }));
+
+        // Without the attribute, the number of fields must line up:
+        let (type_id, types) = make_type::<(u8, bool, String)>();
+        let encoded = newer.encode();
+        assert!(Strict::decode_as_type(&mut &*encoded, type_id, &types).is_err());
+    }
+
+    #[test]
+    fn newtype_macro_works() {
+        #[derive(codec::Encode, scale_info::TypeInfo, Debug, PartialEq)]
+        struct Id(u64);
+        #[derive(Debug, PartialEq)]
+        struct Name(String);
+
+        crate::impl_decode_as_type_for_newtype!(Id(u64), Name(String));
+
+        // Like the transparent derive, this decodes exactly as the inner type would:
+        assert_encode_decode_to(&123u8, &Id(123));
+        assert_encode_decode_to(&(123u16,), &Id(123));
+        assert_encode_decode_to(&("hi", 1u8), &(Name("hi".to_string()), Id(1)));
+        assert_encode_decode_as_fields(Id(123));
+
+        let (type_id, types) = make_type::<i8>();
+        let encoded = (-1i8).encode();
+        let err = Id::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+    }
+
+    #[test]
+    fn visitor_error_macro_works() {
+        use crate::visitor::DecodeError;
+
+        #[derive(Debug, thiserror::Error)]
+        enum MyError {
+            #[error("{0}")]
+            Decode(DecodeError),
+            #[error("odd number")]
+            Odd,
+        }
+        crate::impl_visitor_error!(MyError::Decode);
+
+        struct EvenVisitor<R>(PhantomData<R>);
+        impl<R: TypeResolver> Visitor for EvenVisitor<R> {
+            type Value<'scale, 'resolver> = u64;
+            type Error = MyError;
+            type TypeResolver = R;
+
+            fn visit_u64<'scale, 'resolver>(
+                self,
+                value: u64,
+                _type_id: R::TypeId,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                if value % 2 == 0 {
+                    Ok(value)
+                } else {
+                    Err(MyError::Odd)
+                }
+            }
+        }
+
+        let decode = |value: u64| {
+            let (type_id, types) = make_type::<u64>();
+            let visitor = visitor::VisitorWithCrateError(EvenVisitor(PhantomData));
+            decode_with_visitor(&mut &*value.encode(), type_id, &types, visitor)
+        };
+        assert_eq!(decode(2).unwrap(), 2);
+        let err = decode(3).unwrap_err();
+        #[cfg(feature = "boxed-custom-errors")]
+        assert!(matches!(err.kind(), ErrorKind::Custom(_)));
+        #[cfg(not(feature = "boxed-custom-errors"))]
+        assert!(matches!(err.kind(), ErrorKind::CustomMessage { .. }));
+        assert!(err.to_string().contains("odd number"));
+
+        // Decode errors are converted as normal:
+        let err = MyError::from(DecodeError::VariantNotFound(1)).at_variant("Foo");
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::VisitorDecodeError(DecodeError::VariantNotFound(1))
+        ));
+        assert_eq!(err.context().path().to_string(), "(Foo)");
+    }
+
+    #[test]
+    fn decoding_can_skip_enum_variants() {
+        #[derive(DecodeAsType, PartialEq, Debug)]
+        #[decode_as_type(crate_path = "crate")]
+        #[allow(dead_code)]
+        enum Foo {
+            A(u8),
+            #[decode_as_type(skip)]
+            B(u8),
+            #[codec(skip)]
+            Unsupported,
+        }
+
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum FooPartial {
+            A(u8),
+            B(u8),
+            Unsupported,
+        }
+
+        assert_encode_decode_to(&FooPartial::A(1), &Foo::A(1));
+
+        for val in [FooPartial::B(2), FooPartial::Unsupported] {
+            let (type_id, types) = make_type::<FooPartial>();
+            let encoded = val.encode();
+            let err = Foo::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+            match err.kind() {
+                ErrorKind::CannotFindVariant { expected, .. } => assert_eq!(expected, &["A"]),
+                kind => panic!("unexpected error: {kind:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn decoding_can_reorder_variant_fields() {
+        #[derive(DecodeAsType, PartialEq, Debug)]
+        #[decode_as_type(crate_path = "crate")]
+        enum Foo {
+            #[decode_as_type(reorder_fields)]
+            A { a: u8, b: bool },
+        }
+
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum FooReordered {
+            A { extra: String, b: bool, a: u8 },
+        }
+
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum FooUnnamed {
+            A(u8, bool),
+        }
+
+        assert_encode_decode_to(
+            &FooReordered::A { extra: "hi".to_string(), b: true, a: 1 },
+            &Foo::A { a: 1, b: true },
+        );
+
+        // Fields are never matched up by position:
+        let (type_id, types) = make_type::<FooUnnamed>();
+        let encoded = FooUnnamed::A(1, true).encode();
+        let err = Foo::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::CannotFindField { name } if name == "a"));
+    }
+
+    #[test]
+    fn derive_can_skip_bounds_on_some_params() {
+        // This doesn't implement IntoVisitor:
+        #[derive(PartialEq, Debug)]
+        struct Marker;
+
+        #[derive(DecodeAsType, PartialEq, Debug)]
+        #[decode_as_type(crate_path = "crate")]
+        struct Foo<#[decode_as_type(bound_skip)] M, T> {
+            a: T,
+            #[decode_as_type(skip)]
+            marker: PhantomData<M>,
+        }
+
+        #[derive(DecodeAsType, PartialEq, Debug)]
+        #[decode_as_type(crate_path = "crate")]
+        enum Bar<T, #[decode_as_type(bound_skip)] M> {
+            A(T, PhantomData<M>),
+        }
+
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        struct FooInfo {
+            a: u8,
+        }
+
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum BarInfo {
+            A(u8, ()),
+        }
+
+        assert_encode_decode_to(
+            &FooInfo { a: 1 },
+            &Foo::<Marker, u64> { a: 1, marker: PhantomData },
+        );
+        assert_encode_decode_to(&BarInfo::A(1, ()), &Bar::<u64, Marker>::A(1, PhantomData));
+    }
+
+    #[test]
+    fn decoding_fields_as_compact_works() {
+        // The type information here says nothing about compact encoding:
+        #[derive(scale_info::TypeInfo)]
+        #[allow(dead_code)]
+        struct Info {
+            a: u8,
+            b: u64,
+            c: u32,
+        }
+        #[derive(scale_info::TypeInfo)]
+        #[allow(dead_code)]
+        struct InfoUnnamed(u8, u64, u32);
+        #[derive(scale_info::TypeInfo)]
+        #[allow(dead_code)]
+        enum InfoEnum {
+            Named { a: u8, b: u64, c: u32 },
+            Unnamed(u8, u64, u32),
+        }
+
+        // But the bytes were compact encoded in places anyway:
+        let encoded = (1u8, Compact(2u64), 3u32).encode();
+
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate")]
+        struct Foo {
+            a: u8,
+            #[decode_as_type(compact)]
+            b: u64,
+            c: u32,
+        }
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate")]
+        struct FooUnnamed(u8, #[decode_as_type(compact)] u64, u32);
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate")]
+        enum FooEnum {
+            Named {
+                a: u8,
+                #[decode_as_type(compact)]
+                b: u64,
+                c: u32,
+            },
+            Unnamed(u8, #[decode_as_type(compact)] u64, u32),
+        }
+
+        fn decode<T: DecodeAsType, Ty: scale_info::TypeInfo + 'static>(bytes: &[u8]) -> T {
+            let (type_id, types) = make_type::<Ty>();
+            let input = &mut &*bytes;
+            let val = T::decode_as_type(input, type_id, &types).unwrap();
+            assert!(input.is_empty(), "all bytes should be decoded");
+            val
+        }
+
+        let foo = Foo { a: 1, b: 2, c: 3 };
+        assert_eq!(decode::<Foo, Info>(&encoded), foo);
+        assert_eq!(decode::<Foo, InfoUnnamed>(&encoded), foo);
+        assert_eq!(decode::<FooUnnamed, InfoUnnamed>(&encoded), FooUnnamed(1, 2, 3));
+
+        let named = [&[0u8][..], &encoded].concat();
+        let unnamed = [&[1u8][..], &encoded].concat();
+        let foo_named = FooEnum::Named { a: 1, b: 2, c: 3 };
+        assert_eq!(decode::<FooEnum, InfoEnum>(&named), foo_named);
+        assert_eq!(decode::<FooEnum, InfoEnum>(&unnamed), FooEnum::Unnamed(1, 2, 3));
+
+        // `#[codec(compact)]` doesn't force compact decoding; the type information decides:
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate")]
+        struct Target {
+            #[codec(compact)]
+            a: u32,
+        }
+        #[derive(scale_info::TypeInfo)]
+        #[allow(dead_code)]
+        struct FixedWidth {
+            a: u32,
+        }
+        assert_eq!(decode::<Target, FixedWidth>(&1_000_000u32.encode()), Target { a: 1_000_000 });
+    }
+
+    #[test]
+    fn derive_reports_field_mismatches() {
+        #[derive(DecodeAsType, Debug)]
+        #[decode_as_type(crate_path = "crate")]
+        #[allow(dead_code)]
+        struct Foo {
+            a: u8,
+            #[decode_as_type(skip)]
+            skipped: bool,
+            b: u8,
+        }
+
+        #[derive(DecodeAsType, Debug)]
+        #[decode_as_type(crate_path = "crate")]
+        #[allow(dead_code)]
+        enum Bar {
+            A { a: u8, b: u8 },
+        }
+
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum BarUnnamed {
+            A(u8, u8, u8),
+        }
+
+        let (type_id, types) = make_type::<(u8, u8, u8)>();
+        let encoded = (1u8, 2u8, 3u8).encode();
+        let err = Foo::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        match err.kind() {
+            ErrorKind::FieldMismatch { expected, found } => {
+                assert_eq!(expected, &["a", "b"]);
+                assert_eq!(found, &[None, None, None]);
+            }
+            kind => panic!("unexpected error: {kind:?}"),
+        }
+        assert_eq!(
+            err.to_string(),
+            "Error at : Cannot decode 3 fields [None, None, None] into fields [\"a\", \"b\"]"
+        );
+
+        let (type_id, types) = make_type::<BarUnnamed>();
+        let encoded = BarUnnamed::A(1, 2, 3).encode();
+        let err = Bar::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::FieldMismatch { found, .. } if found.len() == 3));
+    }
+
+    #[test]
+    fn decode_as_fields_works() {
+        use core::fmt::Debug;
+
+        #[derive(DecodeAsType, codec::Encode, PartialEq, Debug, scale_info::TypeInfo)]
+        #[decode_as_type(crate_path = "crate")]
+        struct Foo {
+            some_field: u8,
+            value: u16,
+        }
+
+        assert_encode_decode_as_fields(Foo { some_field: 123, value: 456 });
+
+        #[derive(DecodeAsType, codec::Encode, PartialEq, Debug, scale_info::TypeInfo)]
+        #[decode_as_type(crate_path = "crate")]
+        struct Foo2(String, bool, u64);
+
+        assert_encode_decode_as_fields(Foo2("hello".to_string(), true, 12345));
+
+        #[derive(DecodeAsType, codec::Encode, PartialEq, Debug, scale_info::TypeInfo)]
+        #[decode_as_type(crate_path = "crate")]
+        struct Foo3;
+
+        assert_encode_decode_as_fields(Foo3);
+
+        // Tuples should work, too:
         assert_encode_decode_as_fields((true, 123u8, "hello".to_string()));
     }
+
+    #[test]
+    fn decode_as_fields_works_for_enums() {
+        #[derive(DecodeAsType, PartialEq, Debug)]
+        #[decode_as_type(crate_path = "crate", fields_from_variant = "Transfer")]
+        enum Event<T> {
+            #[allow(dead_code)]
+            Other(bool),
+            Transfer {
+                from: u8,
+                to: u8,
+                amount: T,
+            },
+        }
+
+        #[derive(DecodeAsType, PartialEq, Debug)]
+        #[decode_as_type(crate_path = "crate", fields_from_variant = "Pair")]
+        enum Unnamed {
+            #[allow(dead_code)]
+            Other,
+            Pair(u8, String),
+        }
+
+        // The fields of some type, as would be given to DecodeAsFields:
+        fn decode_fields_of<T, D>(value: &T) -> D
+        where
+            T: codec::Encode + scale_info::TypeInfo + 'static,
+            D: DecodeAsFields,
+        {
+            let (ty, types) = make_type::<T>();
+            let encoded = value.encode();
+            let cursor = &mut &*encoded;
+            let scale_info::TypeDef::Composite(c) = &types.resolve(ty).unwrap().type_def else {
+                panic!("Expected composite type def")
+            };
+            let mut field_iter = c.fields.iter().map(|f| Field::new(f.ty.id, f.name));
+            let decoded = D::decode_as_fields(cursor, &mut field_iter, &types).unwrap();
+            assert!(cursor.is_empty(), "all bytes should be consumed");
+            decoded
+        }
+
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        struct TransferFields {
+            to: u8,
+            from: u8,
+            amount: u32,
+        }
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        struct PairFields(u8, String);
+
+        let event: Event<u64> = decode_fields_of(&TransferFields { to: 2, from: 1, amount: 100 });
+        assert_eq!(event, Event::Transfer { from: 1, to: 2, amount: 100 });
+
+        let pair: Unnamed = decode_fields_of(&PairFields(1, "hi".to_string()));
+        assert_eq!(pair, Unnamed::Pair(1, "hi".to_string()));
+    }
+
+    #[test]
+    fn derive_can_generate_constructor() {
+        #[derive(DecodeAsType, PartialEq, Debug)]
+        #[decode_as_type(crate_path = "crate", constructor)]
+        struct Foo<T> {
+            a: T,
+            b: bool,
+        }
+
+        #[derive(DecodeAsType, PartialEq, Debug)]
+        #[decode_as_type(crate_path = "crate", constructor)]
+        enum Bar {
+            #[allow(dead_code)]
+            A,
+            B(u64),
+        }
+
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        struct FooEncoded {
+            a: u8,
+            b: bool,
+        }
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum BarEncoded {
+            B(u8),
+        }
+
+        let (ty, types) = make_type::<FooEncoded>();
+        let encoded = FooEncoded { a: 1, b: true }.encode();
+        let foo = Foo::<u32>::decode_from(&mut &*encoded, ty, &types).unwrap();
+        assert_eq!(foo, Foo { a: 1, b: true });
+
+        let (ty, types) = make_type::<BarEncoded>();
+        let encoded = BarEncoded::B(2).encode();
+        assert_eq!(Bar::decode_from(&mut &*encoded, ty, &types).unwrap(), Bar::B(2));
+    }
+
+    #[test]
+    fn derive_rename_attribute_works() {
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate")]
+        struct Foo {
+            #[decode_as_type(rename = "kind")]
+            ty: u8,
+            #[decode_as_type(rename = "count")]
+            n: u64,
+        }
+
+        #[derive(DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate")]
+        enum Bar {
+            #[decode_as_type(rename = "Some")]
+            Value {
+                #[decode_as_type(rename = "inner")]
+                value: bool,
+            },
+            #[decode_as_type(rename = "None")]
+            Empty,
+        }
+
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        struct FooIn {
+            count: u8,
+            kind: u8,
+        }
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum BarIn {
+            Some { inner: bool },
+            None,
+        }
+
+        assert_encode_decode_to(&FooIn { count: 2, kind: 1 }, &Foo { ty: 1, n: 2 });
+        assert_encode_decode_to(&BarIn::Some { inner: true }, &Bar::Value { value: true });
+        assert_encode_decode_to(&BarIn::None, &Bar::Empty);
+
+        // Errors list the names that we decode from:
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum BarOther {
+            Value { value: bool },
+        }
+        let (type_id, types) = make_type::<BarOther>();
+        let encoded = BarOther::Value { value: true }.encode();
+        let err = Bar::decode_as_type(&mut &*encoded, type_id, &types).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::CannotFindVariant { expected, .. } if expected == &["Some", "None"]
+        ));
+    }
+
+    #[cfg(feature = "codec-as-type")]
+    #[test]
+    fn derive_codec_as_type_round_trips() {
+        use crate::ext::scale_encode::{EncodeAsFields, EncodeAsType};
+
+        #[derive(crate::CodecAsType, Debug, PartialEq)]
+        #[codec_as_type(crate_path = "crate")]
+        struct Foo<T> {
+            #[codec_as_type(rename = "kind")]
+            ty: T,
+            #[codec(skip)]
+            cached: Option<u64>,
+            #[codec_as_type(skip)]
+            seen: bool,
+            names: Vec<String>,
+        }
+
+        #[derive(crate::CodecAsType, Debug, PartialEq)]
+        #[codec_as_type(crate_path = "crate")]
+        enum Bar {
+            #[codec_as_type(rename = "Some")]
+            Value {
+                #[codec_as_type(rename = "inner")]
+                value: bool,
+            },
+            Pair(u8, #[codec_as_type(skip)] bool, String),
+            #[codec_as_type(rename = "None")]
+            Empty,
+        }
+
+        // The shape that both sides are expected to encode to and decode from:
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        struct FooWire {
+            names: Vec<String>,
+            #[codec(compact)]
+            kind: u32,
+        }
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        enum BarWire {
+            None,
+            Some { inner: bool },
+            Pair(u8, String),
+        }
+
+        let (type_id, types) = make_type::<FooWire>();
+        let foo = Foo {
+            ty: 7u32,
+            cached: None,
+            seen: false,
+            names: vec!["a".to_string(), "b".to_string()],
+        };
+        let encoded = foo.encode_as_type(type_id, &types).unwrap();
+        assert_eq!(encoded, FooWire { names: foo.names.clone(), kind: 7 }.encode());
+        assert_eq!(Foo::<u32>::decode_as_type(&mut &*encoded, type_id, &types).unwrap(), foo);
+
+        // Skipped fields are populated with their defaults when decoding:
+        let seen = Foo { cached: Some(1), seen: true, ..foo };
+        let encoded = seen.encode_as_type(type_id, &types).unwrap();
+        let decoded = Foo::<u32>::decode_as_type(&mut &*encoded, type_id, &types).unwrap();
+        assert_eq!(decoded, Foo { cached: None, seen: false, ..seen });
+
+        // Structs can be encoded to and decoded from fields too:
+        let scale_info::TypeDef::Composite(c) = &types.resolve(type_id).unwrap().type_def else {
+            panic!("Expected composite type def")
+        };
+        let fields = || c.fields.iter().map(|f| Field::new(f.ty.id, f.name));
+        let mut encoded = Vec::new();
+        decoded.encode_as_fields_to(&mut fields(), &types, &mut encoded).unwrap();
+        let decoded_fields =
+            Foo::<u32>::decode_as_fields(&mut &*encoded, &mut fields(), &types).unwrap();
+        assert_eq!(decoded_fields, decoded);
+
+        let (type_id, types) = make_type::<BarWire>();
+        let bars = [
+            (Bar::Value { value: true }, BarWire::Some { inner: true }),
+            (Bar::Pair(1, false, "hi".to_string()), BarWire::Pair(1, "hi".to_string())),
+            (Bar::Empty, BarWire::None),
+        ];
+        for (bar, wire) in bars {
+            let encoded = bar.encode_as_type(type_id, &types).unwrap();
+            assert_eq!(encoded, wire.encode());
+            assert_eq!(Bar::decode_as_type(&mut &*encoded, type_id, &types).unwrap(), bar);
+        }
+    }
 }
@@ -0,0 +1,207 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use super::{impl_into_visitor, visit_single_field_composite_tuple_impls, BasicVisitor};
+use crate::{
+    error::{Error, ErrorKind},
+    visitor::{
+        types::{Array, Composite, Tuple, Variant},
+        Visitor,
+    },
+    IntoVisitor,
+};
+use alloc::{string::ToString, vec};
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use scale_type_resolver::TypeResolver;
+
+// Decode a fixed number of bytes from an array.
+fn visit_byte_array<const N: usize, R: TypeResolver>(
+    value: &mut Array<'_, '_, R>,
+) -> Result<[u8; N], Error> {
+    if value.remaining() != N {
+        return Err(Error::new(ErrorKind::WrongLength {
+            actual_len: value.remaining(),
+            expected_len: N,
+        }));
+    }
+    let mut bytes = [0u8; N];
+    for (idx, byte) in bytes.iter_mut().enumerate() {
+        *byte = value
+            .decode_item(u8::into_visitor::<R>())
+            .expect("length already checked via .remaining()")
+            .map_err(|e| e.at_idx(idx))?;
+    }
+    Ok(bytes)
+}
+
+// Ipv4 addresses are decoded from a u32, or from 4 bytes in network order.
+impl<R: TypeResolver> Visitor for BasicVisitor<Ipv4Addr, R> {
+    type Error = Error;
+    type Value<'scale, 'resolver> = Ipv4Addr;
+    type TypeResolver = R;
+
+    fn visit_u32<'scale, 'resolver>(
+        self,
+        value: u32,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        Ok(Ipv4Addr::from(value))
+    }
+    fn visit_array<'scale, 'resolver>(
+        self,
+        value: &mut Array<'scale, 'resolver, R>,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        visit_byte_array::<4, R>(value).map(Ipv4Addr::from)
+    }
+    visit_single_field_composite_tuple_impls!(R);
+}
+impl_into_visitor!(Ipv4Addr);
+
+// Ipv6 addresses are decoded from 16 bytes in network order, or from a u128.
+impl<R: TypeResolver> Visitor for BasicVisitor<Ipv6Addr, R> {
+    type Error = Error;
+    type Value<'scale, 'resolver> = Ipv6Addr;
+    type TypeResolver = R;
+
+    fn visit_u128<'scale, 'resolver>(
+        self,
+        value: u128,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        Ok(Ipv6Addr::from(value))
+    }
+    fn visit_array<'scale, 'resolver>(
+        self,
+        value: &mut Array<'scale, 'resolver, R>,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        visit_byte_array::<16, R>(value).map(Ipv6Addr::from)
+    }
+    visit_single_field_composite_tuple_impls!(R);
+}
+impl_into_visitor!(Ipv6Addr);
+
+// Decode the single field in some variant, adding the variant name as context to any errors.
+fn decode_single_variant_field<T: IntoVisitor, R: TypeResolver>(
+    value: &mut Variant<'_, '_, R>,
+) -> Result<T, Error> {
+    let name = value.name();
+    let fields = value.fields();
+    if fields.remaining() != 1 {
+        return Err(Error::new(ErrorKind::WrongLength {
+            actual_len: fields.remaining(),
+            expected_len: 1,
+        })
+        .at_variant(name.to_string()));
+    }
+    fields
+        .decode_item(T::into_visitor::<R>())
+        .expect("length already checked via .remaining()")
+        .map_err(|e| e.at_variant(name.to_string()))
+}
+
+// IP addresses are decoded from an enum with "V4" and "V6" variants, like std's.
+impl<R: TypeResolver> Visitor for BasicVisitor<IpAddr, R> {
+    type Error = Error;
+    type Value<'scale, 'resolver> = IpAddr;
+    type TypeResolver = R;
+
+    fn visit_variant<'scale, 'resolver>(
+        self,
+        value: &mut Variant<'scale, 'resolver, R>,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        match value.name() {
+            "V4" => decode_single_variant_field(value).map(IpAddr::V4),
+            "V6" => decode_single_variant_field(value).map(IpAddr::V6),
+            _ => Err(Error::cannot_find_variant(value.name(), value.index(), vec!["V4", "V6"])),
+        }
+    }
+    visit_single_field_composite_tuple_impls!(R);
+}
+impl_into_visitor!(IpAddr);
+
+// Socket addresses are decoded from an IP address followed by a u16 port. Any other fields
+// (eg the flow info and scope ID of a SocketAddrV6) are expected to be absent.
+macro_rules! impl_socket_addr_visitor {
+    ($ty:ident, $ip:ident: $make:expr) => {
+        impl<R: TypeResolver> Visitor for BasicVisitor<$ty, R> {
+            type Error = Error;
+            type Value<'scale, 'resolver> = $ty;
+            type TypeResolver = R;
+
+            fn visit_composite<'scale, 'resolver>(
+                self,
+                value: &mut Composite<'scale, 'resolver, R>,
+                type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                if value.remaining() == 1 {
+                    return value.decode_item(self).unwrap();
+                }
+                let (ip, port) = <($ip, u16)>::into_visitor().visit_composite(value, type_id)?;
+                Ok(($make)(ip, port))
+            }
+            fn visit_tuple<'scale, 'resolver>(
+                self,
+                value: &mut Tuple<'scale, 'resolver, R>,
+                type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                if value.remaining() == 1 {
+                    return value.decode_item(self).unwrap();
+                }
+                let (ip, port) = <($ip, u16)>::into_visitor().visit_tuple(value, type_id)?;
+                Ok(($make)(ip, port))
+            }
+        }
+        impl_into_visitor!($ty);
+    };
+}
+impl_socket_addr_visitor!(SocketAddrV4, Ipv4Addr: SocketAddrV4::new);
+impl_socket_addr_visitor!(SocketAddrV6, Ipv6Addr: |ip, port| SocketAddrV6::new(ip, port, 0, 0));
+
+// Socket addresses are decoded from an enum with "V4" and "V6" variants, like std's. The variants
+// can either contain a single socket address, or an IP address and port directly.
+fn decode_socket_variant_fields<T: IntoVisitor, R: TypeResolver>(
+    value: &mut Variant<'_, '_, R>,
+    type_id: R::TypeId,
+) -> Result<T, Error> {
+    if value.fields().remaining() == 1 {
+        return decode_single_variant_field(value);
+    }
+    let name = value.name();
+    T::into_visitor::<R>()
+        .visit_composite(value.fields(), type_id)
+        .map_err(|e| e.at_variant(name.to_string()))
+}
+
+impl<R: TypeResolver> Visitor for BasicVisitor<SocketAddr, R> {
+    type Error = Error;
+    type Value<'scale, 'resolver> = SocketAddr;
+    type TypeResolver = R;
+
+    fn visit_variant<'scale, 'resolver>(
+        self,
+        value: &mut Variant<'scale, 'resolver, R>,
+        type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        match value.name() {
+            "V4" => decode_socket_variant_fields(value, type_id).map(SocketAddr::V4),
+            "V6" => decode_socket_variant_fields(value, type_id).map(SocketAddr::V6),
+            _ => Err(Error::cannot_find_variant(value.name(), value.index(), vec!["V4", "V6"])),
+        }
+    }
+    visit_single_field_composite_tuple_impls!(R);
+}
+impl_into_visitor!(SocketAddr);
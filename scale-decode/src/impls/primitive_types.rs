@@ -13,13 +13,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::BasicVisitor;
+use super::{visit_single_field_composite_tuple_impls, BasicVisitor};
 use crate::{
-    error::Error,
-    visitor::{decode_with_visitor, DecodeAsTypeResult, Visitor},
+    error::{Error, ErrorKind},
+    visitor::{decode_with_visitor, types::Array, DecodeAsTypeResult, Visitor},
     IntoVisitor,
 };
-use primitive_types::{H128, H160, H256, H384, H512, H768};
+use alloc::{format, string::ToString};
+use primitive_types::{H128, H160, H256, H384, H512, H768, U128, U256, U512};
 use scale_type_resolver::TypeResolver;
 
 macro_rules! impl_visitor {
@@ -63,3 +64,114 @@ impl_visitor!(H256: 256);
 impl_visitor!(H384: 384);
 impl_visitor!(H512: 512);
 impl_visitor!(H768: 768);
+
+// Visit any unsigned or non-negative signed number, converting it to the given uint type.
+macro_rules! visit_uint_number_fn_impl {
+    ($name:ident : $ty:ty) => {
+        #[allow(unused_comparisons)]
+        fn $name<'scale, 'resolver>(
+            self,
+            value: $ty,
+            _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            if value < 0 {
+                return Err(Error::new(ErrorKind::NumberOutOfRange { value: value.to_string() }));
+            }
+            Ok((value as u128).into())
+        }
+    };
+}
+
+// Decode a uint type from 32 little endian bytes, failing if it doesn't fit.
+macro_rules! uint_from_u256_bytes {
+    ($ty:ty: $limbs:literal, $bytes:expr) => {{
+        let bytes: &[u8; 32] = $bytes;
+        let len = ($limbs * 8).min(32);
+        if bytes[len..].iter().any(|b| *b != 0) {
+            Err(Error::new(ErrorKind::NumberOutOfRange {
+                value: U256::from_little_endian(bytes).to_string(),
+            }))
+        } else {
+            Ok(<$ty>::from_little_endian(&bytes[..len]))
+        }
+    }};
+}
+
+macro_rules! impl_uint_visitor {
+    ($ty:ident: $limbs:literal) => {
+        impl<R: TypeResolver> Visitor for BasicVisitor<$ty, R> {
+            type Error = Error;
+            type Value<'scale, 'resolver> = $ty;
+            type TypeResolver = R;
+
+            visit_uint_number_fn_impl!(visit_u8: u8);
+            visit_uint_number_fn_impl!(visit_u16: u16);
+            visit_uint_number_fn_impl!(visit_u32: u32);
+            visit_uint_number_fn_impl!(visit_u64: u64);
+            visit_uint_number_fn_impl!(visit_u128: u128);
+            visit_uint_number_fn_impl!(visit_i8: i8);
+            visit_uint_number_fn_impl!(visit_i16: i16);
+            visit_uint_number_fn_impl!(visit_i32: i32);
+            visit_uint_number_fn_impl!(visit_i64: i64);
+            visit_uint_number_fn_impl!(visit_i128: i128);
+
+            fn visit_u256<'resolver>(
+                self,
+                value: &[u8; 32],
+                _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+            ) -> Result<Self::Value<'_, 'resolver>, Self::Error> {
+                uint_from_u256_bytes!($ty: $limbs, value)
+            }
+            fn visit_i256<'resolver>(
+                self,
+                value: &[u8; 32],
+                _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+            ) -> Result<Self::Value<'_, 'resolver>, Self::Error> {
+                // The top bit is set for negative numbers, which we can't represent:
+                if value[31] & 0x80 != 0 {
+                    let (abs, _) = (!U256::from_little_endian(value)).overflowing_add(U256::one());
+                    return Err(Error::new(ErrorKind::NumberOutOfRange {
+                        value: format!("-{abs}"),
+                    }));
+                }
+                uint_from_u256_bytes!($ty: $limbs, value)
+            }
+            // In type information, these types are described as a composite wrapping an
+            // array of u64 limbs, least significant first. We accept any number of limbs
+            // so long as the value fits.
+            fn visit_array<'scale, 'resolver>(
+                self,
+                value: &mut Array<'scale, 'resolver, R>,
+                _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                let mut limbs = [0u64; $limbs];
+                let mut idx = 0;
+                while let Some(limb) = value.decode_item(u64::into_visitor::<R>()) {
+                    let limb = limb.map_err(|e| e.at_idx(idx))?;
+                    match limbs.get_mut(idx) {
+                        Some(l) => *l = limb,
+                        None if limb == 0 => {}
+                        None => {
+                            return Err(Error::new(ErrorKind::NumberOutOfRange {
+                                value: format!("u64 limb {limb} at index {idx}"),
+                            }))
+                        }
+                    }
+                    idx += 1;
+                }
+                Ok($ty(limbs))
+            }
+            visit_single_field_composite_tuple_impls!(R);
+        }
+
+        impl IntoVisitor for $ty {
+            type AnyVisitor<R: TypeResolver> = BasicVisitor<$ty, R>;
+            fn into_visitor<R: TypeResolver>() -> Self::AnyVisitor<R> {
+                BasicVisitor { _marker: core::marker::PhantomData }
+            }
+        }
+    };
+}
+impl_uint_visitor!(U128: 2);
+impl_uint_visitor!(U256: 4);
+impl_uint_visitor!(U512: 8);
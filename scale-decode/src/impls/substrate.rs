@@ -0,0 +1,203 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use super::{visit_single_field_composite_tuple_impls, BasicVisitor};
+use crate::{
+    error::{Error, ErrorKind},
+    substrate::{AccountId32, Era, MultiAddress},
+    visitor::{decode_with_visitor, types::Variant, DecodeAsTypeResult, Visitor},
+    IntoVisitor,
+};
+use alloc::{format, string::ToString, vec};
+use scale_type_resolver::TypeResolver;
+use sp_arithmetic::{PerThing, Perbill, Percent, Permill};
+
+impl<R: TypeResolver> Visitor for BasicVisitor<AccountId32, R> {
+    type Error = Error;
+    type Value<'scale, 'resolver> = AccountId32;
+    type TypeResolver = R;
+
+    fn unchecked_decode_as_type<'scale, 'resolver>(
+        self,
+        input: &mut &'scale [u8],
+        type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+        types: &'resolver Self::TypeResolver,
+    ) -> DecodeAsTypeResult<Self, Result<Self::Value<'scale, 'resolver>, Self::Error>> {
+        let res =
+            decode_with_visitor(input, type_id, types, <[u8; 32]>::into_visitor()).map(AccountId32);
+        DecodeAsTypeResult::Decoded(res)
+    }
+}
+
+impl IntoVisitor for AccountId32 {
+    type AnyVisitor<R: TypeResolver> = BasicVisitor<AccountId32, R>;
+    fn into_visitor<R: TypeResolver>() -> Self::AnyVisitor<R> {
+        BasicVisitor { _marker: core::marker::PhantomData }
+    }
+}
+
+impl<A, I, R> Visitor for BasicVisitor<MultiAddress<A, I>, R>
+where
+    A: IntoVisitor,
+    I: IntoVisitor,
+    R: TypeResolver,
+{
+    type Error = Error;
+    type Value<'scale, 'resolver> = MultiAddress<A, I>;
+    type TypeResolver = R;
+
+    fn visit_variant<'scale, 'resolver>(
+        self,
+        value: &mut Variant<'scale, 'resolver, R>,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        // Decode the single field in the variant into the given type.
+        fn single_field<'scale, 'resolver, T: IntoVisitor, R: TypeResolver>(
+            value: &mut Variant<'scale, 'resolver, R>,
+        ) -> Result<T, Error> {
+            let name = value.name();
+            let fields = value.fields();
+            if fields.remaining() != 1 {
+                return Err(Error::new(ErrorKind::WrongLength {
+                    actual_len: fields.remaining(),
+                    expected_len: 1,
+                })
+                .at_variant(name.to_string()));
+            }
+            fields
+                .decode_item(T::into_visitor::<R>())
+                .expect("checked for 1 field already so should be ok")
+                .map_err(|e| e.at_variant(name.to_string()))
+        }
+
+        match value.name() {
+            "Id" => single_field(value).map(MultiAddress::Id),
+            "Index" => single_field(value).map(MultiAddress::Index),
+            "Raw" => single_field(value).map(MultiAddress::Raw),
+            "Address32" => single_field(value).map(MultiAddress::Address32),
+            "Address20" => single_field(value).map(MultiAddress::Address20),
+            _ => Err(Error::cannot_find_variant(
+                value.name(),
+                value.index(),
+                vec!["Id", "Index", "Raw", "Address32", "Address20"],
+            )),
+        }
+    }
+    visit_single_field_composite_tuple_impls!(R);
+}
+
+impl<A: IntoVisitor, I: IntoVisitor> IntoVisitor for MultiAddress<A, I> {
+    type AnyVisitor<R: TypeResolver> = BasicVisitor<MultiAddress<A, I>, R>;
+    fn into_visitor<R: TypeResolver>() -> Self::AnyVisitor<R> {
+        BasicVisitor { _marker: core::marker::PhantomData }
+    }
+}
+
+impl<R: TypeResolver> Visitor for BasicVisitor<Era, R> {
+    type Error = Error;
+    type Value<'scale, 'resolver> = Era;
+    type TypeResolver = R;
+
+    // In metadata, eras are described as a variant with an "Immortal" variant at index 0,
+    // and "Mortal1" to "Mortal255" variants which each contain a single u8. The variant
+    // index and this u8 together form the little endian u16 that encodes the period and phase.
+    fn visit_variant<'scale, 'resolver>(
+        self,
+        value: &mut Variant<'scale, 'resolver, R>,
+        _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        if value.index() == 0 {
+            return Ok(Era::Immortal);
+        }
+
+        let name = value.name();
+        let fields = value.fields();
+        if fields.remaining() != 1 {
+            return Err(Error::new(ErrorKind::WrongLength {
+                actual_len: fields.remaining(),
+                expected_len: 1,
+            })
+            .at_variant(name.to_string()));
+        }
+        let second: u8 = fields
+            .decode_item(u8::into_visitor::<R>())
+            .expect("checked for 1 field already so should be ok")
+            .map_err(|e| e.at_variant(name.to_string()))?;
+
+        let encoded = value.index() as u64 + ((second as u64) << 8);
+        let period = 2 << (encoded % (1 << 4));
+        let quantize_factor = (period >> 12).max(1);
+        let phase = (encoded >> 4) * quantize_factor;
+
+        if period >= 4 && phase < period {
+            Ok(Era::Mortal(period, phase))
+        } else {
+            Err(Error::custom_string(format!(
+                "Invalid era: period {period} and phase {phase} are not valid"
+            )))
+        }
+    }
+    visit_single_field_composite_tuple_impls!(R);
+}
+
+impl IntoVisitor for Era {
+    type AnyVisitor<R: TypeResolver> = BasicVisitor<Era, R>;
+    fn into_visitor<R: TypeResolver>() -> Self::AnyVisitor<R> {
+        BasicVisitor { _marker: core::marker::PhantomData }
+    }
+}
+
+// Per-things are described as a composite containing a single number, which must
+// be no greater than the accuracy of the type.
+macro_rules! impl_per_thing {
+    ($ty:ident) => {
+        impl<R: TypeResolver> Visitor for BasicVisitor<$ty, R> {
+            type Error = Error;
+            type Value<'scale, 'resolver> = $ty;
+            type TypeResolver = R;
+
+            fn unchecked_decode_as_type<'scale, 'resolver>(
+                self,
+                input: &mut &'scale [u8],
+                type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+                types: &'resolver Self::TypeResolver,
+            ) -> DecodeAsTypeResult<Self, Result<Self::Value<'scale, 'resolver>, Self::Error>> {
+                let res = decode_with_visitor(
+                    input,
+                    type_id,
+                    types,
+                    <<$ty as PerThing>::Inner>::into_visitor(),
+                )
+                .and_then(|parts| {
+                    if parts > <$ty>::ACCURACY {
+                        Err(Error::new(ErrorKind::NumberOutOfRange { value: parts.to_string() }))
+                    } else {
+                        Ok(<$ty>::from_parts(parts))
+                    }
+                });
+                DecodeAsTypeResult::Decoded(res)
+            }
+        }
+
+        impl IntoVisitor for $ty {
+            type AnyVisitor<R: TypeResolver> = BasicVisitor<$ty, R>;
+            fn into_visitor<R: TypeResolver>() -> Self::AnyVisitor<R> {
+                BasicVisitor { _marker: core::marker::PhantomData }
+            }
+        }
+    };
+}
+impl_per_thing!(Perbill);
+impl_per_thing!(Permill);
+impl_per_thing!(Percent);
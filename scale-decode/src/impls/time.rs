@@ -0,0 +1,36 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::timestamp::{FromTimestamp, TimestampUnit, TimestampVisitor};
+use crate::IntoVisitor;
+use scale_type_resolver::TypeResolver;
+use time::OffsetDateTime;
+
+impl FromTimestamp for OffsetDateTime {
+    fn from_timestamp(value: i64, unit: TimestampUnit) -> Option<Self> {
+        match unit {
+            TimestampUnit::Seconds => OffsetDateTime::from_unix_timestamp(value).ok(),
+            TimestampUnit::Millis => {
+                OffsetDateTime::from_unix_timestamp_nanos(value as i128 * 1_000_000).ok()
+            }
+        }
+    }
+}
+
+impl IntoVisitor for OffsetDateTime {
+    type AnyVisitor<R: TypeResolver> = TimestampVisitor<OffsetDateTime, R>;
+    fn into_visitor<R: TypeResolver>() -> Self::AnyVisitor<R> {
+        TimestampVisitor::new(TimestampUnit::Millis)
+    }
+}
@@ -0,0 +1,337 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decode any SCALE encoded value straight into a [`serde_json::Value`].
+//!
+//! Values are mapped to JSON as follows:
+//!
+//! - Booleans, strings and chars map to JSON booleans and strings.
+//! - Numbers map to JSON numbers, except for 128 bit numbers which don't fit into a 64 bit one,
+//!   and 256 bit numbers, which become strings (hex encoded and big endian for 256 bit numbers).
+//! - Sequences, arrays, tuples, bit sequences and composites with unnamed fields map to JSON arrays.
+//! - Composites with named fields map to JSON objects.
+//! - Variants map to `{ "VariantName": fields }`, where the fields are mapped like a composite.
+//!   Variants with no fields are mapped to the string `"VariantName"`.
+//!
+//! ```rust
+//! use codec::Encode;
+//! use scale_decode::json::to_json;
+//! use scale_info::{PortableRegistry, TypeInfo};
+//!
+//! #[derive(Encode, TypeInfo)]
+//! enum Foo {
+//!     Bar { a: u8, b: (bool, String) },
+//! }
+//!
+//! let m = scale_info::MetaType::new::<Foo>();
+//! let mut types = scale_info::Registry::new();
+//! let type_id = types.register_type(&m).id;
+//! let types: PortableRegistry = types.into();
+//!
+//! let encoded = Foo::Bar { a: 1, b: (true, "hi".to_string()) }.encode();
+//! let value = to_json(&mut &*encoded, type_id, &types).unwrap();
+//! assert_eq!(value, serde_json::json!({ "Bar": { "a": 1, "b": [true, "hi"] } }));
+//! ```
+
+use crate::{
+    error::{Error, ErrorKind},
+    visitor::{
+        decode_with_visitor,
+        types::{Array, BitSequence, Composite, Sequence, Str, Tuple, Variant},
+        DecodeItemIterator, Detach, TypeIdFor, Visitor,
+    },
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::marker::PhantomData;
+use scale_type_resolver::TypeResolver;
+use serde_json::{Map, Number, Value};
+
+/// Decode some bytes, whose structure is given by the type ID and type resolver provided, into a
+/// [`serde_json::Value`]. The provided pointer to the bytes is moved forwards past the decoded
+/// value.
+pub fn to_json<R: TypeResolver>(
+    input: &mut &[u8],
+    type_id: R::TypeId,
+    types: &R,
+) -> Result<Value, Error> {
+    decode_with_visitor(input, type_id, types, JsonVisitor::new())
+}
+
+/// A [`Visitor`] which decodes any value into a [`serde_json::Value`]. This is what the
+/// [`crate::IntoVisitor`] impl for [`serde_json::Value`] hands back.
+pub struct JsonVisitor<R>(PhantomData<R>);
+
+impl<R> JsonVisitor<R> {
+    /// Construct a new [`JsonVisitor`].
+    pub fn new() -> Self {
+        JsonVisitor(PhantomData)
+    }
+}
+
+impl<R> Default for JsonVisitor<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+macro_rules! visit_number_fn_impl {
+    ($name:ident : $ty:ty) => {
+        fn $name<'scale, 'resolver>(
+            self,
+            value: $ty,
+            _type_id: TypeIdFor<Self>,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            Ok(Value::Number(value.into()))
+        }
+    };
+}
+
+// Decode each item in some container into a JSON array.
+fn decode_items<'scale, 'resolver, R, D>(items: &mut D) -> Result<Value, Error>
+where
+    R: TypeResolver,
+    D: DecodeItemIterator<'scale, 'resolver, R>,
+{
+    let mut idx = 0;
+    let mut vals = Vec::new();
+    while let Some(val) = items.decode_item(JsonVisitor::new()) {
+        vals.push(val.map_err(|e| e.at_idx(idx))?);
+        idx += 1;
+    }
+    Ok(Value::Array(vals))
+}
+
+// Encode a little endian 256 bit number as a big endian hex string.
+fn hex_string(value: &[u8; 32]) -> Value {
+    let hex: String = value.iter().rev().map(|b| format!("{b:02x}")).collect();
+    Value::String(format!("0x{hex}"))
+}
+
+impl<R: TypeResolver> Visitor for JsonVisitor<R> {
+    type Value<'scale, 'resolver> = Value;
+    type Error = Error;
+    type TypeResolver = R;
+
+    fn visit_bool<'scale, 'resolver>(
+        self,
+        value: bool,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        Ok(Value::Bool(value))
+    }
+    fn visit_char<'scale, 'resolver>(
+        self,
+        value: char,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        Ok(Value::String(value.to_string()))
+    }
+    fn visit_str<'scale, 'resolver>(
+        self,
+        value: &mut Str<'scale>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        Ok(Value::String(value.as_str()?.to_string()))
+    }
+
+    visit_number_fn_impl!(visit_u8: u8);
+    visit_number_fn_impl!(visit_u16: u16);
+    visit_number_fn_impl!(visit_u32: u32);
+    visit_number_fn_impl!(visit_u64: u64);
+    visit_number_fn_impl!(visit_i8: i8);
+    visit_number_fn_impl!(visit_i16: i16);
+    visit_number_fn_impl!(visit_i32: i32);
+    visit_number_fn_impl!(visit_i64: i64);
+
+    fn visit_u128<'scale, 'resolver>(
+        self,
+        value: u128,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        match u64::try_from(value) {
+            Ok(n) => Ok(Value::Number(Number::from(n))),
+            Err(_) => Ok(Value::String(value.to_string())),
+        }
+    }
+    fn visit_i128<'scale, 'resolver>(
+        self,
+        value: i128,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        match i64::try_from(value) {
+            Ok(n) => Ok(Value::Number(Number::from(n))),
+            Err(_) => Ok(Value::String(value.to_string())),
+        }
+    }
+    fn visit_u256<'resolver>(
+        self,
+        value: &[u8; 32],
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'_, 'resolver>, Self::Error> {
+        Ok(hex_string(value))
+    }
+    fn visit_i256<'resolver>(
+        self,
+        value: &[u8; 32],
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'_, 'resolver>, Self::Error> {
+        Ok(hex_string(value))
+    }
+
+    fn visit_sequence<'scale, 'resolver>(
+        self,
+        value: &mut Sequence<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        decode_items(value)
+    }
+    fn visit_array<'scale, 'resolver>(
+        self,
+        value: &mut Array<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        decode_items(value)
+    }
+    fn visit_tuple<'scale, 'resolver>(
+        self,
+        value: &mut Tuple<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        decode_items(value)
+    }
+    fn visit_composite<'scale, 'resolver>(
+        self,
+        value: &mut Composite<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        if value.has_unnamed_fields() {
+            return decode_items(value);
+        }
+        let mut map = Map::new();
+        while let Some(name) = value.peek_name() {
+            let val = value
+                .decode_item(JsonVisitor::new())
+                .expect("a field exists, since it has a name")
+                .map_err(|e| e.at_field(name.to_string()))?;
+            map.insert(name.to_string(), val);
+        }
+        Ok(Value::Object(map))
+    }
+    fn visit_variant<'scale, 'resolver>(
+        self,
+        value: &mut Variant<'scale, 'resolver, R>,
+        type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let name = value.name().to_string();
+        let fields = value.fields();
+        if fields.remaining() == 0 {
+            return Ok(Value::String(name));
+        }
+        let fields = self.visit_composite(fields, type_id)?;
+        Ok(Value::Object(Map::from_iter([(name, fields)])))
+    }
+    fn visit_bitsequence<'scale, 'resolver>(
+        self,
+        value: &mut BitSequence<'scale>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        value
+            .decode()?
+            .map(|bit| bit.map(Value::Bool))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array)
+            .map_err(|e| Error::new(ErrorKind::VisitorDecodeError(e.into())))
+    }
+}
+
+impl<R: TypeResolver> Detach for JsonVisitor<R> {
+    type Owned = Value;
+    fn detach(value: Value) -> Value {
+        value
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+    use codec::{Compact, Encode};
+    use scale_info::TypeInfo;
+    use serde_json::json;
+
+    fn encode_to_json<T: Encode + TypeInfo + 'static>(value: T) -> Value {
+        let m = scale_info::MetaType::new::<T>();
+        let mut types = scale_info::Registry::new();
+        let type_id = types.register_type(&m).id;
+        let types: scale_info::PortableRegistry = types.into();
+
+        let encoded = value.encode();
+        let cursor = &mut &*encoded;
+        let json = to_json(cursor, type_id, &types).unwrap();
+        assert!(cursor.is_empty(), "all bytes should be consumed");
+        json
+    }
+
+    #[test]
+    fn primitives_to_json() {
+        assert_eq!(encode_to_json(true), json!(true));
+        assert_eq!(encode_to_json(String::from("hello")), json!("hello"));
+        assert_eq!(encode_to_json(123u8), json!(123));
+        assert_eq!(encode_to_json(-123i64), json!(-123));
+        assert_eq!(encode_to_json(Compact(123u128)), json!(123));
+        assert_eq!(encode_to_json(u128::MAX), json!(u128::MAX.to_string()));
+        assert_eq!(encode_to_json(i128::MIN), json!(i128::MIN.to_string()));
+    }
+
+    #[test]
+    fn collections_to_json() {
+        assert_eq!(encode_to_json(vec![1u8, 2, 3]), json!([1, 2, 3]));
+        assert_eq!(encode_to_json([true, false]), json!([true, false]));
+        assert_eq!(encode_to_json((1u8, "hi")), json!([1, "hi"]));
+
+        let bits: scale_bits::Bits = [true, false, true].into_iter().collect();
+        assert_eq!(encode_to_json(bits), json!([true, false, true]));
+    }
+
+    #[test]
+    fn composites_and_variants_to_json() {
+        #[derive(Encode, TypeInfo)]
+        struct Named {
+            a: u8,
+            b: Vec<bool>,
+        }
+        #[derive(Encode, TypeInfo)]
+        struct Unnamed(u8, bool);
+        #[derive(Encode, TypeInfo)]
+        enum Foo {
+            A,
+            B(u8),
+            C { c: Unnamed },
+        }
+
+        assert_eq!(encode_to_json(Named { a: 1, b: vec![true] }), json!({ "a": 1, "b": [true] }));
+        assert_eq!(encode_to_json(Unnamed(1, true)), json!([1, true]));
+        assert_eq!(encode_to_json(Foo::A), json!("A"));
+        assert_eq!(encode_to_json(Foo::B(1)), json!({ "B": [1] }));
+        assert_eq!(
+            encode_to_json(Foo::C { c: Unnamed(2, false) }),
+            json!({ "C": { "c": [2, false] } })
+        );
+    }
+}
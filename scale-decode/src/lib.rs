@@ -68,7 +68,8 @@ where
 {
     let (type_id, types) = get_type_info::<A>();
     let a_bytes = a.encode();
-    let new_b = B::decode_as_type(&mut &*a_bytes, type_id, &types).unwrap();
+    // `decode_as_type_exact` takes a plain slice and errors if any bytes are left over.
+    let new_b = B::decode_as_type_exact(&a_bytes, type_id, &types).unwrap();
     assert_eq!(b, new_b);
 }
 
@@ -138,10 +139,42 @@ for efficient type based decoding.
 #![deny(missing_docs)]
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 mod impls;
 
+#[cfg(feature = "bumpalo")]
+pub mod arena;
+pub mod dynamic;
 pub mod error;
+#[cfg(feature = "events")]
+pub mod events;
+#[cfg(feature = "extrinsic")]
+pub mod extrinsic;
+pub mod fingerprint;
+#[cfg(feature = "fixed-point")]
+pub mod fixed_point;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "frame-metadata")]
+pub mod metadata;
+pub mod metrics;
+pub mod multi_decoder;
+pub mod numeric;
+#[cfg(feature = "std")]
+pub mod recursion;
+pub mod resolver;
+pub mod shape;
+pub mod storage_key;
+#[cfg(feature = "substrate-types")]
+pub mod substrate;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+#[cfg(any(feature = "chrono", feature = "time"))]
+pub mod timestamp;
+pub mod validate;
+pub mod value;
 pub mod visitor;
 
 pub use crate::error::Error;
@@ -152,13 +185,58 @@ pub use visitor::Visitor;
 
 // This is exported for generated derive code to use, to be compatible with std or no-std as needed.
 #[doc(hidden)]
-pub use alloc::{collections::BTreeMap, string::ToString, vec};
+pub use alloc::{
+    borrow::Cow,
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec,
+};
+// Generated derive code refers to these via the crate path rather than via `::core`, so that
+// everything it needs is found through `scale_decode` wherever that lives.
+#[doc(hidden)]
+pub use core::{
+    convert::{From, TryFrom},
+    default::Default,
+    marker::PhantomData,
+    option::Option::{self, None, Some},
+    result::Result::{self, Err, Ok},
+};
 
 /// Re-exports of external crates.
 pub mod ext {
+    #[cfg(feature = "arrayvec")]
+    pub use arrayvec;
+    #[cfg(feature = "bitvec")]
+    pub use bitvec;
+    #[cfg(feature = "bumpalo")]
+    pub use bumpalo;
+    #[cfg(feature = "bytes")]
+    pub use bytes;
+    #[cfg(feature = "chrono")]
+    pub use chrono;
+    #[cfg(feature = "either")]
+    pub use either;
+    #[cfg(feature = "frame-metadata")]
+    pub use frame_metadata;
+    #[cfg(feature = "heapless")]
+    pub use heapless;
     #[cfg(feature = "primitive-types")]
     pub use primitive_types;
+    #[cfg(feature = "fixed-point")]
+    pub use rust_decimal;
+    #[cfg(feature = "codec-as-type")]
+    pub use scale_encode;
     pub use scale_type_resolver;
+    #[cfg(feature = "json")]
+    pub use serde_json;
+    #[cfg(feature = "smol_str")]
+    pub use smol_str;
+    #[cfg(feature = "substrate-types")]
+    pub use sp_arithmetic;
+    #[cfg(feature = "time")]
+    pub use time;
+    #[cfg(feature = "uuid")]
+    pub use uuid;
 }
 
 /// This trait is implemented for any type `T` where `T` implements [`IntoVisitor`] and the errors returned
@@ -168,6 +246,10 @@ pub trait DecodeAsType: Sized + IntoVisitor {
     /// Given some input bytes, a `type_id`, and type registry, attempt to decode said bytes into
     /// `Self`. Implementations should modify the `&mut` reference to the bytes such that any bytes
     /// not used in the course of decoding are still pointed to after decoding is complete.
+    ///
+    /// This does not require that all of the input bytes are used; any that remain are left for
+    /// the caller to handle. Use [`DecodeAsType::decode_as_type_exact()`] if all of the input is
+    /// expected to be consumed.
     fn decode_as_type<R: TypeResolver>(
         input: &mut &[u8],
         type_id: R::TypeId,
@@ -176,6 +258,22 @@ pub trait DecodeAsType: Sized + IntoVisitor {
         Self::decode_as_type_maybe_compact(input, type_id, types, false)
     }
 
+    /// Given some input bytes, a `type_id`, and type registry, attempt to decode said bytes into
+    /// `Self`, returning an [`error::ErrorKind::LeftoverBytes`] error if any of the input bytes
+    /// were not used in the course of decoding.
+    fn decode_as_type_exact<R: TypeResolver>(
+        input: &[u8],
+        type_id: R::TypeId,
+        types: &R,
+    ) -> Result<Self, Error> {
+        let input = &mut &*input;
+        let val = Self::decode_as_type(input, type_id, types)?;
+        if !input.is_empty() {
+            return Err(Error::new(error::ErrorKind::LeftoverBytes { count: input.len() }));
+        }
+        Ok(val)
+    }
+
     /// Given some input bytes, a `type_id`, and type registry, attempt to decode said bytes into
     /// `Self`. Implementations should modify the `&mut` reference to the bytes such that any bytes
     /// not used in the course of decoding are still pointed to after decoding is complete.
@@ -197,14 +295,18 @@ impl<T: Sized + IntoVisitor> DecodeAsType for T {
         types: &R,
         is_compact: bool,
     ) -> Result<Self, Error> {
+        #[cfg(feature = "type-id-path")]
+        let id = type_id.clone();
         let res = visitor::decode_with_visitor_maybe_compact(
             input,
             type_id,
             types,
             T::into_visitor::<R>(),
             is_compact,
-        )?;
-        Ok(res)
+        );
+        #[cfg(feature = "type-id-path")]
+        let res = res.map_err(|e| e.at_type_id(id));
+        res
     }
 }
 
@@ -218,6 +320,457 @@ pub trait DecodeAsFields: Sized {
         fields: &mut dyn FieldIter<'resolver, R::TypeId>,
         types: &'resolver R,
     ) -> Result<Self, Error>;
+
+    /// Given some bytes and some fields denoting their structure, attempt to decode, returning an
+    /// [`error::ErrorKind::LeftoverBytes`] error if any of the input bytes were not used in the
+    /// course of decoding. This is the [`DecodeAsFields`] equivalent of
+    /// [`DecodeAsType::decode_as_type_exact()`].
+    fn decode_as_fields_exact<'resolver, R: TypeResolver>(
+        input: &[u8],
+        fields: &mut dyn FieldIter<'resolver, R::TypeId>,
+        types: &'resolver R,
+    ) -> Result<Self, Error> {
+        let input = &mut &*input;
+        let val = Self::decode_as_fields(input, fields, types)?;
+        if !input.is_empty() {
+            return Err(Error::new(error::ErrorKind::LeftoverBytes { count: input.len() }));
+        }
+        Ok(val)
+    }
+}
+
+/// Decode some bytes, whose structure is given by the fields provided, into any type `T` whose
+/// [`Visitor`] knows how to handle composite values. Any fields not consumed by the visitor are
+/// skipped over, and `input` is updated to point to the bytes after the last field.
+///
+/// This is useful when implementing [`DecodeAsFields`] manually; the [`macro@DecodeAsType`]
+/// macro uses this to implement [`DecodeAsFields`] on structs. See also
+/// [`visitor::decode_fields_with_visitor()`], which this delegates to.
+///
+/// ```rust
+/// use scale_decode::{decode_composite_as_fields, DecodeAsFields, Error, FieldIter, TypeResolver};
+///
+/// #[derive(scale_decode::DecodeAsType)]
+/// struct Foo {
+///     a: u8,
+///     b: bool,
+/// }
+///
+/// struct Wrapper(Foo);
+///
+/// impl DecodeAsFields for Wrapper {
+///     fn decode_as_fields<'resolver, R: TypeResolver>(
+///         input: &mut &[u8],
+///         fields: &mut dyn FieldIter<'resolver, R::TypeId>,
+///         types: &'resolver R,
+///     ) -> Result<Self, Error> {
+///         decode_composite_as_fields::<Foo, R>(input, fields, types).map(Wrapper)
+///     }
+/// }
+/// ```
+pub fn decode_composite_as_fields<'resolver, T: IntoVisitor, R: TypeResolver>(
+    input: &mut &[u8],
+    fields: &mut dyn FieldIter<'resolver, R::TypeId>,
+    types: &'resolver R,
+) -> Result<T, Error> {
+    visitor::decode_fields_with_visitor(input, fields, types, T::into_visitor::<R>())
+}
+
+/// Implement [`IntoVisitor`] (and thus [`DecodeAsType`]) and [`DecodeAsFields`] for newtypes
+/// which wrap a single value, without needing the [`macro@DecodeAsType`] derive macro. The
+/// generated impls are the same as those that `#[decode_as_type(transparent)]` would generate;
+/// the newtype is decoded exactly as the value that it wraps would be.
+///
+/// Each newtype should be given with the same visibility that it's declared with, for instance
+/// `pub AccountIndex(u64)` for a `pub struct AccountIndex(u64)`.
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_decode::DecodeAsType;
+///
+/// #[derive(Debug, PartialEq)]
+/// pub struct AccountIndex(u64);
+/// #[derive(Debug, PartialEq)]
+/// struct Name(String);
+///
+/// scale_decode::impl_decode_as_type_for_newtype!(pub AccountIndex(u64), Name(String));
+///
+/// let m = scale_info::MetaType::new::<(u32, String)>();
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&m).id;
+/// let types: scale_info::PortableRegistry = types.into();
+///
+/// let encoded = (123u32, "Alice").encode();
+/// let (index, name) =
+///     <(AccountIndex, Name)>::decode_as_type(&mut &*encoded, type_id, &types).unwrap();
+/// assert_eq!(index, AccountIndex(123));
+/// assert_eq!(name, Name("Alice".to_string()));
+/// ```
+#[macro_export]
+macro_rules! impl_decode_as_type_for_newtype {
+    ($($vis:vis $ty:ident($inner:ty)),+ $(,)?) => {$(
+        const _: () = {
+            $vis struct Visitor<R>(::core::marker::PhantomData<R>);
+
+            impl $crate::IntoVisitor for $ty {
+                type AnyVisitor<R: $crate::TypeResolver> = Visitor<R>;
+                fn into_visitor<R: $crate::TypeResolver>() -> Self::AnyVisitor<R> {
+                    Visitor(::core::marker::PhantomData)
+                }
+            }
+
+            impl<R: $crate::TypeResolver> $crate::Visitor for Visitor<R> {
+                type Error = $crate::Error;
+                type Value<'scale, 'resolver> = $ty;
+                type TypeResolver = R;
+
+                // Decode exactly as the inner type would, and wrap the result.
+                fn unchecked_decode_as_type<'scale, 'resolver>(
+                    self,
+                    input: &mut &'scale [u8],
+                    type_id: R::TypeId,
+                    types: &'resolver R,
+                ) -> $crate::visitor::DecodeAsTypeResult<
+                    Self,
+                    Result<Self::Value<'scale, 'resolver>, Self::Error>,
+                > {
+                    let res = $crate::visitor::decode_with_visitor(
+                        input,
+                        type_id,
+                        types,
+                        <$inner as $crate::IntoVisitor>::into_visitor::<R>(),
+                    )
+                    .map($ty);
+                    $crate::visitor::DecodeAsTypeResult::Decoded(res)
+                }
+                // Used when decoding via DecodeAsFields, which hands us the fields directly.
+                fn visit_composite<'scale, 'resolver>(
+                    self,
+                    value: &mut $crate::visitor::types::Composite<'scale, 'resolver, R>,
+                    _type_id: R::TypeId,
+                ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                    if value.remaining() != 1 {
+                        return Err($crate::Error::new($crate::error::ErrorKind::WrongLength {
+                            actual_len: value.remaining(),
+                            expected_len: 1,
+                        }));
+                    }
+                    value
+                        .decode_item(<$inner as $crate::IntoVisitor>::into_visitor::<R>())
+                        .expect("field count checked above")
+                        .map($ty)
+                        .map_err(|e| e.at_idx(0))
+                }
+            }
+
+            impl $crate::DecodeAsFields for $ty {
+                fn decode_as_fields<'resolver, R: $crate::TypeResolver>(
+                    input: &mut &[u8],
+                    fields: &mut dyn $crate::FieldIter<'resolver, R::TypeId>,
+                    types: &'resolver R,
+                ) -> Result<Self, $crate::Error> {
+                    $crate::decode_composite_as_fields::<Self, R>(input, fields, types)
+                }
+            }
+        };
+    )+};
+}
+
+/// Implement the conversions needed to use some custom error type as the [`Visitor::Error`] of a
+/// visitor. The error type is given along with the variant which holds a [`visitor::DecodeError`],
+/// for instance `MyError::Decode` for a `MyError::Decode(DecodeError)` variant. This generates:
+///
+/// - `From<DecodeError>`, which every [`Visitor::Error`] needs, and `From<Infallible>`.
+/// - `From<MyError> for scale_decode::Error`, so that the visitor can be wrapped in a
+///   [`visitor::VisitorWithCrateError`] and used to implement [`IntoVisitor`]. Decode errors are
+///   converted as normal, and any other errors are handed to [`Error::custom_error()`], and so
+///   the error type must implement [`core::error::Error`], [`Send`] and [`Sync`].
+/// - `at_idx`, `at_field` and `at_variant` methods on the error type, which convert it into an
+///   [`Error`] with the given location added to its context.
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_decode::visitor::{DecodeError, TypeIdFor};
+/// use scale_decode::Visitor;
+/// use scale_info::PortableRegistry;
+///
+/// #[derive(Debug, thiserror::Error)]
+/// enum MyError {
+///     #[error("{0}")]
+///     Decode(DecodeError),
+///     #[error("the number {0} is too big")]
+///     TooBig(u8),
+/// }
+///
+/// scale_decode::impl_visitor_error!(MyError::Decode);
+///
+/// struct SmallNumber;
+/// impl Visitor for SmallNumber {
+///     type Value<'scale, 'resolver> = u8;
+///     type Error = MyError;
+///     type TypeResolver = PortableRegistry;
+///
+///     fn visit_u8<'scale, 'resolver>(
+///         self,
+///         value: u8,
+///         _type_id: TypeIdFor<Self>,
+///     ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+///         if value > 10 { Err(MyError::TooBig(value)) } else { Ok(value) }
+///     }
+/// }
+///
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&scale_info::MetaType::new::<u8>()).id;
+/// let types: PortableRegistry = types.into();
+///
+/// let encoded = 100u8.encode();
+/// let err = scale_decode::visitor::decode_with_visitor(&mut &*encoded, type_id, &types, SmallNumber)
+///     .unwrap_err();
+/// assert!(matches!(err, MyError::TooBig(100)));
+///
+/// // Convert the error into a `scale_decode::Error`, noting where it happened:
+/// let err = err.at_field("foo");
+/// assert_eq!(err.context().path().to_string(), "foo");
+/// ```
+#[macro_export]
+macro_rules! impl_visitor_error {
+    ($($ty:ident :: $variant:ident),+ $(,)?) => {$(
+        impl ::core::convert::From<$crate::visitor::DecodeError> for $ty {
+            fn from(err: $crate::visitor::DecodeError) -> Self {
+                $ty::$variant(err)
+            }
+        }
+
+        impl ::core::convert::From<::core::convert::Infallible> for $ty {
+            fn from(err: ::core::convert::Infallible) -> Self {
+                match err {}
+            }
+        }
+
+        impl ::core::convert::From<$ty> for $crate::Error {
+            fn from(err: $ty) -> Self {
+                #[allow(unreachable_patterns)]
+                match err {
+                    $ty::$variant(err) => $crate::Error::from(err),
+                    err => $crate::Error::custom_error(err),
+                }
+            }
+        }
+
+        impl $ty {
+            /// Convert this into a `scale_decode::Error` at the given index.
+            #[allow(dead_code)]
+            pub fn at_idx(self, idx: usize) -> $crate::Error {
+                $crate::Error::from(self).at_idx(idx)
+            }
+            /// Convert this into a `scale_decode::Error` at the given field.
+            #[allow(dead_code)]
+            pub fn at_field(
+                self,
+                field: impl ::core::convert::Into<$crate::Cow<'static, str>>,
+            ) -> $crate::Error {
+                $crate::Error::from(self).at_field(field)
+            }
+            /// Convert this into a `scale_decode::Error` at the given variant.
+            #[allow(dead_code)]
+            pub fn at_variant(
+                self,
+                variant: impl ::core::convert::Into<$crate::Cow<'static, str>>,
+            ) -> $crate::Error {
+                $crate::Error::from(self).at_variant(variant)
+            }
+        }
+    )+};
+}
+
+/// A type which can be decoded from any SCALE encoded value, ignoring it entirely. This is useful
+/// for decoding a composite type while discarding some of its fields, without needing to know
+/// what types they are.
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_decode::{DecodeAsType, Ignored};
+///
+/// #[derive(Encode, scale_info::TypeInfo)]
+/// struct Foo {
+///     a: u8,
+///     b: Vec<(String, bool)>,
+/// }
+///
+/// #[derive(DecodeAsType, Debug, PartialEq)]
+/// struct FooTarget {
+///     a: u8,
+///     b: Ignored,
+/// }
+///
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&scale_info::MetaType::new::<Foo>()).id;
+/// let types: scale_info::PortableRegistry = types.into();
+///
+/// let encoded = Foo { a: 1, b: vec![("hi".to_string(), true)] }.encode();
+/// let decoded = FooTarget::decode_as_type(&mut &*encoded, type_id, &types).unwrap();
+/// assert_eq!(decoded, FooTarget { a: 1, b: Ignored });
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ignored;
+
+/// An [`Option`] which is decoded from any enum with the same shape as an [`Option`], regardless
+/// of the names of its variants. The [`IntoVisitor`] impl for [`Option`] expects variants named
+/// `None` and `Some`, whereas this expects a variant at index 0 with no fields, which decodes to
+/// [`None`], or a variant at index 1 with one field, which decodes to [`Some`].
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_decode::{DecodeAsType, ShapeOption};
+///
+/// #[derive(Encode, scale_info::TypeInfo)]
+/// enum Maybe {
+///     Nothing,
+///     Just(u8),
+/// }
+///
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&scale_info::MetaType::new::<Maybe>()).id;
+/// let types: scale_info::PortableRegistry = types.into();
+///
+/// let encoded = Maybe::Just(1).encode();
+/// let decoded = ShapeOption::<u64>::decode_as_type(&mut &*encoded, type_id, &types).unwrap();
+/// assert_eq!(decoded.0, Some(1));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShapeOption<T>(pub Option<T>);
+
+/// A [`Result`] which is decoded from any enum with the same shape as a [`Result`], regardless of
+/// the names of its variants. The [`IntoVisitor`] impl for [`Result`] expects variants named `Ok`
+/// and `Err`, whereas this expects a variant at index 0 with one field, which decodes to [`Ok`],
+/// or a variant at index 1 with one field, which decodes to [`Err`]. See [`ShapeOption`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShapeResult<T, E>(pub Result<T, E>);
+
+/// A number which remembers whether it was compact encoded. This decodes exactly like `T` does,
+/// and so can be used in place of some numeric field in order to re-encode it exactly as it was
+/// given; the [`codec::Encode`] impl compact encodes the value if it was compact encoded.
+///
+/// ```rust
+/// use codec::{Compact, Encode};
+/// use scale_decode::{DecodeAsType, MaybeCompact};
+///
+/// #[derive(Encode, scale_info::TypeInfo)]
+/// struct Foo {
+///     #[codec(compact)]
+///     a: u32,
+///     b: u32,
+/// }
+///
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&scale_info::MetaType::new::<Foo>()).id;
+/// let types: scale_info::PortableRegistry = types.into();
+///
+/// let encoded = Foo { a: 1, b: 2 }.encode();
+/// let (a, b) =
+///     <(MaybeCompact<u32>, MaybeCompact<u32>)>::decode_as_type(&mut &*encoded, type_id, &types)
+///         .unwrap();
+///
+/// assert_eq!(a, MaybeCompact { value: 1, was_compact: true });
+/// assert_eq!(b, MaybeCompact { value: 2, was_compact: false });
+/// assert_eq!((a, b).encode(), encoded);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MaybeCompact<T> {
+    /// The decoded value.
+    pub value: T,
+    /// Was the value compact encoded?
+    pub was_compact: bool,
+}
+
+impl<T> codec::Encode for MaybeCompact<T>
+where
+    T: codec::Encode + Copy,
+    codec::Compact<T>: codec::Encode,
+{
+    fn size_hint(&self) -> usize {
+        if self.was_compact {
+            codec::Compact(self.value).size_hint()
+        } else {
+            self.value.size_hint()
+        }
+    }
+    fn encode_to<W: codec::Output + ?Sized>(&self, dest: &mut W) {
+        if self.was_compact {
+            codec::Compact(self.value).encode_to(dest)
+        } else {
+            self.value.encode_to(dest)
+        }
+    }
+}
+
+/// Some value of type `Dst` which is decoded by first decoding a `Src`, and then converting it
+/// via [`TryFrom`]. Any conversion error is handed back as a custom [`Error`]. This allows types
+/// which don't implement [`IntoVisitor`] (and which you can't implement it on) to be decoded, so
+/// long as they can be converted from something that does. See [`visitor::TryFromVisitor`] to do
+/// the same for a type that you own.
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_decode::{DecodeAsType, DecodeVia};
+///
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&scale_info::MetaType::new::<u32>()).id;
+/// let types: scale_info::PortableRegistry = types.into();
+///
+/// let encoded = 0x1F600u32.encode();
+/// let decoded = DecodeVia::<u32, char>::decode_as_type(&mut &*encoded, type_id, &types).unwrap();
+/// assert_eq!(decoded.into_inner(), '😀');
+///
+/// // Not a valid char:
+/// let encoded = 0xD800u32.encode();
+/// assert!(DecodeVia::<u32, char>::decode_as_type(&mut &*encoded, type_id, &types).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DecodeVia<Src, Dst>(pub Dst, PhantomData<fn() -> Src>);
+
+impl<Src, Dst> DecodeVia<Src, Dst> {
+    /// Wrap some value.
+    pub fn new(value: Dst) -> Self {
+        DecodeVia(value, PhantomData)
+    }
+    /// Return the wrapped value.
+    pub fn into_inner(self) -> Dst {
+        self.0
+    }
+}
+
+/// A tuple which is decoded from the first items of some composite, tuple, array or sequence,
+/// which may have more items than the tuple does. The remaining items are skipped over. For
+/// example, a `PrefixTuple<(A, B)>` can be decoded from an `(A, B, C)`, whereas an `(A, B)`
+/// would fail to decode with an [`error::ErrorKind::WrongLength`] error. This is useful when
+/// only the first few components of some value, like the keys of a storage map, are needed.
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_decode::{DecodeAsType, PrefixTuple};
+///
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&scale_info::MetaType::new::<(u32, bool, String)>()).id;
+/// let types: scale_info::PortableRegistry = types.into();
+///
+/// let encoded = (123u32, true, "hello".to_string()).encode();
+/// let decoded =
+///     PrefixTuple::<(u64, bool)>::decode_as_type(&mut &*encoded, type_id, &types).unwrap();
+/// assert_eq!(decoded.into_inner(), (123, true));
+///
+/// // There need to be at least as many items as there are in the tuple:
+/// let res = PrefixTuple::<(u64, bool, String, u8)>::decode_as_type(&mut &*encoded, type_id, &types);
+/// assert!(res.is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct PrefixTuple<T>(pub T);
+
+impl<T> PrefixTuple<T> {
+    /// Return the wrapped tuple.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
 }
 
 /// This trait can be implemented on any type that has an associated [`Visitor`] responsible for decoding
@@ -307,14 +860,185 @@ pub trait IntoVisitor {
 /// - `#[decode_as_type(crate_path = "::path::to::scale_decode")]`:
 ///   By default, the macro expects `scale_decode` to be a top level dependency,
 ///   available as `::scale_decode`. If this is not the case, you can provide the
-///   crate path here.
+///   crate path here. The generated code reaches everything it needs (including
+///   things from `core` and `alloc`) through this path, so it works when `scale_decode`
+///   is renamed or re-exported from another crate, and no paths to `parity-scale-codec`
+///   or `scale-info` need to be given.
 /// - `#[decode_as_type(trait_bounds = "T: Foo, U::Input: DecodeAsType")]`:
 ///   By default, for each generate type parameter, the macro will add trait bounds such
 ///   that these type parameters must implement `DecodeAsType` too. You can override this
 ///   behaviour and provide your own trait bounds instead using this option.
+/// - `#[decode_as_type(bound_skip)]`:
+///   Only valid on generic type parameters, for example `struct Foo<#[decode_as_type(bound_skip)] M, T>`.
+///   The default trait bounds are added for every type parameter except those annotated with this,
+///   which is useful for marker types that are never decoded into, without needing to use
+///   `trait_bounds` to restate the bounds for every other parameter.
+/// - `#[decode_as_type(expecting = "an XCM v3 MultiLocation")]`:
+///   A human friendly description of the type, which is attached (see
+///   [`crate::error::Context::expected()`]) to any errors that the generated code
+///   produces when the input doesn't line up with the type, such as unknown variants
+///   or mismatched lengths.
+/// - `#[decode_as_type(transparent)]`:
+///   Only valid on structs with a single field (ignoring any skipped fields). The struct
+///   will be decoded exactly as that field would be, rather than only being decoded through
+///   composites and tuples that contain a single field.
+/// - `#[decode_as_type(fields_from_variant = "VariantName")]`:
+///   Only valid on enums. [`DecodeAsFields`] is implemented on structs, but not on enums unless
+///   this is given, in which case the fields handed to [`DecodeAsFields`] are decoded into the
+///   named variant, exactly as that variant's fields would be decoded if it were encountered
+///   while decoding the enum. This is useful when some fields are known to correspond to a single
+///   variant, such as the fields of an event.
+/// - `#[decode_as_type(tag = "type", content = "data")]`:
+///   Only valid on enums. As well as being decoded from variants, the enum can then be decoded
+///   from a composite which names the variant to decode into in its `type` field (which must
+///   decode into a string), and holds a composite of that variant's fields in its `data` field.
+///   The `data` field can be omitted for unit variants. This is useful when enums have been
+///   represented this way in some type information, for instance when bridged from JSON.
+/// - `#[decode_as_type(allow_extra_fields)]`:
+///   Fields are normally decoded by position when the encoded struct or variant has unnamed
+///   fields, which requires the number of fields to line up exactly. With this attribute, any
+///   encoded fields beyond those that we expect are skipped over instead. Named fields are
+///   always looked up by name, and so extra named fields are ignored regardless. This is useful
+///   when decoding values from a newer runtime into older local types, which may lack fields
+///   that were appended since.
+/// - `#[decode_as_type(max_depth = 128)]`:
+///   Return an [`error::ErrorKind::MaxDepthExceeded`] error if a value of the type is nested
+///   inside itself more than this many levels deep, rather than recursing any further. This is
+///   useful for recursive types like `enum Expr { Leaf(u32), Node(Box<Expr>, Box<Expr>) }`,
+///   which could otherwise overflow the stack given deeply nested input. This requires the `std`
+///   feature; see [`recursion`].
+/// - `#[decode_as_type(constructor)]`:
+///   Also generate an inherent `decode_from` method with the same signature as
+///   [`DecodeAsType::decode_as_type()`], so that the type can be decoded without needing to
+///   import the [`DecodeAsType`] trait.
+/// - `#[decode_as_type(shape)]`:
+///   Also implement [`shape::ShapeOf`], describing the fields and variants that the type
+///   expects to be decoded from, so that it can be checked against some type information without
+///   any bytes via [`shape::check_decodable()`], or inspected and displayed by tools via
+///   [`shape::Shape::of()`]. Type parameters are given a `ShapeOf` bound in
+///   place of the usual `DecodeAsType` one, and every field type (or the type given via `as`)
+///   must implement `ShapeOf` too.
+/// - `#[decode_as_type(from_index)]`:
+///   Only valid on enums whose variants have no fields. As well as being decoded from variants
+///   by name, the enum can then be decoded from a bare `u8`, `u16` or `u32` (or compact encoded
+///   version of these) which gives the index of the variant to decode into (see the `index`
+///   attribute below). Without this, decoding such an enum from a number is an error.
+/// - `#[decode_as_type(try_from_u8)]`:
+///   Only valid on enums whose variants have no fields. This generates a `TryFrom<u8>` impl
+///   which converts the index of one of the variants (see the `index` attribute below) into
+///   that variant, returning an [`Error`] for unknown indexes. This is useful when some status
+///   enum has been encoded as a bare number that is decoded by other means.
+/// - `#[decode_as_type(remote = "other_crate::Type")]`:
+///   Declares that the type is a shadow of some type from another crate, with the same fields
+///   or variants. As well as the usual impls for the shadow type, a [`From`] impl is generated
+///   which moves each field of the shadow type into the remote type (whose fields must therefore
+///   be visible). Trait impls can't be given for types from other crates, but the remote type
+///   can then be decoded via the shadow type, using `DecodeVia<Shadow, other_crate::Type>` (see
+///   [`DecodeVia`]), or `#[decode_as_type(as = "Shadow")]` on fields of the remote type.
 /// - `#[decode_as_type(skip)]` (or `#[codec(skip)]`):
 ///   Any fields annotated with this will be skipped when attempting to decode into the
 ///   type, and instead will be populated with their default value (and therefore must
 ///   implement [`core::default::Default`]).
+///   Enum variants can also be annotated with this, in which case they will never be
+///   decoded into, and won't be listed as an expected variant in any errors.
+/// - `#[decode_as_type(rename = "name")]`:
+///   Any named fields or enum variants annotated with this will be decoded from fields or
+///   variants with the given name, rather than their own name. This is useful when the names in
+///   the type information don't line up with Rust naming conventions, or clash with keywords.
+/// - `#[decode_as_type(compact)]`:
+///   Any fields annotated with this will be decoded as if they were compact encoded, even
+///   if the type information doesn't say so. This is useful when the type information has
+///   been synthesized, for instance to decode some hand-rolled byte format. Unlike `skip`, this
+///   isn't read from `#[codec(compact)]`, since the type information already says whether such
+///   fields are compact encoded.
+/// - `#[decode_as_type(as = "u64")]`:
+///   Any fields annotated with this will be decoded as the given type (which must implement
+///   [`IntoVisitor`]), and then converted into the field type via [`TryFrom`] (and so also via
+///   [`From`]). Errors from the conversion are returned via [`Error::custom_error()`], and so
+///   must implement [`core::error::Error`], [`Send`] and [`Sync`]. This is useful when a single
+///   field needs adapting, for instance to decode a `u64` into some custom `Balance` type,
+///   without writing a whole [`Visitor`] by hand.
+/// - `#[decode_as_type(validate = "path::to::function")]`:
+///   Can be given on the type or on any of its fields. The function is handed a reference to the
+///   decoded value (or field, after any conversion via `as`), and returns a `Result<(), E>` where
+///   `E` implements [`core::error::Error`], [`Send`] and [`Sync`]. Any error is returned via
+///   [`Error::custom_error()`] (and so can be downcast back to an `E` with the
+///   `boxed-custom-errors` feature), along with the location of the field if given on a field.
+///   This is useful for checking invariants as values are decoded, for instance that some vector
+///   has at most 32 items.
+/// - `#[decode_as_type(index = 3)]` (or `#[codec(index = 3)]`):
+///   Gives the index of a variant, for enums which can be decoded from the index of one of
+///   their variants via the `from_index` attribute, or converted from one via `try_from_u8`.
+///   Without this, the explicit discriminant of the variant is used if given, or else its
+///   position in the enum, just as when it's SCALE encoded.
+/// - `#[decode_as_type(reorder_fields)]`:
+///   Only valid on struct-like enum variants. Named fields are normally looked up by name,
+///   but if the encoded variant has unnamed fields then they are decoded by position instead,
+///   which requires the number of fields to line up. With this attribute, the fields of the
+///   variant are always looked up by name, regardless of their order, and any extra encoded
+///   fields are ignored.
 #[cfg(feature = "derive")]
 pub use scale_decode_derive::DecodeAsType;
+
+/// The `CodecAsType` derive macro implements both [`DecodeAsType`] (exactly as the
+/// [`macro@DecodeAsType`] macro does) and `scale_encode::EncodeAsType` on structs and enums, as
+/// well as `scale_encode::EncodeAsFields` on structs. Both sides are generated from the same
+/// attributes, so that any value encodes to bytes which decode back into that same value. This
+/// requires the `codec-as-type` feature, which re-exports `scale_encode` as [`ext::scale_encode`].
+///
+/// ```rust
+/// use scale_decode::ext::scale_encode::EncodeAsType;
+/// use scale_decode::{CodecAsType, DecodeAsType};
+///
+/// #[derive(CodecAsType, scale_info::TypeInfo, Debug, PartialEq)]
+/// enum Message {
+///     Ping,
+///     Text {
+///         #[codec_as_type(rename = "body")]
+///         text: String,
+///         #[codec(skip)]
+///         #[codec_as_type(skip)]
+///         received: bool,
+///     },
+/// }
+///
+/// // Type information naming the field `body`, which is how we encode and decode `text`:
+/// #[derive(scale_info::TypeInfo)]
+/// enum Wire {
+///     Ping,
+///     Text { body: String },
+/// }
+///
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&scale_info::MetaType::new::<Wire>()).id;
+/// let types: scale_info::PortableRegistry = types.into();
+///
+/// let message = Message::Text { text: "hello".to_string(), received: false };
+/// let encoded = message.encode_as_type(type_id, &types).unwrap();
+/// let decoded = Message::decode_as_type(&mut &*encoded, type_id, &types).unwrap();
+/// assert_eq!(decoded, message);
+/// ```
+///
+/// Each type parameter must implement both `DecodeAsType` and `EncodeAsType`.
+///
+/// # Attributes
+///
+/// Only attributes which both sides can honour are accepted; any others (including the
+/// `decode_as_type` ones which only affect decoding, like `as` or `validate`) are an error, since
+/// they would let the two sides disagree. Use the separate derive macros if they're needed.
+///
+/// - `#[codec_as_type(crate_path = "::path::to::scale_decode")]`:
+///   The path to `scale_decode`, as with the [`macro@DecodeAsType`] macro.
+/// - `#[codec_as_type(encode_crate_path = "::path::to::scale_encode")]`:
+///   The path to `scale_encode`. By default, the one re-exported from `scale_decode` is used, and
+///   so this need only be given if some other version of `scale_encode` should be implemented.
+/// - `#[codec_as_type(skip)]` (or `#[codec(skip)]`):
+///   Any fields annotated with this are neither encoded nor decoded, and are populated with their
+///   default value when decoding. Enum variants can't be skipped, since they couldn't be encoded.
+///   Any other `codec` attributes are ignored; they describe things, like compact encoding, that
+///   the type information given when encoding and decoding already reflects.
+/// - `#[codec_as_type(rename = "name")]`:
+///   Any named fields or enum variants annotated with this are encoded as and decoded from fields
+///   or variants with the given name, rather than their own name.
+#[cfg(feature = "codec-as-type")]
+pub use scale_decode_derive::CodecAsType;
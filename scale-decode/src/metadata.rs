@@ -0,0 +1,370 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decode storage values, constants and events given some [`frame_metadata`], by looking up the
+//! types to decode them as using the names of the pallet and item in question. [`Metadata`] can
+//! be constructed from [`RuntimeMetadataV14`] or [`RuntimeMetadataV15`], and holds on to the
+//! [`PortableRegistry`] that these contain, so that it can also be used to decode any other types.
+//!
+//! ```rust
+//! use codec::Encode;
+//! use frame_metadata::v14::{
+//!     ExtrinsicMetadata, PalletConstantMetadata, PalletEventMetadata, PalletMetadata,
+//!     RuntimeMetadataV14,
+//! };
+//! use scale_decode::metadata::Metadata;
+//! use scale_info::meta_type;
+//!
+//! #[derive(Encode, scale_info::TypeInfo)]
+//! enum Event {
+//!     Transfer { from: u64, to: u64, amount: u128 },
+//! }
+//!
+//! let balances = PalletMetadata {
+//!     name: "Balances",
+//!     storage: None,
+//!     calls: None,
+//!     event: Some(PalletEventMetadata { ty: meta_type::<Event>() }),
+//!     constants: vec![PalletConstantMetadata {
+//!         name: "ExistentialDeposit",
+//!         ty: meta_type::<u128>(),
+//!         value: 500u128.encode(),
+//!         docs: vec![],
+//!     }],
+//!     error: None,
+//!     index: 5,
+//! };
+//! let extrinsic = ExtrinsicMetadata { ty: meta_type::<()>(), version: 4, signed_extensions: vec![] };
+//! let metadata: Metadata = RuntimeMetadataV14::new(vec![balances], extrinsic, meta_type::<()>()).into();
+//!
+//! let deposit: u64 = metadata.decode_constant("Balances", "ExistentialDeposit").unwrap();
+//! assert_eq!(deposit, 500);
+//!
+//! // The fields of some `Balances.Transfer` event:
+//! let fields = (1u64, 2u64, 300u128).encode();
+//! let amount: u64 =
+//!     metadata.decode_event_field("Balances", "Transfer", "amount", &mut &*fields).unwrap();
+//! assert_eq!(amount, 300);
+//! ```
+
+use crate::{
+    error::{Error, ErrorKind},
+    visitor::encoded_size_of,
+    DecodeAsFields, DecodeAsType, Field,
+};
+use ::frame_metadata::{
+    v14::{RuntimeMetadataV14, StorageEntryType},
+    v15::RuntimeMetadataV15,
+};
+use alloc::{borrow::ToOwned, string::String, vec::Vec};
+use scale_info::{form::PortableForm, PortableRegistry, TypeDef};
+
+/// The parts of some runtime metadata that are needed in order to decode storage values,
+/// constants and events. See the [module docs](self) for an example.
+#[derive(Debug, Clone)]
+pub struct Metadata {
+    types: PortableRegistry,
+    pallets: Vec<Pallet>,
+}
+
+#[derive(Debug, Clone)]
+struct Pallet {
+    name: String,
+    // The name of each storage entry along with the type of its values.
+    storage: Vec<(String, u32)>,
+    constants: Vec<Constant>,
+    event: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
+struct Constant {
+    name: String,
+    ty: u32,
+    value: Vec<u8>,
+}
+
+// The v14 and v15 pallet metadata are different types with the same fields that we care about.
+macro_rules! impl_from_metadata {
+    ($metadata:ty) => {
+        impl From<$metadata> for Metadata {
+            fn from(metadata: $metadata) -> Self {
+                let pallets = metadata
+                    .pallets
+                    .into_iter()
+                    .map(|p| Pallet {
+                        name: p.name.into(),
+                        storage: p
+                            .storage
+                            .map(|s| s.entries)
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|e| (e.name.into(), storage_value_type(&e.ty)))
+                            .collect(),
+                        constants: p
+                            .constants
+                            .into_iter()
+                            .map(|c| Constant { name: c.name.into(), ty: c.ty.id, value: c.value })
+                            .collect(),
+                        event: p.event.map(|e| e.ty.id),
+                    })
+                    .collect();
+                Metadata { types: metadata.types, pallets }
+            }
+        }
+    };
+}
+
+impl_from_metadata!(RuntimeMetadataV14);
+impl_from_metadata!(RuntimeMetadataV15);
+
+fn storage_value_type(ty: &StorageEntryType<PortableForm>) -> u32 {
+    match ty {
+        StorageEntryType::Plain(ty) => ty.id,
+        StorageEntryType::Map { value, .. } => value.id,
+    }
+}
+
+impl Metadata {
+    /// The types that the metadata refers to, which can be used to decode any values described
+    /// by it.
+    pub fn types(&self) -> &PortableRegistry {
+        &self.types
+    }
+
+    /// Decode the bytes of the value stored in some storage entry of some pallet.
+    pub fn decode_storage_value<T: DecodeAsType>(
+        &self,
+        pallet: &str,
+        entry: &str,
+        input: &mut &[u8],
+    ) -> Result<T, Error> {
+        let (_, ty) = self
+            .pallet(pallet)?
+            .storage
+            .iter()
+            .find(|(name, _)| name == entry)
+            .ok_or_else(|| cannot_find("storage entry", entry))?;
+        T::decode_as_type(input, *ty, &self.types)
+    }
+
+    /// Decode the value of some constant in some pallet, which is held in the metadata itself.
+    pub fn decode_constant<T: DecodeAsType>(
+        &self,
+        pallet: &str,
+        constant: &str,
+    ) -> Result<T, Error> {
+        let constant = self
+            .pallet(pallet)?
+            .constants
+            .iter()
+            .find(|c| c.name == constant)
+            .ok_or_else(|| cannot_find("constant", constant))?;
+        T::decode_as_type_exact(&constant.value, constant.ty, &self.types)
+    }
+
+    /// Decode the bytes of the fields of some event in some pallet (that is, the bytes which
+    /// follow the pallet and event indexes).
+    pub fn decode_event_fields<T: DecodeAsFields>(
+        &self,
+        pallet: &str,
+        event: &str,
+        input: &mut &[u8],
+    ) -> Result<T, Error> {
+        let fields = self.event_fields(pallet, event)?;
+        let mut fields = fields.iter().map(|f| Field::new(f.ty.id, field_name(f)));
+        T::decode_as_fields(input, &mut fields, &self.types)
+    }
+
+    /// Decode a single named field from the bytes of the fields of some event in some pallet
+    /// (that is, the bytes which follow the pallet and event indexes). Any fields before it are
+    /// skipped over, and `input` is left pointing to the bytes after the field.
+    pub fn decode_event_field<T: DecodeAsType>(
+        &self,
+        pallet: &str,
+        event: &str,
+        field: &str,
+        input: &mut &[u8],
+    ) -> Result<T, Error> {
+        for f in self.event_fields(pallet, event)? {
+            if field_name(f) == Some(field) {
+                return T::decode_as_type(input, f.ty.id, &self.types)
+                    .map_err(|e| e.at_field(field.to_owned()));
+            }
+            let len = encoded_size_of(input, f.ty.id, &self.types)?;
+            *input = &input[len..];
+        }
+        Err(Error::new(ErrorKind::CannotFindField { name: field.to_owned() }))
+    }
+
+    fn pallet(&self, name: &str) -> Result<&Pallet, Error> {
+        self.pallets.iter().find(|p| p.name == name).ok_or_else(|| cannot_find("pallet", name))
+    }
+
+    fn event_fields(
+        &self,
+        pallet: &str,
+        event: &str,
+    ) -> Result<&[scale_info::Field<PortableForm>], Error> {
+        let ty = self.pallet(pallet)?.event.ok_or_else(|| cannot_find("event", event))?;
+        let variants = match self.types.resolve(ty).map(|t| &t.type_def) {
+            Some(TypeDef::Variant(def)) => &def.variants,
+            _ => return Err(cannot_find("event", event)),
+        };
+        variants
+            .iter()
+            .find(|v| v.name == event)
+            .map(|v| &*v.fields)
+            .ok_or_else(|| cannot_find("event", event))
+    }
+}
+
+// Names are `String`s or `&'static str`s in the portable form, depending on the features of
+// `scale-info` that are enabled.
+fn field_name(field: &scale_info::Field<PortableForm>) -> Option<&str> {
+    field.name.as_ref().map(AsRef::as_ref)
+}
+
+fn cannot_find(item: &'static str, name: &str) -> Error {
+    Error::new(ErrorKind::CannotFindMetadataItem { item, name: name.to_owned() })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+    use codec::Encode;
+    use frame_metadata::v15::{
+        CustomMetadata, ExtrinsicMetadata, OuterEnums, PalletEventMetadata, PalletMetadata,
+        PalletStorageMetadata, StorageEntryMetadata, StorageEntryModifier, StorageHasher,
+    };
+    use scale_info::meta_type;
+
+    #[derive(Encode, scale_info::TypeInfo)]
+    #[allow(dead_code)]
+    enum Event {
+        Created(u32),
+        Renamed { id: u32, old: String, new: String },
+    }
+
+    fn metadata() -> Metadata {
+        let storage = PalletStorageMetadata {
+            prefix: "Things",
+            entries: vec![
+                StorageEntryMetadata {
+                    name: "Count",
+                    modifier: StorageEntryModifier::Default,
+                    ty: StorageEntryType::Plain(meta_type::<u32>()),
+                    default: 0u32.encode(),
+                    docs: vec![],
+                },
+                StorageEntryMetadata {
+                    name: "Names",
+                    modifier: StorageEntryModifier::Optional,
+                    ty: StorageEntryType::Map {
+                        hashers: vec![StorageHasher::Twox64Concat],
+                        key: meta_type::<u32>(),
+                        value: meta_type::<String>(),
+                    },
+                    default: vec![],
+                    docs: vec![],
+                },
+            ],
+        };
+        let things = PalletMetadata {
+            name: "Things",
+            storage: Some(storage),
+            calls: None,
+            event: Some(PalletEventMetadata { ty: meta_type::<Event>() }),
+            constants: vec![],
+            error: None,
+            index: 1,
+            docs: vec![],
+        };
+        let extrinsic = ExtrinsicMetadata {
+            version: 4,
+            address_ty: meta_type::<()>(),
+            call_ty: meta_type::<()>(),
+            signature_ty: meta_type::<()>(),
+            extra_ty: meta_type::<()>(),
+            signed_extensions: vec![],
+        };
+        let outer_enums = OuterEnums {
+            call_enum_ty: meta_type::<()>(),
+            event_enum_ty: meta_type::<()>(),
+            error_enum_ty: meta_type::<()>(),
+        };
+        let custom = CustomMetadata { map: Default::default() };
+
+        RuntimeMetadataV15::new(
+            vec![things],
+            extrinsic,
+            meta_type::<()>(),
+            vec![],
+            outer_enums,
+            custom,
+        )
+        .into()
+    }
+
+    #[test]
+    fn decodes_storage_values() {
+        let metadata = metadata();
+
+        let count: u64 =
+            metadata.decode_storage_value("Things", "Count", &mut &*10u32.encode()).unwrap();
+        assert_eq!(count, 10);
+
+        let name: String =
+            metadata.decode_storage_value("Things", "Names", &mut &*"hi".encode()).unwrap();
+        assert_eq!(name, "hi");
+    }
+
+    #[test]
+    fn decodes_event_fields() {
+        let metadata = metadata();
+        let encoded = (1u32, "a", "b").encode();
+
+        let fields: (u32, String, String) =
+            metadata.decode_event_fields("Things", "Renamed", &mut &*encoded).unwrap();
+        assert_eq!(fields, (1, "a".to_owned(), "b".to_owned()));
+
+        let input = &mut &*encoded;
+        let new: String = metadata.decode_event_field("Things", "Renamed", "new", input).unwrap();
+        assert_eq!(new, "b");
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn errors_on_missing_items() {
+        let metadata = metadata();
+        let err_kind = |res: Result<u32, Error>| res.unwrap_err().into_parts().0;
+
+        let kind = err_kind(metadata.decode_storage_value("Nope", "Count", &mut &[][..]));
+        assert!(matches!(kind, ErrorKind::CannotFindMetadataItem { item: "pallet", .. }));
+
+        let kind = err_kind(metadata.decode_storage_value("Things", "Nope", &mut &[][..]));
+        assert!(matches!(kind, ErrorKind::CannotFindMetadataItem { item: "storage entry", .. }));
+
+        let kind = err_kind(metadata.decode_constant("Things", "Nope"));
+        assert!(matches!(kind, ErrorKind::CannotFindMetadataItem { item: "constant", .. }));
+
+        let kind = err_kind(metadata.decode_event_field("Things", "Nope", "id", &mut &[][..]));
+        assert!(matches!(kind, ErrorKind::CannotFindMetadataItem { item: "event", .. }));
+
+        let encoded = 1u32.encode();
+        let kind = err_kind(metadata.decode_event_field("Things", "Created", "id", &mut &*encoded));
+        assert!(matches!(kind, ErrorKind::CannotFindField { .. }));
+    }
+}
@@ -0,0 +1,365 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Collect some [`DecodeMetrics`] about a value alongside decoding it, for instance to keep an
+//! eye on how large or deeply nested the values coming from some source are. Wrap any
+//! [`Visitor`] in an [`InstrumentedVisitor`] to have it hand back the metrics along with the
+//! value it decoded:
+//!
+//! ```rust
+//! use codec::Encode;
+//! use scale_decode::{metrics::InstrumentedVisitor, visitor::decode_with_visitor, IntoVisitor};
+//! use scale_info::{PortableRegistry, TypeInfo};
+//!
+//! #[derive(Encode, TypeInfo, scale_decode::DecodeAsType, Debug, PartialEq)]
+//! struct Foo {
+//!     a: Vec<u32>,
+//!     b: bool,
+//! }
+//!
+//! let m = scale_info::MetaType::new::<Foo>();
+//! let mut types = scale_info::Registry::new();
+//! let type_id = types.register_type(&m).id;
+//! let types: PortableRegistry = types.into();
+//!
+//! let encoded = Foo { a: vec![1, 2], b: true }.encode();
+//! let visitor = InstrumentedVisitor::new(Foo::into_visitor::<PortableRegistry>());
+//! let (foo, metrics) = decode_with_visitor(&mut &*encoded, type_id, &types, visitor).unwrap();
+//!
+//! assert_eq!(foo, Foo { a: vec![1, 2], b: true });
+//! assert_eq!(metrics.bytes, encoded.len());
+//! // Foo, its two fields, and the two items in the sequence:
+//! assert_eq!(metrics.nodes, 5);
+//! assert_eq!(metrics.max_depth, 3);
+//! // The sequence is 1 byte of length plus 2 u32s, and the bool is 1 byte:
+//! let fields: Vec<_> = metrics.fields.iter().map(|f| (f.name.as_deref(), f.bytes)).collect();
+//! assert_eq!(fields, [(Some("a"), 9), (Some("b"), 1)]);
+//! ```
+//!
+//! The metrics are gathered by first walking over the value in the same way as
+//! [`crate::validate::validate()`] does, and so invalid input is rejected before the wrapped
+//! visitor is handed any of it.
+
+use crate::{
+    error::Error,
+    validate::{validate, ValidateOnlyVisitor, ValidationStats},
+    visitor::{
+        decode_with_visitor,
+        types::{Composite, Tuple, Variant},
+        DecodeAsTypeResult, Detach, TypeIdFor, Unexpected, Visitor,
+    },
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::marker::PhantomData;
+use scale_type_resolver::TypeResolver;
+
+/// Some metrics about a value which has been decoded using an [`InstrumentedVisitor`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DecodeMetrics {
+    /// The number of values that were visited, including this one and every value nested
+    /// inside it.
+    pub nodes: usize,
+    /// How deeply nested the value is. Primitive values, and containers with nothing in them,
+    /// have a depth of 1, and each level of nesting adds 1 to this.
+    pub max_depth: usize,
+    /// The number of bytes that the value occupies.
+    pub bytes: usize,
+    /// The name of the variant, if the value is a variant.
+    pub variant: Option<String>,
+    /// Metrics for each of the fields of the value, if it's a composite, variant or tuple. This
+    /// is empty for any other value.
+    pub fields: Vec<FieldMetrics>,
+}
+
+/// Metrics for one of the top level fields of some value. See [`DecodeMetrics::fields`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FieldMetrics {
+    /// The name of the field, if it has one. Tuple items and unnamed fields have no name.
+    pub name: Option<String>,
+    /// The number of bytes that the field occupies.
+    pub bytes: usize,
+    /// The number of values that were visited in this field.
+    pub nodes: usize,
+    /// How deeply nested the field is.
+    pub max_depth: usize,
+}
+
+impl DecodeMetrics {
+    fn from_stats(stats: ValidationStats) -> Self {
+        DecodeMetrics {
+            nodes: stats.values,
+            max_depth: stats.depth,
+            bytes: stats.bytes,
+            variant: None,
+            fields: Vec::new(),
+        }
+    }
+
+    fn from_fields(fields: Vec<FieldMetrics>, bytes: usize) -> Self {
+        DecodeMetrics {
+            nodes: 1 + fields.iter().map(|f| f.nodes).sum::<usize>(),
+            max_depth: 1 + fields.iter().map(|f| f.max_depth).max().unwrap_or(0),
+            bytes,
+            variant: None,
+            fields,
+        }
+    }
+}
+
+impl FieldMetrics {
+    fn new(name: Option<&str>, stats: ValidationStats) -> Self {
+        FieldMetrics {
+            name: name.map(ToString::to_string),
+            bytes: stats.bytes,
+            nodes: stats.values,
+            max_depth: stats.depth,
+        }
+    }
+}
+
+/// A [`Visitor`] which wraps some other visitor, handing back [`DecodeMetrics`] about the value
+/// alongside whatever the wrapped visitor decodes it into. See [the module docs](self).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct InstrumentedVisitor<V>(V);
+
+impl<V> InstrumentedVisitor<V> {
+    /// Wrap the given visitor.
+    pub fn new(visitor: V) -> Self {
+        InstrumentedVisitor(visitor)
+    }
+
+    /// Return the wrapped visitor.
+    pub fn into_inner(self) -> V {
+        self.0
+    }
+}
+
+impl<V: Visitor> Visitor for InstrumentedVisitor<V>
+where
+    V::Error: Into<Error>,
+{
+    type Value<'scale, 'resolver> = (V::Value<'scale, 'resolver>, DecodeMetrics);
+    type Error = Error;
+    type TypeResolver = V::TypeResolver;
+
+    fn unchecked_decode_as_type<'scale, 'resolver>(
+        self,
+        input: &mut &'scale [u8],
+        type_id: TypeIdFor<Self>,
+        types: &'resolver Self::TypeResolver,
+    ) -> DecodeAsTypeResult<Self, Result<Self::Value<'scale, 'resolver>, Self::Error>> {
+        let res = collect_metrics(input, type_id.clone(), types).and_then(|metrics| {
+            let value = decode_with_visitor(input, type_id, types, self.0).map_err(Into::into)?;
+            Ok((value, metrics))
+        });
+        DecodeAsTypeResult::Decoded(res)
+    }
+}
+
+impl<V: Detach> Detach for InstrumentedVisitor<V>
+where
+    V::Error: Into<Error>,
+{
+    type Owned = (V::Owned, DecodeMetrics);
+    fn detach((value, metrics): Self::Value<'_, '_>) -> Self::Owned {
+        (V::detach(value), metrics)
+    }
+}
+
+// Walk over the value at the start of the input, breaking the metrics down by field if it has
+// any, and falling back to plain validation if not.
+fn collect_metrics<R: TypeResolver>(
+    input: &[u8],
+    type_id: R::TypeId,
+    types: &R,
+) -> Result<DecodeMetrics, Error> {
+    let mut cursor = input;
+    match decode_with_visitor(&mut cursor, type_id.clone(), types, FieldMetricsVisitor::new())? {
+        Some(metrics) => Ok(metrics),
+        None => {
+            let mut cursor = input;
+            Ok(DecodeMetrics::from_stats(validate(&mut cursor, type_id, types)?))
+        }
+    }
+}
+
+// Collect metrics for each field of a composite, variant or tuple, returning `None` for
+// anything else.
+struct FieldMetricsVisitor<R>(PhantomData<R>);
+
+impl<R> FieldMetricsVisitor<R> {
+    fn new() -> Self {
+        FieldMetricsVisitor(PhantomData)
+    }
+}
+
+fn composite_field_metrics<R: TypeResolver>(
+    fields: &mut Composite<'_, '_, R>,
+) -> Result<Vec<FieldMetrics>, Error> {
+    let mut metrics = Vec::with_capacity(fields.remaining());
+    for (idx, field) in fields.by_ref().enumerate() {
+        let field = field?;
+        let stats =
+            field.decode_with_visitor(ValidateOnlyVisitor::new()).map_err(|e| {
+                match field.name() {
+                    Some(name) => e.at_field(name.to_string()),
+                    None => e.at_idx(idx),
+                }
+            })?;
+        metrics.push(FieldMetrics::new(field.name(), stats));
+    }
+    Ok(metrics)
+}
+
+impl<R: TypeResolver> Visitor for FieldMetricsVisitor<R> {
+    type Value<'scale, 'resolver> = Option<DecodeMetrics>;
+    type Error = Error;
+    type TypeResolver = R;
+
+    fn visit_unexpected<'scale, 'resolver>(
+        self,
+        _unexpected: Unexpected,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        Ok(None)
+    }
+    fn visit_composite<'scale, 'resolver>(
+        self,
+        value: &mut Composite<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let fields = composite_field_metrics(value)?;
+        let bytes = value.bytes_from_start().len() - value.bytes_from_undecoded().len();
+        Ok(Some(DecodeMetrics::from_fields(fields, bytes)))
+    }
+    fn visit_variant<'scale, 'resolver>(
+        self,
+        value: &mut Variant<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let name = value.name();
+        let fields =
+            composite_field_metrics(value.fields()).map_err(|e| e.at_variant(name.to_string()))?;
+        let bytes = value.bytes_from_start().len() - value.bytes_from_undecoded().len();
+        let mut metrics = DecodeMetrics::from_fields(fields, bytes);
+        metrics.variant = Some(name.to_string());
+        Ok(Some(metrics))
+    }
+    fn visit_tuple<'scale, 'resolver>(
+        self,
+        value: &mut Tuple<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let mut fields = Vec::with_capacity(value.remaining());
+        for (idx, item) in value.by_ref().enumerate() {
+            let stats =
+                item?.decode_with_visitor(ValidateOnlyVisitor::new()).map_err(|e| e.at_idx(idx))?;
+            fields.push(FieldMetrics::new(None, stats));
+        }
+        let bytes = value.bytes_from_start().len() - value.bytes_from_undecoded().len();
+        Ok(Some(DecodeMetrics::from_fields(fields, bytes)))
+    }
+}
+
+#[cfg(feature = "derive")]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::IntoVisitor;
+    use alloc::{string::String, vec, vec::Vec};
+    use codec::Encode;
+    use scale_info::{PortableRegistry, TypeInfo};
+
+    fn make_type<T: TypeInfo + 'static>() -> (u32, PortableRegistry) {
+        let m = scale_info::MetaType::new::<T>();
+        let mut types = scale_info::Registry::new();
+        let id = types.register_type(&m);
+        (id.id, types.into())
+    }
+
+    // Decode the value with metrics, checking that the input is moved past it.
+    fn decode_with_metrics<T>(value: T) -> (T, DecodeMetrics)
+    where
+        T: Encode + TypeInfo + IntoVisitor + 'static,
+    {
+        let (type_id, types) = make_type::<T>();
+        let mut encoded = value.encode();
+        let len = encoded.len();
+        encoded.extend_from_slice(&[1, 2, 3]);
+
+        let cursor = &mut &*encoded;
+        let visitor = InstrumentedVisitor::new(T::into_visitor::<PortableRegistry>());
+        let (value, metrics) = decode_with_visitor(cursor, type_id, &types, visitor).unwrap();
+        assert_eq!(cursor, &[1, 2, 3], "value should be consumed");
+        assert_eq!(metrics.bytes, len);
+        (value, metrics)
+    }
+
+    #[test]
+    fn primitive_metrics() {
+        let (value, metrics) = decode_with_metrics(123u64);
+        assert_eq!(value, 123);
+        assert_eq!(
+            metrics,
+            DecodeMetrics { nodes: 1, max_depth: 1, bytes: 8, variant: None, fields: vec![] }
+        );
+
+        let (_, metrics) = decode_with_metrics(vec![vec![1u8, 2], vec![]]);
+        assert_eq!((metrics.nodes, metrics.max_depth), (5, 3));
+        assert!(metrics.fields.is_empty());
+    }
+
+    #[test]
+    fn field_metrics() {
+        #[derive(Encode, TypeInfo, crate::DecodeAsType, Debug, PartialEq)]
+        #[decode_as_type(crate_path = "crate")]
+        enum Foo {
+            A(String, (bool, u16)),
+            B { a: Vec<u32> },
+        }
+
+        let (value, metrics) = decode_with_metrics(Foo::A("hi".to_string(), (true, 1)));
+        assert_eq!(value, Foo::A("hi".to_string(), (true, 1)));
+        assert_eq!(metrics.variant.as_deref(), Some("A"));
+        // Foo, the string, the tuple and its two items:
+        assert_eq!((metrics.nodes, metrics.max_depth), (5, 3));
+        let fields: Vec<_> = metrics.fields.iter().map(|f| (f.name.clone(), f.bytes)).collect();
+        assert_eq!(fields, [(None, 3), (None, 3)]);
+
+        let (_, metrics) = decode_with_metrics(Foo::B { a: vec![1, 2, 3] });
+        assert_eq!(metrics.variant.as_deref(), Some("B"));
+        assert_eq!(metrics.fields.len(), 1);
+        assert_eq!(metrics.fields[0].name.as_deref(), Some("a"));
+        assert_eq!(metrics.fields[0].bytes, 13);
+        assert_eq!(metrics.fields[0].nodes, 4);
+
+        let (_, metrics) = decode_with_metrics((1u8, "hello".to_string()));
+        let bytes: Vec<_> = metrics.fields.iter().map(|f| f.bytes).collect();
+        assert_eq!(bytes, [1, 6]);
+    }
+
+    #[test]
+    fn invalid_input_is_rejected() {
+        let (type_id, types) = make_type::<(u8, String)>();
+        let mut encoded = (1u8, "hi".to_string()).encode();
+        *encoded.last_mut().unwrap() = 0xFF;
+
+        let visitor = InstrumentedVisitor::new(<(u8, String)>::into_visitor::<PortableRegistry>());
+        let err = decode_with_visitor(&mut &*encoded, type_id, &types, visitor).unwrap_err();
+        assert_eq!(err.context().path().to_string(), "[1]");
+    }
+}
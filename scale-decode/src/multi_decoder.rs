@@ -0,0 +1,177 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A [`MultiDecoder`] decodes a series of SCALE encoded values, each of a different type, one
+//! after the other from the same bytes. This is the case when decoding call arguments or event
+//! fields, for instance.
+//!
+//! ```rust
+//! use codec::Encode;
+//! use scale_decode::multi_decoder::MultiDecoder;
+//! use scale_info::PortableRegistry;
+//!
+//! let mut types = scale_info::Registry::new();
+//! let u8_id = types.register_type(&scale_info::MetaType::new::<u8>()).id;
+//! let str_id = types.register_type(&scale_info::MetaType::new::<String>()).id;
+//! let types: PortableRegistry = types.into();
+//!
+//! let encoded = (1u8, "hello").encode();
+//!
+//! let mut decoder = MultiDecoder::new(&encoded, &types);
+//! let a: u64 = decoder.decode_next(u8_id).unwrap();
+//! let b: String = decoder.decode_next(str_id).unwrap();
+//! decoder.finish().unwrap();
+//!
+//! assert_eq!(a, 1);
+//! assert_eq!(b, "hello");
+//! ```
+
+use crate::{
+    error::ErrorKind,
+    visitor::{decode_with_visitor, encoded_size_of, DecodeError, Visitor},
+    DecodeAsType, Error,
+};
+use alloc::vec::Vec;
+use core::ops::Range;
+use scale_type_resolver::TypeResolver;
+
+/// Decode a series of values from some bytes, keeping track of where each value was found.
+pub struct MultiDecoder<'scale, 'resolver, R: TypeResolver> {
+    bytes: &'scale [u8],
+    offset: usize,
+    types: &'resolver R,
+    ranges: Vec<Range<usize>>,
+}
+
+impl<'scale, 'resolver, R: TypeResolver> MultiDecoder<'scale, 'resolver, R> {
+    /// Construct a new [`MultiDecoder`], which will decode values from the start of the bytes given.
+    pub fn new(bytes: &'scale [u8], types: &'resolver R) -> Self {
+        MultiDecoder { bytes, offset: 0, types, ranges: Vec::new() }
+    }
+
+    /// Decode the next value, described by the type ID given. If decoding fails, we don't move
+    /// past the value, and the error is given the index of the value that we failed to decode.
+    pub fn decode_next<T: DecodeAsType>(&mut self, type_id: R::TypeId) -> Result<T, Error> {
+        let idx = self.ranges.len();
+        let input = &mut self.remaining_bytes();
+        let val = T::decode_as_type(input, type_id, self.types).map_err(|e| e.at_idx(idx))?;
+        self.advance_to(input);
+        Ok(val)
+    }
+
+    /// Decode the next value, described by the type ID given, using the visitor provided. If
+    /// decoding fails, we don't move past the value.
+    pub fn decode_next_with_visitor<V: Visitor<TypeResolver = R>>(
+        &mut self,
+        type_id: R::TypeId,
+        visitor: V,
+    ) -> Result<V::Value<'scale, 'resolver>, V::Error> {
+        let input = &mut self.remaining_bytes();
+        let val = decode_with_visitor(input, type_id, self.types, visitor)?;
+        self.advance_to(input);
+        Ok(val)
+    }
+
+    /// Skip over the next value, described by the type ID given, without decoding it.
+    pub fn skip_next(&mut self, type_id: R::TypeId) -> Result<(), DecodeError> {
+        let size = encoded_size_of(self.remaining_bytes(), type_id, self.types)?;
+        self.ranges.push(self.offset..self.offset + size);
+        self.offset += size;
+        Ok(())
+    }
+
+    /// The byte ranges of each of the values that have been decoded or skipped so far, in order.
+    pub fn item_ranges(&self) -> &[Range<usize>] {
+        &self.ranges
+    }
+
+    /// The bytes of each of the values that have been decoded or skipped so far, in order.
+    pub fn item_bytes(&self) -> impl ExactSizeIterator<Item = &'scale [u8]> + '_ {
+        let bytes = self.bytes;
+        self.ranges.iter().map(move |r| &bytes[r.clone()])
+    }
+
+    /// The bytes that have not yet been decoded.
+    pub fn remaining_bytes(&self) -> &'scale [u8] {
+        &self.bytes[self.offset..]
+    }
+
+    /// Check that all of the bytes have been decoded, returning an
+    /// [`ErrorKind::LeftoverBytes`] error if not.
+    pub fn finish(self) -> Result<(), Error> {
+        let count = self.bytes.len() - self.offset;
+        if count > 0 {
+            return Err(Error::new(ErrorKind::LeftoverBytes { count }));
+        }
+        Ok(())
+    }
+
+    // Move our offset along to point at the remaining bytes given.
+    fn advance_to(&mut self, remaining: &[u8]) {
+        let end = self.bytes.len() - remaining.len();
+        self.ranges.push(self.offset..end);
+        self.offset = end;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::{String, ToString};
+    use alloc::vec;
+    use codec::Encode;
+    use scale_info::PortableRegistry;
+
+    fn make_types() -> (u32, u32, u32, PortableRegistry) {
+        let mut types = scale_info::Registry::new();
+        let u8_id = types.register_type(&scale_info::MetaType::new::<u8>()).id;
+        let str_id = types.register_type(&scale_info::MetaType::new::<String>()).id;
+        let vec_id = types.register_type(&scale_info::MetaType::new::<Vec<u16>>()).id;
+        (u8_id, str_id, vec_id, types.into())
+    }
+
+    #[test]
+    fn decodes_values_and_tracks_ranges() {
+        let (u8_id, str_id, vec_id, types) = make_types();
+        let encoded = (7u8, "hi".to_string(), vec![1u16, 2]).encode();
+
+        let mut decoder = MultiDecoder::new(&encoded, &types);
+        assert_eq!(decoder.decode_next::<u8>(u8_id).unwrap(), 7);
+        decoder.skip_next(str_id).unwrap();
+        assert_eq!(decoder.decode_next::<Vec<u64>>(vec_id).unwrap(), vec![1, 2]);
+
+        assert_eq!(decoder.item_ranges(), &[0..1, 1..4, 4..9]);
+        assert_eq!(decoder.item_bytes().nth(1), Some(&encoded[1..4]));
+        decoder.finish().unwrap();
+    }
+
+    #[test]
+    fn failures_dont_move_the_cursor() {
+        let (u8_id, str_id, _, types) = make_types();
+        let encoded = (300u16, 1u8).encode();
+
+        let mut decoder = MultiDecoder::new(&encoded, &types);
+        assert!(decoder.decode_next::<String>(str_id).is_err());
+        assert_eq!(decoder.remaining_bytes(), &encoded[..]);
+
+        assert_eq!(decoder.decode_next::<u8>(u8_id).unwrap(), 44);
+        let err = decoder.decode_next::<bool>(u8_id).unwrap_err();
+        assert_eq!(err.context().path().to_string(), "[1]");
+
+        assert_eq!(decoder.decode_next::<u8>(u8_id).unwrap(), 1);
+        let err = decoder.finish().unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::LeftoverBytes { count: 1 }));
+    }
+}
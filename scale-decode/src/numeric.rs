@@ -0,0 +1,400 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decode numbers with control over how they are converted into the target type.
+//!
+//! The [`crate::IntoVisitor`] impls for numbers will decode any number whose value fits into the
+//! target type, regardless of the type it was encoded as; this is [`NumericPolicy::Lossless`].
+//! To be stricter or more lenient than this, decode using a [`NumberVisitor`] or
+//! [`decode_as_type_with()`] and some other [`NumericPolicy`]:
+//!
+//! ```rust
+//! use codec::Encode;
+//! use scale_decode::numeric::{decode_as_type_with, DecodeOptions, NumericPolicy};
+//! use scale_info::PortableRegistry;
+//!
+//! let m = scale_info::MetaType::new::<i64>();
+//! let mut types = scale_info::Registry::new();
+//! let type_id = types.register_type(&m).id;
+//! let types: PortableRegistry = types.into();
+//!
+//! let encoded = (-1i64).encode();
+//! let decode = |numeric| {
+//!     decode_as_type_with::<u32, _>(&mut &*encoded, type_id, &types, DecodeOptions { numeric })
+//! };
+//!
+//! // An i64 can't always be represented as a u32, so this is rejected outright:
+//! assert!(decode(NumericPolicy::Strict).is_err());
+//! // -1 doesn't fit into a u32, so this is rejected too:
+//! assert!(decode(NumericPolicy::Lossless).is_err());
+//! // But we can clamp it to the nearest valid value instead:
+//! assert_eq!(decode(NumericPolicy::Saturating).unwrap(), 0);
+//! ```
+//!
+//! [`NumberVisitor`] can also be used when writing a [`Visitor`] which decodes numbers nested
+//! inside some other value, for instance via
+//! [`Composite::decode_item()`](crate::visitor::types::Composite::decode_item), in order to apply
+//! the same policy to them.
+//!
+//! To check that a number lies within some range as it's decoded, decode into a [`Bounded`]
+//! number instead.
+
+use crate::{
+    error::{Error, ErrorKind},
+    visitor::{decode_with_visitor, DecodeAsTypeResult, Detach, TypeIdFor, Unexpected, Visitor},
+    IntoVisitor,
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::marker::PhantomData;
+use scale_type_resolver::TypeResolver;
+
+/// How numbers are converted into the type that we're decoding into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumericPolicy {
+    /// Only numbers whose type can always be represented by the target type are accepted, for
+    /// instance a `u16` or `i32` can be decoded into an `i64`, but an `i64` can't be decoded into a
+    /// `u32`, whatever its value.
+    Strict,
+    /// Any number whose value can be represented by the target type is accepted. This is what the
+    /// [`crate::IntoVisitor`] impls for numbers do.
+    #[default]
+    Lossless,
+    /// Any number is accepted. Values which can't be represented by the target type are clamped
+    /// to its minimum or maximum value.
+    Saturating,
+}
+
+/// Options that control how values are decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DecodeOptions {
+    /// How numbers are converted into the type that we're decoding into.
+    pub numeric: NumericPolicy,
+}
+
+/// Decode some bytes, whose structure is given by the type ID and type resolver provided, into
+/// the number `T`, according to the [`DecodeOptions`] given.
+pub fn decode_as_type_with<T: Number, R: TypeResolver>(
+    input: &mut &[u8],
+    type_id: R::TypeId,
+    types: &R,
+    options: DecodeOptions,
+) -> Result<T, Error> {
+    decode_with_visitor(input, type_id, types, NumberVisitor::new(options.numeric))
+}
+
+/// The primitive number types that a [`NumberVisitor`] can decode into.
+pub trait Number:
+    Copy
+    + ToString
+    + TryFrom<u8>
+    + TryFrom<u16>
+    + TryFrom<u32>
+    + TryFrom<u64>
+    + TryFrom<u128>
+    + TryFrom<i8>
+    + TryFrom<i16>
+    + TryFrom<i32>
+    + TryFrom<i64>
+    + TryFrom<i128>
+    + private::Sealed
+{
+    /// The smallest value of this type.
+    const MIN: Self;
+    /// The largest value of this type.
+    const MAX: Self;
+    /// The number of bits needed to represent this type.
+    const BITS: u32;
+    /// Can this type represent negative numbers?
+    const SIGNED: bool;
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+macro_rules! impl_number {
+    ($($ty:ident: $signed:literal),*) => {$(
+        impl private::Sealed for $ty {}
+        impl Number for $ty {
+            const MIN: Self = $ty::MIN;
+            const MAX: Self = $ty::MAX;
+            const BITS: u32 = $ty::BITS;
+            const SIGNED: bool = $signed;
+        }
+    )*};
+}
+impl_number!(
+    u8: false, u16: false, u32: false, u64: false, u128: false, usize: false,
+    i8: true, i16: true, i32: true, i64: true, i128: true, isize: true
+);
+
+// Can every value of the type `S` be represented by the type `T`?
+fn always_fits<S: Number, T: Number>() -> bool {
+    match (S::SIGNED, T::SIGNED) {
+        (false, false) | (true, true) => S::BITS <= T::BITS,
+        (false, true) => S::BITS < T::BITS,
+        (true, false) => false,
+    }
+}
+
+/// A [`Visitor`] which decodes any number into the number `T`, converting it according to some
+/// [`NumericPolicy`].
+pub struct NumberVisitor<T, R> {
+    policy: NumericPolicy,
+    _marker: PhantomData<(T, R)>,
+}
+
+impl<T, R> NumberVisitor<T, R> {
+    /// Construct a new [`NumberVisitor`] which uses the given [`NumericPolicy`].
+    pub fn new(policy: NumericPolicy) -> Self {
+        NumberVisitor { policy, _marker: PhantomData }
+    }
+}
+
+macro_rules! visit_number_fn_impl {
+    ($name:ident : $ty:ident => $unexpected:ident) => {
+        fn $name<'scale, 'resolver>(
+            self,
+            value: $ty,
+            _type_id: TypeIdFor<Self>,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            if self.policy == NumericPolicy::Strict && !always_fits::<$ty, T>() {
+                return self.visit_unexpected(Unexpected::$unexpected);
+            }
+            match T::try_from(value) {
+                Ok(n) => Ok(n),
+                Err(_) if self.policy == NumericPolicy::Saturating => {
+                    Ok(if value < 0 as $ty { T::MIN } else { T::MAX })
+                }
+                Err(_) => Err(out_of_range(value.to_string())),
+            }
+        }
+    };
+}
+
+fn out_of_range(value: String) -> Error {
+    Error::new(ErrorKind::NumberOutOfRange { value })
+}
+
+impl<T: Number, R: TypeResolver> Visitor for NumberVisitor<T, R> {
+    type Value<'scale, 'resolver> = T;
+    type Error = Error;
+    type TypeResolver = R;
+
+    visit_number_fn_impl!(visit_u8: u8 => U8);
+    visit_number_fn_impl!(visit_u16: u16 => U16);
+    visit_number_fn_impl!(visit_u32: u32 => U32);
+    visit_number_fn_impl!(visit_u64: u64 => U64);
+    visit_number_fn_impl!(visit_u128: u128 => U128);
+    visit_number_fn_impl!(visit_i8: i8 => I8);
+    visit_number_fn_impl!(visit_i16: i16 => I16);
+    visit_number_fn_impl!(visit_i32: i32 => I32);
+    visit_number_fn_impl!(visit_i64: i64 => I64);
+    visit_number_fn_impl!(visit_i128: i128 => I128);
+    crate::impls::visit_single_field_composite_tuple_impls!(R);
+}
+
+impl<T: Number + 'static, R: TypeResolver> Detach for NumberVisitor<T, R> {
+    type Owned = T;
+    fn detach(value: T) -> T {
+        value
+    }
+}
+
+/// A number `T` which is known to lie within the range `MIN..=MAX`. Decoding into this decodes
+/// `T` and then checks that it's in range, returning an [`ErrorKind::NumberOutOfRange`] error if
+/// not.
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_decode::{numeric::Bounded, DecodeAsType};
+/// use scale_info::PortableRegistry;
+///
+/// type Percent = Bounded<u8, 0, 100>;
+///
+/// let m = scale_info::MetaType::new::<u32>();
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&m).id;
+/// let types: PortableRegistry = types.into();
+///
+/// let encoded = 50u32.encode();
+/// let percent = Percent::decode_as_type(&mut &*encoded, type_id, &types).unwrap();
+/// assert_eq!(percent.get(), 50);
+///
+/// let encoded = 101u32.encode();
+/// assert!(Percent::decode_as_type(&mut &*encoded, type_id, &types).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bounded<T, const MIN: i128, const MAX: i128>(T);
+
+impl<T: Copy + TryInto<i128>, const MIN: i128, const MAX: i128> Bounded<T, MIN, MAX> {
+    /// Construct a new [`Bounded`] number, returning `None` if the value given is not within
+    /// `MIN..=MAX`.
+    pub fn new(value: T) -> Option<Self> {
+        let in_range = value.try_into().is_ok_and(|n| (MIN..=MAX).contains(&n));
+        in_range.then_some(Bounded(value))
+    }
+}
+
+impl<T, const MIN: i128, const MAX: i128> Bounded<T, MIN, MAX> {
+    /// Return the underlying number.
+    pub fn get(self) -> T {
+        self.0
+    }
+}
+
+/// The [`Visitor`] used to decode [`Bounded`] numbers.
+pub struct BoundedVisitor<T, R, const MIN: i128, const MAX: i128>(PhantomData<(T, R)>);
+
+impl<T, R, const MIN: i128, const MAX: i128> Visitor for BoundedVisitor<T, R, MIN, MAX>
+where
+    T: IntoVisitor + Copy + TryInto<i128> + ToString,
+    R: TypeResolver,
+{
+    type Value<'scale, 'resolver> = Bounded<T, MIN, MAX>;
+    type Error = Error;
+    type TypeResolver = R;
+
+    fn unchecked_decode_as_type<'scale, 'resolver>(
+        self,
+        input: &mut &'scale [u8],
+        type_id: TypeIdFor<Self>,
+        types: &'resolver R,
+    ) -> DecodeAsTypeResult<Self, Result<Self::Value<'scale, 'resolver>, Self::Error>> {
+        let res = decode_with_visitor(input, type_id, types, T::into_visitor()).and_then(|n| {
+            Bounded::new(n).ok_or_else(|| {
+                out_of_range(n.to_string())
+                    .expecting(format!("a number between {MIN} and {MAX} inclusive"))
+            })
+        });
+        DecodeAsTypeResult::Decoded(res)
+    }
+}
+
+impl<T, const MIN: i128, const MAX: i128> IntoVisitor for Bounded<T, MIN, MAX>
+where
+    T: IntoVisitor + Copy + TryInto<i128> + ToString,
+{
+    type AnyVisitor<R: TypeResolver> = BoundedVisitor<T, R, MIN, MAX>;
+    fn into_visitor<R: TypeResolver>() -> Self::AnyVisitor<R> {
+        BoundedVisitor(PhantomData)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use codec::{Compact, Encode};
+
+    fn decode<S, T>(value: S, numeric: NumericPolicy) -> Result<T, Error>
+    where
+        S: Encode + scale_info::TypeInfo + 'static,
+        T: Number,
+    {
+        let m = scale_info::MetaType::new::<S>();
+        let mut types = scale_info::Registry::new();
+        let type_id = types.register_type(&m).id;
+        let types: scale_info::PortableRegistry = types.into();
+
+        let encoded = value.encode();
+        decode_as_type_with(&mut &*encoded, type_id, &types, DecodeOptions { numeric })
+    }
+
+    #[test]
+    fn strict_only_allows_widening() {
+        use NumericPolicy::Strict;
+
+        assert_eq!(decode::<_, u64>(1u8, Strict).unwrap(), 1);
+        assert_eq!(decode::<_, u32>(1u32, Strict).unwrap(), 1);
+        assert_eq!(decode::<_, i64>(1u32, Strict).unwrap(), 1);
+        assert_eq!(decode::<_, i64>(-1i16, Strict).unwrap(), -1);
+        assert_eq!(decode::<_, u64>(Compact(1u32), Strict).unwrap(), 1);
+
+        // Even when the value would fit, the types don't line up:
+        for err in [
+            decode::<_, u32>(1i64, Strict).unwrap_err(),
+            decode::<_, u32>(1i8, Strict).unwrap_err(),
+            decode::<_, i32>(1u32, Strict).unwrap_err(),
+            decode::<_, u8>(1u16, Strict).unwrap_err(),
+        ] {
+            assert!(matches!(
+                err.kind(),
+                ErrorKind::VisitorDecodeError(crate::visitor::DecodeError::Unexpected(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn lossless_allows_values_that_fit() {
+        use NumericPolicy::Lossless;
+
+        assert_eq!(decode::<_, u32>(1i64, Lossless).unwrap(), 1);
+        assert_eq!(decode::<_, i8>(127u128, Lossless).unwrap(), 127);
+        let err = decode::<_, u32>(-1i64, Lossless).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { value } if value == "-1"));
+        let err = decode::<_, u8>(256u16, Lossless).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { value } if value == "256"));
+    }
+
+    #[test]
+    fn saturating_clamps_values() {
+        use NumericPolicy::Saturating;
+
+        assert_eq!(decode::<_, u32>(1i64, Saturating).unwrap(), 1);
+        assert_eq!(decode::<_, u32>(-1i64, Saturating).unwrap(), 0);
+        assert_eq!(decode::<_, u8>(1000u16, Saturating).unwrap(), u8::MAX);
+        assert_eq!(decode::<_, i8>(i128::MIN, Saturating).unwrap(), i8::MIN);
+        assert_eq!(decode::<_, i8>(u128::MAX, Saturating).unwrap(), i8::MAX);
+        assert_eq!(decode::<_, usize>(u128::MAX, Saturating).unwrap(), usize::MAX);
+    }
+
+    #[test]
+    fn bounded_numbers_are_range_checked() {
+        use crate::DecodeAsType;
+
+        fn decode_bounded<S, T>(value: S) -> Result<T, Error>
+        where
+            S: Encode + scale_info::TypeInfo + 'static,
+            T: DecodeAsType,
+        {
+            let m = scale_info::MetaType::new::<S>();
+            let mut types = scale_info::Registry::new();
+            let type_id = types.register_type(&m).id;
+            let types: scale_info::PortableRegistry = types.into();
+            T::decode_as_type(&mut &*value.encode(), type_id, &types)
+        }
+
+        type Small = Bounded<i32, -10, 10>;
+        assert_eq!(decode_bounded::<_, Small>(-10i64).unwrap().get(), -10);
+        assert_eq!(decode_bounded::<_, Small>(Compact(10u8)).unwrap().get(), 10);
+        assert_eq!(decode_bounded::<_, Small>((5u16,)).unwrap().get(), 5);
+
+        let err = decode_bounded::<_, Small>(11u8).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { value } if value == "11"));
+        assert_eq!(err.context().expected(), Some("a number between -10 and 10 inclusive"));
+
+        // Values which don't fit into the underlying type are out of range as before:
+        let err = decode_bounded::<_, Small>(u64::MAX).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+
+        // Bounds are checked even if the value is too large for an i128:
+        type Large = Bounded<u128, 0, { i128::MAX }>;
+        assert!(decode_bounded::<_, Large>(u128::MAX).is_err());
+        assert_eq!(Large::new(i128::MAX as u128).map(Bounded::get), Some(i128::MAX as u128));
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bound how deeply a type can be nested inside itself while it's being decoded.
+//!
+//! Recursive types like `enum Expr { Leaf(u32), Node(Box<Expr>, Box<Expr>) }` are decoded by
+//! recursing once for each level of nesting, and so deeply nested (and possibly malicious) input
+//! can overflow the stack. A [`DepthCounter`] tracks how many decodes of some type are in progress,
+//! so that an error can be returned instead once some maximum depth is exceeded. This is what the
+//! `#[decode_as_type(max_depth = N)]` attribute uses, but it can also be used in hand written
+//! [`crate::Visitor`] impls:
+//!
+//! ```rust
+//! use scale_decode::recursion::DepthCounter;
+//!
+//! static DEPTH: DepthCounter = DepthCounter::new();
+//!
+//! let a = DEPTH.enter(2).unwrap();
+//! let b = DEPTH.enter(2).unwrap();
+//! assert!(DEPTH.enter(2).is_err());
+//!
+//! // Dropping a guard leaves that level again:
+//! drop(b);
+//! let b = DEPTH.enter(2).unwrap();
+//! # drop((a, b));
+//! ```
+//!
+//! The depth is tracked separately on each thread, so that values decoded at the same time on
+//! different threads don't count towards each other's depth. This needs thread locals, and so
+//! this module is only available with the `std` feature.
+
+use crate::error::{Error, ErrorKind};
+
+std::thread_local! {
+    // The current depth of each counter (keyed by its address) which has been entered on this
+    // thread. There are rarely more than a handful of these, so a Vec is fine.
+    static DEPTHS: core::cell::RefCell<alloc::vec::Vec<(usize, usize)>> =
+        const { core::cell::RefCell::new(alloc::vec::Vec::new()) };
+}
+
+/// This tracks how many levels deep the decoding of some type currently is. It's expected to be
+/// stored in a `static`, and [`DepthCounter::enter()`]'d each time a value of the type starts
+/// being decoded.
+#[derive(Debug, Default)]
+pub struct DepthCounter {
+    // This isn't used, but gives each counter a unique address to be keyed by (statics of zero
+    // sized types may share an address).
+    _unique: u8,
+}
+
+impl DepthCounter {
+    /// Construct a new [`DepthCounter`].
+    pub const fn new() -> Self {
+        DepthCounter { _unique: 0 }
+    }
+
+    /// Enter one more level of decoding, returning a guard which leaves it again when dropped.
+    /// An [`ErrorKind::MaxDepthExceeded`] error is returned if we are now more than `max_depth`
+    /// levels deep.
+    pub fn enter(&'static self, max_depth: usize) -> Result<DepthGuard, Error> {
+        let depth = self.increment();
+        let guard = DepthGuard { counter: self };
+        if depth > max_depth {
+            return Err(Error::new(ErrorKind::MaxDepthExceeded { max_depth }));
+        }
+        Ok(guard)
+    }
+
+    fn increment(&self) -> usize {
+        let key = self.key();
+        DEPTHS.with(|depths| {
+            let mut depths = depths.borrow_mut();
+            match depths.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, depth)) => {
+                    *depth += 1;
+                    *depth
+                }
+                None => {
+                    depths.push((key, 1));
+                    1
+                }
+            }
+        })
+    }
+
+    fn decrement(&self) {
+        let key = self.key();
+        DEPTHS.with(|depths| {
+            let mut depths = depths.borrow_mut();
+            if let Some(idx) = depths.iter().position(|(k, _)| *k == key) {
+                depths[idx].1 -= 1;
+                if depths[idx].1 == 0 {
+                    depths.swap_remove(idx);
+                }
+            }
+        })
+    }
+
+    fn key(&self) -> usize {
+        self as *const DepthCounter as usize
+    }
+}
+
+/// Returned from [`DepthCounter::enter()`]. This leaves the level of decoding that was entered
+/// when it's dropped.
+#[derive(Debug)]
+pub struct DepthGuard {
+    counter: &'static DepthCounter,
+}
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        self.counter.decrement();
+    }
+}
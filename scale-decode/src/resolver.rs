@@ -0,0 +1,526 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An [`InMemoryResolver`] is a [`TypeResolver`] whose types are described by hand, using an
+//! [`InMemoryResolverBuilder`]. This allows SCALE bytes to be decoded without needing `scale-info`
+//! or any other source of type information, which is useful when decoding formats which don't come
+//! with their own type information.
+//!
+//! ```rust
+//! use scale_decode::resolver::{InMemoryResolver, Primitive};
+//! use scale_decode::DecodeAsType;
+//!
+//! // A struct with the fields `a: u32` and `b: Vec<u8>`:
+//! let mut builder = InMemoryResolver::builder();
+//! let u32_id = builder.add_primitive(Primitive::U32);
+//! let bytes_id = builder.add_bytes();
+//! let foo_id = builder.add_struct("Foo", [("a", u32_id), ("b", bytes_id)]);
+//! let types = builder.build();
+//!
+//! let encoded = [1, 0, 0, 0, 8, 2, 3];
+//! let decoded = <(u64, Vec<u8>)>::decode_as_type(&mut &encoded[..], foo_id, &types).unwrap();
+//! assert_eq!(decoded, (1, vec![2, 3]));
+//! ```
+//!
+//! This module also provides [`ResolverExt`], which adds methods to look up the paths and names of
+//! types to any [`TypeResolver`].
+
+use crate::visitor::cache::{resolve_shape, CachedField, CachedVariant, Shape};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::marker::PhantomData;
+use scale_type_resolver::{
+    Field, FieldIter, PathIter, ResolvedTypeVisitor, TypeResolver, VariantIter,
+};
+
+pub use scale_type_resolver::{
+    BitsOrderFormat, BitsStoreFormat, Primitive, UnhandledKind, Variant,
+};
+
+/// A [`TypeResolver`] which resolves the types that were added to it via an
+/// [`InMemoryResolverBuilder`]. Any other type IDs are reported as not found.
+pub struct InMemoryResolver {
+    shapes: Vec<Shape<u32>>,
+}
+
+impl InMemoryResolver {
+    /// Construct an [`InMemoryResolverBuilder`], which types can be added to.
+    pub fn builder() -> InMemoryResolverBuilder {
+        InMemoryResolverBuilder { shapes: Vec::new(), primitives: Vec::new() }
+    }
+}
+
+impl TypeResolver for InMemoryResolver {
+    type TypeId = u32;
+    type Error = core::convert::Infallible;
+
+    fn resolve_type<'this, V: ResolvedTypeVisitor<'this, TypeId = Self::TypeId>>(
+        &'this self,
+        type_id: Self::TypeId,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.shapes.get(type_id as usize) {
+            Some(shape) => Ok(resolve_shape(shape, visitor)),
+            None => Ok(visitor.visit_not_found()),
+        }
+    }
+}
+
+/// Add types to this in order to build an [`InMemoryResolver`]. Each method returns the type ID
+/// of the type that was added, so that it can be referred to by later types.
+pub struct InMemoryResolverBuilder {
+    shapes: Vec<Shape<u32>>,
+    primitives: Vec<(Primitive, u32)>,
+}
+
+impl InMemoryResolverBuilder {
+    /// Add a primitive type. Primitive types are only added once; adding the same primitive type
+    /// again returns the existing type ID.
+    pub fn add_primitive(&mut self, primitive: Primitive) -> u32 {
+        if let Some((_, id)) = self.primitives.iter().find(|(p, _)| *p == primitive) {
+            return *id;
+        }
+        let id = self.add(Shape::Primitive(primitive));
+        self.primitives.push((primitive, id));
+        id
+    }
+    /// Add a sequence of bytes, ie a `Vec<u8>`.
+    pub fn add_bytes(&mut self) -> u32 {
+        let u8_id = self.add_primitive(Primitive::U8);
+        self.add_sequence(u8_id)
+    }
+    /// Add a struct with the given name and named fields.
+    pub fn add_struct<'a>(
+        &mut self,
+        name: &str,
+        fields: impl IntoIterator<Item = (&'a str, u32)>,
+    ) -> u32 {
+        let fields = fields.into_iter().map(|(name, id)| Field::named(id, name));
+        self.add_composite([name], fields)
+    }
+    /// Add an enum with the given name and variants. Variants are given indexes in the order
+    /// that they are provided, starting from 0. Use [`Self::add_variant()`] for more control.
+    pub fn add_enum<'a, Fields: IntoIterator<Item = Field<'a, u32>>>(
+        &mut self,
+        name: &str,
+        variants: impl IntoIterator<Item = (&'a str, Fields)>,
+    ) -> u32 {
+        let variants = variants.into_iter().enumerate().map(|(index, (name, fields))| Variant {
+            index: index as u8,
+            name,
+            fields,
+        });
+        self.add_variant([name], variants)
+    }
+    /// Add a composite type with the given path and fields.
+    pub fn add_composite<'a, 'p>(
+        &mut self,
+        path: impl IntoIterator<Item = &'p str>,
+        fields: impl IntoIterator<Item = Field<'a, u32>>,
+    ) -> u32 {
+        self.add(Shape::Composite { path: to_path(path), fields: to_fields(fields) })
+    }
+    /// Add a variant type with the given path and variants.
+    pub fn add_variant<'a, 'p, Fields: IntoIterator<Item = Field<'a, u32>>>(
+        &mut self,
+        path: impl IntoIterator<Item = &'p str>,
+        variants: impl IntoIterator<Item = Variant<'a, Fields>>,
+    ) -> u32 {
+        let variants = variants
+            .into_iter()
+            .map(|v| CachedVariant {
+                index: v.index,
+                name: v.name.to_string(),
+                fields: to_fields(v.fields),
+            })
+            .collect();
+        self.add(Shape::Variant { path: to_path(path), variants })
+    }
+    /// Add a sequence type whose items have the given type ID.
+    pub fn add_sequence(&mut self, inner: u32) -> u32 {
+        self.add(Shape::Sequence { path: Vec::new(), inner })
+    }
+    /// Add an array type of the given length, whose items have the given type ID.
+    pub fn add_array(&mut self, inner: u32, len: usize) -> u32 {
+        self.add(Shape::Array { inner, len })
+    }
+    /// Add a tuple type whose items have the given type IDs.
+    pub fn add_tuple(&mut self, type_ids: impl IntoIterator<Item = u32>) -> u32 {
+        self.add(Shape::Tuple(type_ids.into_iter().collect()))
+    }
+    /// Add a compact encoded type wrapping the given type ID.
+    pub fn add_compact(&mut self, inner: u32) -> u32 {
+        self.add(Shape::Compact(inner))
+    }
+    /// Add a bit sequence type with the given store and order formats.
+    pub fn add_bit_sequence(&mut self, store: BitsStoreFormat, order: BitsOrderFormat) -> u32 {
+        self.add(Shape::BitSequence(store, order))
+    }
+    /// Build a [`InMemoryResolver`] which can resolve each of the types added.
+    pub fn build(self) -> InMemoryResolver {
+        InMemoryResolver { shapes: self.shapes }
+    }
+
+    fn add(&mut self, shape: Shape<u32>) -> u32 {
+        self.shapes.push(shape);
+        (self.shapes.len() - 1) as u32
+    }
+}
+
+fn to_path<'a>(path: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    path.into_iter().map(ToString::to_string).collect()
+}
+
+fn to_fields<'a>(fields: impl IntoIterator<Item = Field<'a, u32>>) -> Vec<CachedField<u32>> {
+    fields
+        .into_iter()
+        .map(|f| CachedField { name: f.name.map(ToString::to_string), id: f.id })
+        .collect()
+}
+
+/// Extra methods which are available on every [`TypeResolver`], for looking up information about
+/// types beyond what's needed to decode them. Visitors can use these to special-case types based
+/// on their paths, eg to handle any `AccountId32` in some particular way:
+///
+/// ```rust
+/// use scale_decode::resolver::{InMemoryResolver, Primitive, ResolverExt};
+///
+/// let mut builder = InMemoryResolver::builder();
+/// let u8_id = builder.add_primitive(Primitive::U8);
+/// let bytes_id = builder.add_array(u8_id, 32);
+/// let account_id = builder.add_composite(
+///     ["sp_core", "crypto", "AccountId32"],
+///     [scale_decode::Field::unnamed(bytes_id)],
+/// );
+/// let types = builder.build();
+///
+/// assert_eq!(types.type_path(account_id), Some(vec!["sp_core", "crypto", "AccountId32"]));
+/// assert_eq!(types.type_name(account_id), Some("AccountId32"));
+/// assert_eq!(types.type_name(u8_id), None);
+/// ```
+pub trait ResolverExt: TypeResolver {
+    /// Return the path to the given type, including its name. Only composite, variant and
+    /// sequence types can have paths. `None` is returned if the type has no path or can't be
+    /// resolved.
+    fn type_path(&self, type_id: Self::TypeId) -> Option<Vec<&str>>;
+    /// Return the name of the given type, which is the last item in its path. See
+    /// [`ResolverExt::type_path()`].
+    fn type_name(&self, type_id: Self::TypeId) -> Option<&str>;
+}
+
+impl<R: TypeResolver> ResolverExt for R {
+    fn type_path(&self, type_id: Self::TypeId) -> Option<Vec<&str>> {
+        let path = self.resolve_type(type_id, PathVisitor(PhantomData)).ok()?;
+        (!path.is_empty()).then_some(path)
+    }
+    fn type_name(&self, type_id: Self::TypeId) -> Option<&str> {
+        self.type_path(type_id)?.pop()
+    }
+}
+
+// Collect the path of whatever type is resolved.
+struct PathVisitor<'resolver, TypeId>(PhantomData<(&'resolver (), TypeId)>);
+
+impl<'resolver, TypeId: scale_type_resolver::TypeId + 'static> ResolvedTypeVisitor<'resolver>
+    for PathVisitor<'resolver, TypeId>
+{
+    type TypeId = TypeId;
+    type Value = Vec<&'resolver str>;
+
+    fn visit_unhandled(self, _kind: UnhandledKind) -> Self::Value {
+        Vec::new()
+    }
+    fn visit_composite<Path, Fields>(self, path: Path, _fields: Fields) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+        Fields: FieldIter<'resolver, Self::TypeId>,
+    {
+        path.collect()
+    }
+    fn visit_variant<Path, Fields, Var>(self, path: Path, _variants: Var) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+        Fields: FieldIter<'resolver, Self::TypeId>,
+        Var: VariantIter<'resolver, Fields>,
+    {
+        path.collect()
+    }
+    fn visit_sequence<Path>(self, path: Path, _type_id: Self::TypeId) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+    {
+        path.collect()
+    }
+}
+
+/// Extra information about a field, beyond what's needed to decode it. See [`FieldInfoResolver`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldInfo<'resolver> {
+    /// The name of the field's type as it was written in the source code, if known.
+    pub type_name: Option<&'resolver str>,
+    /// Documentation for the field, one line per item.
+    pub docs: Vec<&'resolver str>,
+}
+
+/// A [`TypeResolver`] which may be able to provide extra information about the fields of
+/// composite and variant types, which is then exposed via
+/// [`CompositeField::info()`](crate::visitor::types::CompositeField::info). This is useful for
+/// things like building user interfaces from dynamically decoded values.
+///
+/// Every method has a default implementation which returns no information, so resolvers which
+/// have nothing extra to offer can implement this trait with an empty `impl` block.
+pub trait FieldInfoResolver: TypeResolver {
+    /// Return information about a field of the given composite type, or of the variant with the
+    /// given index if the type is a variant type. `field_index` is the position of the field
+    /// within the composite or variant.
+    fn field_info(
+        &self,
+        _type_id: Self::TypeId,
+        _variant_index: Option<u8>,
+        _field_index: usize,
+    ) -> Option<FieldInfo<'_>> {
+        None
+    }
+}
+
+impl FieldInfoResolver for InMemoryResolver {}
+
+#[cfg(feature = "scale-info")]
+impl FieldInfoResolver for scale_info::PortableRegistry {
+    fn field_info(
+        &self,
+        type_id: Self::TypeId,
+        variant_index: Option<u8>,
+        field_index: usize,
+    ) -> Option<FieldInfo<'_>> {
+        use scale_info::TypeDef;
+
+        let ty = self.resolve(type_id)?;
+        let fields = match (&ty.type_def, variant_index) {
+            (TypeDef::Composite(c), None) => &c.fields,
+            (TypeDef::Variant(v), Some(index)) => {
+                &v.variants.iter().find(|v| v.index == index)?.fields
+            }
+            _ => return None,
+        };
+        let field = fields.get(field_index)?;
+
+        // Depending on the features enabled in scale-info, these strings may be `&'static str`
+        // or `String`, so we go via `AsRef<str>` to handle either.
+        Some(FieldInfo {
+            type_name: field.type_name.as_ref().map(AsRef::as_ref),
+            docs: field.docs.iter().map(AsRef::as_ref).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{visitor::DecodeError, DecodeAsType};
+    use alloc::vec;
+
+    #[test]
+    fn mock_resolver_resolves_types() {
+        let mut builder = InMemoryResolver::builder();
+        let u8_id = builder.add_primitive(Primitive::U8);
+        let bytes_id = builder.add_sequence(u8_id);
+        let pair_id = builder.add_tuple([u8_id, bytes_id]);
+        let array_id = builder.add_array(pair_id, 2);
+        let foo_id =
+            builder.add_composite(["Foo"], [Field::named(u8_id, "a"), Field::named(array_id, "b")]);
+        let types = builder.build();
+
+        let bytes = [1, 7, 8, 1, 2, 8, 0];
+        let decoded = <(u8, [(u8, Vec<u8>); 2])>::decode_as_type(&mut &bytes[..], foo_id, &types);
+        assert_eq!(decoded.unwrap(), (1, [(7, vec![1, 2]), (8, vec![])]));
+
+        let err = u8::decode_as_type(&mut &bytes[..], 123, &types).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::VisitorDecodeError(DecodeError::TypeIdNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn convenience_constructors_work() {
+        let mut builder = InMemoryResolver::builder();
+        let bool_id = builder.add_primitive(Primitive::Bool);
+        let bytes_id = builder.add_bytes();
+        let foo_id = builder.add_enum(
+            "Foo",
+            [("A", vec![]), ("B", vec![Field::unnamed(bool_id), Field::unnamed(bytes_id)])],
+        );
+
+        // Primitives are only added once:
+        assert_eq!(builder.add_primitive(Primitive::Bool), bool_id);
+        let types = builder.build();
+
+        // Variants are indexed in order:
+        let size_of = |bytes: &[u8]| crate::visitor::encoded_size_of(bytes, foo_id, &types);
+        assert_eq!(size_of(&[0]), Ok(1));
+        assert_eq!(size_of(&[1, 1, 4, 9]), Ok(4));
+        assert_eq!(size_of(&[2]), Err(DecodeError::VariantNotFound(2)));
+    }
+
+    #[test]
+    fn type_paths_can_be_looked_up() {
+        #[derive(scale_info::TypeInfo)]
+        #[allow(dead_code)]
+        struct AccountId32([u8; 32]);
+        #[derive(scale_info::TypeInfo)]
+        #[allow(dead_code)]
+        enum MultiAddress {
+            Id(AccountId32),
+            Index(u32),
+        }
+
+        let m = scale_info::MetaType::new::<MultiAddress>();
+        let mut types = scale_info::Registry::new();
+        let address_id = types.register_type(&m).id;
+        let types: scale_info::PortableRegistry = types.into();
+
+        assert_eq!(
+            types.type_path(address_id),
+            Some(vec!["scale_decode", "resolver", "test", "MultiAddress"])
+        );
+        assert_eq!(types.type_name(address_id), Some("MultiAddress"));
+
+        // Visitors can look up the paths of the types that they are given via the resolver:
+        struct NameVisitor;
+        impl crate::Visitor for NameVisitor {
+            type Value<'scale, 'resolver> = Option<&'resolver str>;
+            type Error = DecodeError;
+            type TypeResolver = scale_info::PortableRegistry;
+
+            fn visit_variant<'scale, 'resolver>(
+                self,
+                value: &mut crate::visitor::types::Variant<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: crate::visitor::TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                let field_id = value.fields().fields()[0].id;
+                Ok(value.types().type_name(field_id))
+            }
+        }
+
+        let bytes = [0; 33];
+        let name =
+            crate::visitor::decode_with_visitor(&mut &bytes[..], address_id, &types, NameVisitor);
+        assert_eq!(name.unwrap(), Some("AccountId32"));
+
+        // Types without paths, or which don't exist, have no name:
+        let mut builder = InMemoryResolver::builder();
+        let u8_id = builder.add_primitive(Primitive::U8);
+        let bytes_id = builder.add_bytes();
+        let types = builder.build();
+        assert_eq!(types.type_path(u8_id), None);
+        assert_eq!(types.type_path(bytes_id), None);
+        assert_eq!(types.type_path(123), None);
+    }
+
+    // Collect the info for each field of a composite or variant.
+    struct FieldInfoVisitor<R>(PhantomData<R>);
+    impl<R: FieldInfoResolver> crate::Visitor for FieldInfoVisitor<R> {
+        type Value<'scale, 'resolver> = Vec<Option<FieldInfo<'resolver>>>;
+        type Error = DecodeError;
+        type TypeResolver = R;
+
+        fn visit_composite<'scale, 'resolver>(
+            self,
+            value: &mut crate::visitor::types::Composite<'scale, 'resolver, R>,
+            _type_id: crate::visitor::TypeIdFor<Self>,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            value.map(|f| f.map(|f| f.info())).collect()
+        }
+        fn visit_variant<'scale, 'resolver>(
+            self,
+            value: &mut crate::visitor::types::Variant<'scale, 'resolver, R>,
+            type_id: crate::visitor::TypeIdFor<Self>,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            self.visit_composite(value.fields(), type_id)
+        }
+    }
+
+    #[test]
+    fn field_info_is_none_when_resolver_has_none() {
+        let mut builder = InMemoryResolver::builder();
+        let u8_id = builder.add_primitive(Primitive::U8);
+        let foo_id = builder.add_struct("Foo", [("a", u8_id)]);
+        let types = builder.build();
+
+        let visitor = FieldInfoVisitor(PhantomData);
+        let infos = crate::visitor::decode_with_visitor(&mut &[1u8][..], foo_id, &types, visitor);
+        assert_eq!(infos.unwrap(), vec![None]);
+    }
+
+    #[cfg(feature = "scale-info")]
+    #[test]
+    fn field_info_comes_from_portable_registry() {
+        #[allow(dead_code)]
+        #[derive(scale_info::TypeInfo)]
+        struct Foo {
+            /// The first field.
+            a: u8,
+            b: Vec<u8>,
+        }
+        #[allow(dead_code)]
+        #[derive(scale_info::TypeInfo)]
+        enum Bar {
+            A,
+            B {
+                /// A bool.
+                /// Over two lines.
+                c: bool,
+            },
+        }
+
+        let mut types = scale_info::Registry::new();
+        let foo_id = types.register_type(&scale_info::MetaType::new::<Foo>()).id;
+        let bar_id = types.register_type(&scale_info::MetaType::new::<Bar>()).id;
+        let types: scale_info::PortableRegistry = types.into();
+
+        let bytes = [1u8, 4, 2];
+        let infos = crate::visitor::decode_with_visitor(
+            &mut &bytes[..],
+            foo_id,
+            &types,
+            FieldInfoVisitor(PhantomData),
+        );
+        assert_eq!(
+            infos.unwrap(),
+            vec![
+                Some(FieldInfo { type_name: Some("u8"), docs: vec!["The first field."] }),
+                Some(FieldInfo { type_name: Some("Vec<u8>"), docs: vec![] }),
+            ]
+        );
+
+        let bytes = [1u8, 1];
+        let infos = crate::visitor::decode_with_visitor(
+            &mut &bytes[..],
+            bar_id,
+            &types,
+            FieldInfoVisitor(PhantomData),
+        );
+        assert_eq!(
+            infos.unwrap(),
+            vec![Some(FieldInfo {
+                type_name: Some("bool"),
+                docs: vec!["A bool.", "Over two lines."]
+            })]
+        );
+    }
+}
@@ -0,0 +1,982 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Check whether some type can be decoded from values of a given type ID without needing any
+//! bytes to decode. Types implement [`ShapeOf`] to describe the [`Shape`] of values that they
+//! expect to be decoded from, and [`check_decodable()`] walks this alongside the resolved type,
+//! returning a [`CompatibilityError`] at the first place that they don't line up.
+//!
+//! This is useful for checking a set of types against some new metadata up front, rather than
+//! finding out that they no longer line up on the first decode:
+//!
+//! ```rust
+//! use scale_decode::shape::{check_decodable, ShapeOf};
+//! use scale_decode::DecodeAsType;
+//! use scale_info::{PortableRegistry, TypeInfo};
+//!
+//! #[derive(TypeInfo)]
+//! struct Foo {
+//!     a: u8,
+//!     b: Vec<bool>,
+//! }
+//!
+//! #[derive(DecodeAsType)]
+//! #[decode_as_type(shape)]
+//! struct Bar {
+//!     b: Vec<bool>,
+//! }
+//!
+//! #[derive(DecodeAsType)]
+//! #[decode_as_type(shape)]
+//! struct Wibble {
+//!     b: String,
+//! }
+//!
+//! let m = scale_info::MetaType::new::<Foo>();
+//! let mut types = scale_info::Registry::new();
+//! let type_id = types.register_type(&m).id;
+//! let types: PortableRegistry = types.into();
+//!
+//! assert!(check_decodable::<Bar, _>(type_id, &types).is_ok());
+//! assert!(check_decodable::<Wibble, _>(type_id, &types).is_err());
+//! ```
+//!
+//! Shapes can also be inspected directly, or displayed in a Rust-like syntax, to see what some type
+//! will accept. This is useful for documenting types, or comparing the shapes of types over time:
+//!
+//! ```rust
+//! use scale_decode::shape::Shape;
+//! use scale_decode::DecodeAsType;
+//!
+//! #[derive(DecodeAsType)]
+//! #[decode_as_type(shape)]
+//! enum Event {
+//!     Transfer { to: [u8; 32], amount: u128 },
+//!     Burn(Option<u64>),
+//! }
+//!
+//! assert_eq!(
+//!     Shape::of::<Event>().to_string(),
+//!     "enum Event { Transfer { to: [number; 32], amount: number }, Burn(enum { None, Some(number) }) }"
+//! );
+//! ```
+//!
+//! A successful check doesn't guarantee that every value will decode; things like numeric ranges,
+//! sequence lengths and any validation functions can only be checked once there are some bytes.
+
+use crate::{
+    error::{Context, Location},
+    visitor::{cache, DecodeError},
+    Ignored, MaybeCompact,
+};
+use alloc::{
+    borrow::{Cow, ToOwned},
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque},
+    format,
+    rc::Rc,
+    string::String,
+    sync::Arc,
+    vec,
+    vec::Vec,
+};
+use codec::Compact;
+use core::fmt::Display;
+use core::marker::PhantomData;
+use core::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize, Saturating, Wrapping,
+};
+use core::ops::{Range, RangeFrom, RangeInclusive, RangeTo, RangeToInclusive};
+use core::time::Duration;
+use scale_bits::Bits;
+use scale_type_resolver::{Primitive, TypeResolver};
+
+// How many single field composites, tuples and compact wrappers we'll look through when
+// trying to find a type which lines up with what is expected.
+const MAX_WRAPPERS: usize = 32;
+
+/// Implemented by types which can describe the [`Shape`] of values that they expect to be decoded
+/// from. This can be derived alongside [`crate::DecodeAsType`] via the `#[decode_as_type(shape)]`
+/// attribute.
+pub trait ShapeOf {
+    /// Describe the shape of values that this type can be decoded from.
+    fn shape() -> Shape;
+}
+
+/// A function returning the [`Shape`] of some type. Nested shapes are handed back as functions so
+/// that recursive types can describe themselves.
+pub type ShapeFn = fn() -> Shape;
+
+/// A description of the values that some type expects to be decoded from.
+#[derive(Debug, Clone)]
+pub enum Shape {
+    /// Any value is accepted.
+    Any,
+    /// A boolean.
+    Bool,
+    /// A char.
+    Char,
+    /// A string.
+    Str,
+    /// Any number, which may be compact encoded.
+    Number,
+    /// A sequence of bits.
+    BitSequence,
+    /// A sequence of items of the given shape. Arrays are also accepted.
+    Sequence(ShapeFn),
+    /// An array of the given length whose items have the given shape. Sequences are also accepted,
+    /// since their length can only be checked when decoding.
+    Array(ShapeFn, usize),
+    /// A tuple whose entries have the given shapes. Composites, arrays and sequences with the
+    /// right number of items are also accepted.
+    Tuple(Vec<ShapeFn>),
+    /// A composite type with the given fields.
+    Composite(CompositeShape),
+    /// A variant type whose variants must be among those given.
+    Variant(Vec<VariantShape>),
+    /// The shape of some named type, like a struct or enum. Values are accepted if they line up
+    /// with the inner shape; the name is only used to describe it.
+    Named {
+        /// The name of the type.
+        name: &'static str,
+        /// The shape of the type.
+        shape: Box<Shape>,
+    },
+}
+
+impl Shape {
+    /// Describe the shape of some type `T`.
+    pub fn of<T: ShapeOf + ?Sized>() -> Shape {
+        T::shape()
+    }
+    /// The name of the type that this shape describes, if it's a [`Shape::Named`] shape.
+    pub fn name(&self) -> Option<&'static str> {
+        match self {
+            Shape::Named { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+    /// Give some shape the name of the type that it describes.
+    pub fn named(name: &'static str, shape: Shape) -> Shape {
+        Shape::Named { name, shape: Box::new(shape) }
+    }
+    /// Return the shape, looking through any [`Shape::Named`] wrapper.
+    pub fn without_name(&self) -> &Shape {
+        match self {
+            Shape::Named { shape, .. } => shape.without_name(),
+            shape => shape,
+        }
+    }
+
+    // A short description of the shape, to use in errors.
+    fn description(&self) -> &'static str {
+        match self {
+            Shape::Named { shape, .. } => shape.description(),
+            Shape::Any => "anything",
+            Shape::Bool => "a bool",
+            Shape::Char => "a char",
+            Shape::Str => "a string",
+            Shape::Number => "a number",
+            Shape::BitSequence => "a bit sequence",
+            Shape::Sequence(_) => "a sequence",
+            Shape::Array(..) => "an array",
+            Shape::Tuple(_) => "a tuple",
+            Shape::Composite(_) => "a composite",
+            Shape::Variant(_) => "a variant",
+        }
+    }
+}
+
+// Shapes are displayed in a Rust-like syntax, with nested shapes shown in full (other than types
+// which contain themselves, which are shown by name the second time around), for instance
+// `struct Foo { a: number, b: [Bar(bool); 2], c: enum { None, Some(str) } }`.
+impl Display for Shape {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        ShapeDisplay { shape: self, stack: &mut Vec::new() }.fmt(f)
+    }
+}
+
+struct ShapeDisplay<'a> {
+    shape: &'a Shape,
+    // The shapes that we're in the middle of displaying.
+    stack: &'a mut Vec<usize>,
+}
+
+impl ShapeDisplay<'_> {
+    fn fmt(&mut self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.shape {
+            Shape::Any => f.write_str("_"),
+            Shape::Bool => f.write_str("bool"),
+            Shape::Char => f.write_str("char"),
+            Shape::Str => f.write_str("str"),
+            Shape::Number => f.write_str("number"),
+            Shape::BitSequence => f.write_str("bits"),
+            Shape::Sequence(item) => {
+                f.write_str("[")?;
+                self.fmt_nested(*item, f)?;
+                f.write_str("]")
+            }
+            Shape::Array(item, len) => {
+                f.write_str("[")?;
+                self.fmt_nested(*item, f)?;
+                write!(f, "; {len}]")
+            }
+            Shape::Tuple(items) => {
+                f.write_str("(")?;
+                for (idx, item) in items.iter().enumerate() {
+                    if idx != 0 {
+                        f.write_str(", ")?;
+                    }
+                    self.fmt_nested(*item, f)?;
+                }
+                if items.len() == 1 {
+                    f.write_str(",")?;
+                }
+                f.write_str(")")
+            }
+            Shape::Composite(composite) => {
+                f.write_str("struct")?;
+                self.fmt_fields(composite, f)
+            }
+            Shape::Variant(variants) => {
+                f.write_str("enum ")?;
+                self.fmt_variants(variants, f)
+            }
+            Shape::Named { name, shape } => match &**shape {
+                Shape::Composite(composite) => {
+                    write!(f, "struct {name}")?;
+                    self.fmt_fields(composite, f)
+                }
+                Shape::Variant(variants) => {
+                    write!(f, "enum {name} ")?;
+                    self.fmt_variants(variants, f)
+                }
+                shape => {
+                    write!(f, "{name} = ")?;
+                    ShapeDisplay { shape, stack: self.stack }.fmt(f)
+                }
+            },
+        }
+    }
+
+    fn fmt_nested(
+        &mut self,
+        shape: ShapeFn,
+        f: &mut core::fmt::Formatter<'_>,
+    ) -> core::fmt::Result {
+        let key = shape as usize;
+        let shape = shape();
+        if self.stack.contains(&key) {
+            return f.write_str(shape.name().unwrap_or(".."));
+        }
+        self.stack.push(key);
+        ShapeDisplay { shape: &shape, stack: self.stack }.fmt(f)?;
+        self.stack.pop();
+        Ok(())
+    }
+
+    fn fmt_variants(
+        &mut self,
+        variants: &[VariantShape],
+        f: &mut core::fmt::Formatter<'_>,
+    ) -> core::fmt::Result {
+        f.write_str("{ ")?;
+        for (idx, variant) in variants.iter().enumerate() {
+            if idx != 0 {
+                f.write_str(", ")?;
+            }
+            f.write_str(variant.name)?;
+            if !variant.fields.fields.is_empty() {
+                self.fmt_fields(&variant.fields, f)?;
+            }
+        }
+        f.write_str(" }")
+    }
+
+    // Fields are written with a leading space if they're named, like `{ a: bool }`, and
+    // without if not, like `(bool)`.
+    fn fmt_fields(
+        &mut self,
+        composite: &CompositeShape,
+        f: &mut core::fmt::Formatter<'_>,
+    ) -> core::fmt::Result {
+        let named = composite.fields.iter().any(|field| field.name.is_some());
+        f.write_str(if named { " { " } else { "(" })?;
+        for (idx, field) in composite.fields.iter().enumerate() {
+            if idx != 0 {
+                f.write_str(", ")?;
+            }
+            if let Some(name) = field.name {
+                write!(f, "{name}: ")?;
+            }
+            self.fmt_nested(field.shape, f)?;
+        }
+        if composite.allow_extra_fields {
+            f.write_str(if composite.fields.is_empty() { ".." } else { ", .." })?;
+        }
+        f.write_str(if named { " }" } else { ")" })
+    }
+}
+
+/// The fields that a composite type or variant expects.
+#[derive(Debug, Clone, Default)]
+pub struct CompositeShape {
+    /// The fields expected. If these are named, they are looked up by name in any composite with
+    /// named fields, and else they are expected in order.
+    pub fields: Vec<FieldShape>,
+    /// If true, more fields than those expected are allowed when fields are found in order.
+    pub allow_extra_fields: bool,
+}
+
+impl CompositeShape {
+    /// Construct a new [`CompositeShape`] expecting exactly the fields given.
+    pub fn new(fields: Vec<FieldShape>) -> Self {
+        CompositeShape { fields, allow_extra_fields: false }
+    }
+}
+
+/// A single field in a [`CompositeShape`].
+#[derive(Debug, Clone)]
+pub struct FieldShape {
+    /// The name of the field, if it's named.
+    pub name: Option<&'static str>,
+    /// The shape of the field.
+    pub shape: ShapeFn,
+}
+
+impl FieldShape {
+    /// A named field of the given shape.
+    pub fn named(name: &'static str, shape: ShapeFn) -> Self {
+        FieldShape { name: Some(name), shape }
+    }
+    /// An unnamed field of the given shape.
+    pub fn unnamed(shape: ShapeFn) -> Self {
+        FieldShape { name: None, shape }
+    }
+}
+
+/// A single variant in a [`Shape::Variant`].
+#[derive(Debug, Clone)]
+pub struct VariantShape {
+    /// The name of the variant.
+    pub name: &'static str,
+    /// The fields of the variant.
+    pub fields: CompositeShape,
+}
+
+impl VariantShape {
+    /// A variant with the given name and fields.
+    pub fn new(name: &'static str, fields: CompositeShape) -> Self {
+        VariantShape { name, fields }
+    }
+}
+
+/// Check that values of the given type ID could be decoded into `T`, without needing any bytes
+/// to decode.
+pub fn check_decodable<T: ShapeOf, R: TypeResolver>(
+    type_id: R::TypeId,
+    types: &R,
+) -> Result<(), CompatibilityError> {
+    Checker { types, in_progress: BTreeSet::new() }.check(T::shape, type_id, 0)
+}
+
+struct Checker<'r, R> {
+    types: &'r R,
+    // The type IDs and expected shapes that we're in the middle of checking. If we come across
+    // one of these again then the types are recursive, and we assume that they line up.
+    in_progress: BTreeSet<(String, usize)>,
+}
+
+impl<R: TypeResolver> Checker<'_, R> {
+    fn check(
+        &mut self,
+        expected: ShapeFn,
+        type_id: R::TypeId,
+        wrappers: usize,
+    ) -> Result<(), CompatibilityError> {
+        let key = (format!("{type_id:?}"), expected as usize);
+        if !self.in_progress.insert(key.clone()) {
+            return Ok(());
+        }
+        let res = self.check_shape(&expected(), type_id, wrappers);
+        self.in_progress.remove(&key);
+        res
+    }
+
+    fn check_shape(
+        &mut self,
+        expected: &Shape,
+        type_id: R::TypeId,
+        wrappers: usize,
+    ) -> Result<(), CompatibilityError> {
+        let found = cache::resolve_owned_shape(type_id, self.types)
+            .map_err(|e| CompatibilityError::new(CompatibilityErrorKind::CannotResolveType(e)))?;
+
+        let res = self.check_resolved(expected, &found);
+        if res.is_ok() || wrappers >= MAX_WRAPPERS {
+            return res;
+        }
+
+        // Like the visitors do, look through compact encoding and single field composites and
+        // tuples to find something which lines up with what we expect.
+        let inner = match &found {
+            cache::Shape::Compact(inner) => inner,
+            cache::Shape::Composite { fields, .. } if fields.len() == 1 => &fields[0].id,
+            cache::Shape::Tuple(ids) if ids.len() == 1 => &ids[0],
+            _ => return res,
+        };
+        self.check_shape(expected, inner.clone(), wrappers + 1).map_err(|_| {
+            res.expect_err("result was checked to be an error above; please file a bug report")
+        })
+    }
+
+    fn check_resolved(
+        &mut self,
+        expected: &Shape,
+        found: &cache::Shape<R::TypeId>,
+    ) -> Result<(), CompatibilityError> {
+        use cache::Shape as Found;
+
+        match (expected, found) {
+            (Shape::Named { shape, .. }, _) => self.check_resolved(shape, found),
+            (Shape::Any, _)
+            | (Shape::Bool, Found::Primitive(Primitive::Bool))
+            | (Shape::Char, Found::Primitive(Primitive::Char))
+            | (Shape::Str, Found::Primitive(Primitive::Str))
+            | (Shape::BitSequence, Found::BitSequence(..)) => Ok(()),
+            (Shape::Number, Found::Primitive(p)) if is_number(p) => Ok(()),
+            (Shape::Sequence(item), Found::Sequence { inner, .. } | Found::Array { inner, .. })
+            | (Shape::Array(item, _), Found::Sequence { inner, .. }) => {
+                self.check(*item, inner.clone(), 0).map_err(|e| e.at_idx(0))
+            }
+            (Shape::Array(item, len), Found::Array { inner, len: found_len }) => {
+                if len != found_len {
+                    return Err(CompatibilityError::new(CompatibilityErrorKind::WrongLength {
+                        expected: *len,
+                        found: *found_len,
+                    }));
+                }
+                self.check(*item, inner.clone(), 0).map_err(|e| e.at_idx(0))
+            }
+            (Shape::Tuple(items), Found::Sequence { inner, .. }) => {
+                self.check_items(items, items.iter().map(|_| inner))
+            }
+            (Shape::Tuple(items), Found::Array { inner, len }) => {
+                check_len(items.len(), *len, false)?;
+                self.check_items(items, items.iter().map(|_| inner))
+            }
+            (Shape::Tuple(items), Found::Composite { fields, .. }) => {
+                check_len(items.len(), fields.len(), false)?;
+                self.check_items(items, fields.iter().map(|f| &f.id))
+            }
+            (Shape::Tuple(items), Found::Tuple(ids)) => {
+                check_len(items.len(), ids.len(), false)?;
+                self.check_items(items, ids.iter())
+            }
+            // A 1-tuple can also be decoded from anything its entry can be decoded from.
+            (Shape::Tuple(items), _) if items.len() == 1 => self.check_resolved(&items[0](), found),
+            (Shape::Composite(composite), Found::Composite { fields, .. }) => {
+                let fields: Vec<_> = fields.iter().map(|f| (f.name.as_deref(), &f.id)).collect();
+                self.check_fields(composite, &fields)
+            }
+            (Shape::Composite(composite), Found::Tuple(ids)) => {
+                let fields: Vec<_> = ids.iter().map(|id| (None, id)).collect();
+                self.check_fields(composite, &fields)
+            }
+            (Shape::Variant(variants), Found::Variant { variants: found_variants, .. }) => {
+                for found_variant in found_variants {
+                    let Some(variant) = variants.iter().find(|v| v.name == found_variant.name)
+                    else {
+                        return Err(CompatibilityError::new(
+                            CompatibilityErrorKind::CannotFindVariant {
+                                name: found_variant.name.clone(),
+                                expected: variants.iter().map(|v| v.name).collect(),
+                            },
+                        ));
+                    };
+                    let fields: Vec<_> =
+                        found_variant.fields.iter().map(|f| (f.name.as_deref(), &f.id)).collect();
+                    self.check_fields(&variant.fields, &fields)
+                        .map_err(|e| e.at_variant(variant.name))?;
+                }
+                Ok(())
+            }
+            // Variants without any fields can also be decoded from their index.
+            (
+                Shape::Variant(variants),
+                Found::Primitive(Primitive::U8 | Primitive::U16 | Primitive::U32),
+            ) if variants.iter().all(|v| v.fields.fields.is_empty()) => Ok(()),
+            _ => Err(CompatibilityError::new(CompatibilityErrorKind::WrongShape {
+                expected: expected.description(),
+                found: found_description(found),
+            })),
+        }
+    }
+
+    // Check each of the items given against the corresponding type ID.
+    fn check_items<'a>(
+        &mut self,
+        items: &[ShapeFn],
+        ids: impl Iterator<Item = &'a R::TypeId>,
+    ) -> Result<(), CompatibilityError> {
+        for (idx, (item, id)) in items.iter().zip(ids).enumerate() {
+            self.check(*item, id.clone(), 0).map_err(|e| e.at_idx(idx))?;
+        }
+        Ok(())
+    }
+
+    // Check the fields we expect against the (optionally named) fields found.
+    fn check_fields(
+        &mut self,
+        expected: &CompositeShape,
+        found: &[(Option<&str>, &R::TypeId)],
+    ) -> Result<(), CompatibilityError> {
+        let expected_named = expected.fields.iter().any(|f| f.name.is_some());
+        let found_named = found.iter().any(|(name, _)| name.is_some());
+
+        // Named fields are looked up by name when we're given named fields.
+        if expected_named && found_named {
+            for field in &expected.fields {
+                let name = field.name.unwrap_or_default();
+                let Some((_, id)) = found.iter().find(|(n, _)| *n == Some(name)) else {
+                    return Err(CompatibilityError::new(CompatibilityErrorKind::CannotFindField {
+                        name,
+                    }));
+                };
+                self.check(field.shape, (*id).clone(), 0).map_err(|e| e.at_field(name))?;
+            }
+            return Ok(());
+        }
+
+        // Otherwise, fields are decoded in order.
+        check_len(expected.fields.len(), found.len(), expected.allow_extra_fields)?;
+        for (idx, (field, (_, id))) in expected.fields.iter().zip(found).enumerate() {
+            let res = self.check(field.shape, (*id).clone(), 0);
+            match field.name {
+                Some(name) => res.map_err(|e| e.at_field(name))?,
+                None => res.map_err(|e| e.at_idx(idx))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_len(expected: usize, found: usize, allow_extra: bool) -> Result<(), CompatibilityError> {
+    if found == expected || (allow_extra && found > expected) {
+        Ok(())
+    } else {
+        Err(CompatibilityError::new(CompatibilityErrorKind::WrongLength { expected, found }))
+    }
+}
+
+fn is_number(p: &Primitive) -> bool {
+    !matches!(p, Primitive::Bool | Primitive::Char | Primitive::Str)
+}
+
+fn found_description<TypeId>(found: &cache::Shape<TypeId>) -> &'static str {
+    match found {
+        cache::Shape::Composite { .. } => "a composite",
+        cache::Shape::Variant { .. } => "a variant",
+        cache::Shape::Sequence { .. } => "a sequence",
+        cache::Shape::Array { .. } => "an array",
+        cache::Shape::Tuple(_) => "a tuple",
+        cache::Shape::Compact(_) => "a compact encoded value",
+        cache::Shape::BitSequence(..) => "a bit sequence",
+        cache::Shape::Primitive(Primitive::Bool) => "a bool",
+        cache::Shape::Primitive(Primitive::Char) => "a char",
+        cache::Shape::Primitive(Primitive::Str) => "a string",
+        cache::Shape::Primitive(_) => "a number",
+    }
+}
+
+/// An error handed back from [`check_decodable()`] if some type can't be decoded from values of
+/// the given type ID.
+#[derive(Debug)]
+pub struct CompatibilityError {
+    context: Context,
+    kind: CompatibilityErrorKind,
+}
+
+impl core::error::Error for CompatibilityError {}
+
+impl CompatibilityError {
+    /// Construct a new error given a kind.
+    pub fn new(kind: CompatibilityErrorKind) -> CompatibilityError {
+        CompatibilityError { context: Context::new(), kind }
+    }
+    /// Retrieve more information about what went wrong.
+    pub fn kind(&self) -> &CompatibilityErrorKind {
+        &self.kind
+    }
+    /// Retrieve details about where the error occurred.
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+    /// Note which sequence index the error occurred in.
+    pub fn at_idx(mut self, idx: usize) -> Self {
+        self.context.push(Location::idx(idx));
+        self
+    }
+    /// Note which field the error occurred in.
+    pub fn at_field(mut self, field: impl Into<Cow<'static, str>>) -> Self {
+        self.context.push(Location::field(field));
+        self
+    }
+    /// Note which variant the error occurred in.
+    pub fn at_variant(mut self, variant: impl Into<Cow<'static, str>>) -> Self {
+        self.context.push(Location::variant(variant));
+        self
+    }
+}
+
+impl Display for CompatibilityError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let path = self.context.path();
+        let kind = &self.kind;
+        write!(f, "Incompatible type at {path}: {kind}")
+    }
+}
+
+/// The underlying nature of a [`CompatibilityError`].
+#[derive(Debug, thiserror::Error)]
+pub enum CompatibilityErrorKind {
+    /// The type ID given, or some type ID that it refers to, could not be resolved.
+    #[error("Cannot resolve type: {_0}")]
+    CannotResolveType(DecodeError),
+    /// The type found doesn't have the expected shape.
+    #[error("Expected {expected} but found {found}")]
+    WrongShape {
+        /// What we expected to find.
+        expected: &'static str,
+        /// What we found instead.
+        found: &'static str,
+    },
+    /// A named field that we expect doesn't exist.
+    #[error("Cannot find field {name}")]
+    CannotFindField {
+        /// The name of the field.
+        name: &'static str,
+    },
+    /// A variant exists which we can't decode into.
+    #[error("Cannot decode variant {name}; expects one of {expected:?}")]
+    CannotFindVariant {
+        /// The name of the variant.
+        name: String,
+        /// The variants that can be decoded.
+        expected: Vec<&'static str>,
+    },
+    /// The number of fields or items found doesn't line up with the number expected.
+    #[error("Expected {expected} fields or items but found {found}")]
+    WrongLength {
+        /// The number expected.
+        expected: usize,
+        /// The number found.
+        found: usize,
+    },
+}
+
+macro_rules! impl_shape_of {
+    ($shape:expr => $($ty:ty),*) => {
+        $(
+            impl ShapeOf for $ty {
+                fn shape() -> Shape {
+                    $shape
+                }
+            }
+        )*
+    };
+}
+impl_shape_of!(Shape::Bool => bool);
+impl_shape_of!(Shape::Char => char);
+impl_shape_of!(Shape::Str => String, str);
+impl_shape_of!(Shape::BitSequence => Bits);
+impl_shape_of!(Shape::Any => Ignored, Duration);
+impl_shape_of!(Shape::Number => u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+impl_shape_of!(Shape::Number => NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU128, NonZeroUsize);
+impl_shape_of!(Shape::Number => NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize);
+
+// Types which expect the same shape as some other type.
+macro_rules! impl_shape_of_like {
+    ($target:ident < $param:ident > as $source:ty) => {
+        impl<$param: ShapeOf> ShapeOf for $target<$param> {
+            fn shape() -> Shape {
+                <$source>::shape()
+            }
+        }
+    };
+}
+impl_shape_of_like!(Compact<T> as T);
+impl_shape_of_like!(MaybeCompact<T> as T);
+impl_shape_of_like!(Arc<T> as T);
+impl_shape_of_like!(Rc<T> as T);
+impl_shape_of_like!(Box<T> as T);
+impl_shape_of_like!(Wrapping<T> as T);
+impl_shape_of_like!(Saturating<T> as T);
+impl_shape_of_like!(Range<T> as (T, T));
+impl_shape_of_like!(RangeInclusive<T> as (T, T));
+impl_shape_of_like!(RangeFrom<T> as T);
+impl_shape_of_like!(RangeTo<T> as T);
+impl_shape_of_like!(RangeToInclusive<T> as T);
+
+impl<'a, T: ToOwned + ?Sized> ShapeOf for Cow<'a, T>
+where
+    T::Owned: ShapeOf,
+{
+    fn shape() -> Shape {
+        T::Owned::shape()
+    }
+}
+
+macro_rules! impl_shape_of_seq {
+    ($($ty:ident),*) => {
+        $(
+            impl<T: ShapeOf> ShapeOf for $ty<T> {
+                fn shape() -> Shape {
+                    Shape::Sequence(T::shape)
+                }
+            }
+        )*
+    };
+}
+impl_shape_of_seq!(Vec, VecDeque, LinkedList, BinaryHeap, BTreeSet);
+
+impl<const N: usize, T: ShapeOf> ShapeOf for [T; N] {
+    fn shape() -> Shape {
+        Shape::Array(T::shape, N)
+    }
+}
+
+// Maps can be decoded from several different shapes, so we accept anything.
+impl<K, V> ShapeOf for BTreeMap<K, V> {
+    fn shape() -> Shape {
+        Shape::Any
+    }
+}
+
+impl<T> ShapeOf for PhantomData<T> {
+    fn shape() -> Shape {
+        Shape::Composite(CompositeShape::default())
+    }
+}
+
+impl<T: ShapeOf> ShapeOf for Option<T> {
+    fn shape() -> Shape {
+        Shape::Variant(vec![
+            VariantShape::new("None", CompositeShape::default()),
+            VariantShape::new("Some", CompositeShape::new(vec![FieldShape::unnamed(T::shape)])),
+        ])
+    }
+}
+
+impl<T: ShapeOf, E: ShapeOf> ShapeOf for Result<T, E> {
+    fn shape() -> Shape {
+        Shape::Variant(vec![
+            VariantShape::new("Ok", CompositeShape::new(vec![FieldShape::unnamed(T::shape)])),
+            VariantShape::new("Err", CompositeShape::new(vec![FieldShape::unnamed(E::shape)])),
+        ])
+    }
+}
+
+macro_rules! impl_shape_of_tuple {
+    ($($t:ident)*) => {
+        impl<$($t: ShapeOf),*> ShapeOf for ($($t,)*) {
+            fn shape() -> Shape {
+                Shape::Tuple(vec![$($t::shape),*])
+            }
+        }
+    };
+}
+impl_shape_of_tuple!();
+impl_shape_of_tuple!(A);
+impl_shape_of_tuple!(A B);
+impl_shape_of_tuple!(A B C);
+impl_shape_of_tuple!(A B C D);
+impl_shape_of_tuple!(A B C D E);
+impl_shape_of_tuple!(A B C D E F);
+impl_shape_of_tuple!(A B C D E F G);
+impl_shape_of_tuple!(A B C D E F G H);
+impl_shape_of_tuple!(A B C D E F G H I);
+impl_shape_of_tuple!(A B C D E F G H I J);
+impl_shape_of_tuple!(A B C D E F G H I J K);
+impl_shape_of_tuple!(A B C D E F G H I J K L);
+impl_shape_of_tuple!(A B C D E F G H I J K L M);
+impl_shape_of_tuple!(A B C D E F G H I J K L M N);
+impl_shape_of_tuple!(A B C D E F G H I J K L M N O);
+impl_shape_of_tuple!(A B C D E F G H I J K L M N O P);
+impl_shape_of_tuple!(A B C D E F G H I J K L M N O P Q);
+impl_shape_of_tuple!(A B C D E F G H I J K L M N O P Q R);
+impl_shape_of_tuple!(A B C D E F G H I J K L M N O P Q R S);
+impl_shape_of_tuple!(A B C D E F G H I J K L M N O P Q R S T);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+    use scale_info::{PortableRegistry, TypeInfo};
+
+    fn make_type<T: TypeInfo + 'static>() -> (u32, PortableRegistry) {
+        let m = scale_info::MetaType::new::<T>();
+        let mut types = scale_info::Registry::new();
+        let id = types.register_type(&m);
+        (id.id, types.into())
+    }
+
+    fn check<T: ShapeOf, Info: TypeInfo + 'static>() -> Result<(), CompatibilityError> {
+        let (type_id, types) = make_type::<Info>();
+        check_decodable::<T, _>(type_id, &types)
+    }
+
+    #[test]
+    fn primitives_are_checked() {
+        assert!(check::<u8, u64>().is_ok());
+        assert!(check::<u64, Compact<u8>>().is_ok());
+        assert!(check::<String, String>().is_ok());
+        assert!(check::<(bool, char), (bool, char)>().is_ok());
+
+        let err = check::<bool, String>().unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            CompatibilityErrorKind::WrongShape { expected: "a bool", found: "a string" }
+        ));
+    }
+
+    #[test]
+    fn wrappers_are_looked_through() {
+        #[allow(dead_code)]
+        #[derive(TypeInfo)]
+        struct Wrapper(u32);
+        #[allow(dead_code)]
+        #[derive(TypeInfo)]
+        struct Outer {
+            inner: (Wrapper,),
+        }
+
+        assert!(check::<u32, Outer>().is_ok());
+        assert!(check::<(u32,), u32>().is_ok());
+        assert!(check::<String, Outer>().is_err());
+    }
+
+    #[test]
+    fn sequences_and_arrays_are_checked() {
+        assert!(check::<Vec<u8>, [u8; 4]>().is_ok());
+        assert!(check::<[u8; 4], Vec<u8>>().is_ok());
+        assert!(check::<(u8, u16), [u8; 2]>().is_ok());
+        assert!(check::<Vec<bool>, Vec<u8>>().is_err());
+
+        let err = check::<[u8; 3], [u8; 4]>().unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            CompatibilityErrorKind::WrongLength { expected: 3, found: 4 }
+        ));
+    }
+
+    #[test]
+    fn variants_are_checked() {
+        #[derive(TypeInfo)]
+        enum Foo {
+            #[allow(dead_code)]
+            Some(bool),
+            #[allow(dead_code)]
+            None,
+        }
+        #[derive(TypeInfo)]
+        enum Bar {
+            #[allow(dead_code)]
+            Some(bool),
+            #[allow(dead_code)]
+            Other,
+        }
+
+        assert!(check::<Option<bool>, Foo>().is_ok());
+        assert!(check::<Option<u8>, Foo>().is_err());
+
+        let err = check::<Option<bool>, Bar>().unwrap_err();
+        assert_eq!(err.to_string(), "Incompatible type at : Cannot decode variant Other; expects one of [\"None\", \"Some\"]");
+    }
+
+    #[test]
+    fn named_fields_are_looked_up() {
+        #[allow(dead_code)]
+        #[derive(TypeInfo)]
+        struct Foo {
+            a: Vec<u8>,
+            b: bool,
+        }
+
+        let bar =
+            || Shape::Composite(CompositeShape::new(vec![FieldShape::named("b", bool::shape)]));
+        let wibble = || {
+            Shape::Composite(CompositeShape::new(vec![FieldShape::named("a", Vec::<bool>::shape)]))
+        };
+        let (type_id, types) = make_type::<Foo>();
+        let mut checker = Checker { types: &types, in_progress: BTreeSet::new() };
+
+        assert!(checker.check(bar, type_id, 0).is_ok());
+        let err = checker.check(wibble, type_id, 0).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Incompatible type at [0].a: Expected a bool but found a number"
+        );
+    }
+
+    #[test]
+    fn shapes_are_displayed() {
+        assert_eq!(
+            Shape::of::<(u8, Vec<bool>, [char; 2])>().to_string(),
+            "(number, [bool], [char; 2])"
+        );
+        assert_eq!(Shape::of::<(String,)>().to_string(), "(str,)");
+        assert_eq!(
+            Shape::of::<Result<Option<Ignored>, Bits>>().to_string(),
+            "enum { Ok(enum { None, Some(_) }), Err(bits) }"
+        );
+
+        let composite = Shape::named(
+            "Foo",
+            Shape::Composite(CompositeShape {
+                fields: vec![FieldShape::named("a", u8::shape), FieldShape::named("b", str::shape)],
+                allow_extra_fields: true,
+            }),
+        );
+        assert_eq!(composite.to_string(), "struct Foo { a: number, b: str, .. }");
+        assert_eq!(composite.name(), Some("Foo"));
+        assert!(matches!(composite.without_name(), Shape::Composite(_)));
+    }
+
+    #[test]
+    fn recursive_types_are_checked() {
+        #[allow(dead_code)]
+        #[derive(TypeInfo)]
+        struct Node {
+            children: Vec<Node>,
+        }
+        struct MyNode;
+        impl ShapeOf for MyNode {
+            fn shape() -> Shape {
+                Shape::Composite(CompositeShape::new(vec![FieldShape::named(
+                    "children",
+                    Vec::<MyNode>::shape,
+                )]))
+            }
+        }
+
+        assert!(check::<MyNode, Node>().is_ok());
+        assert_eq!(
+            Shape::of::<MyNode>().to_string(),
+            "struct { children: [struct { children: .. }] }"
+        );
+    }
+}
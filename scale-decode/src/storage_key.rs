@@ -0,0 +1,274 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decode Substrate storage keys. A storage key is made up of the hash of the pallet name and
+//! the hash of the storage entry name (together, [`PREFIX_LEN`] bytes), followed by a part for
+//! each of the keys of the storage entry. Each part is the output of some [`StorageHasher`],
+//! which for some hashers is followed by the SCALE encoded key itself, which can then be decoded.
+//!
+//! ```rust
+//! use codec::Encode;
+//! use scale_decode::storage_key::{decode_storage_key, StorageHasher, PREFIX_LEN};
+//! use scale_info::PortableRegistry;
+//!
+//! let mut types = scale_info::Registry::new();
+//! let u32_id = types.register_type(&scale_info::MetaType::new::<u32>()).id;
+//! let str_id = types.register_type(&scale_info::MetaType::new::<String>()).id;
+//! let types: PortableRegistry = types.into();
+//!
+//! // Some key for a storage map with the keys `(u32, String)`, hashed with these hashers:
+//! let hashers = [(StorageHasher::Twox64Concat, u32_id), (StorageHasher::Blake2_128, str_id)];
+//! let mut key = vec![0u8; PREFIX_LEN];
+//! key.extend([1; 8]);
+//! key.extend(123u32.encode());
+//! key.extend([2; 16]);
+//!
+//! let parts = decode_storage_key(&mut &key[PREFIX_LEN..], hashers, &types).unwrap();
+//!
+//! // Both parts have some hash, but only the first key can be recovered:
+//! assert_eq!(parts[0].hash(), &[1; 8]);
+//! assert_eq!(parts[0].decode_as_type::<u64>().unwrap().unwrap(), 123);
+//! assert_eq!(parts[1].hash(), &[2; 16]);
+//! assert!(parts[1].decode_as_type::<String>().is_none());
+//! ```
+
+use crate::{
+    error::Error,
+    visitor::{decode_with_visitor, encoded_size_of, DecodeError, Visitor},
+    DecodeAsType,
+};
+use alloc::vec::Vec;
+use scale_type_resolver::TypeResolver;
+
+/// The number of bytes at the start of every storage key, which are the 16 byte hash of the
+/// pallet name followed by the 16 byte hash of the storage entry name.
+pub const PREFIX_LEN: usize = 32;
+
+/// The hashers that can be used to hash the keys of a storage entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StorageHasher {
+    /// A 128 bit Blake2 hash.
+    Blake2_128,
+    /// A 256 bit Blake2 hash.
+    Blake2_256,
+    /// A 128 bit Blake2 hash, followed by the SCALE encoded key.
+    Blake2_128Concat,
+    /// A 128 bit XX hash.
+    Twox128,
+    /// A 256 bit XX hash.
+    Twox256,
+    /// A 64 bit XX hash, followed by the SCALE encoded key.
+    Twox64Concat,
+    /// No hash; just the SCALE encoded key.
+    Identity,
+}
+
+impl StorageHasher {
+    /// The number of bytes of hash that this hasher outputs, not including any key which follows
+    /// them.
+    pub fn hash_len(&self) -> usize {
+        match self {
+            StorageHasher::Blake2_128 | StorageHasher::Blake2_128Concat => 16,
+            StorageHasher::Blake2_256 => 32,
+            StorageHasher::Twox128 => 16,
+            StorageHasher::Twox256 => 32,
+            StorageHasher::Twox64Concat => 8,
+            StorageHasher::Identity => 0,
+        }
+    }
+    /// Is the hash followed by the SCALE encoded key, such that the key can be decoded?
+    pub fn is_transparent(&self) -> bool {
+        matches!(
+            self,
+            StorageHasher::Blake2_128Concat | StorageHasher::Twox64Concat | StorageHasher::Identity
+        )
+    }
+}
+
+/// Decode the parts of a storage key, given the hasher and key type ID of each part. The input
+/// should not include the [`PREFIX_LEN`] bytes at the start of the storage key.
+///
+/// On success, the input is moved past the parts. This doesn't check that no bytes remain, so
+/// that the parts of partial keys can also be decoded. On error, the error will point to the
+/// index of the part which couldn't be decoded.
+pub fn decode_storage_key<'scale, 'resolver, R: TypeResolver>(
+    input: &mut &'scale [u8],
+    parts: impl IntoIterator<Item = (StorageHasher, R::TypeId)>,
+    types: &'resolver R,
+) -> Result<Vec<StorageKeyPart<'scale, 'resolver, R>>, Error> {
+    parts
+        .into_iter()
+        .enumerate()
+        .map(|(idx, (hasher, type_id))| {
+            decode_part(input, hasher, type_id, types).map_err(|e| Error::from(e).at_idx(idx))
+        })
+        .collect()
+}
+
+fn decode_part<'scale, 'resolver, R: TypeResolver>(
+    input: &mut &'scale [u8],
+    hasher: StorageHasher,
+    type_id: R::TypeId,
+    types: &'resolver R,
+) -> Result<StorageKeyPart<'scale, 'resolver, R>, DecodeError> {
+    let hash_len = hasher.hash_len();
+    if input.len() < hash_len {
+        return Err(DecodeError::NotEnoughInput(hash_len - input.len()));
+    }
+    let (hash, rest) = input.split_at(hash_len);
+    *input = rest;
+
+    let value = if hasher.is_transparent() {
+        let len = encoded_size_of(input, type_id.clone(), types)?;
+        let (value, rest) = input.split_at(len);
+        *input = rest;
+        Some(value)
+    } else {
+        None
+    };
+
+    Ok(StorageKeyPart { hasher, hash, value, type_id, types })
+}
+
+/// A single part of a storage key. See [`decode_storage_key()`].
+#[derive(Debug)]
+pub struct StorageKeyPart<'scale, 'resolver, R: TypeResolver> {
+    hasher: StorageHasher,
+    hash: &'scale [u8],
+    value: Option<&'scale [u8]>,
+    type_id: R::TypeId,
+    types: &'resolver R,
+}
+
+impl<'scale, 'resolver, R: TypeResolver> Clone for StorageKeyPart<'scale, 'resolver, R> {
+    fn clone(&self) -> Self {
+        StorageKeyPart {
+            hasher: self.hasher,
+            hash: self.hash,
+            value: self.value,
+            type_id: self.type_id.clone(),
+            types: self.types,
+        }
+    }
+}
+
+impl<'scale, 'resolver, R: TypeResolver> StorageKeyPart<'scale, 'resolver, R> {
+    /// The hasher that was used for this part.
+    pub fn hasher(&self) -> StorageHasher {
+        self.hasher
+    }
+    /// The bytes of the hash. This is empty for [`StorageHasher::Identity`].
+    pub fn hash(&self) -> &'scale [u8] {
+        self.hash
+    }
+    /// The SCALE encoded bytes of the key, if the hasher is transparent. See
+    /// [`StorageHasher::is_transparent()`].
+    pub fn value_bytes(&self) -> Option<&'scale [u8]> {
+        self.value
+    }
+    /// The type ID of the key.
+    pub fn type_id(&self) -> &R::TypeId {
+        &self.type_id
+    }
+    /// Decode the key using a visitor. This returns `None` if the hasher isn't transparent, and
+    /// so the key can't be decoded.
+    pub fn decode_with_visitor<V: Visitor<TypeResolver = R>>(
+        &self,
+        visitor: V,
+    ) -> Option<Result<V::Value<'scale, 'resolver>, V::Error>> {
+        let value = self.value?;
+        Some(decode_with_visitor(&mut &*value, self.type_id.clone(), self.types, visitor))
+    }
+    /// Decode the key into some type via [`DecodeAsType`]. This returns `None` if the hasher isn't
+    /// transparent, and so the key can't be decoded.
+    pub fn decode_as_type<T: DecodeAsType>(&self) -> Option<Result<T, Error>> {
+        let value = self.value?;
+        Some(T::decode_as_type(&mut &*value, self.type_id.clone(), self.types))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::{
+        string::{String, ToString},
+        vec,
+    };
+    use codec::Encode;
+
+    #[test]
+    fn storage_key_parts_are_decoded() {
+        #[derive(Encode, scale_info::TypeInfo)]
+        struct Foo {
+            a: bool,
+            b: Vec<u8>,
+        }
+
+        let mut types = scale_info::Registry::new();
+        let foo_id = types.register_type(&scale_info::MetaType::new::<Foo>()).id;
+        let u64_id = types.register_type(&scale_info::MetaType::new::<u64>()).id;
+        let types: scale_info::PortableRegistry = types.into();
+
+        let foo = Foo { a: true, b: vec![1, 2, 3] };
+        let mut key = vec![];
+        key.extend([1; 16]);
+        key.extend(foo.encode());
+        key.extend(u64::MAX.encode());
+        key.extend([2; 32]);
+        key.extend([3; 4]);
+
+        let hashers = [
+            (StorageHasher::Blake2_128Concat, foo_id),
+            (StorageHasher::Identity, u64_id),
+            (StorageHasher::Twox256, u64_id),
+        ];
+        let input = &mut &*key;
+        let parts = decode_storage_key(input, hashers, &types).unwrap();
+        assert_eq!(input, &[3; 4], "trailing bytes should be left alone");
+
+        assert_eq!(parts[0].hash(), &[1; 16]);
+        assert_eq!(parts[0].value_bytes(), Some(&*foo.encode()));
+        let (a, b) = parts[0].decode_as_type::<(bool, Vec<u8>)>().unwrap().unwrap();
+        assert!(a);
+        assert_eq!(b, vec![1, 2, 3]);
+
+        assert_eq!(parts[1].hasher(), StorageHasher::Identity);
+        assert!(parts[1].hash().is_empty());
+        assert_eq!(parts[1].decode_as_type::<u64>().unwrap().unwrap(), u64::MAX);
+
+        assert_eq!(parts[2].hash(), &[2; 32]);
+        assert!(parts[2].value_bytes().is_none());
+        assert!(parts[2].decode_as_type::<String>().is_none());
+    }
+
+    #[test]
+    fn errors_point_to_the_failing_part() {
+        let mut types = scale_info::Registry::new();
+        let u64_id = types.register_type(&scale_info::MetaType::new::<u64>()).id;
+        let types: scale_info::PortableRegistry = types.into();
+
+        let hashers = [(StorageHasher::Twox64Concat, u64_id), (StorageHasher::Twox128, u64_id)];
+
+        // The second hash is cut short:
+        let key = [1u8; 8 + 8 + 15];
+        let err = decode_storage_key(&mut &key[..], hashers, &types).unwrap_err();
+        assert_eq!(err.context().path().to_string(), "[1]");
+
+        // The first value is cut short:
+        let key = [1u8; 8 + 7];
+        let err = decode_storage_key(&mut &key[..], hashers, &types).unwrap_err();
+        assert_eq!(err.context().path().to_string(), "[0]");
+    }
+}
@@ -0,0 +1,61 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Types which mirror the shape of common Substrate types, so that they can be decoded without
+//! depending on `sp-core` or `sp-runtime`. Implementations for `Perbill`, `Permill` and
+//! `Percent` from `sp-arithmetic` are also provided, and `H256` style hashes are handled by the
+//! `primitive-types` impls.
+
+use alloc::vec::Vec;
+
+/// A 32 byte account ID, as found in `sp_core::crypto::AccountId32`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AccountId32(pub [u8; 32]);
+
+impl AsRef<[u8]> for AccountId32 {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<[u8; 32]> for AccountId32 {
+    fn from(bytes: [u8; 32]) -> Self {
+        AccountId32(bytes)
+    }
+}
+
+/// A multi-format address, as found in `sp_runtime::MultiAddress`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MultiAddress<AccountId, AccountIndex> {
+    /// An account ID.
+    Id(AccountId),
+    /// An account index.
+    Index(AccountIndex),
+    /// Some arbitrary bytes.
+    Raw(Vec<u8>),
+    /// A 32 byte address.
+    Address32([u8; 32]),
+    /// A 20 byte address.
+    Address20([u8; 20]),
+}
+
+/// A transaction era, as found in `sp_runtime::generic::Era`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Era {
+    /// The transaction is valid forever.
+    Immortal,
+    /// The transaction is valid for `period` blocks, starting at the block
+    /// whose number modulo `period` is `phase`. These are given as `(period, phase)`.
+    Mortal(u64, u64),
+}
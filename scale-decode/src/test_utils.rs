@@ -0,0 +1,151 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Utilities for testing [`crate::Visitor`] implementations without needing `scale-info`.
+//!
+//! A [`MockResolver`] describes a handful of types, each of which is given a `u32` type ID as it's
+//! added. Along with the `from_parts` constructors on [`crate::visitor::types::Composite`],
+//! [`crate::visitor::types::Tuple`] and [`crate::visitor::types::Variant`], this allows visitors
+//! to be handed exactly the input that we want to test them with.
+//!
+//! ```rust
+//! use scale_decode::test_utils::MockResolver;
+//! use scale_decode::resolver::Primitive;
+//! use scale_decode::visitor::types::Composite;
+//! use scale_decode::Field;
+//!
+//! let mut builder = MockResolver::builder();
+//! let u8_id = builder.add_primitive(Primitive::U8);
+//! let bool_id = builder.add_primitive(Primitive::Bool);
+//! let types = builder.build();
+//!
+//! // A composite value that we could hand to some visitor that we want to test:
+//! let fields = [Field::named(u8_id, "a"), Field::named(bool_id, "b")];
+//! let composite = Composite::from_parts(&[1, 1], &mut fields.into_iter(), &types);
+//! assert_eq!(composite.remaining(), 2);
+//! ```
+
+use crate::resolver::{InMemoryResolver, InMemoryResolverBuilder};
+
+/// A [`crate::TypeResolver`] which resolves the types that were added to it via a
+/// [`MockResolverBuilder`]. This is just an [`InMemoryResolver`].
+pub type MockResolver = InMemoryResolver;
+
+/// Add types to this in order to build a [`MockResolver`]. This is just an
+/// [`InMemoryResolverBuilder`].
+pub type MockResolverBuilder = InMemoryResolverBuilder;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::visitor::{
+        types::{Composite, Tuple},
+        DecodeError, TypeIdFor, Unexpected, Visitor,
+    };
+    use crate::{
+        resolver::{Primitive, Variant},
+        Field,
+    };
+    use alloc::{vec, vec::Vec};
+
+    // Collect the names and byte lengths of each field.
+    struct FieldsVisitor;
+    impl Visitor for FieldsVisitor {
+        type Value<'scale, 'resolver> = Vec<(Option<&'resolver str>, usize)>;
+        type Error = DecodeError;
+        type TypeResolver = MockResolver;
+
+        fn visit_composite<'scale, 'resolver>(
+            self,
+            value: &mut Composite<'scale, 'resolver, Self::TypeResolver>,
+            _type_id: TypeIdFor<Self>,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            value.map(|f| f.map(|f| (f.name(), f.bytes().len()))).collect()
+        }
+        fn visit_tuple<'scale, 'resolver>(
+            self,
+            value: &mut Tuple<'scale, 'resolver, Self::TypeResolver>,
+            _type_id: TypeIdFor<Self>,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            value.map(|f| f.map(|f| (None, f.bytes().len()))).collect()
+        }
+        fn visit_variant<'scale, 'resolver>(
+            self,
+            value: &mut crate::visitor::types::Variant<'scale, 'resolver, Self::TypeResolver>,
+            type_id: TypeIdFor<Self>,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            self.visit_composite(value.fields(), type_id)
+        }
+        fn visit_unexpected<'scale, 'resolver>(
+            self,
+            unexpected: Unexpected,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            Err(DecodeError::Unexpected(unexpected))
+        }
+    }
+
+    #[test]
+    fn composite_from_parts_works() {
+        let mut builder = MockResolver::builder();
+        let u16_id = builder.add_primitive(Primitive::U16);
+        let compact_id = builder.add_compact(u16_id);
+        let types = builder.build();
+
+        let fields = [Field::named(u16_id, "a"), Field::named(compact_id, "b")];
+        let bytes = [1, 0, 4, 2];
+        let mut composite = Composite::from_parts(&bytes, &mut fields.into_iter(), &types);
+
+        let res = FieldsVisitor.visit_composite(&mut composite, 0).unwrap();
+        assert_eq!(res, vec![(Some("a"), 2), (Some("b"), 1)]);
+        assert_eq!(composite.bytes_from_undecoded(), &[2]);
+    }
+
+    #[test]
+    fn tuple_from_parts_works() {
+        let mut builder = MockResolver::builder();
+        let bool_id = builder.add_primitive(Primitive::Bool);
+        let str_id = builder.add_primitive(Primitive::Str);
+        let types = builder.build();
+
+        let fields = [Field::unnamed(str_id), Field::unnamed(bool_id)];
+        let bytes = [8, b'h', b'i', 1];
+        let mut tuple = Tuple::from_parts(&bytes, &mut fields.into_iter(), &types);
+
+        let res = FieldsVisitor.visit_tuple(&mut tuple, 0).unwrap();
+        assert_eq!(res, vec![(None, 3), (None, 1)]);
+    }
+
+    #[test]
+    fn variant_from_parts_works() {
+        let mut builder = MockResolver::builder();
+        let u32_id = builder.add_primitive(Primitive::U32);
+        let types = builder.build();
+
+        let variants = [
+            Variant { index: 0, name: "A", fields: vec![].into_iter() },
+            Variant { index: 3, name: "B", fields: vec![Field::named(u32_id, "b")].into_iter() },
+        ];
+        let mut variant = crate::visitor::types::Variant::from_parts(
+            &[3, 1, 0, 0, 0],
+            variants.into_iter(),
+            &types,
+        )
+        .unwrap();
+
+        assert_eq!(variant.name(), "B");
+        let res = FieldsVisitor.visit_variant(&mut variant, 0).unwrap();
+        assert_eq!(res, vec![(Some("b"), 4)]);
+    }
+}
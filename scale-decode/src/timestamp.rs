@@ -0,0 +1,110 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Decode integer timestamps, like those stored by `pallet-timestamp`, into date/time types from
+//! `chrono` or `time`.
+//!
+//! The [`crate::IntoVisitor`] impls for these types expect a number of milliseconds since the Unix
+//! epoch. To decode a number of seconds instead, use a [`TimestampVisitor`]:
+//!
+//! ```rust
+//! # #[cfg(feature = "chrono")] {
+//! use codec::Encode;
+//! use scale_decode::timestamp::{TimestampUnit, TimestampVisitor};
+//! use scale_info::PortableRegistry;
+//!
+//! let m = scale_info::MetaType::new::<u64>();
+//! let mut types = scale_info::Registry::new();
+//! let type_id = types.register_type(&m).id;
+//! let types: PortableRegistry = types.into();
+//!
+//! let encoded = 1_700_000_000u64.encode();
+//! let date: chrono::DateTime<chrono::Utc> = scale_decode::visitor::decode_with_visitor(
+//!     &mut &*encoded,
+//!     type_id,
+//!     &types,
+//!     TimestampVisitor::new(TimestampUnit::Seconds),
+//! )
+//! .unwrap();
+//! assert_eq!(date.timestamp(), 1_700_000_000);
+//! # }
+//! ```
+
+use crate::error::{Error, ErrorKind};
+use crate::visitor::Visitor;
+use alloc::string::ToString;
+use core::marker::PhantomData;
+use scale_type_resolver::TypeResolver;
+
+/// The unit that a timestamp is measured in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampUnit {
+    /// Seconds since the Unix epoch.
+    Seconds,
+    /// Milliseconds since the Unix epoch. This is what `pallet-timestamp` uses.
+    #[default]
+    Millis,
+}
+
+/// Types which can be constructed from a timestamp.
+pub trait FromTimestamp: Sized {
+    /// Construct `Self` from a timestamp in the given unit, returning `None` if it's out of range.
+    fn from_timestamp(value: i64, unit: TimestampUnit) -> Option<Self>;
+}
+
+/// A [`Visitor`] which decodes an integer timestamp in the given [`TimestampUnit`] into `T`.
+pub struct TimestampVisitor<T, R> {
+    unit: TimestampUnit,
+    _marker: PhantomData<(T, R)>,
+}
+
+impl<T, R> TimestampVisitor<T, R> {
+    /// Construct a new [`TimestampVisitor`] which expects timestamps in the given unit.
+    pub fn new(unit: TimestampUnit) -> Self {
+        TimestampVisitor { unit, _marker: PhantomData }
+    }
+}
+
+macro_rules! visit_timestamp_fn_impl {
+    ($name:ident : $ty:ty) => {
+        fn $name<'scale, 'resolver>(
+            self,
+            value: $ty,
+            _type_id: <Self::TypeResolver as TypeResolver>::TypeId,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            i64::try_from(value)
+                .ok()
+                .and_then(|value| T::from_timestamp(value, self.unit))
+                .ok_or_else(|| Error::new(ErrorKind::NumberOutOfRange { value: value.to_string() }))
+        }
+    };
+}
+
+impl<T: FromTimestamp, R: TypeResolver> Visitor for TimestampVisitor<T, R> {
+    type Error = Error;
+    type Value<'scale, 'resolver> = T;
+    type TypeResolver = R;
+
+    visit_timestamp_fn_impl!(visit_u8: u8);
+    visit_timestamp_fn_impl!(visit_u16: u16);
+    visit_timestamp_fn_impl!(visit_u32: u32);
+    visit_timestamp_fn_impl!(visit_u64: u64);
+    visit_timestamp_fn_impl!(visit_u128: u128);
+    visit_timestamp_fn_impl!(visit_i8: i8);
+    visit_timestamp_fn_impl!(visit_i16: i16);
+    visit_timestamp_fn_impl!(visit_i32: i32);
+    visit_timestamp_fn_impl!(visit_i64: i64);
+    visit_timestamp_fn_impl!(visit_i128: i128);
+    crate::impls::visit_single_field_composite_tuple_impls!(R);
+}
@@ -0,0 +1,371 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Walk over some SCALE encoded value, checking that it's valid according to its type, but
+//! without decoding it into anything. This is a cheap way to check some untrusted input before
+//! doing a more expensive decode into some concrete type.
+//!
+//! Every variant index must correspond to a known variant, every string must be valid UTF8, and
+//! there must be enough bytes for every value, including for each item in a sequence. Rather than
+//! any data, some [`ValidationStats`] are handed back about the value:
+//!
+//! ```rust
+//! use codec::Encode;
+//! use scale_decode::validate::validate;
+//! use scale_info::{PortableRegistry, TypeInfo};
+//!
+//! #[derive(Encode, TypeInfo)]
+//! enum Foo {
+//!     Bar { a: Vec<u32>, b: String },
+//! }
+//!
+//! let m = scale_info::MetaType::new::<Foo>();
+//! let mut types = scale_info::Registry::new();
+//! let type_id = types.register_type(&m).id;
+//! let types: PortableRegistry = types.into();
+//!
+//! let encoded = Foo::Bar { a: vec![1, 2, 3], b: "hi".to_string() }.encode();
+//! let stats = validate(&mut &*encoded, type_id, &types).unwrap();
+//!
+//! assert_eq!(stats.bytes, encoded.len());
+//! // The variant, its two fields, and the three items in the sequence:
+//! assert_eq!(stats.values, 6);
+//! // The variant, then the sequence, then its items:
+//! assert_eq!(stats.depth, 3);
+//!
+//! // Invalid UTF8 is caught:
+//! let mut encoded = encoded;
+//! *encoded.last_mut().unwrap() = 0xFF;
+//! assert!(validate(&mut &*encoded, type_id, &types).is_err());
+//! ```
+
+use crate::{
+    error::Error,
+    visitor::{
+        decode_with_visitor,
+        types::{Array, BitSequence, Composite, Sequence, Str, Tuple, Variant},
+        CompactLocation, DecodeItemIterator, Detach, TypeIdFor, Visitor,
+    },
+};
+use alloc::string::ToString;
+use codec::{Compact, Encode};
+use core::marker::PhantomData;
+use scale_type_resolver::TypeResolver;
+
+/// Validate the SCALE encoded value at the start of the input, whose structure is given by the
+/// type ID and type resolver provided. On success, the input is moved past the value.
+pub fn validate<R: TypeResolver>(
+    input: &mut &[u8],
+    type_id: R::TypeId,
+    types: &R,
+) -> Result<ValidationStats, Error> {
+    decode_with_visitor(input, type_id, types, ValidateOnlyVisitor::new())
+}
+
+/// Some statistics about a value which has been validated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ValidationStats {
+    /// How deeply nested the value is. Primitive values, and containers with nothing in them,
+    /// have a depth of 1, and each level of nesting adds 1 to this.
+    pub depth: usize,
+    /// The number of values that were seen, including this one and every value nested inside it.
+    pub values: usize,
+    /// The number of bytes that the value occupies.
+    pub bytes: usize,
+}
+
+impl ValidationStats {
+    fn leaf(bytes: usize) -> Self {
+        ValidationStats { depth: 1, values: 1, bytes }
+    }
+}
+
+/// A [`Visitor`] which validates a value without decoding it into anything, returning some
+/// [`ValidationStats`] about it instead. See [`validate()`].
+pub struct ValidateOnlyVisitor<R>(PhantomData<R>);
+
+impl<R> Default for ValidateOnlyVisitor<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R> ValidateOnlyVisitor<R> {
+    /// Construct a new [`ValidateOnlyVisitor`].
+    pub fn new() -> Self {
+        ValidateOnlyVisitor(PhantomData)
+    }
+}
+
+macro_rules! visit_fixed_fn_impl {
+    ($name:ident : $ty:ty) => {
+        fn $name<'scale, 'resolver>(
+            self,
+            _value: $ty,
+            _type_id: TypeIdFor<Self>,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            Ok(ValidationStats::leaf(core::mem::size_of::<$ty>()))
+        }
+    };
+}
+
+macro_rules! visit_compact_fn_impl {
+    ($name:ident : $ty:ty) => {
+        fn $name<'scale, 'resolver>(
+            self,
+            value: $ty,
+            _location: CompactLocation,
+            _type_id: TypeIdFor<Self>,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            Ok(ValidationStats::leaf(Compact(value).encoded_size()))
+        }
+    };
+}
+
+// Validate each item in some container, returning the stats for the container, given the bytes
+// from the start of it.
+fn validate_items<'scale, 'resolver, R, D>(
+    items: &mut D,
+    bytes_from_start: &[u8],
+    bytes_from_undecoded: impl Fn(&D) -> &'scale [u8],
+) -> Result<ValidationStats, Error>
+where
+    R: TypeResolver,
+    D: DecodeItemIterator<'scale, 'resolver, R>,
+{
+    let mut stats = ValidationStats { depth: 1, values: 1, bytes: 0 };
+    let mut idx = 0;
+    while let Some(item) = items.decode_item(ValidateOnlyVisitor::new()) {
+        let item = item.map_err(|e| e.at_idx(idx))?;
+        stats.depth = stats.depth.max(item.depth + 1);
+        stats.values += item.values;
+        idx += 1;
+    }
+    stats.bytes = bytes_from_start.len() - bytes_from_undecoded(items).len();
+    Ok(stats)
+}
+
+// Validate each field in some composite, returning the stats for the composite.
+fn validate_fields<R: TypeResolver>(
+    fields: &mut Composite<'_, '_, R>,
+    bytes_from_start: &[u8],
+) -> Result<ValidationStats, Error> {
+    let mut stats = ValidationStats { depth: 1, values: 1, bytes: 0 };
+    for (idx, field) in fields.by_ref().enumerate() {
+        let field = field?;
+        let item =
+            field.decode_with_visitor(ValidateOnlyVisitor::new()).map_err(|e| {
+                match field.name() {
+                    Some(name) => e.at_field(name.to_string()),
+                    None => e.at_idx(idx),
+                }
+            })?;
+        stats.depth = stats.depth.max(item.depth + 1);
+        stats.values += item.values;
+    }
+    stats.bytes = bytes_from_start.len() - fields.bytes_from_undecoded().len();
+    Ok(stats)
+}
+
+impl<R: TypeResolver> Visitor for ValidateOnlyVisitor<R> {
+    type Value<'scale, 'resolver> = ValidationStats;
+    type Error = Error;
+    type TypeResolver = R;
+
+    visit_fixed_fn_impl!(visit_bool: bool);
+    visit_fixed_fn_impl!(visit_char: char);
+    visit_fixed_fn_impl!(visit_u8: u8);
+    visit_fixed_fn_impl!(visit_u16: u16);
+    visit_fixed_fn_impl!(visit_u32: u32);
+    visit_fixed_fn_impl!(visit_u64: u64);
+    visit_fixed_fn_impl!(visit_u128: u128);
+    visit_fixed_fn_impl!(visit_i8: i8);
+    visit_fixed_fn_impl!(visit_i16: i16);
+    visit_fixed_fn_impl!(visit_i32: i32);
+    visit_fixed_fn_impl!(visit_i64: i64);
+    visit_fixed_fn_impl!(visit_i128: i128);
+    #[cfg(feature = "float")]
+    visit_fixed_fn_impl!(visit_f32: f32);
+    #[cfg(feature = "float")]
+    visit_fixed_fn_impl!(visit_f64: f64);
+    visit_compact_fn_impl!(visit_compact_u8: u8);
+    visit_compact_fn_impl!(visit_compact_u16: u16);
+    visit_compact_fn_impl!(visit_compact_u32: u32);
+    visit_compact_fn_impl!(visit_compact_u64: u64);
+    visit_compact_fn_impl!(visit_compact_u128: u128);
+    fn visit_u256<'resolver>(
+        self,
+        _value: &[u8; 32],
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'_, 'resolver>, Self::Error> {
+        Ok(ValidationStats::leaf(32))
+    }
+    fn visit_i256<'resolver>(
+        self,
+        _value: &[u8; 32],
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'_, 'resolver>, Self::Error> {
+        Ok(ValidationStats::leaf(32))
+    }
+    fn visit_str<'scale, 'resolver>(
+        self,
+        value: &mut Str<'scale>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        value.as_str()?;
+        let bytes = value.bytes_from_start().len() - value.bytes_after()?.len();
+        Ok(ValidationStats::leaf(bytes))
+    }
+    fn visit_sequence<'scale, 'resolver>(
+        self,
+        value: &mut Sequence<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let start = value.bytes_from_start();
+        validate_items(value, start, Sequence::bytes_from_undecoded)
+    }
+    fn visit_array<'scale, 'resolver>(
+        self,
+        value: &mut Array<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let start = value.bytes_from_start();
+        validate_items(value, start, Array::bytes_from_undecoded)
+    }
+    fn visit_tuple<'scale, 'resolver>(
+        self,
+        value: &mut Tuple<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let start = value.bytes_from_start();
+        validate_items(value, start, Tuple::bytes_from_undecoded)
+    }
+    fn visit_composite<'scale, 'resolver>(
+        self,
+        value: &mut Composite<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let start = value.bytes_from_start();
+        validate_fields(value, start)
+    }
+    fn visit_variant<'scale, 'resolver>(
+        self,
+        value: &mut Variant<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let start = value.bytes_from_start();
+        let name = value.name();
+        validate_fields(value.fields(), start).map_err(|e| e.at_variant(name.to_string()))
+    }
+    fn visit_bitsequence<'scale, 'resolver>(
+        self,
+        value: &mut BitSequence<'scale>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        // Decoding checks that all of the bytes for the bits are present.
+        value.decode()?;
+        let bytes = value.bytes_from_start().len() - value.bytes_after()?.len();
+        Ok(ValidationStats::leaf(bytes))
+    }
+}
+
+impl<R: TypeResolver> Detach for ValidateOnlyVisitor<R> {
+    type Owned = ValidationStats;
+    fn detach(value: ValidationStats) -> ValidationStats {
+        value
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::{string::String, vec, vec::Vec};
+    use scale_info::TypeInfo;
+
+    // Validate the value, checking that the input is moved past it.
+    fn validate_value<T: Encode + TypeInfo + 'static>(value: T) -> ValidationStats {
+        let m = scale_info::MetaType::new::<T>();
+        let mut types = scale_info::Registry::new();
+        let type_id = types.register_type(&m).id;
+        let types: scale_info::PortableRegistry = types.into();
+
+        let mut encoded = value.encode();
+        let len = encoded.len();
+        encoded.extend_from_slice(&[1, 2, 3]);
+        let cursor = &mut &*encoded;
+        let stats = validate(cursor, type_id, &types).unwrap();
+        assert_eq!(cursor, &[1, 2, 3], "value should be consumed");
+        assert_eq!(stats.bytes, len);
+        stats
+    }
+
+    #[test]
+    fn primitives_are_validated() {
+        assert_eq!(validate_value(true), ValidationStats { depth: 1, values: 1, bytes: 1 });
+        assert_eq!(validate_value(123u64).bytes, 8);
+        assert_eq!(validate_value(Compact(1u32)).bytes, 1);
+        assert_eq!(validate_value(Compact(u128::MAX)).bytes, 17);
+        assert_eq!(validate_value("hello".to_string()).bytes, 6);
+    }
+
+    #[test]
+    fn nested_values_are_validated() {
+        #[derive(Encode, TypeInfo)]
+        enum Foo {
+            A,
+            B { a: Vec<Vec<u8>>, b: (bool, [u16; 2]) },
+        }
+
+        assert_eq!(validate_value(Foo::A), ValidationStats { depth: 1, values: 1, bytes: 1 });
+        assert_eq!(
+            validate_value(Foo::B { a: vec![vec![1, 2], vec![]], b: (true, [1, 2]) }),
+            // Foo, a, a's 2 items, a[0]'s 2 items, b, b's 2 items, b.1's 2 items:
+            ValidationStats { depth: 4, values: 11, bytes: 11 }
+        );
+        assert_eq!(validate_value(Vec::<u8>::new()).depth, 1);
+    }
+
+    #[test]
+    fn invalid_values_are_caught() {
+        #[derive(Encode, TypeInfo)]
+        enum Foo {
+            A(String),
+        }
+
+        let m = scale_info::MetaType::new::<Vec<Foo>>();
+        let mut types = scale_info::Registry::new();
+        let type_id = types.register_type(&m).id;
+        let types: scale_info::PortableRegistry = types.into();
+        let encoded = vec![Foo::A("hi".to_string()), Foo::A("there".to_string())].encode();
+        assert!(validate(&mut &*encoded, type_id, &types).is_ok());
+
+        // Unknown variant index:
+        let mut bad = encoded.clone();
+        bad[5] = 1;
+        let err = validate(&mut &*bad, type_id, &types).unwrap_err();
+        assert_eq!(err.context().path().to_string(), "[1]");
+
+        // Invalid UTF8:
+        let mut bad = encoded.clone();
+        *bad.last_mut().unwrap() = 0xFF;
+        let err = validate(&mut &*bad, type_id, &types).unwrap_err();
+        assert_eq!(err.context().path().to_string(), "[0].(A).[1]");
+
+        // Not enough bytes for the items in the sequence:
+        let mut bad = encoded.clone();
+        bad[0] = 3 << 2;
+        assert!(validate(&mut &*bad, type_id, &types).is_err());
+    }
+}
@@ -0,0 +1,743 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A [`DecodedValue`] is an owned representation of any SCALE encoded value, which retains
+//! enough information to be encoded back into exactly the bytes that it was decoded from. It's
+//! useful when some value needs to be inspected, compared or reported on without knowing its
+//! type ahead of time.
+//!
+//! ```rust
+//! use codec::{Compact, Encode};
+//! use scale_decode::value::{decode_value, DecodedValue};
+//! use scale_info::{PortableRegistry, TypeInfo};
+//!
+//! #[derive(Encode, TypeInfo)]
+//! enum Foo {
+//!     Bar { a: Compact<u32>, b: (bool, String) },
+//! }
+//!
+//! let m = scale_info::MetaType::new::<Foo>();
+//! let mut types = scale_info::Registry::new();
+//! let type_id = types.register_type(&m).id;
+//! let types: PortableRegistry = types.into();
+//!
+//! let encoded = Foo::Bar { a: Compact(1), b: (true, "hi".to_string()) }.encode();
+//! let value = decode_value(&mut &*encoded, type_id, &types).unwrap();
+//!
+//! let DecodedValue::Variant { name, fields, .. } = &value else { panic!("expected variant") };
+//! assert_eq!(name, "Bar");
+//! assert_eq!(fields[0], (Some("a".to_string()), DecodedValue::Compact(1)));
+//!
+//! // The value can be encoded back into the same bytes:
+//! assert_eq!(value.encoded_size(), encoded.len());
+//! assert_eq!(value.encode(), encoded);
+//! ```
+
+use crate::{
+    error::{Error, ErrorKind},
+    visitor::{
+        decode_with_visitor, decode_with_visitor_partial,
+        types::{Array, BitSequence, Composite, Sequence, Str, Tuple, Variant},
+        CompactLocation, DecodeItemIterator, Detach, PartialError, TypeIdFor, Visitor,
+    },
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use codec::{Compact, Encode, Output};
+use core::marker::PhantomData;
+use scale_bits::{Bits, Format};
+use scale_type_resolver::TypeResolver;
+
+/// Decode some bytes, whose structure is given by the type ID and type resolver provided, into a
+/// [`DecodedValue`]. The provided pointer to the bytes is moved forwards past the decoded value.
+pub fn decode_value<R: TypeResolver>(
+    input: &mut &[u8],
+    type_id: R::TypeId,
+    types: &R,
+) -> Result<DecodedValue, Error> {
+    decode_with_visitor(input, type_id, types, DecodedValueVisitor::new())
+}
+
+/// Decode some bytes into a [`DecodedValue`] like [`decode_value()`], but if an error is hit,
+/// hand back whatever was decoded before it along with the error. Containers in the partial value
+/// contain only the items before the one that failed to decode, and that item itself if
+/// some part of it was decoded.
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_decode::value::{decode_value_partial, DecodedValue};
+/// use scale_info::PortableRegistry;
+///
+/// let m = scale_info::MetaType::new::<(u8, Vec<u32>)>();
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&m).id;
+/// let types: PortableRegistry = types.into();
+///
+/// // Chop off the end of the encoded value, so that the second u32 can't be decoded:
+/// let encoded = (1u8, vec![2u32, 3]).encode();
+/// let err = decode_value_partial(&mut &encoded[..8], type_id, &types).unwrap_err();
+///
+/// assert_eq!(err.offset(), 6);
+/// assert_eq!(
+///     err.partial(),
+///     Some(&DecodedValue::Tuple(vec![
+///         DecodedValue::U8(1),
+///         DecodedValue::Sequence(vec![DecodedValue::U32(2)]),
+///     ]))
+/// );
+/// ```
+pub fn decode_value_partial<R: TypeResolver>(
+    input: &mut &[u8],
+    type_id: R::TypeId,
+    types: &R,
+) -> Result<DecodedValue, PartialError<DecodedValue>> {
+    decode_with_visitor_partial(input, type_id, types, PartialDecodedValueVisitor::new())
+}
+
+/// An owned representation of some decoded value. Its [`Encode`] impl produces the bytes that
+/// it was decoded from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodedValue {
+    /// A boolean.
+    Bool(bool),
+    /// A char.
+    Char(char),
+    /// A `u8`.
+    U8(u8),
+    /// A `u16`.
+    U16(u16),
+    /// A `u32`.
+    U32(u32),
+    /// A `u64`.
+    U64(u64),
+    /// A `u128`.
+    U128(u128),
+    /// A `u256`, as little endian bytes.
+    U256([u8; 32]),
+    /// An `i8`.
+    I8(i8),
+    /// An `i16`.
+    I16(i16),
+    /// An `i32`.
+    I32(i32),
+    /// An `i64`.
+    I64(i64),
+    /// An `i128`.
+    I128(i128),
+    /// An `i256`, as little endian bytes.
+    I256([u8; 32]),
+    /// A compact encoded unsigned number of any size.
+    Compact(u128),
+    /// A string.
+    Str(String),
+    /// A sequence of values, which is prefixed with its length when encoded.
+    Sequence(Vec<DecodedValue>),
+    /// A fixed length array of values.
+    Array(Vec<DecodedValue>),
+    /// A tuple of values.
+    Tuple(Vec<DecodedValue>),
+    /// A composite type, along with the name of each field if it has one.
+    Composite(Vec<(Option<String>, DecodedValue)>),
+    /// A variant, along with the name of each of its fields if it has one.
+    Variant {
+        /// The index of the variant.
+        index: u8,
+        /// The name of the variant.
+        name: String,
+        /// The fields of the variant.
+        fields: Vec<(Option<String>, DecodedValue)>,
+    },
+    /// A sequence of bits, along with the format that they are encoded with.
+    BitSequence(Bits, Format),
+}
+
+impl DecodedValue {
+    /// The number of bytes that this value is encoded into, which is the same as the number of
+    /// bytes that it was decoded from.
+    pub fn encoded_size(&self) -> usize {
+        Encode::encoded_size(self)
+    }
+}
+
+impl Encode for DecodedValue {
+    fn encode_to<T: Output + ?Sized>(&self, dest: &mut T) {
+        match self {
+            DecodedValue::Bool(v) => v.encode_to(dest),
+            DecodedValue::Char(v) => (*v as u32).encode_to(dest),
+            DecodedValue::U8(v) => v.encode_to(dest),
+            DecodedValue::U16(v) => v.encode_to(dest),
+            DecodedValue::U32(v) => v.encode_to(dest),
+            DecodedValue::U64(v) => v.encode_to(dest),
+            DecodedValue::U128(v) => v.encode_to(dest),
+            DecodedValue::U256(v) | DecodedValue::I256(v) => dest.write(v),
+            DecodedValue::I8(v) => v.encode_to(dest),
+            DecodedValue::I16(v) => v.encode_to(dest),
+            DecodedValue::I32(v) => v.encode_to(dest),
+            DecodedValue::I64(v) => v.encode_to(dest),
+            DecodedValue::I128(v) => v.encode_to(dest),
+            DecodedValue::Compact(v) => Compact(*v).encode_to(dest),
+            DecodedValue::Str(v) => v.encode_to(dest),
+            DecodedValue::Sequence(vals) => {
+                Compact(vals.len() as u64).encode_to(dest);
+                vals.iter().for_each(|v| v.encode_to(dest));
+            }
+            DecodedValue::Array(vals) | DecodedValue::Tuple(vals) => {
+                vals.iter().for_each(|v| v.encode_to(dest));
+            }
+            DecodedValue::Composite(fields) => {
+                fields.iter().for_each(|(_, v)| v.encode_to(dest));
+            }
+            DecodedValue::Variant { index, fields, .. } => {
+                index.encode_to(dest);
+                fields.iter().for_each(|(_, v)| v.encode_to(dest));
+            }
+            DecodedValue::BitSequence(bits, format) => {
+                let mut out = Vec::new();
+                scale_bits::encode_using_format_to(bits.iter(), *format, &mut out);
+                dest.write(&out);
+            }
+        }
+    }
+}
+
+/// A [`Visitor`] which decodes any value into a [`DecodedValue`]. This is what the
+/// [`crate::IntoVisitor`] impl for [`DecodedValue`] hands back.
+pub struct DecodedValueVisitor<R>(PhantomData<R>);
+
+impl<R> DecodedValueVisitor<R> {
+    /// Construct a new [`DecodedValueVisitor`].
+    pub fn new() -> Self {
+        DecodedValueVisitor(PhantomData)
+    }
+}
+
+impl<R> Default for DecodedValueVisitor<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+macro_rules! visit_number_fn_impl {
+    ($name:ident : $ty:ty => $variant:ident) => {
+        fn $name<'scale, 'resolver>(
+            self,
+            value: $ty,
+            _type_id: TypeIdFor<Self>,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            Ok(DecodedValue::$variant(value))
+        }
+    };
+}
+
+macro_rules! visit_compact_fn_impl {
+    ($name:ident : $ty:ty) => {
+        fn $name<'scale, 'resolver>(
+            self,
+            value: $ty,
+            _location: CompactLocation,
+            _type_id: TypeIdFor<Self>,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            Ok(DecodedValue::Compact(value.into()))
+        }
+    };
+}
+
+// Decode each item in some container.
+fn decode_items<'scale, 'resolver, R, D>(items: &mut D) -> Result<Vec<DecodedValue>, Error>
+where
+    R: TypeResolver,
+    D: DecodeItemIterator<'scale, 'resolver, R>,
+{
+    let mut idx = 0;
+    let mut vals = Vec::new();
+    while let Some(val) = items.decode_item(DecodedValueVisitor::new()) {
+        vals.push(val.map_err(|e| e.at_idx(idx))?);
+        idx += 1;
+    }
+    Ok(vals)
+}
+
+// Decode each field in some composite, along with its name.
+fn decode_fields<R: TypeResolver>(
+    fields: &mut Composite<'_, '_, R>,
+) -> Result<Vec<(Option<String>, DecodedValue)>, Error> {
+    let mut vals = Vec::new();
+    for (idx, field) in fields.by_ref().enumerate() {
+        let field = field?;
+        let name = field.name();
+        let val =
+            field.decode_with_visitor(DecodedValueVisitor::new()).map_err(|e| match name {
+                Some(name) => e.at_field(name.to_string()),
+                None => e.at_idx(idx),
+            })?;
+        vals.push((name.map(ToString::to_string), val));
+    }
+    Ok(vals)
+}
+
+impl<R: TypeResolver> Visitor for DecodedValueVisitor<R> {
+    type Value<'scale, 'resolver> = DecodedValue;
+    type Error = Error;
+    type TypeResolver = R;
+
+    fn visit_bool<'scale, 'resolver>(
+        self,
+        value: bool,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        Ok(DecodedValue::Bool(value))
+    }
+    fn visit_char<'scale, 'resolver>(
+        self,
+        value: char,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        Ok(DecodedValue::Char(value))
+    }
+    visit_number_fn_impl!(visit_u8: u8 => U8);
+    visit_number_fn_impl!(visit_u16: u16 => U16);
+    visit_number_fn_impl!(visit_u32: u32 => U32);
+    visit_number_fn_impl!(visit_u64: u64 => U64);
+    visit_number_fn_impl!(visit_u128: u128 => U128);
+    visit_number_fn_impl!(visit_i8: i8 => I8);
+    visit_number_fn_impl!(visit_i16: i16 => I16);
+    visit_number_fn_impl!(visit_i32: i32 => I32);
+    visit_number_fn_impl!(visit_i64: i64 => I64);
+    visit_number_fn_impl!(visit_i128: i128 => I128);
+    visit_compact_fn_impl!(visit_compact_u8: u8);
+    visit_compact_fn_impl!(visit_compact_u16: u16);
+    visit_compact_fn_impl!(visit_compact_u32: u32);
+    visit_compact_fn_impl!(visit_compact_u64: u64);
+    visit_compact_fn_impl!(visit_compact_u128: u128);
+    fn visit_u256<'resolver>(
+        self,
+        value: &[u8; 32],
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'_, 'resolver>, Self::Error> {
+        Ok(DecodedValue::U256(*value))
+    }
+    fn visit_i256<'resolver>(
+        self,
+        value: &[u8; 32],
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'_, 'resolver>, Self::Error> {
+        Ok(DecodedValue::I256(*value))
+    }
+    fn visit_str<'scale, 'resolver>(
+        self,
+        value: &mut Str<'scale>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        Ok(DecodedValue::Str(value.as_str()?.to_string()))
+    }
+    fn visit_sequence<'scale, 'resolver>(
+        self,
+        value: &mut Sequence<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        decode_items(value).map(DecodedValue::Sequence)
+    }
+    fn visit_array<'scale, 'resolver>(
+        self,
+        value: &mut Array<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        decode_items(value).map(DecodedValue::Array)
+    }
+    fn visit_tuple<'scale, 'resolver>(
+        self,
+        value: &mut Tuple<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        decode_items(value).map(DecodedValue::Tuple)
+    }
+    fn visit_composite<'scale, 'resolver>(
+        self,
+        value: &mut Composite<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        decode_fields(value).map(DecodedValue::Composite)
+    }
+    fn visit_variant<'scale, 'resolver>(
+        self,
+        value: &mut Variant<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let name = value.name();
+        let fields = decode_fields(value.fields()).map_err(|e| e.at_variant(name.to_string()))?;
+        Ok(DecodedValue::Variant { index: value.index(), name: name.to_string(), fields })
+    }
+    fn visit_bitsequence<'scale, 'resolver>(
+        self,
+        value: &mut BitSequence<'scale>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let bits = value
+            .decode()?
+            .collect::<Result<Bits, _>>()
+            .map_err(|e| Error::new(ErrorKind::VisitorDecodeError(e.into())))?;
+        Ok(DecodedValue::BitSequence(bits, value.format()))
+    }
+}
+
+impl<R: TypeResolver> Detach for DecodedValueVisitor<R> {
+    type Owned = DecodedValue;
+    fn detach(value: DecodedValue) -> DecodedValue {
+        value
+    }
+}
+
+/// A [`Visitor`] which decodes any value into a [`DecodedValue`], like [`DecodedValueVisitor`],
+/// but hands back any partially decoded value if an error is hit. See [`decode_value_partial()`].
+pub struct PartialDecodedValueVisitor<R>(PhantomData<R>);
+
+impl<R> PartialDecodedValueVisitor<R> {
+    /// Construct a new [`PartialDecodedValueVisitor`].
+    pub fn new() -> Self {
+        PartialDecodedValueVisitor(PhantomData)
+    }
+}
+
+impl<R> Default for PartialDecodedValueVisitor<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Nothing can be partially decoded in primitive values, so just use the non-partial visitor.
+macro_rules! visit_primitive_partial_fn_impl {
+    ($name:ident : $ty:ty) => {
+        fn $name<'scale, 'resolver>(
+            self,
+            value: $ty,
+            type_id: TypeIdFor<Self>,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            DecodedValueVisitor::<R>::new().$name(value, type_id).map_err(PartialError::new)
+        }
+    };
+}
+
+macro_rules! visit_compact_partial_fn_impl {
+    ($name:ident : $ty:ty) => {
+        fn $name<'scale, 'resolver>(
+            self,
+            value: $ty,
+            location: CompactLocation,
+            type_id: TypeIdFor<Self>,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            DecodedValueVisitor::<R>::new()
+                .$name(value, location, type_id)
+                .map_err(PartialError::new)
+        }
+    };
+}
+
+// Decode each item in some container, handing back a partial container on error.
+fn decode_items_partial<'scale, 'resolver, R, D>(
+    items: &mut D,
+    bytes_from_undecoded: impl Fn(&D) -> &'scale [u8],
+    container: impl Fn(Vec<DecodedValue>) -> DecodedValue,
+) -> Result<DecodedValue, PartialError<DecodedValue>>
+where
+    R: TypeResolver,
+    D: DecodeItemIterator<'scale, 'resolver, R>,
+{
+    let mut vals = Vec::new();
+    loop {
+        let remaining = bytes_from_undecoded(items);
+        match items.decode_item(PartialDecodedValueVisitor::new()) {
+            None => return Ok(container(vals)),
+            Some(Ok(val)) => vals.push(val),
+            Some(Err(mut e)) => {
+                let idx = vals.len();
+                vals.extend(e.take_partial());
+                return Err(e
+                    .at_remaining(remaining)
+                    .map_error(|e| e.at_idx(idx))
+                    .with_partial(container(vals)));
+            }
+        }
+    }
+}
+
+type NamedValues = Vec<(Option<String>, DecodedValue)>;
+
+// Decode each field in some composite, along with its name, handing back the fields decoded
+// so far on error.
+fn decode_fields_partial<R: TypeResolver>(
+    fields: &mut Composite<'_, '_, R>,
+) -> Result<NamedValues, (NamedValues, PartialError<DecodedValue>)> {
+    let mut vals = Vec::new();
+    loop {
+        let idx = vals.len();
+        let remaining = fields.bytes_from_undecoded();
+        let name = fields.peek_name();
+        match fields.decode_item(PartialDecodedValueVisitor::new()) {
+            None => return Ok(vals),
+            Some(Ok(val)) => vals.push((name.map(ToString::to_string), val)),
+            Some(Err(mut e)) => {
+                if let Some(val) = e.take_partial() {
+                    vals.push((name.map(ToString::to_string), val));
+                }
+                let e = e.at_remaining(remaining).map_error(|e| match name {
+                    Some(name) => e.at_field(name.to_string()),
+                    None => e.at_idx(idx),
+                });
+                return Err((vals, e));
+            }
+        }
+    }
+}
+
+impl<R: TypeResolver> Visitor for PartialDecodedValueVisitor<R> {
+    type Value<'scale, 'resolver> = DecodedValue;
+    type Error = PartialError<DecodedValue>;
+    type TypeResolver = R;
+
+    visit_primitive_partial_fn_impl!(visit_bool: bool);
+    visit_primitive_partial_fn_impl!(visit_char: char);
+    visit_primitive_partial_fn_impl!(visit_u8: u8);
+    visit_primitive_partial_fn_impl!(visit_u16: u16);
+    visit_primitive_partial_fn_impl!(visit_u32: u32);
+    visit_primitive_partial_fn_impl!(visit_u64: u64);
+    visit_primitive_partial_fn_impl!(visit_u128: u128);
+    visit_primitive_partial_fn_impl!(visit_i8: i8);
+    visit_primitive_partial_fn_impl!(visit_i16: i16);
+    visit_primitive_partial_fn_impl!(visit_i32: i32);
+    visit_primitive_partial_fn_impl!(visit_i64: i64);
+    visit_primitive_partial_fn_impl!(visit_i128: i128);
+    visit_compact_partial_fn_impl!(visit_compact_u8: u8);
+    visit_compact_partial_fn_impl!(visit_compact_u16: u16);
+    visit_compact_partial_fn_impl!(visit_compact_u32: u32);
+    visit_compact_partial_fn_impl!(visit_compact_u64: u64);
+    visit_compact_partial_fn_impl!(visit_compact_u128: u128);
+    fn visit_u256<'resolver>(
+        self,
+        value: &[u8; 32],
+        type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'_, 'resolver>, Self::Error> {
+        DecodedValueVisitor::<R>::new().visit_u256(value, type_id).map_err(PartialError::new)
+    }
+    fn visit_i256<'resolver>(
+        self,
+        value: &[u8; 32],
+        type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'_, 'resolver>, Self::Error> {
+        DecodedValueVisitor::<R>::new().visit_i256(value, type_id).map_err(PartialError::new)
+    }
+    fn visit_str<'scale, 'resolver>(
+        self,
+        value: &mut Str<'scale>,
+        type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        DecodedValueVisitor::<R>::new().visit_str(value, type_id).map_err(PartialError::new)
+    }
+    fn visit_bitsequence<'scale, 'resolver>(
+        self,
+        value: &mut BitSequence<'scale>,
+        type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        DecodedValueVisitor::<R>::new().visit_bitsequence(value, type_id).map_err(PartialError::new)
+    }
+    fn visit_sequence<'scale, 'resolver>(
+        self,
+        value: &mut Sequence<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        decode_items_partial(value, |v| v.bytes_from_undecoded(), DecodedValue::Sequence)
+    }
+    fn visit_array<'scale, 'resolver>(
+        self,
+        value: &mut Array<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        decode_items_partial(value, |v| v.bytes_from_undecoded(), DecodedValue::Array)
+    }
+    fn visit_tuple<'scale, 'resolver>(
+        self,
+        value: &mut Tuple<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        decode_items_partial(value, |v| v.bytes_from_undecoded(), DecodedValue::Tuple)
+    }
+    fn visit_composite<'scale, 'resolver>(
+        self,
+        value: &mut Composite<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        decode_fields_partial(value)
+            .map(DecodedValue::Composite)
+            .map_err(|(fields, e)| e.with_partial(DecodedValue::Composite(fields)))
+    }
+    fn visit_variant<'scale, 'resolver>(
+        self,
+        value: &mut Variant<'scale, 'resolver, R>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let index = value.index();
+        let name = value.name();
+        match decode_fields_partial(value.fields()) {
+            Ok(fields) => Ok(DecodedValue::Variant { index, name: name.to_string(), fields }),
+            Err((fields, e)) => Err(e
+                .map_error(|e| e.at_variant(name.to_string()))
+                .with_partial(DecodedValue::Variant { index, name: name.to_string(), fields })),
+        }
+    }
+}
+
+impl<R: TypeResolver> Detach for PartialDecodedValueVisitor<R> {
+    type Owned = DecodedValue;
+    fn detach(value: DecodedValue) -> DecodedValue {
+        value
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+    use codec::Encode;
+    use scale_info::TypeInfo;
+
+    // Decode the value, and check that it encodes back to the same bytes.
+    fn round_trip<T: Encode + TypeInfo + 'static>(value: T) -> DecodedValue {
+        let m = scale_info::MetaType::new::<T>();
+        let mut types = scale_info::Registry::new();
+        let type_id = types.register_type(&m).id;
+        let types: scale_info::PortableRegistry = types.into();
+
+        let encoded = value.encode();
+        let cursor = &mut &*encoded;
+        let decoded = decode_value(cursor, type_id, &types).unwrap();
+        assert!(cursor.is_empty(), "all bytes should be consumed");
+        assert_eq!(decoded.encode(), encoded, "value should encode to the same bytes");
+        assert_eq!(decoded.encoded_size(), encoded.len());
+        decoded
+    }
+
+    #[test]
+    fn primitives_round_trip() {
+        assert_eq!(round_trip(true), DecodedValue::Bool(true));
+        assert_eq!(round_trip(123u16), DecodedValue::U16(123));
+        assert_eq!(round_trip(-123i128), DecodedValue::I128(-123));
+        assert_eq!(round_trip(Compact(1u8)), DecodedValue::Compact(1));
+        assert_eq!(round_trip(Compact(u128::MAX)), DecodedValue::Compact(u128::MAX));
+        assert_eq!(round_trip("hello".to_string()), DecodedValue::Str("hello".to_string()));
+        round_trip(primitive_types::U256::MAX);
+    }
+
+    #[test]
+    fn collections_round_trip() {
+        assert_eq!(
+            round_trip(vec![1u8, 2]),
+            DecodedValue::Sequence(vec![DecodedValue::U8(1), DecodedValue::U8(2)])
+        );
+        assert_eq!(
+            round_trip((true, [1u32; 2])),
+            DecodedValue::Tuple(vec![
+                DecodedValue::Bool(true),
+                DecodedValue::Array(vec![DecodedValue::U32(1), DecodedValue::U32(1)])
+            ])
+        );
+
+        let bits: Bits = [true, false, true].into_iter().collect();
+        round_trip(bits);
+        let bits: bitvec::vec::BitVec<u32, bitvec::order::Msb0> =
+            [true, false, true].into_iter().collect();
+        round_trip(bits);
+    }
+
+    #[test]
+    fn composites_and_variants_round_trip() {
+        #[derive(Encode, TypeInfo)]
+        struct Named {
+            #[codec(compact)]
+            a: u64,
+            b: Vec<Unnamed>,
+        }
+        #[derive(Encode, TypeInfo)]
+        struct Unnamed(u8, bool);
+        #[derive(Encode, TypeInfo)]
+        enum Foo {
+            A,
+            B(Named),
+        }
+
+        let foo = Foo::B(Named { a: 1, b: vec![Unnamed(2, true)] });
+        let unnamed = DecodedValue::Composite(vec![
+            (None, DecodedValue::U8(2)),
+            (None, DecodedValue::Bool(true)),
+        ]);
+        let named = DecodedValue::Composite(vec![
+            (Some("a".to_string()), DecodedValue::Compact(1)),
+            (Some("b".to_string()), DecodedValue::Sequence(vec![unnamed])),
+        ]);
+        assert_eq!(
+            round_trip(foo),
+            DecodedValue::Variant { index: 1, name: "B".to_string(), fields: vec![(None, named)] }
+        );
+        assert_eq!(
+            round_trip(Foo::A),
+            DecodedValue::Variant { index: 0, name: "A".to_string(), fields: vec![] }
+        );
+    }
+
+    #[test]
+    fn partial_values_are_returned_on_error() {
+        #[derive(Encode, TypeInfo)]
+        struct Named {
+            a: u8,
+            b: Vec<(u8, bool)>,
+        }
+        #[derive(Encode, TypeInfo)]
+        enum Foo {
+            A(Named),
+        }
+
+        let m = scale_info::MetaType::new::<Foo>();
+        let mut types = scale_info::Registry::new();
+        let type_id = types.register_type(&m).id;
+        let types: scale_info::PortableRegistry = types.into();
+
+        // Corrupt the last bool, so that it can't be decoded:
+        let mut encoded = Foo::A(Named { a: 1, b: vec![(2, true), (3, true)] }).encode();
+        *encoded.last_mut().unwrap() = 2;
+
+        let cursor = &mut &*encoded;
+        let err = decode_value_partial(cursor, type_id, &types).unwrap_err();
+        assert_eq!(cursor.len(), encoded.len(), "cursor should not move on error");
+        assert_eq!(err.offset(), encoded.len() - 1);
+        assert_eq!(err.error().context().path().to_string(), "[1].[1].b.[0].(A)");
+
+        let b = DecodedValue::Sequence(vec![
+            DecodedValue::Tuple(vec![DecodedValue::U8(2), DecodedValue::Bool(true)]),
+            DecodedValue::Tuple(vec![DecodedValue::U8(3)]),
+        ]);
+        let named = DecodedValue::Composite(vec![
+            (Some("a".to_string()), DecodedValue::U8(1)),
+            (Some("b".to_string()), b),
+        ]);
+        let expected =
+            DecodedValue::Variant { index: 0, name: "A".to_string(), fields: vec![(None, named)] };
+        assert_eq!(err.partial(), Some(&expected));
+
+        // Nothing partial is handed back if the very first value fails to decode:
+        let err = decode_value_partial(&mut &[1u8][..], type_id, &types).unwrap_err();
+        assert_eq!(err.offset(), 0);
+        assert!(err.partial().is_none());
+    }
+}
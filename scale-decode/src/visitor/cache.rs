@@ -0,0 +1,390 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! A [`CachedResolver`] wraps some [`TypeResolver`] and resolves a set of types ahead of time,
+//! so that decoding many values against the same types doesn't need to go back to the underlying
+//! resolver each time. This is particularly useful when the underlying resolver does a lot of work
+//! to resolve each type.
+//!
+//! ```rust
+//! use codec::Encode;
+//! use scale_decode::{visitor::cache::CachedResolver, DecodeAsType};
+//! use scale_info::PortableRegistry;
+//!
+//! #[derive(Encode, scale_info::TypeInfo)]
+//! struct Foo {
+//!     a: u8,
+//!     b: Vec<bool>,
+//! }
+//!
+//! let m = scale_info::MetaType::new::<Foo>();
+//! let mut types = scale_info::Registry::new();
+//! let type_id = types.register_type(&m).id;
+//! let types: PortableRegistry = types.into();
+//!
+//! // Resolve the type (and any types it refers to) once, up front:
+//! let types = CachedResolver::new(types, [type_id]).unwrap();
+//!
+//! let encoded = (1u8, vec![true, false]).encode();
+//! let decoded = <(u8, Vec<bool>)>::decode_as_type(&mut &*encoded, type_id, &types).unwrap();
+//! assert_eq!(decoded, (1, vec![true, false]));
+//! ```
+
+use crate::visitor::DecodeError;
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use scale_type_resolver::{
+    BitsOrderFormat, BitsStoreFormat, Field, FieldIter, PathIter, Primitive, ResolvedTypeVisitor,
+    TypeResolver, UnhandledKind, Variant, VariantIter,
+};
+
+/// A [`TypeResolver`] which wraps another, and holds a precomputed description of some set of
+/// types. Those types are resolved without calling the underlying resolver at all, and any other
+/// types are resolved by the underlying resolver as normal.
+pub struct CachedResolver<R: TypeResolver> {
+    inner: R,
+    shapes: BTreeMap<R::TypeId, Shape<R::TypeId>>,
+}
+
+impl<R: TypeResolver> CachedResolver<R>
+where
+    R::TypeId: Ord,
+{
+    /// Wrap the given resolver, resolving the given types and any types that they refer to
+    /// ahead of time.
+    pub fn new(
+        inner: R,
+        type_ids: impl IntoIterator<Item = R::TypeId>,
+    ) -> Result<Self, DecodeError> {
+        let mut cached = CachedResolver { inner, shapes: BTreeMap::new() };
+        for type_id in type_ids {
+            cached.cache_type(type_id)?;
+        }
+        Ok(cached)
+    }
+
+    /// Resolve the given type and any types that it refers to ahead of time, if they haven't
+    /// been already.
+    pub fn cache_type(&mut self, type_id: R::TypeId) -> Result<(), DecodeError> {
+        let mut to_resolve = alloc::vec![type_id];
+        while let Some(type_id) = to_resolve.pop() {
+            if self.shapes.contains_key(&type_id) {
+                continue;
+            }
+            let shape = resolve_owned_shape(type_id.clone(), &self.inner)?;
+            to_resolve.extend(
+                shape.type_ids().into_iter().filter(|id| !self.shapes.contains_key(id)).cloned(),
+            );
+            self.shapes.insert(type_id, shape);
+        }
+        Ok(())
+    }
+
+    /// Is the given type cached?
+    pub fn is_cached(&self, type_id: &R::TypeId) -> bool {
+        self.shapes.contains_key(type_id)
+    }
+
+    /// Access the underlying resolver.
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Return the underlying resolver, discarding anything that was cached.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: TypeResolver> TypeResolver for CachedResolver<R>
+where
+    R::TypeId: Ord,
+{
+    type TypeId = R::TypeId;
+    type Error = R::Error;
+
+    fn resolve_type<'this, V: ResolvedTypeVisitor<'this, TypeId = Self::TypeId>>(
+        &'this self,
+        type_id: Self::TypeId,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let Some(shape) = self.shapes.get(&type_id) else {
+            return self.inner.resolve_type(type_id, visitor);
+        };
+
+        Ok(resolve_shape(shape, visitor))
+    }
+}
+
+// Hand the given shape to the visitor provided.
+pub(crate) fn resolve_shape<'a, TypeId, V>(shape: &'a Shape<TypeId>, visitor: V) -> V::Value
+where
+    TypeId: scale_type_resolver::TypeId + 'static,
+    V: ResolvedTypeVisitor<'a, TypeId = TypeId>,
+{
+    match shape {
+        Shape::Composite { path, fields } => {
+            visitor.visit_composite(path_iter(path), field_iter(fields))
+        }
+        Shape::Variant { path, variants } => {
+            let variants = variants.iter().map(|v| Variant {
+                index: v.index,
+                name: &v.name,
+                fields: field_iter(&v.fields),
+            });
+            visitor.visit_variant(path_iter(path), variants)
+        }
+        Shape::Sequence { path, inner } => visitor.visit_sequence(path_iter(path), inner.clone()),
+        Shape::Array { inner, len } => visitor.visit_array(inner.clone(), *len),
+        Shape::Tuple(type_ids) => visitor.visit_tuple(type_ids.iter().cloned()),
+        Shape::Primitive(primitive) => visitor.visit_primitive(*primitive),
+        Shape::Compact(inner) => visitor.visit_compact(inner.clone()),
+        Shape::BitSequence(store, order) => visitor.visit_bit_sequence(*store, *order),
+    }
+}
+
+fn path_iter(path: &[String]) -> impl PathIter<'_> {
+    path.iter().map(|s| s.as_str())
+}
+
+fn field_iter<TypeId: Clone>(fields: &[CachedField<TypeId>]) -> impl FieldIter<'_, TypeId> {
+    fields.iter().map(|f| Field::new(f.id.clone(), f.name.as_deref()))
+}
+
+// Resolve the given type into an owned description of how it's SCALE encoded.
+pub(crate) fn resolve_owned_shape<R: TypeResolver>(
+    type_id: R::TypeId,
+    types: &R,
+) -> Result<Shape<R::TypeId>, DecodeError> {
+    types
+        .resolve_type(type_id.clone(), ShapeBuilder(core::marker::PhantomData))
+        .map_err(|e| DecodeError::TypeResolvingError(e.to_string()))?
+        .ok_or_else(|| DecodeError::TypeIdNotFound(alloc::format!("{type_id:?}")))
+}
+
+// An owned description of how some type is SCALE encoded.
+pub(crate) enum Shape<TypeId> {
+    Composite { path: Vec<String>, fields: Vec<CachedField<TypeId>> },
+    Variant { path: Vec<String>, variants: Vec<CachedVariant<TypeId>> },
+    Sequence { path: Vec<String>, inner: TypeId },
+    Array { inner: TypeId, len: usize },
+    Tuple(Vec<TypeId>),
+    Primitive(Primitive),
+    Compact(TypeId),
+    BitSequence(BitsStoreFormat, BitsOrderFormat),
+}
+
+impl<TypeId> Shape<TypeId> {
+    // Any type IDs that this shape refers to.
+    fn type_ids(&self) -> Vec<&TypeId> {
+        match self {
+            Shape::Composite { fields, .. } => fields.iter().map(|f| &f.id).collect(),
+            Shape::Variant { variants, .. } => {
+                variants.iter().flat_map(|v| v.fields.iter().map(|f| &f.id)).collect()
+            }
+            Shape::Sequence { inner, .. } | Shape::Array { inner, .. } | Shape::Compact(inner) => {
+                alloc::vec![inner]
+            }
+            Shape::Tuple(type_ids) => type_ids.iter().collect(),
+            Shape::Primitive(_) | Shape::BitSequence(..) => Vec::new(),
+        }
+    }
+}
+
+pub(crate) struct CachedField<TypeId> {
+    pub(crate) name: Option<String>,
+    pub(crate) id: TypeId,
+}
+
+pub(crate) struct CachedVariant<TypeId> {
+    pub(crate) index: u8,
+    pub(crate) name: String,
+    pub(crate) fields: Vec<CachedField<TypeId>>,
+}
+
+// Build a [`Shape`] from some resolved type, or return `None` if the type wasn't found.
+struct ShapeBuilder<TypeId>(core::marker::PhantomData<TypeId>);
+
+fn cache_fields<'a, TypeId>(fields: impl FieldIter<'a, TypeId>) -> Vec<CachedField<TypeId>> {
+    fields.map(|f| CachedField { name: f.name.map(ToString::to_string), id: f.id }).collect()
+}
+
+fn cache_path<'a>(path: impl PathIter<'a>) -> Vec<String> {
+    path.map(ToString::to_string).collect()
+}
+
+impl<'resolver, TypeId: scale_type_resolver::TypeId + 'static> ResolvedTypeVisitor<'resolver>
+    for ShapeBuilder<TypeId>
+{
+    type TypeId = TypeId;
+    type Value = Option<Shape<TypeId>>;
+
+    fn visit_unhandled(self, _kind: UnhandledKind) -> Self::Value {
+        None
+    }
+
+    fn visit_composite<Path, Fields>(self, path: Path, fields: Fields) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+        Fields: FieldIter<'resolver, Self::TypeId>,
+    {
+        Some(Shape::Composite { path: cache_path(path), fields: cache_fields(fields) })
+    }
+
+    fn visit_variant<Path, Fields, Var>(self, path: Path, variants: Var) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+        Fields: FieldIter<'resolver, Self::TypeId>,
+        Var: VariantIter<'resolver, Fields>,
+    {
+        let variants = variants
+            .map(|v| CachedVariant {
+                index: v.index,
+                name: v.name.to_string(),
+                fields: cache_fields(v.fields),
+            })
+            .collect();
+        Some(Shape::Variant { path: cache_path(path), variants })
+    }
+
+    fn visit_sequence<Path>(self, path: Path, inner: Self::TypeId) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+    {
+        Some(Shape::Sequence { path: cache_path(path), inner })
+    }
+
+    fn visit_array(self, inner: Self::TypeId, len: usize) -> Self::Value {
+        Some(Shape::Array { inner, len })
+    }
+
+    fn visit_tuple<TypeIds>(self, type_ids: TypeIds) -> Self::Value
+    where
+        TypeIds: ExactSizeIterator<Item = Self::TypeId>,
+    {
+        Some(Shape::Tuple(type_ids.collect()))
+    }
+
+    fn visit_primitive(self, primitive: Primitive) -> Self::Value {
+        Some(Shape::Primitive(primitive))
+    }
+
+    fn visit_compact(self, inner: Self::TypeId) -> Self::Value {
+        Some(Shape::Compact(inner))
+    }
+
+    fn visit_bit_sequence(
+        self,
+        store_format: BitsStoreFormat,
+        order_format: BitsOrderFormat,
+    ) -> Self::Value {
+        Some(Shape::BitSequence(store_format, order_format))
+    }
+}
+
+#[cfg(feature = "derive")]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::DecodeAsType;
+    use alloc::string::String;
+    use codec::Encode;
+    use core::cell::Cell;
+    use scale_info::PortableRegistry;
+
+    // A resolver which counts how many times it's asked to resolve a type.
+    struct CountingResolver {
+        inner: PortableRegistry,
+        count: Cell<usize>,
+    }
+
+    impl TypeResolver for CountingResolver {
+        type TypeId = u32;
+        type Error = <PortableRegistry as TypeResolver>::Error;
+
+        fn resolve_type<'this, V: ResolvedTypeVisitor<'this, TypeId = Self::TypeId>>(
+            &'this self,
+            type_id: Self::TypeId,
+            visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            self.count.set(self.count.get() + 1);
+            self.inner.resolve_type(type_id, visitor)
+        }
+    }
+
+    fn make_type<T: scale_info::TypeInfo + 'static>() -> (u32, CountingResolver) {
+        let m = scale_info::MetaType::new::<T>();
+        let mut types = scale_info::Registry::new();
+        let id = types.register_type(&m);
+        let portable_registry: PortableRegistry = types.into();
+
+        (id.id, CountingResolver { inner: portable_registry, count: Cell::new(0) })
+    }
+
+    #[derive(Encode, scale_info::TypeInfo, DecodeAsType, Debug, PartialEq)]
+    #[decode_as_type(crate_path = "crate")]
+    enum Tree {
+        Leaf(String),
+        Node(Vec<Tree>, codec::Compact<u32>, [u8; 2], (bool, u16)),
+    }
+
+    #[test]
+    fn cached_types_dont_use_the_underlying_resolver() {
+        let (type_id, types) = make_type::<Tree>();
+        let types = CachedResolver::new(types, [type_id]).unwrap();
+        assert!(types.is_cached(&type_id));
+
+        let resolved_count = types.inner().count.get();
+        assert!(resolved_count > 0);
+
+        let tree = Tree::Node(
+            alloc::vec![
+                Tree::Leaf("a".into()),
+                Tree::Node(alloc::vec![], 2.into(), [1, 2], (true, 2))
+            ],
+            1.into(),
+            [3, 4],
+            (false, 3),
+        );
+        let encoded = tree.encode();
+        for _ in 0..3 {
+            let decoded = Tree::decode_as_type(&mut &*encoded, type_id, &types).unwrap();
+            assert_eq!(decoded, tree);
+        }
+
+        // Nothing further was resolved by the underlying resolver:
+        assert_eq!(types.inner().count.get(), resolved_count);
+    }
+
+    #[test]
+    fn uncached_types_fall_back_to_the_underlying_resolver() {
+        let (type_id, types) = make_type::<Tree>();
+        let types = CachedResolver::new(types, []).unwrap();
+        assert!(!types.is_cached(&type_id));
+
+        let encoded = Tree::Leaf("hi".into()).encode();
+        let decoded = Tree::decode_as_type(&mut &*encoded, type_id, &types).unwrap();
+        assert_eq!(decoded, Tree::Leaf("hi".into()));
+        assert!(types.inner().count.get() > 0);
+    }
+
+    #[test]
+    fn caching_unknown_types_fails() {
+        let (_, types) = make_type::<Tree>();
+        assert!(CachedResolver::new(types, [12345]).is_err());
+    }
+}
@@ -0,0 +1,249 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decode a single compact encoded number, along with the trail of single-field composites and
+//! tuples (like `Compact<Perbill>`) that it was found inside of. When decoding via a
+//! [`crate::Visitor`], the `visit_compact_*` methods are only told whether the number was
+//! wrapped in such types (see [`crate::visitor::CompactLocation`]); this hands back the
+//! wrapping types themselves, for instance so that the value can be re-encoded faithfully.
+//!
+//! ```rust
+//! use codec::{Compact, CompactAs, Encode};
+//! use scale_decode::visitor::compact::{decode_compact_with_info, CompactValue};
+//!
+//! #[derive(Encode, scale_info::TypeInfo)]
+//! struct Perbill(u32);
+//!
+//! impl CompactAs for Perbill {
+//!     type As = u32;
+//!     fn encode_as(&self) -> &u32 {
+//!         &self.0
+//!     }
+//!     fn decode_from(n: u32) -> Result<Self, codec::Error> {
+//!         Ok(Perbill(n))
+//!     }
+//! }
+//! impl From<Compact<Perbill>> for Perbill {
+//!     fn from(c: Compact<Perbill>) -> Self {
+//!         c.0
+//!     }
+//! }
+//!
+//! let mut types = scale_info::Registry::new();
+//! let type_id = types.register_type(&scale_info::MetaType::new::<Compact<Perbill>>()).id;
+//! let types: scale_info::PortableRegistry = types.into();
+//!
+//! let encoded = Compact(Perbill(1000)).encode();
+//! let info = decode_compact_with_info(&mut &*encoded, type_id, &types).unwrap();
+//!
+//! assert_eq!(info.value, CompactValue::U32(1000));
+//! assert_eq!(info.trail.len(), 1);
+//! assert_eq!(info.trail[0].name.as_deref(), Some("Perbill"));
+//! ```
+
+use crate::visitor::{cache, DecodeError, Unexpected};
+use alloc::{string::String, vec::Vec};
+use codec::{Compact, Decode};
+use scale_type_resolver::{Primitive, TypeResolver};
+
+// Types aren't expected to be wrapped in more single-field composites than this; bail rather
+// than loop forever if some type refers to itself.
+const MAX_WRAPPERS: usize = 32;
+
+/// A compact encoded number, and the types that it was found inside of. This is returned from
+/// [`decode_compact_with_info()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactInfo<TypeId> {
+    /// The number that was decoded.
+    pub value: CompactValue,
+    /// The type ID of the number itself.
+    pub type_id: TypeId,
+    /// The single-field composites and tuples that the number was found inside of, outermost
+    /// first. This is empty if the number itself was compact encoded, for example `Compact<u64>`.
+    pub trail: Vec<CompactWrapper<TypeId>>,
+}
+
+/// A single-field composite or tuple that some compact encoded number was found inside of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactWrapper<TypeId> {
+    /// The type ID of the composite or tuple.
+    pub type_id: TypeId,
+    /// The name of the composite (the last segment of its path), if it has one. Tuples have no
+    /// name.
+    pub name: Option<String>,
+    /// The name of the single field that we passed through, if it has one.
+    pub field: Option<String>,
+}
+
+/// A number which was compact encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum CompactValue {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+}
+
+/// Decode the compact encoded value described by the type ID given, which must be a compact
+/// type like `Compact<u64>` or `Compact<Perbill>`, returning the number along with the trail
+/// of single-field composites and tuples that were passed through in order to find it. `data`
+/// is moved past the compact encoded value.
+pub fn decode_compact_with_info<R: TypeResolver>(
+    data: &mut &[u8],
+    type_id: R::TypeId,
+    types: &R,
+) -> Result<CompactInfo<R::TypeId>, DecodeError> {
+    let mut type_id = match cache::resolve_owned_shape(type_id, types)? {
+        cache::Shape::Compact(inner) => inner,
+        shape => return Err(DecodeError::Unexpected(unexpected(&shape))),
+    };
+
+    let mut trail = Vec::new();
+    loop {
+        let shape = cache::resolve_owned_shape(type_id.clone(), types)?;
+        let (name, field, inner) = match shape {
+            cache::Shape::Primitive(primitive) => {
+                let value = decode_number(data, primitive)?;
+                return Ok(CompactInfo { value, type_id, trail });
+            }
+            cache::Shape::Composite { path, mut fields } if fields.len() == 1 => {
+                let field = fields.pop().expect("one field");
+                (path.last().cloned(), field.name, field.id)
+            }
+            cache::Shape::Tuple(mut type_ids) if type_ids.len() == 1 => {
+                (None, None, type_ids.pop().expect("one type"))
+            }
+            _ => return Err(DecodeError::CannotDecodeCompactIntoType),
+        };
+
+        if trail.len() == MAX_WRAPPERS {
+            return Err(DecodeError::CannotDecodeCompactIntoType);
+        }
+        trail.push(CompactWrapper { type_id, name, field });
+        type_id = inner;
+    }
+}
+
+fn decode_number(data: &mut &[u8], primitive: Primitive) -> Result<CompactValue, DecodeError> {
+    let value = match primitive {
+        Primitive::U8 => CompactValue::U8(Compact::<u8>::decode(data)?.0),
+        Primitive::U16 => CompactValue::U16(Compact::<u16>::decode(data)?.0),
+        Primitive::U32 => CompactValue::U32(Compact::<u32>::decode(data)?.0),
+        Primitive::U64 => CompactValue::U64(Compact::<u64>::decode(data)?.0),
+        Primitive::U128 => CompactValue::U128(Compact::<u128>::decode(data)?.0),
+        _ => return Err(DecodeError::CannotDecodeCompactIntoType),
+    };
+    Ok(value)
+}
+
+// What we found instead of a compact type.
+fn unexpected<TypeId>(shape: &cache::Shape<TypeId>) -> Unexpected {
+    match shape {
+        cache::Shape::Composite { .. } => Unexpected::Composite,
+        cache::Shape::Variant { .. } => Unexpected::Variant,
+        cache::Shape::Sequence { .. } => Unexpected::Sequence,
+        cache::Shape::Array { .. } => Unexpected::Array,
+        cache::Shape::Tuple(_) => Unexpected::Tuple,
+        cache::Shape::BitSequence(..) => Unexpected::Bitsequence,
+        cache::Shape::Primitive(Primitive::Bool) => Unexpected::Bool,
+        cache::Shape::Primitive(Primitive::Char) => Unexpected::Char,
+        cache::Shape::Primitive(Primitive::Str) => Unexpected::Str,
+        cache::Shape::Primitive(Primitive::U8) => Unexpected::U8,
+        cache::Shape::Primitive(Primitive::U16) => Unexpected::U16,
+        cache::Shape::Primitive(Primitive::U32) => Unexpected::U32,
+        cache::Shape::Primitive(Primitive::U64) => Unexpected::U64,
+        cache::Shape::Primitive(Primitive::U128) => Unexpected::U128,
+        cache::Shape::Primitive(Primitive::U256) => Unexpected::U256,
+        cache::Shape::Primitive(Primitive::I8) => Unexpected::I8,
+        cache::Shape::Primitive(Primitive::I16) => Unexpected::I16,
+        cache::Shape::Primitive(Primitive::I32) => Unexpected::I32,
+        cache::Shape::Primitive(Primitive::I64) => Unexpected::I64,
+        cache::Shape::Primitive(Primitive::I128) => Unexpected::I128,
+        cache::Shape::Primitive(Primitive::I256) => Unexpected::I256,
+        cache::Shape::Compact(_) => unreachable!("compact types are handled by the caller"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use codec::Encode;
+    use scale_info::PortableRegistry;
+
+    fn make_type<T: scale_info::TypeInfo + 'static>() -> (u32, PortableRegistry) {
+        let m = scale_info::MetaType::new::<T>();
+        let mut types = scale_info::Registry::new();
+        let id = types.register_type(&m);
+        (id.id, types.into())
+    }
+
+    #[test]
+    fn decodes_primitive_compacts() {
+        let (type_id, types) = make_type::<Compact<u64>>();
+        let encoded = (Compact(123u64), 1u8).encode();
+        let data = &mut &*encoded;
+
+        let info = decode_compact_with_info(data, type_id, &types).unwrap();
+        assert_eq!(info.value, CompactValue::U64(123));
+        assert!(info.trail.is_empty());
+        assert_eq!(*data, &[1]);
+    }
+
+    #[test]
+    fn records_wrapping_composites_and_tuples() {
+        #[derive(Encode, scale_info::TypeInfo)]
+        struct Inner {
+            value: u32,
+        }
+        #[derive(Encode, scale_info::TypeInfo)]
+        struct Outer((Inner,));
+
+        impl codec::CompactAs for Outer {
+            type As = u32;
+            fn encode_as(&self) -> &u32 {
+                &self.0 .0.value
+            }
+            fn decode_from(n: u32) -> Result<Self, codec::Error> {
+                Ok(Outer((Inner { value: n },)))
+            }
+        }
+        impl From<Compact<Outer>> for Outer {
+            fn from(c: Compact<Outer>) -> Self {
+                c.0
+            }
+        }
+
+        let (type_id, types) = make_type::<Compact<Outer>>();
+        let encoded = Compact(Outer((Inner { value: 70000 },))).encode();
+
+        let info = decode_compact_with_info(&mut &*encoded, type_id, &types).unwrap();
+        assert_eq!(info.value, CompactValue::U32(70000));
+
+        let trail: Vec<_> =
+            info.trail.iter().map(|w| (w.name.as_deref(), w.field.as_deref())).collect();
+        assert_eq!(trail, [(Some("Outer"), None), (None, None), (Some("Inner"), Some("value"))]);
+    }
+
+    #[test]
+    fn errors_on_non_compact_types() {
+        let (type_id, types) = make_type::<u64>();
+        let encoded = 123u64.encode();
+
+        let err = decode_compact_with_info(&mut &*encoded, type_id, &types).unwrap_err();
+        assert_eq!(err, DecodeError::Unexpected(Unexpected::U64));
+    }
+}
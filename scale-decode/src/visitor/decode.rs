@@ -13,8 +13,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 use crate::visitor::{
-    Array, BitSequence, Composite, DecodeAsTypeResult, DecodeError, Sequence, Str, Tuple,
-    TypeIdFor, Variant, Visitor,
+    skip, Array, BitSequence, CompactLocation, Composite, DecodeAsTypeResult, DecodeError, Detach,
+    Opaque, Sequence, Str, Tuple, TypeIdFor, UnknownPrimitive, Variant, Visitor,
 };
 use crate::Field;
 use alloc::format;
@@ -38,12 +38,105 @@ pub fn decode_with_visitor<'scale, 'resolver, V: Visitor>(
     decode_with_visitor_maybe_compact(data, ty_id, types, visitor, false)
 }
 
+/// Decode some owned bytes according to the type ID and type resolver provided, handing back a
+/// value which doesn't borrow from them. This is like [`decode_with_visitor()`], but is more
+/// convenient when decoding from some temporary buffer, since the [`Detach`] trait converts the
+/// value returned from the visitor into an owned value. Any bytes left over after decoding are
+/// ignored.
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_decode::visitor::{decode_with_visitor_owned, DecodeError, Detach, TypeIdFor};
+/// use scale_decode::Visitor;
+/// use scale_info::PortableRegistry;
+///
+/// // A visitor which borrows strings from the bytes being decoded.
+/// struct StrVisitor;
+/// impl Visitor for StrVisitor {
+///     type Value<'scale, 'resolver> = &'scale str;
+///     type Error = DecodeError;
+///     type TypeResolver = PortableRegistry;
+///
+///     fn visit_str<'scale, 'resolver>(
+///         self,
+///         value: &mut scale_decode::visitor::types::Str<'scale>,
+///         _type_id: TypeIdFor<Self>,
+///     ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+///         value.as_str()
+///     }
+/// }
+///
+/// // By saying how to detach its values, we can decode from owned bytes.
+/// impl Detach for StrVisitor {
+///     type Owned = String;
+///     fn detach(value: &str) -> String {
+///         value.to_string()
+///     }
+/// }
+///
+/// fn decode_str(bytes: Vec<u8>, type_id: u32, types: &PortableRegistry) -> String {
+///     decode_with_visitor_owned(bytes, type_id, types, StrVisitor).unwrap()
+/// }
+///
+/// let mut types = scale_info::Registry::new();
+/// let str_id = types.register_type(&scale_info::MetaType::new::<String>()).id;
+/// let types: PortableRegistry = types.into();
+///
+/// assert_eq!(decode_str("hello".encode(), str_id, &types), "hello");
+/// ```
+pub fn decode_with_visitor_owned<V: Detach>(
+    bytes: impl AsRef<[u8]>,
+    ty_id: TypeIdFor<V>,
+    types: &V::TypeResolver,
+    visitor: V,
+) -> Result<V::Owned, V::Error> {
+    decode_with_visitor(&mut bytes.as_ref(), ty_id, types, visitor).map(V::detach)
+}
+
+/// Decode data whose structure is given by the fields provided, by handing a [`Composite`]
+/// representing those fields to [`Visitor::visit_composite()`]. Any fields not consumed by the
+/// visitor are skipped over, and the provided pointer to the data slice is moved to the bytes
+/// after the last field.
+///
+/// Since there is no type ID associated with the fields, the visitor is given a default one.
+pub fn decode_fields_with_visitor<'scale, 'resolver, V: Visitor>(
+    data: &mut &'scale [u8],
+    fields: &mut dyn FieldIter<'resolver, TypeIdFor<V>>,
+    types: &'resolver V::TypeResolver,
+    visitor: V,
+) -> Result<V::Value<'scale, 'resolver>, V::Error> {
+    let mut composite = Composite::new(core::iter::empty(), data, fields, types, false);
+
+    // [jsdw] TODO: Passing a "default type ID" to a visitor just to satisfy the signature is
+    // a bit hideous (and requires `TypeId: Default`). Can we re-work this to avoid?
+    let val = visitor.visit_composite(&mut composite, Default::default());
+
+    // Skip over any bytes that weren't decoded:
+    composite.skip_decoding()?;
+    *data = composite.bytes_from_undecoded();
+
+    val
+}
+
 pub fn decode_with_visitor_maybe_compact<'scale, 'resolver, V: Visitor>(
     data: &mut &'scale [u8],
     ty_id: TypeIdFor<V>,
     types: &'resolver V::TypeResolver,
     visitor: V,
     is_compact: bool,
+) -> Result<V::Value<'scale, 'resolver>, V::Error> {
+    // If we're asked to decode something as compact, it's because we're decoding
+    // fields inside some compact encoded composite or tuple.
+    let compact = is_compact.then_some(CompactLocation::Composite);
+    decode_with_visitor_at(data, ty_id, types, visitor, compact)
+}
+
+fn decode_with_visitor_at<'scale, 'resolver, V: Visitor>(
+    data: &mut &'scale [u8],
+    ty_id: TypeIdFor<V>,
+    types: &'resolver V::TypeResolver,
+    visitor: V,
+    compact: Option<CompactLocation>,
 ) -> Result<V::Value<'scale, 'resolver>, V::Error> {
     // Provide option to "bail out" and do something custom first.
     let visitor = match visitor.unchecked_decode_as_type(data, ty_id.clone(), types) {
@@ -51,7 +144,16 @@ pub fn decode_with_visitor_maybe_compact<'scale, 'resolver, V: Visitor>(
         DecodeAsTypeResult::Skipped(v) => v,
     };
 
-    let decoder = Decoder::new(data, types, ty_id.clone(), visitor, is_compact);
+    // Visitors which don't want any values are just told what was skipped over. Values of kinds
+    // that the type resolver can't describe are still handed over, since only the visitor can
+    // say how many bytes they take up.
+    if !V::WANTS_VALUES {
+        if let Some(unexpected) = skip::skip_value(data, ty_id.clone(), types, compact.is_some())? {
+            return visitor.visit_unexpected(unexpected);
+        }
+    }
+
+    let decoder = Decoder::new(data, types, ty_id.clone(), visitor, compact);
     let res = types.resolve_type(ty_id, decoder);
 
     match res {
@@ -75,7 +177,8 @@ struct Decoder<'a, 'scale, 'resolver, V: Visitor> {
     type_id: TypeIdFor<V>,
     types: &'resolver V::TypeResolver,
     visitor: V,
-    is_compact: bool,
+    // Is the value compact encoded, and if so, how did we get here.
+    compact: Option<CompactLocation>,
 }
 
 impl<'a, 'scale, 'resolver, V: Visitor> Decoder<'a, 'scale, 'resolver, V> {
@@ -84,9 +187,25 @@ impl<'a, 'scale, 'resolver, V: Visitor> Decoder<'a, 'scale, 'resolver, V> {
         types: &'resolver V::TypeResolver,
         type_id: TypeIdFor<V>,
         visitor: V,
-        is_compact: bool,
+        compact: Option<CompactLocation>,
     ) -> Self {
-        Decoder { data, type_id, types, is_compact, visitor }
+        Decoder { data, type_id, types, compact, visitor }
+    }
+}
+
+#[cfg(feature = "float")]
+fn decode_float<'scale, 'resolver, V: Visitor>(
+    name: &str,
+    data: &mut &'scale [u8],
+    visitor: V,
+    type_id: TypeIdFor<V>,
+) -> Result<V::Value<'scale, 'resolver>, V::Error> {
+    if name == "f32" {
+        let bits = u32::decode(data).map_err(DecodeError::from)?;
+        visitor.visit_f32(f32::from_bits(bits), type_id)
+    } else {
+        let bits = u64::decode(data).map_err(DecodeError::from)?;
+        visitor.visit_f64(f64::from_bits(bits), type_id)
     }
 }
 
@@ -118,10 +237,28 @@ impl<'temp, 'scale, 'resolver, V: Visitor> ResolvedTypeVisitor<'resolver>
 
     fn visit_unhandled(self, kind: UnhandledKind) -> Self::Value {
         let type_id = self.type_id;
-        Err(DecodeError::TypeIdNotFound(format!(
-            "Kind {kind:?} (type ID {type_id:?}) has not been properly handled"
-        ))
-        .into())
+        if kind == UnhandledKind::Primitive {
+            if self.compact.is_some() {
+                return Err(DecodeError::CannotDecodeCompactIntoType.into());
+            }
+            // The visitor decides how many bytes the primitive takes up; move past those.
+            let mut primitive = UnknownPrimitive::new(self.data, self.types);
+            let res = self.visitor.visit_unknown_primitive(&mut primitive, type_id)?;
+            *self.data = primitive.bytes_from_undecoded();
+            return Ok(res);
+        }
+        if kind == UnhandledKind::NotFound {
+            return Err(DecodeError::TypeIdNotFound(format!(
+                "Kind {kind:?} (type ID {type_id:?}) has not been properly handled"
+            ))
+            .into());
+        }
+        // We don't know where the value ends, so the visitor decides how many bytes it takes up;
+        // move past those.
+        let mut opaque = Opaque::new(self.data, kind, self.types);
+        let res = self.visitor.visit_opaque(&mut opaque, type_id)?;
+        *self.data = opaque.bytes_from_undecoded();
+        Ok(res)
     }
 
     fn visit_not_found(self) -> Self::Value {
@@ -134,12 +271,30 @@ impl<'temp, 'scale, 'resolver, V: Visitor> ResolvedTypeVisitor<'resolver>
         Path: PathIter<'resolver>,
         Fields: FieldIter<'resolver, Self::TypeId>,
     {
+        // Floats aren't one of the standard primitives, so resolvers describe them as composites
+        // with the path `f32` or `f64`, whose only field holds the bits of the float. We look at
+        // the first segments of the path and then put them back, rather than collecting it.
+        #[cfg(feature = "float")]
+        let path = {
+            let mut path = path;
+            let first = path.next();
+            let mut rest = path.peekable();
+            if let (Some(name @ ("f32" | "f64")), None, 1, None) =
+                (first, rest.peek(), fields.len(), self.compact)
+            {
+                return decode_float(name, self.data, self.visitor, self.type_id);
+            }
+            first.into_iter().chain(rest)
+        };
+
         // guard against invalid compact types: only composites with 1 field can be compact encoded
-        if self.is_compact && fields.len() != 1 {
+        if self.compact.is_some() && fields.len() != 1 {
             return Err(DecodeError::CannotDecodeCompactIntoType.into());
         }
 
-        let mut items = Composite::new(path, self.data, &mut fields, self.types, self.is_compact);
+        let mut items =
+            Composite::new(path, self.data, &mut fields, self.types, self.compact.is_some())
+                .with_parent(self.type_id.clone(), None);
         let res = self.visitor.visit_composite(&mut items, self.type_id);
 
         skip_decoding_and_return!(self, res, items)
@@ -151,11 +306,12 @@ impl<'temp, 'scale, 'resolver, V: Visitor> ResolvedTypeVisitor<'resolver>
         Fields: FieldIter<'resolver, Self::TypeId>,
         Var: VariantIter<'resolver, Fields>,
     {
-        if self.is_compact {
+        if self.compact.is_some() {
             return Err(DecodeError::CannotDecodeCompactIntoType.into());
         }
 
-        let mut variant = Variant::new(self.data, variants, self.types)?;
+        let mut variant =
+            Variant::new(self.data, variants, self.types)?.with_parent(self.type_id.clone());
         let res = self.visitor.visit_variant(&mut variant, self.type_id);
 
         skip_decoding_and_return!(self, res, variant)
@@ -165,7 +321,7 @@ impl<'temp, 'scale, 'resolver, V: Visitor> ResolvedTypeVisitor<'resolver>
     where
         Path: PathIter<'resolver>,
     {
-        if self.is_compact {
+        if self.compact.is_some() {
             return Err(DecodeError::CannotDecodeCompactIntoType.into());
         }
 
@@ -176,7 +332,7 @@ impl<'temp, 'scale, 'resolver, V: Visitor> ResolvedTypeVisitor<'resolver>
     }
 
     fn visit_array(self, inner_type_id: Self::TypeId, len: usize) -> Self::Value {
-        if self.is_compact {
+        if self.compact.is_some() {
             return Err(DecodeError::CannotDecodeCompactIntoType.into());
         }
 
@@ -191,12 +347,12 @@ impl<'temp, 'scale, 'resolver, V: Visitor> ResolvedTypeVisitor<'resolver>
         TypeIds: ExactSizeIterator<Item = Self::TypeId>,
     {
         // guard against invalid compact types: only composites with 1 field can be compact encoded
-        if self.is_compact && type_ids.len() != 1 {
+        if self.compact.is_some() && type_ids.len() != 1 {
             return Err(DecodeError::CannotDecodeCompactIntoType.into());
         }
 
         let mut fields = type_ids.map(Field::unnamed);
-        let mut items = Tuple::new(self.data, &mut fields, self.types, self.is_compact);
+        let mut items = Tuple::new(self.data, &mut fields, self.types, self.compact.is_some());
         let res = self.visitor.visit_tuple(&mut items, self.type_id);
 
         skip_decoding_and_return!(self, res, items)
@@ -215,9 +371,9 @@ impl<'temp, 'scale, 'resolver, V: Visitor> ResolvedTypeVisitor<'resolver>
             data: &mut &'scale [u8],
         ) -> Result<&'scale [u8; 32], DecodeError> {
             // Pull an array from the data if we can, preserving the lifetime.
-            let arr: &'scale [u8; 32] = match (*data).try_into() {
-                Ok(arr) => arr,
-                Err(_) => return Err(DecodeError::NotEnoughInput),
+            let arr: &'scale [u8; 32] = match data.get(..32) {
+                Some(arr) => arr.try_into().expect("slice is 32 bytes long"),
+                None => return Err(DecodeError::NotEnoughInput(32 - data.len())),
             };
             // If we successfully read the bytes, then advance the pointer past them.
             *data = &data[32..];
@@ -225,7 +381,8 @@ impl<'temp, 'scale, 'resolver, V: Visitor> ResolvedTypeVisitor<'resolver>
         }
 
         let data = self.data;
-        let is_compact = self.is_compact;
+        let compact = self.compact;
+        let is_compact = compact.is_some();
         let visitor = self.visitor;
         let type_id = self.type_id;
 
@@ -251,51 +408,56 @@ impl<'temp, 'scale, 'resolver, V: Visitor> ResolvedTypeVisitor<'resolver>
                 *data = s.bytes_after()?;
                 visitor.visit_str(&mut s, type_id)
             }
-            Primitive::U8 => {
-                let n = if is_compact {
-                    codec::Compact::<u8>::decode(data).map(|c| c.0)
-                } else {
-                    u8::decode(data)
+            Primitive::U8 => match compact {
+                Some(location) => {
+                    let n = codec::Compact::<u8>::decode(data).map_err(DecodeError::from)?.0;
+                    visitor.visit_compact_u8(n, location, type_id)
                 }
-                .map_err(Into::into)?;
-                visitor.visit_u8(n, type_id)
-            }
-            Primitive::U16 => {
-                let n = if is_compact {
-                    codec::Compact::<u16>::decode(data).map(|c| c.0)
-                } else {
-                    u16::decode(data)
+                None => {
+                    let n = u8::decode(data).map_err(DecodeError::from)?;
+                    visitor.visit_u8(n, type_id)
                 }
-                .map_err(Into::into)?;
-                visitor.visit_u16(n, type_id)
-            }
-            Primitive::U32 => {
-                let n = if is_compact {
-                    codec::Compact::<u32>::decode(data).map(|c| c.0)
-                } else {
-                    u32::decode(data)
+            },
+            Primitive::U16 => match compact {
+                Some(location) => {
+                    let n = codec::Compact::<u16>::decode(data).map_err(DecodeError::from)?.0;
+                    visitor.visit_compact_u16(n, location, type_id)
                 }
-                .map_err(Into::into)?;
-                visitor.visit_u32(n, type_id)
-            }
-            Primitive::U64 => {
-                let n = if is_compact {
-                    codec::Compact::<u64>::decode(data).map(|c| c.0)
-                } else {
-                    u64::decode(data)
+                None => {
+                    let n = u16::decode(data).map_err(DecodeError::from)?;
+                    visitor.visit_u16(n, type_id)
                 }
-                .map_err(Into::into)?;
-                visitor.visit_u64(n, type_id)
-            }
-            Primitive::U128 => {
-                let n = if is_compact {
-                    codec::Compact::<u128>::decode(data).map(|c| c.0)
-                } else {
-                    u128::decode(data)
+            },
+            Primitive::U32 => match compact {
+                Some(location) => {
+                    let n = codec::Compact::<u32>::decode(data).map_err(DecodeError::from)?.0;
+                    visitor.visit_compact_u32(n, location, type_id)
                 }
-                .map_err(Into::into)?;
-                visitor.visit_u128(n, type_id)
-            }
+                None => {
+                    let n = u32::decode(data).map_err(DecodeError::from)?;
+                    visitor.visit_u32(n, type_id)
+                }
+            },
+            Primitive::U64 => match compact {
+                Some(location) => {
+                    let n = codec::Compact::<u64>::decode(data).map_err(DecodeError::from)?.0;
+                    visitor.visit_compact_u64(n, location, type_id)
+                }
+                None => {
+                    let n = u64::decode(data).map_err(DecodeError::from)?;
+                    visitor.visit_u64(n, type_id)
+                }
+            },
+            Primitive::U128 => match compact {
+                Some(location) => {
+                    let n = codec::Compact::<u128>::decode(data).map_err(DecodeError::from)?.0;
+                    visitor.visit_compact_u128(n, location, type_id)
+                }
+                None => {
+                    let n = u128::decode(data).map_err(DecodeError::from)?;
+                    visitor.visit_u128(n, type_id)
+                }
+            },
             Primitive::U256 => {
                 err_if_compact!(is_compact);
                 let arr = decode_32_bytes(data)?;
@@ -335,7 +497,8 @@ impl<'temp, 'scale, 'resolver, V: Visitor> ResolvedTypeVisitor<'resolver>
     }
 
     fn visit_compact(self, inner_type_id: Self::TypeId) -> Self::Value {
-        decode_with_visitor_maybe_compact(self.data, inner_type_id, self.types, self.visitor, true)
+        let compact = Some(CompactLocation::Primitive);
+        decode_with_visitor_at(self.data, inner_type_id, self.types, self.visitor, compact)
     }
 
     fn visit_bit_sequence(
@@ -343,7 +506,7 @@ impl<'temp, 'scale, 'resolver, V: Visitor> ResolvedTypeVisitor<'resolver>
         store_format: BitsStoreFormat,
         order_format: BitsOrderFormat,
     ) -> Self::Value {
-        if self.is_compact {
+        if self.compact.is_some() {
             return Err(DecodeError::CannotDecodeCompactIntoType.into());
         }
 
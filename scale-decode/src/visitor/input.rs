@@ -0,0 +1,575 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decode values whose bytes arrive a bit at a time, for instance from a socket or file, reading
+//! exactly the bytes that the value needs and no more.
+//!
+//! An [`IncrementalDecoder`] is handed bytes as they are read, and says how many more are needed
+//! before the value can be decoded. It doesn't do any reading itself, and so it can be driven by
+//! any blocking or async reader, and with any async runtime:
+//!
+//! ```rust
+//! use codec::Encode;
+//! use scale_decode::visitor::input::IncrementalDecoder;
+//! use scale_info::PortableRegistry;
+//!
+//! let m = scale_info::MetaType::new::<(String, Vec<u64>)>();
+//! let mut types = scale_info::Registry::new();
+//! let type_id = types.register_type(&m).id;
+//! let types: PortableRegistry = types.into();
+//!
+//! // Some bytes that we'll read from; there are more bytes after the value we want.
+//! let encoded = [("hello".to_string(), vec![1u64, 2, 3]).encode(), vec![1, 2, 3]].concat();
+//! let mut reader = &*encoded;
+//!
+//! let mut decoder = IncrementalDecoder::new(type_id, &types);
+//! while let Some(n) = decoder.bytes_needed().unwrap() {
+//!     // Here, we'd `read_exact` (or `read_exact(..).await`) exactly `n` bytes:
+//!     let (bytes, rest) = reader.split_at(n);
+//!     decoder.push(bytes);
+//!     reader = rest;
+//! }
+//!
+//! let value: (String, Vec<u64>) = decoder.decode().unwrap();
+//! assert_eq!(value, ("hello".to_string(), vec![1, 2, 3]));
+//! // Nothing after the value was read:
+//! assert_eq!(reader, &[1, 2, 3]);
+//! ```
+//!
+//! With the `std` feature enabled, [`decode_from_reader()`] does this given any
+//! [`std::io::Read`] implementation, and with the `futures-io` feature enabled,
+//! [`decode_from_async_reader()`] does the same given any `futures_io::AsyncRead` implementation.
+//!
+//! More generally, values can be decoded from any [`ScaleInput`] via [`decode_from_input()`]. A
+//! [`SliceInput`] decodes straight from the underlying bytes, exactly like decoding from a
+//! `&mut &[u8]`, while inputs which aren't contiguous, like a [`ChunkedInput`], hand over the bytes
+//! that each value needs to an [`IncrementalDecoder`]. Either way, the input keeps track of how
+//! many bytes have been read from it.
+
+use crate::{
+    visitor::{skip::ResumableSkip, DecodeError},
+    DecodeAsType, Error,
+};
+use alloc::vec::Vec;
+use scale_type_resolver::TypeResolver;
+
+/// Decode a single value from bytes which are handed over a bit at a time. See the
+/// [module docs](self) for an example.
+pub struct IncrementalDecoder<'resolver, R: TypeResolver> {
+    type_id: R::TypeId,
+    types: &'resolver R,
+    buf: Vec<u8>,
+    // Finds where the value ends, carrying on from where it got to each time more bytes arrive.
+    skip: ResumableSkip<R::TypeId>,
+}
+
+impl<'resolver, R: TypeResolver> IncrementalDecoder<'resolver, R> {
+    /// Prepare to decode a value of the type given.
+    pub fn new(type_id: R::TypeId, types: &'resolver R) -> Self {
+        let skip = ResumableSkip::new(type_id.clone());
+        IncrementalDecoder { type_id, types, buf: Vec::new(), skip }
+    }
+
+    /// Return how many more bytes need to be pushed before we know whether the value is complete,
+    /// or `None` if we've been given all of the bytes that the value needs. Fewer bytes may be
+    /// asked for than the value needs in total; call this again after pushing them to find out
+    /// how many more are needed.
+    ///
+    /// Errors are returned if the bytes given can't be a valid value of the type, for instance
+    /// because they contain an unknown variant index.
+    ///
+    /// Each call carries on from where the last one got to, so the bytes of the value are only
+    /// looked at once however many pieces they are handed over in.
+    pub fn bytes_needed(&mut self) -> Result<Option<usize>, Error> {
+        match self.skip.resume(&self.buf, self.types) {
+            Ok(_) => Ok(None),
+            Err(DecodeError::NotEnoughInput(needed)) => Ok(Some(needed)),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Hand over some more bytes.
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// The bytes that we've been handed so far.
+    pub fn bytes(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Decode the value into some type. Errors are returned if any bytes are still needed, or if
+    /// more bytes than the value needs have been pushed.
+    pub fn decode<T: DecodeAsType>(&self) -> Result<T, Error> {
+        T::decode_as_type_exact(&self.buf, self.type_id.clone(), self.types)
+    }
+}
+
+/// A source of SCALE encoded bytes which values can be decoded from via [`decode_from_input()`].
+pub trait ScaleInput<'scale> {
+    /// Append the next `n` bytes to `buf`, moving past them. An error is returned if there
+    /// aren't `n` bytes left.
+    fn read(&mut self, n: usize, buf: &mut Vec<u8>) -> Result<(), DecodeError>;
+    /// Append the next `n` bytes to `buf` without moving past them. An error is returned if there
+    /// aren't `n` bytes left.
+    fn peek(&self, n: usize, buf: &mut Vec<u8>) -> Result<(), DecodeError>;
+    /// Move past the next `n` bytes. An error is returned if there aren't `n` bytes left.
+    fn skip(&mut self, n: usize) -> Result<(), DecodeError>;
+    /// How many bytes have been read or skipped over so far.
+    fn offset(&self) -> usize;
+    /// If every remaining byte is available in one slice, return it, so that values can be
+    /// decoded from it without copying any bytes. This returns `None` by default.
+    fn as_slice(&self) -> Option<&'scale [u8]> {
+        None
+    }
+}
+
+/// A [`ScaleInput`] backed by a single slice of bytes. Decoding from this is as cheap as decoding
+/// from the slice directly.
+#[derive(Debug, Clone, Copy)]
+pub struct SliceInput<'scale> {
+    bytes: &'scale [u8],
+    offset: usize,
+}
+
+impl<'scale> SliceInput<'scale> {
+    /// Read from the bytes given.
+    pub fn new(bytes: &'scale [u8]) -> Self {
+        SliceInput { bytes, offset: 0 }
+    }
+    /// The bytes that haven't been read yet.
+    pub fn remaining(&self) -> &'scale [u8] {
+        &self.bytes[self.offset..]
+    }
+    // The next `n` bytes, or an error if there aren't enough.
+    fn next(&self, n: usize) -> Result<&'scale [u8], DecodeError> {
+        let remaining = self.remaining();
+        remaining.get(..n).ok_or_else(|| DecodeError::NotEnoughInput(n - remaining.len()))
+    }
+}
+
+impl<'scale> ScaleInput<'scale> for SliceInput<'scale> {
+    fn read(&mut self, n: usize, buf: &mut Vec<u8>) -> Result<(), DecodeError> {
+        buf.extend_from_slice(self.next(n)?);
+        self.offset += n;
+        Ok(())
+    }
+    fn peek(&self, n: usize, buf: &mut Vec<u8>) -> Result<(), DecodeError> {
+        buf.extend_from_slice(self.next(n)?);
+        Ok(())
+    }
+    fn skip(&mut self, n: usize) -> Result<(), DecodeError> {
+        self.next(n)?;
+        self.offset += n;
+        Ok(())
+    }
+    fn offset(&self) -> usize {
+        self.offset
+    }
+    fn as_slice(&self) -> Option<&'scale [u8]> {
+        Some(self.remaining())
+    }
+}
+
+/// A [`ScaleInput`] backed by several slices of bytes, which are read one after the other, for
+/// instance because they arrived in separate network packets. Values may span several chunks.
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkedInput<'scale> {
+    chunks: &'scale [&'scale [u8]],
+    // The current chunk, and how far into it we are.
+    chunk: usize,
+    pos: usize,
+    offset: usize,
+}
+
+impl<'scale> ChunkedInput<'scale> {
+    /// Read from each of the chunks given in turn.
+    pub fn new(chunks: &'scale [&'scale [u8]]) -> Self {
+        ChunkedInput { chunks, chunk: 0, pos: 0, offset: 0 }
+    }
+    // Hand each piece of the next `n` bytes to `f`, returning where we'd be after them.
+    fn walk(
+        &self,
+        mut n: usize,
+        mut f: impl FnMut(&'scale [u8]),
+    ) -> Result<(usize, usize), DecodeError> {
+        let (mut chunk, mut pos) = (self.chunk, self.pos);
+        while n > 0 {
+            let Some(bytes) = self.chunks.get(chunk) else {
+                return Err(DecodeError::NotEnoughInput(n));
+            };
+            let piece = &bytes[pos..pos + n.min(bytes.len() - pos)];
+            f(piece);
+            n -= piece.len();
+            pos += piece.len();
+            if pos == bytes.len() {
+                chunk += 1;
+                pos = 0;
+            }
+        }
+        Ok((chunk, pos))
+    }
+}
+
+impl<'scale> ScaleInput<'scale> for ChunkedInput<'scale> {
+    fn read(&mut self, n: usize, buf: &mut Vec<u8>) -> Result<(), DecodeError> {
+        let start = buf.len();
+        match self.walk(n, |piece| buf.extend_from_slice(piece)) {
+            Ok((chunk, pos)) => {
+                (self.chunk, self.pos, self.offset) = (chunk, pos, self.offset + n);
+                Ok(())
+            }
+            Err(e) => {
+                buf.truncate(start);
+                Err(e)
+            }
+        }
+    }
+    fn peek(&self, n: usize, buf: &mut Vec<u8>) -> Result<(), DecodeError> {
+        let start = buf.len();
+        self.walk(n, |piece| buf.extend_from_slice(piece)).map(|_| ()).inspect_err(|_| {
+            buf.truncate(start);
+        })
+    }
+    fn skip(&mut self, n: usize) -> Result<(), DecodeError> {
+        (self.chunk, self.pos) = self.walk(n, |_| {})?;
+        self.offset += n;
+        Ok(())
+    }
+    fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+/// Decode a value from the given [`ScaleInput`], moving the input past exactly the bytes that
+/// the value occupies. If the input can hand back its remaining bytes as a single slice, we
+/// decode straight from that, and otherwise the bytes that the value needs are copied out of the
+/// input as they are needed.
+pub fn decode_from_input<'scale, T: DecodeAsType, R: TypeResolver>(
+    input: &mut impl ScaleInput<'scale>,
+    type_id: R::TypeId,
+    types: &R,
+) -> Result<T, Error> {
+    if let Some(bytes) = input.as_slice() {
+        let mut remaining = bytes;
+        let value = T::decode_as_type(&mut remaining, type_id, types)?;
+        input.skip(bytes.len() - remaining.len())?;
+        return Ok(value);
+    }
+
+    let mut decoder = IncrementalDecoder::new(type_id, types);
+    while let Some(needed) = decoder.bytes_needed()? {
+        input.read(needed, &mut decoder.buf)?;
+    }
+    decoder.decode()
+}
+
+/// An error decoding a value from some [`std::io::Read`] implementation.
+#[cfg(feature = "std")]
+#[derive(Debug, thiserror::Error)]
+pub enum ReadError {
+    /// We couldn't read the bytes that we needed.
+    #[error("Error reading bytes: {0}")]
+    Io(#[from] std::io::Error),
+    /// We couldn't decode the bytes that were read.
+    #[error("{0}")]
+    Decode(#[from] Error),
+}
+
+// The most bytes that we'll make room for and read in one go. The number of bytes that a value
+// needs comes from the bytes themselves (for instance the length of a string), so we don't trust
+// it enough to allocate it all up front; the buffer only grows as bytes actually arrive.
+#[cfg(feature = "std")]
+const MAX_READ_LEN: usize = 64 * 1024;
+
+/// Decode a value from the given reader, reading exactly the bytes that the value needs, so that
+/// the reader is left pointing to any bytes after it. Reads may be small, so a buffered reader is
+/// a good idea.
+#[cfg(feature = "std")]
+pub fn decode_from_reader<T: DecodeAsType, R: TypeResolver>(
+    reader: &mut impl std::io::Read,
+    type_id: R::TypeId,
+    types: &R,
+) -> Result<T, ReadError> {
+    let mut decoder = IncrementalDecoder::new(type_id, types);
+    while let Some(needed) = decoder.bytes_needed()? {
+        let start = decoder.buf.len();
+        decoder.buf.resize(start + needed.min(MAX_READ_LEN), 0);
+        reader.read_exact(&mut decoder.buf[start..])?;
+    }
+    Ok(decoder.decode()?)
+}
+
+/// Like [`decode_from_reader()`], but reads from some `futures_io::AsyncRead` implementation, and
+/// so can be used with any async runtime which provides one.
+#[cfg(feature = "futures-io")]
+pub async fn decode_from_async_reader<T: DecodeAsType, R: TypeResolver>(
+    reader: &mut (impl futures_io::AsyncRead + Unpin),
+    type_id: R::TypeId,
+    types: &R,
+) -> Result<T, ReadError> {
+    let mut decoder = IncrementalDecoder::new(type_id, types);
+    while let Some(needed) = decoder.bytes_needed()? {
+        let start = decoder.buf.len();
+        decoder.buf.resize(start + needed.min(MAX_READ_LEN), 0);
+        read_exact_async(reader, &mut decoder.buf[start..]).await?;
+    }
+    Ok(decoder.decode()?)
+}
+
+// Fill the buffer given with bytes from the reader, erroring if it runs out of bytes first.
+#[cfg(feature = "futures-io")]
+async fn read_exact_async(
+    reader: &mut (impl futures_io::AsyncRead + Unpin),
+    mut buf: &mut [u8],
+) -> std::io::Result<()> {
+    while !buf.is_empty() {
+        let read =
+            core::future::poll_fn(|cx| core::pin::Pin::new(&mut *reader).poll_read(cx, &mut *buf))
+                .await;
+        match read {
+            Ok(0) => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => buf = &mut buf[n..],
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{error::ErrorKind, resolver::InMemoryResolver, Ignored};
+    use alloc::{string::String, string::ToString, vec};
+    use codec::{Compact, Encode};
+    use scale_type_resolver::{BitsOrderFormat, BitsStoreFormat, Field, Primitive};
+
+    // Push the bytes asked for until no more are needed, returning how many times we were asked.
+    fn push_all<R: TypeResolver>(decoder: &mut IncrementalDecoder<'_, R>, bytes: &[u8]) -> usize {
+        let mut bytes = bytes;
+        let mut asked = 0;
+        while let Some(needed) = decoder.bytes_needed().unwrap() {
+            asked += 1;
+            decoder.push(&bytes[..needed]);
+            bytes = &bytes[needed..];
+        }
+        asked
+    }
+
+    #[test]
+    fn only_needed_bytes_are_asked_for() {
+        let mut builder = InMemoryResolver::builder();
+        let u32_id = builder.add_primitive(Primitive::U32);
+        let str_id = builder.add_primitive(Primitive::Str);
+        let compact_id = builder.add_compact(u32_id);
+        let bits_id = builder.add_bit_sequence(BitsStoreFormat::U16, BitsOrderFormat::Lsb0);
+        let seq_id = builder.add_sequence(str_id);
+        let variant_id = builder.add_enum(
+            "Foo",
+            [("A", vec![Field::unnamed(compact_id)]), ("B", vec![Field::unnamed(seq_id)])],
+        );
+        let tuple_id = builder.add_tuple([variant_id, bits_id, u32_id]);
+        let types = builder.build();
+
+        let bits: scale_bits::Bits = (0..20).map(|i| i % 3 == 0).collect();
+        let value = (1u8, vec!["a".to_string(), "bcd".to_string()]);
+        let bits_encoded = scale_bits::encode_using_format(
+            bits.iter(),
+            scale_bits::Format::new(BitsStoreFormat::U16, BitsOrderFormat::Lsb0),
+        );
+        let encoded = [value.encode(), bits_encoded, 7u32.encode(), vec![9, 9]].concat();
+
+        let mut decoder = IncrementalDecoder::new(tuple_id, &types);
+        push_all(&mut decoder, &encoded);
+        assert_eq!(decoder.bytes(), &encoded[..encoded.len() - 2]);
+        decoder.decode::<Ignored>().unwrap();
+
+        // The variant index, then the first byte of the compact value (which tells us its size),
+        // then the rest of the compact value:
+        let mut decoder = IncrementalDecoder::new(variant_id, &types);
+        let encoded = (0u8, Compact(1_000_000u32)).encode();
+        assert_eq!(push_all(&mut decoder, &encoded), 3);
+        assert_eq!(decoder.bytes(), &*encoded);
+    }
+
+    #[test]
+    fn bytes_are_only_skipped_over_once() {
+        // Count how many times types are resolved.
+        struct Counting {
+            types: InMemoryResolver,
+            resolved: core::cell::Cell<usize>,
+        }
+        impl TypeResolver for Counting {
+            type TypeId = u32;
+            type Error = <InMemoryResolver as TypeResolver>::Error;
+            fn resolve_type<
+                'this,
+                V: scale_type_resolver::ResolvedTypeVisitor<'this, TypeId = u32>,
+            >(
+                &'this self,
+                type_id: u32,
+                visitor: V,
+            ) -> Result<V::Value, Self::Error> {
+                self.resolved.set(self.resolved.get() + 1);
+                self.types.resolve_type(type_id, visitor)
+            }
+        }
+
+        let mut builder = InMemoryResolver::builder();
+        let str_id = builder.add_primitive(Primitive::Str);
+        let seq_id = builder.add_sequence(str_id);
+        let types = Counting { types: builder.build(), resolved: core::cell::Cell::new(0) };
+
+        let value: Vec<String> = (0..100).map(|i| i.to_string()).collect();
+        let encoded = value.encode();
+        let mut decoder = IncrementalDecoder::new(seq_id, &types);
+        let asked = push_all(&mut decoder, &encoded);
+
+        // We're asked for the two byte length (one byte at a time, since the first byte tells us
+        // the size), and then the length and bytes of each string in turn. Each time, we carry
+        // on from the string we got to rather than skipping over every string again:
+        assert_eq!(asked, 202);
+        assert!(types.resolved.get() < 3 * asked, "resolved {} types", types.resolved.get());
+        assert_eq!(decoder.decode::<Vec<String>>().unwrap(), value);
+    }
+
+    #[test]
+    fn errors_are_returned() {
+        let mut builder = InMemoryResolver::builder();
+        let u8_id = builder.add_primitive(Primitive::U8);
+        let variant_id = builder.add_enum("Foo", [("A", vec![Field::unnamed(u8_id)])]);
+        let types = builder.build();
+
+        // Unknown variants are spotted as soon as we see them:
+        let mut decoder = IncrementalDecoder::new(variant_id, &types);
+        decoder.push(&[1]);
+        let err = decoder.bytes_needed().unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::VisitorDecodeError(DecodeError::VariantNotFound(1))
+        ));
+
+        // Decoding before we have all of the bytes fails:
+        let mut decoder = IncrementalDecoder::new(variant_id, &types);
+        decoder.push(&[0]);
+        assert_eq!(decoder.bytes_needed().unwrap(), Some(1));
+        assert!(decoder.decode::<Ignored>().is_err());
+    }
+
+    #[test]
+    fn decoding_from_inputs_works() {
+        let mut builder = InMemoryResolver::builder();
+        let str_id = builder.add_primitive(Primitive::Str);
+        let u32_id = builder.add_primitive(Primitive::U32);
+        let types = builder.build();
+
+        let encoded = [("hello", 123u32, "world").encode(), vec![1]].concat();
+        let chunks = [&encoded[..2], &encoded[2..5], &encoded[5..6], &encoded[6..]];
+
+        fn decode_all<'scale>(
+            input: &mut impl ScaleInput<'scale>,
+            str_id: u32,
+            u32_id: u32,
+            types: &InMemoryResolver,
+        ) -> (String, u32, String) {
+            let a = decode_from_input(input, str_id, types).unwrap();
+            let b = decode_from_input(input, u32_id, types).unwrap();
+            let c = decode_from_input(input, str_id, types).unwrap();
+            (a, b, c)
+        }
+        let expected = ("hello".to_string(), 123, "world".to_string());
+
+        let mut input = SliceInput::new(&encoded);
+        assert_eq!(decode_all(&mut input, str_id, u32_id, &types), expected);
+        assert_eq!((input.offset(), input.remaining()), (16, &[1u8][..]));
+
+        let mut input = ChunkedInput::new(&chunks);
+        assert_eq!(decode_all(&mut input, str_id, u32_id, &types), expected);
+        assert_eq!(input.offset(), 16);
+
+        // Peeking doesn't move the input along, and running out of bytes leaves it untouched:
+        let mut buf = Vec::new();
+        input.peek(1, &mut buf).unwrap();
+        assert_eq!(input.read(2, &mut buf), Err(DecodeError::NotEnoughInput(1)));
+        assert_eq!((buf, input.offset()), (vec![1], 16));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn decoding_from_reader_works() {
+        let mut builder = InMemoryResolver::builder();
+        let str_id = builder.add_primitive(Primitive::Str);
+        let types = builder.build();
+
+        let encoded = [("hello", "world").encode(), vec![1]].concat();
+        let mut reader = std::io::Cursor::new(encoded);
+
+        let a: String = decode_from_reader(&mut reader, str_id, &types).unwrap();
+        let b: String = decode_from_reader(&mut reader, str_id, &types).unwrap();
+        assert_eq!((a.as_str(), b.as_str()), ("hello", "world"));
+        assert_eq!(reader.position(), 12);
+
+        let err = decode_from_reader::<String, _>(&mut reader, str_id, &types).unwrap_err();
+        assert!(matches!(err, ReadError::Io(_)));
+
+        // Values bigger than we'll read in one go are fine:
+        let long = "a".repeat(3 * MAX_READ_LEN / 2);
+        let mut reader = std::io::Cursor::new(long.encode());
+        let a: String = decode_from_reader(&mut reader, str_id, &types).unwrap();
+        assert_eq!(a, long);
+
+        // The length of a value claiming to be huge isn't trusted:
+        let mut reader = std::io::Cursor::new(Compact(u32::MAX).encode());
+        let err = decode_from_reader::<String, _>(&mut reader, str_id, &types).unwrap_err();
+        assert!(matches!(err, ReadError::Io(e) if e.kind() == std::io::ErrorKind::UnexpectedEof));
+    }
+
+    #[cfg(feature = "futures-io")]
+    #[test]
+    fn decoding_from_async_reader_works() {
+        use core::{future::Future, pin::pin, task::Context};
+        use std::{sync::Arc, task::Wake};
+
+        // Reading from a slice never waits, so we only need to poll once.
+        struct NoopWaker;
+        impl Wake for NoopWaker {
+            fn wake(self: Arc<Self>) {}
+        }
+        fn now<F: Future>(fut: F) -> F::Output {
+            let waker = Arc::new(NoopWaker).into();
+            match pin!(fut).poll(&mut Context::from_waker(&waker)) {
+                core::task::Poll::Ready(output) => output,
+                core::task::Poll::Pending => panic!("slices are always ready to be read"),
+            }
+        }
+
+        let mut builder = InMemoryResolver::builder();
+        let str_id = builder.add_primitive(Primitive::Str);
+        let types = builder.build();
+
+        let encoded = [("hello", "world").encode(), vec![1]].concat();
+        let mut reader = &*encoded;
+
+        let a: String = now(decode_from_async_reader(&mut reader, str_id, &types)).unwrap();
+        let b: String = now(decode_from_async_reader(&mut reader, str_id, &types)).unwrap();
+        assert_eq!((a.as_str(), b.as_str()), ("hello", "world"));
+        assert_eq!(reader, &[1]);
+
+        let err =
+            now(decode_from_async_reader::<String, _>(&mut reader, str_id, &types)).unwrap_err();
+        assert!(matches!(err, ReadError::Io(e) if e.kind() == std::io::ErrorKind::UnexpectedEof));
+    }
+}
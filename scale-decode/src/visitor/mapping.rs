@@ -0,0 +1,178 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A [`TypeMapping`] wraps some [`TypeResolver`] and substitutes some type IDs for others
+//! whenever they are resolved. This allows bytes to be decoded against a locally patched set of
+//! types, for instance to decode some `RuntimeCall` type as opaque bytes instead.
+//!
+//! Substitution happens in the resolver rather than in a [`crate::Visitor`], so that it applies
+//! to every type that is resolved while decoding (including the types of nested fields and
+//! items), and not just to the type that decoding starts from. Any visitor can be used as
+//! normal, so long as its `TypeResolver` is the [`TypeMapping`].
+//!
+//! ```rust
+//! use codec::Encode;
+//! use scale_decode::{visitor::mapping::TypeMapping, DecodeAsType};
+//! use scale_info::PortableRegistry;
+//! use std::collections::BTreeMap;
+//!
+//! #[derive(Encode, scale_info::TypeInfo)]
+//! struct Foo {
+//!     a: u8,
+//!     b: u16,
+//! }
+//!
+//! let mut types = scale_info::Registry::new();
+//! let foo_id = types.register_type(&scale_info::MetaType::new::<Foo>()).id;
+//! let u16_id = types.register_type(&scale_info::MetaType::new::<u16>()).id;
+//! let bytes_id = types.register_type(&scale_info::MetaType::new::<[u8; 2]>()).id;
+//! let types: PortableRegistry = types.into();
+//!
+//! // Decode any u16 as a pair of bytes instead:
+//! let mapping = BTreeMap::from([(u16_id, bytes_id)]);
+//! let types = TypeMapping::new(&types, mapping);
+//!
+//! let encoded = Foo { a: 1, b: 2 }.encode();
+//! let decoded = <(u8, [u8; 2])>::decode_as_type(&mut &*encoded, foo_id, &types).unwrap();
+//! assert_eq!(decoded, (1, [2, 0]));
+//! ```
+
+use alloc::collections::BTreeMap;
+use scale_type_resolver::{ResolvedTypeVisitor, TypeResolver};
+
+/// Something which can substitute one type ID for another. This is implemented for
+/// [`BTreeMap`]s from type ID to type ID, and for closures of the form
+/// `Fn(&TypeId) -> Option<TypeId>`.
+pub trait TypeIdMapping<TypeId> {
+    /// Return the type ID to use in place of the one given, or `None` to leave it as is.
+    fn map_type_id(&self, type_id: &TypeId) -> Option<TypeId>;
+}
+
+impl<TypeId: Ord + Clone> TypeIdMapping<TypeId> for BTreeMap<TypeId, TypeId> {
+    fn map_type_id(&self, type_id: &TypeId) -> Option<TypeId> {
+        self.get(type_id).cloned()
+    }
+}
+
+impl<TypeId, F: Fn(&TypeId) -> Option<TypeId>> TypeIdMapping<TypeId> for F {
+    fn map_type_id(&self, type_id: &TypeId) -> Option<TypeId> {
+        self(type_id)
+    }
+}
+
+/// A [`TypeResolver`] which wraps another, substituting type IDs according to some
+/// [`TypeIdMapping`] before resolving them using the underlying resolver.
+pub struct TypeMapping<'resolver, R, M> {
+    inner: &'resolver R,
+    mapping: M,
+}
+
+impl<'resolver, R: TypeResolver, M: TypeIdMapping<R::TypeId>> TypeMapping<'resolver, R, M> {
+    /// Wrap the given resolver, substituting type IDs using the mapping provided.
+    pub fn new(inner: &'resolver R, mapping: M) -> Self {
+        TypeMapping { inner, mapping }
+    }
+
+    /// Access the underlying resolver.
+    pub fn inner(&self) -> &'resolver R {
+        self.inner
+    }
+
+    /// Access the mapping used to substitute type IDs.
+    pub fn mapping(&self) -> &M {
+        &self.mapping
+    }
+}
+
+impl<R: TypeResolver, M: TypeIdMapping<R::TypeId>> TypeResolver for TypeMapping<'_, R, M> {
+    type TypeId = R::TypeId;
+    type Error = R::Error;
+
+    fn resolve_type<'this, V: ResolvedTypeVisitor<'this, TypeId = Self::TypeId>>(
+        &'this self,
+        type_id: Self::TypeId,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let type_id = self.mapping.map_type_id(&type_id).unwrap_or(type_id);
+        self.inner.resolve_type(type_id, visitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::DecodeAsType;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use codec::Encode;
+    use scale_info::PortableRegistry;
+
+    #[derive(Encode, scale_info::TypeInfo)]
+    #[allow(dead_code)]
+    enum Call {
+        Remark(Vec<u8>),
+        Transfer { to: [u8; 4], amount: u32 },
+    }
+
+    #[derive(Encode, scale_info::TypeInfo)]
+    struct Extrinsic {
+        nonce: u8,
+        calls: Vec<Call>,
+    }
+
+    fn make_types() -> (u32, u32, u32, PortableRegistry) {
+        let mut types = scale_info::Registry::new();
+        let ext_id = types.register_type(&scale_info::MetaType::new::<Extrinsic>()).id;
+        let call_id = types.register_type(&scale_info::MetaType::new::<Call>()).id;
+        let opaque_id = types.register_type(&scale_info::MetaType::new::<(u8, [u8; 8])>()).id;
+        (ext_id, call_id, opaque_id, types.into())
+    }
+
+    #[test]
+    fn nested_types_are_substituted() {
+        let (ext_id, call_id, opaque_id, types) = make_types();
+        let ext = Extrinsic {
+            nonce: 1,
+            calls: vec![
+                Call::Transfer { to: [1, 2, 3, 4], amount: 5 },
+                Call::Transfer { to: [6, 7, 8, 9], amount: 10 },
+            ],
+        };
+        let encoded = ext.encode();
+
+        // Treat each call as a variant index followed by 8 opaque bytes:
+        let types = TypeMapping::new(&types, BTreeMap::from([(call_id, opaque_id)]));
+        let decoded =
+            <(u8, Vec<(u8, [u8; 8])>)>::decode_as_type(&mut &*encoded, ext_id, &types).unwrap();
+
+        assert_eq!(
+            decoded,
+            (1, vec![(1, [1, 2, 3, 4, 5, 0, 0, 0]), (1, [6, 7, 8, 9, 10, 0, 0, 0])])
+        );
+    }
+
+    #[test]
+    fn closures_can_be_used_as_mappings() {
+        let (ext_id, call_id, opaque_id, types) = make_types();
+        let encoded = Extrinsic { nonce: 2, calls: vec![] }.encode();
+
+        let mapping = |id: &u32| (*id == call_id).then_some(opaque_id);
+        let types = TypeMapping::new(&types, mapping);
+
+        // The Call type is mapped, but there are no calls to decode here:
+        let decoded = <(u8, Vec<()>)>::decode_as_type(&mut &*encoded, ext_id, &types).unwrap();
+        assert_eq!(decoded, (2, vec![]));
+    }
+}
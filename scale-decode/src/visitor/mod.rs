@@ -15,7 +15,13 @@
 
 //! The [`Visitor`] trait and associated types.
 
+pub mod cache;
+pub mod compact;
 mod decode;
+pub mod input;
+pub mod mapping;
+mod partial;
+mod skip;
 pub mod types;
 
 use alloc::string::String;
@@ -23,8 +29,10 @@ use core::marker::PhantomData;
 use scale_type_resolver::TypeResolver;
 use types::*;
 
-pub use decode::decode_with_visitor;
 pub(crate) use decode::decode_with_visitor_maybe_compact;
+pub use decode::{decode_fields_with_visitor, decode_with_visitor, decode_with_visitor_owned};
+pub use partial::{decode_with_visitor_partial, PartialError};
+pub use skip::{encoded_size_of, split_by_types};
 
 /// Return the type ID type of some [`Visitor`].
 pub type TypeIdFor<V> = <<V as Visitor>::TypeResolver as TypeResolver>::TypeId;
@@ -41,6 +49,16 @@ pub trait Visitor: Sized {
     /// The thing we'll use to resolve type IDs into concrete types.
     type TypeResolver: TypeResolver;
 
+    /// Whether this visitor wants to be handed the values being decoded. Visitors which ignore
+    /// every value can set this to `false`, in which case each value is skipped over without
+    /// being visited, and [`Visitor::visit_unexpected()`] is called with the kind of value that
+    /// was skipped. Skipping does only as much work as is needed to find where a value ends:
+    /// sequences and arrays of fixed size items (like `Vec<u8>`) are jumped over in one go, and
+    /// the bytes of strings aren't checked to be valid UTF8.
+    ///
+    /// [`Visitor::unchecked_decode_as_type()`] is still called first either way.
+    const WANTS_VALUES: bool = true;
+
     /// This method is called immediately upon running [`decode_with_visitor()`]. By default we ignore
     /// this call and return our visitor back (ie [`DecodeAsTypeResult::Skipped(visitor)`]). If you choose to
     /// do some decoding at this stage, return [`DecodeAsTypeResult::Decoded(result)`]. In either case, any bytes
@@ -182,6 +200,76 @@ pub trait Visitor: Sized {
     ) -> Result<Self::Value<'_, 'resolver>, Self::Error> {
         self.visit_unexpected(Unexpected::I256)
     }
+    /// Called when an f32 is seen in the input bytes. Type resolvers describe these as a
+    /// composite with the path `f32` and a single 4 byte field holding its bits.
+    #[cfg(feature = "float")]
+    fn visit_f32<'scale, 'resolver>(
+        self,
+        _value: f32,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        self.visit_unexpected(Unexpected::F32)
+    }
+    /// Called when an f64 is seen in the input bytes. Type resolvers describe these as a
+    /// composite with the path `f64` and a single 8 byte field holding its bits.
+    #[cfg(feature = "float")]
+    fn visit_f64<'scale, 'resolver>(
+        self,
+        _value: f64,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        self.visit_unexpected(Unexpected::F64)
+    }
+    /// Called when a compact encoded u8 is seen in the input bytes. By default, this
+    /// forwards to [`Visitor::visit_u8`].
+    fn visit_compact_u8<'scale, 'resolver>(
+        self,
+        value: u8,
+        _location: CompactLocation,
+        type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        self.visit_u8(value, type_id)
+    }
+    /// Called when a compact encoded u16 is seen in the input bytes. By default, this
+    /// forwards to [`Visitor::visit_u16`].
+    fn visit_compact_u16<'scale, 'resolver>(
+        self,
+        value: u16,
+        _location: CompactLocation,
+        type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        self.visit_u16(value, type_id)
+    }
+    /// Called when a compact encoded u32 is seen in the input bytes. By default, this
+    /// forwards to [`Visitor::visit_u32`].
+    fn visit_compact_u32<'scale, 'resolver>(
+        self,
+        value: u32,
+        _location: CompactLocation,
+        type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        self.visit_u32(value, type_id)
+    }
+    /// Called when a compact encoded u64 is seen in the input bytes. By default, this
+    /// forwards to [`Visitor::visit_u64`].
+    fn visit_compact_u64<'scale, 'resolver>(
+        self,
+        value: u64,
+        _location: CompactLocation,
+        type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        self.visit_u64(value, type_id)
+    }
+    /// Called when a compact encoded u128 is seen in the input bytes. By default, this
+    /// forwards to [`Visitor::visit_u128`].
+    fn visit_compact_u128<'scale, 'resolver>(
+        self,
+        value: u128,
+        _location: CompactLocation,
+        type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        self.visit_u128(value, type_id)
+    }
     /// Called when a sequence of values is seen in the input bytes.
     fn visit_sequence<'scale, 'resolver>(
         self,
@@ -238,6 +326,45 @@ pub trait Visitor: Sized {
     ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
         self.visit_unexpected(Unexpected::Bitsequence)
     }
+    /// Called when the type resolver describes a primitive type which isn't one of the standard
+    /// [`crate::resolver::Primitive`]s, by calling
+    /// [`scale_type_resolver::ResolvedTypeVisitor::visit_unhandled()`] with
+    /// [`scale_type_resolver::UnhandledKind::Primitive`]. This allows custom resolvers to support
+    /// primitives like floats, so long as the visitor knows how to decode them. See
+    /// [`UnknownPrimitive`].
+    ///
+    /// By default, this returns an error, since we don't know how many bytes to consume.
+    fn visit_unknown_primitive<'scale, 'resolver>(
+        self,
+        _value: &mut UnknownPrimitive<'scale, 'resolver, Self::TypeResolver>,
+        type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        Err(DecodeError::TypeIdNotFound(alloc::format!(
+            "Kind Primitive (type ID {type_id:?}) has not been properly handled"
+        ))
+        .into())
+    }
+    /// Called when the type resolver describes a type of some kind that it couldn't handle, by
+    /// calling [`scale_type_resolver::ResolvedTypeVisitor::visit_unhandled()`] with any
+    /// [`crate::resolver::UnhandledKind`] other than `Primitive` (see
+    /// [`Visitor::visit_unknown_primitive()`]) or `NotFound`. This can happen when decoding
+    /// against metadata which is newer than the resolver. Lenient visitors can use this to
+    /// capture the raw bytes of such values and carry on.
+    ///
+    /// Since we can't tell where the value ends, the visitor decides how many bytes it takes
+    /// up, and exactly those bytes are moved past. See [`Opaque`]. By default, this returns an
+    /// error.
+    fn visit_opaque<'scale, 'resolver>(
+        self,
+        value: &mut Opaque<'scale, 'resolver, Self::TypeResolver>,
+        type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        let kind = value.kind();
+        Err(DecodeError::TypeIdNotFound(alloc::format!(
+            "Kind {kind:?} (type ID {type_id:?}) has not been properly handled"
+        ))
+        .into())
+    }
 }
 
 /// An error decoding SCALE bytes.
@@ -258,15 +385,19 @@ pub enum DecodeError {
     /// We could not convert the [`u32`] that we found into a valid [`char`].
     #[error("{_0} is expected to be a valid char, but is not")]
     InvalidChar(u32),
-    /// We expected more bytes to finish decoding, but could not find them.
-    #[error("Ran out of data during decoding")]
-    NotEnoughInput,
+    /// We expected more bytes to finish decoding, but could not find them. This
+    /// holds the number of additional bytes that we needed.
+    #[error("Ran out of data during decoding; {_0} more bytes were needed")]
+    NotEnoughInput(usize),
     /// We found a variant that does not match with any in the type we're trying to decode from.
     #[error("Could not find variant with index {_0}")]
     VariantNotFound(u8),
     /// Some error emitted from a [`codec::Decode`] impl.
     #[error("Decode error: {0}")]
     CodecError(codec::Error),
+    /// A bit sequence could not be decoded.
+    #[error("{0}")]
+    InvalidBitSequence(InvalidBitSequence),
     /// This is returned by default if a visitor function is not implemented.
     #[error("Unexpected type {_0}")]
     Unexpected(#[from] Unexpected),
@@ -280,6 +411,34 @@ impl From<codec::Error> for DecodeError {
     }
 }
 
+/// Details about a bit sequence which could not be decoded, either because its length
+/// couldn't be decoded or because there weren't enough bytes to hold the bits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidBitSequence {
+    /// The store format of the bit sequence, ie the size of each chunk of bits.
+    pub store_format: scale_type_resolver::BitsStoreFormat,
+    /// The order in which bits are stored in each chunk.
+    pub order_format: scale_type_resolver::BitsOrderFormat,
+    /// The number of bits that the bit sequence declared, or `None` if this couldn't be decoded.
+    pub bits_len: Option<usize>,
+    /// The number of bytes that were available to decode the bit sequence from, including
+    /// its length.
+    pub available_bytes: usize,
+}
+
+impl core::fmt::Display for InvalidBitSequence {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let InvalidBitSequence { store_format, order_format, bits_len, available_bytes } = self;
+        write!(f, "Cannot decode bit sequence (store format {store_format:?}, order format {order_format:?}) ")?;
+        match bits_len {
+            Some(bits_len) => write!(f, "of {bits_len} bits from {available_bytes} bytes"),
+            None => write!(f, "from {available_bytes} bytes: the length could not be decoded"),
+        }
+    }
+}
+
+impl core::error::Error for InvalidBitSequence {}
+
 /// This is returned by default when a visitor function isn't implemented.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
 #[allow(missing_docs)]
@@ -312,6 +471,10 @@ pub enum Unexpected {
     I128,
     #[error("i256")]
     I256,
+    #[error("f32")]
+    F32,
+    #[error("f64")]
+    F64,
     #[error("sequence")]
     Sequence,
     #[error("composite")]
@@ -328,6 +491,17 @@ pub enum Unexpected {
     Bitsequence,
 }
 
+/// Where the compact encoding of a number handed to one of the `visit_compact_*` methods
+/// on [`Visitor`] came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompactLocation {
+    /// The number itself is compact encoded, for example `Compact<u64>`.
+    Primitive,
+    /// The number is a field in some compact encoded composite or tuple type, for example
+    /// `Compact<Perbill>`. The visitor will have been handed this composite or tuple already.
+    Composite,
+}
+
 /// The response from [`Visitor::unchecked_decode_as_type()`].
 pub enum DecodeAsTypeResult<V, R> {
     /// Skip any manual decoding and return the visitor instead.
@@ -356,6 +530,16 @@ impl<V, R> DecodeAsTypeResult<V, R> {
     }
 }
 
+/// This is implemented for [`Visitor`]s whose values can be converted into owned values which
+/// don't borrow from the bytes being decoded or from the type resolver. See
+/// [`decode_with_visitor_owned()`].
+pub trait Detach: Visitor {
+    /// The owned version of [`Visitor::Value`].
+    type Owned: 'static;
+    /// Convert a value returned from this visitor into its owned version.
+    fn detach(value: Self::Value<'_, '_>) -> Self::Owned;
+}
+
 /// This is implemented for visitor related types which have a `decode_item` method,
 /// and allows you to generically talk about decoding unnamed items.
 pub trait DecodeItemIterator<'scale, 'resolver, R: TypeResolver> {
@@ -364,9 +548,91 @@ pub trait DecodeItemIterator<'scale, 'resolver, R: TypeResolver> {
         &mut self,
         visitor: V,
     ) -> Option<Result<V::Value<'scale, 'resolver>, V::Error>>;
+
+    /// Decode a single item using a visitor handed out by the given [`VisitorFactory`].
+    #[allow(clippy::type_complexity)]
+    fn decode_item_with<F>(
+        &mut self,
+        factory: &F,
+    ) -> Option<
+        Result<<F::Visitor as Visitor>::Value<'scale, 'resolver>, <F::Visitor as Visitor>::Error>,
+    >
+    where
+        F: VisitorFactory,
+        F::Visitor: Visitor<TypeResolver = R>,
+    {
+        self.decode_item(factory.visitor())
+    }
+
+    /// Decode each of the remaining items using a fresh visitor from the given
+    /// [`VisitorFactory`], collecting them into some container and stopping at the first error.
+    ///
+    /// ```rust
+    /// use scale_decode::visitor::{types::Sequence, DecodeItemIterator, TypeIdFor, Visitor};
+    /// use scale_decode::{Error, TypeResolver};
+    ///
+    /// // A visitor which sums up nested sequences of numbers.
+    /// struct SumVisitor<R>(core::marker::PhantomData<R>);
+    ///
+    /// impl<R: TypeResolver> Visitor for SumVisitor<R> {
+    ///     type Value<'scale, 'resolver> = u64;
+    ///     type Error = Error;
+    ///     type TypeResolver = R;
+    ///
+    ///     fn visit_u8<'scale, 'resolver>(
+    ///         self,
+    ///         value: u8,
+    ///         _type_id: TypeIdFor<Self>,
+    ///     ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+    ///         Ok(value.into())
+    ///     }
+    ///     fn visit_sequence<'scale, 'resolver>(
+    ///         self,
+    ///         value: &mut Sequence<'scale, 'resolver, R>,
+    ///         _type_id: TypeIdFor<Self>,
+    ///     ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+    ///         // No need to construct a new visitor by hand for each item:
+    ///         let items: Vec<u64> =
+    ///             value.decode_items_using(&|| SumVisitor(core::marker::PhantomData))?;
+    ///         Ok(items.into_iter().sum())
+    ///     }
+    /// }
+    /// ```
+    fn decode_items_using<F, C>(&mut self, factory: &F) -> Result<C, <F::Visitor as Visitor>::Error>
+    where
+        F: VisitorFactory,
+        F::Visitor: Visitor<TypeResolver = R>,
+        C: FromIterator<<F::Visitor as Visitor>::Value<'scale, 'resolver>>,
+    {
+        core::iter::from_fn(|| self.decode_item(factory.visitor())).collect()
+    }
+}
+
+/// Something which can hand out a fresh [`Visitor`] whenever one is needed, for instance to decode
+/// each of the items in a sequence via [`DecodeItemIterator::decode_items_using()`]. This is
+/// implemented for any closure returning a visitor.
+pub trait VisitorFactory {
+    /// The visitor that is handed out.
+    type Visitor: Visitor;
+    /// Hand out a new visitor.
+    fn visitor(&self) -> Self::Visitor;
+}
+
+impl<V: Visitor, F: Fn() -> V> VisitorFactory for F {
+    type Visitor = V;
+    fn visitor(&self) -> V {
+        self()
+    }
 }
 
-/// A [`Visitor`] implementation that just ignores all of the bytes.
+/// A [`Visitor`] implementation that just ignores all of the bytes. Since it doesn't want any
+/// values (see [`Visitor::WANTS_VALUES`]), the bytes are skipped over as cheaply as possible
+/// rather than being decoded.
+///
+/// Values of kinds that the type resolver can't describe (see [`Visitor::visit_opaque()`]) are
+/// assumed to take up the rest of the input, since there's no telling where they end. Unknown
+/// primitives (see [`Visitor::visit_unknown_primitive()`]) can't be ignored for the same reason,
+/// and need a visitor which knows how big they are.
 pub struct IgnoreVisitor<R>(PhantomData<R>);
 
 impl<R> Default for IgnoreVisitor<R> {
@@ -387,6 +653,8 @@ impl<R: TypeResolver> Visitor for IgnoreVisitor<R> {
     type Error = DecodeError;
     type TypeResolver = R;
 
+    const WANTS_VALUES: bool = false;
+
     // Whatever the value we visit is, just ignore it.
     fn visit_unexpected<'scale, 'resolver>(
         self,
@@ -394,6 +662,22 @@ impl<R: TypeResolver> Visitor for IgnoreVisitor<R> {
     ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
         Ok(())
     }
+
+    // We can't tell where values of kinds that the type resolver can't describe end, so the best
+    // that we can do is to assume that they run to the end of the input.
+    fn visit_opaque<'scale, 'resolver>(
+        self,
+        value: &mut Opaque<'scale, 'resolver, Self::TypeResolver>,
+        _type_id: TypeIdFor<Self>,
+    ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+        value.take_rest();
+        Ok(())
+    }
+}
+
+impl<R: TypeResolver> Detach for IgnoreVisitor<R> {
+    type Owned = ();
+    fn detach(_value: ()) {}
 }
 
 /// Some [`Visitor`] implementations may want to return an error type other than [`crate::Error`], which means
@@ -425,6 +709,103 @@ where
     }
 }
 
+impl<V: Detach> Detach for VisitorWithCrateError<V>
+where
+    V::Error: Into<crate::Error>,
+{
+    type Owned = V::Owned;
+    fn detach(value: Self::Value<'_, '_>) -> Self::Owned {
+        V::detach(value)
+    }
+}
+
+/// A [`Visitor`] which decodes bytes into some type `Src` using its [`crate::IntoVisitor`] impl,
+/// and then converts this into `Dst` using [`TryFrom`]. Any conversion error is handed back as a
+/// custom [`crate::Error`].
+///
+/// This can be used as the [`crate::IntoVisitor::AnyVisitor`] for types which are most easily
+/// decoded by way of some other type. To decode into some type that you can't implement
+/// [`crate::IntoVisitor`] on, see [`crate::DecodeVia`].
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_decode::{visitor::TryFromVisitor, DecodeAsType, IntoVisitor, TypeResolver};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Even(u64);
+///
+/// #[derive(Debug)]
+/// struct NotEven;
+///
+/// impl core::fmt::Display for NotEven {
+///     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+///         write!(f, "number is not even")
+///     }
+/// }
+/// impl std::error::Error for NotEven {}
+///
+/// impl TryFrom<u64> for Even {
+///     type Error = NotEven;
+///     fn try_from(n: u64) -> Result<Self, Self::Error> {
+///         if n % 2 == 0 { Ok(Even(n)) } else { Err(NotEven) }
+///     }
+/// }
+///
+/// impl IntoVisitor for Even {
+///     type AnyVisitor<R: TypeResolver> = TryFromVisitor<u64, Even, R>;
+///     fn into_visitor<R: TypeResolver>() -> Self::AnyVisitor<R> {
+///         TryFromVisitor::new()
+///     }
+/// }
+///
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&scale_info::MetaType::new::<u32>()).id;
+/// let types: scale_info::PortableRegistry = types.into();
+///
+/// let even = Even::decode_as_type(&mut &*4u32.encode(), type_id, &types).unwrap();
+/// assert_eq!(even, Even(4));
+///
+/// let odd = Even::decode_as_type(&mut &*3u32.encode(), type_id, &types);
+/// assert!(odd.is_err());
+/// ```
+pub struct TryFromVisitor<Src, Dst, R>(PhantomData<fn(Src, R) -> Dst>);
+
+impl<Src, Dst, R> Default for TryFromVisitor<Src, Dst, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Src, Dst, R> TryFromVisitor<Src, Dst, R> {
+    /// Construct a new [`TryFromVisitor`].
+    pub fn new() -> Self {
+        TryFromVisitor(PhantomData)
+    }
+}
+
+impl<Src, Dst, R> Visitor for TryFromVisitor<Src, Dst, R>
+where
+    Src: crate::IntoVisitor,
+    Dst: TryFrom<Src>,
+    <Dst as TryFrom<Src>>::Error: core::error::Error + Send + Sync + 'static,
+    R: TypeResolver,
+{
+    type Value<'scale, 'resolver> = Dst;
+    type Error = crate::Error;
+    type TypeResolver = R;
+
+    fn unchecked_decode_as_type<'scale, 'resolver>(
+        self,
+        input: &mut &'scale [u8],
+        type_id: TypeIdFor<Self>,
+        types: &'resolver Self::TypeResolver,
+    ) -> DecodeAsTypeResult<Self, Result<Self::Value<'scale, 'resolver>, Self::Error>> {
+        let res = decode_with_visitor(input, type_id, types, Src::into_visitor::<R>())
+            .and_then(|src| Dst::try_from(src).map_err(crate::Error::custom_error));
+        DecodeAsTypeResult::Decoded(res)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -762,6 +1143,75 @@ mod test {
         encode_decode_check("Hello there".to_string(), Value::Str("Hello there".to_owned()));
     }
 
+    #[test]
+    fn decode_256_bit_numbers_followed_by_other_bytes() {
+        struct U256;
+        impl scale_info::TypeInfo for U256 {
+            type Identity = Self;
+            fn type_info() -> scale_info::Type {
+                scale_info::TypeDefPrimitive::U256.into()
+            }
+        }
+
+        let (id, types) = make_type::<(U256, u8)>();
+        let mut encoded = vec![1u8; 32];
+        encoded.push(2);
+
+        let val = decode_with_visitor(&mut &*encoded, id, &types, ValueVisitor::new()).unwrap();
+        assert_eq!(val, Value::Tuple(vec![Value::U256([1; 32]), Value::U8(2)]));
+
+        let err = decode_with_visitor(&mut &encoded[..30], id, &types, ValueVisitor::new());
+        assert_eq!(err, Err(DecodeError::NotEnoughInput(2)));
+    }
+
+    #[test]
+    fn compact_locations_are_reported() {
+        // Report where compact numbers were found, and decode through composites.
+        struct CompactVisitor;
+        impl Visitor for CompactVisitor {
+            type Value<'scale, 'resolver> = Option<CompactLocation>;
+            type Error = DecodeError;
+            type TypeResolver = PortableRegistry;
+
+            fn visit_u32<'scale, 'resolver>(
+                self,
+                _value: u32,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                Ok(None)
+            }
+            fn visit_compact_u32<'scale, 'resolver>(
+                self,
+                value: u32,
+                location: CompactLocation,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                assert_eq!(value, 123);
+                Ok(Some(location))
+            }
+            fn visit_composite<'scale, 'resolver>(
+                self,
+                value: &mut Composite<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                value.decode_item(self).unwrap()
+            }
+        }
+
+        #[derive(Encode, scale_info::TypeInfo, CompactAs)]
+        struct MyWrapper(u32);
+
+        fn location<T: Encode + scale_info::TypeInfo + 'static>(val: T) -> Option<CompactLocation> {
+            let (id, types) = make_type::<T>();
+            let encoded = val.encode();
+            decode_with_visitor(&mut &*encoded, id, &types, CompactVisitor).unwrap()
+        }
+
+        assert_eq!(location(123u32), None);
+        assert_eq!(location(codec::Compact(123u32)), Some(CompactLocation::Primitive));
+        assert_eq!(location(codec::Compact(MyWrapper(123))), Some(CompactLocation::Composite));
+    }
+
     #[test]
     fn decode_compact_named_wrapper_struct() {
         // A struct that can be compact encoded:
@@ -1153,6 +1603,97 @@ mod test {
         assert_eq!(decoded, BTreeMap::from_iter([("hello", "hi"), ("world", "planet")]));
     }
 
+    #[test]
+    fn container_items_can_be_handled_uniformly() {
+        use types::{ArrayItem, CompositeField, SequenceItem, TupleField};
+
+        // Something common to the items handed back from each container type:
+        trait Item<'scale> {
+            fn item_bytes(&self) -> &'scale [u8];
+            fn decode_u64(&self) -> Result<u64, crate::Error>;
+        }
+        macro_rules! impl_item {
+            ($($ty:ident),*) => {$(
+                impl<'scale, 'resolver> Item<'scale> for $ty<'scale, 'resolver, PortableRegistry> {
+                    fn item_bytes(&self) -> &'scale [u8] {
+                        self.bytes()
+                    }
+                    fn decode_u64(&self) -> Result<u64, crate::Error> {
+                        self.decode_as_type()
+                    }
+                }
+            )*}
+        }
+        impl_item!(ArrayItem, CompositeField, SequenceItem, TupleField);
+
+        fn sum<'scale, I: Item<'scale>>(
+            items: impl Iterator<Item = Result<I, DecodeError>>,
+        ) -> (u64, usize) {
+            items.map(|item| item.unwrap()).fold((0, 0), |(total, len), item| {
+                (total + item.decode_u64().unwrap(), len + item.item_bytes().len())
+            })
+        }
+
+        // Collect the sum of the items and the number of bytes they took up:
+        struct SumVisitor;
+        impl Visitor for SumVisitor {
+            type Value<'scale, 'resolver> = (u64, usize);
+            type Error = DecodeError;
+            type TypeResolver = PortableRegistry;
+
+            fn visit_sequence<'scale, 'resolver>(
+                self,
+                value: &mut Sequence<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                Ok(sum(value))
+            }
+            fn visit_array<'scale, 'resolver>(
+                self,
+                value: &mut Array<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                Ok(sum(value))
+            }
+            fn visit_composite<'scale, 'resolver>(
+                self,
+                value: &mut Composite<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                Ok(sum(value))
+            }
+            fn visit_tuple<'scale, 'resolver>(
+                self,
+                value: &mut Tuple<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                Ok(sum(value))
+            }
+        }
+
+        #[derive(codec::Encode, scale_info::TypeInfo)]
+        struct Foo {
+            a: u8,
+            b: u16,
+            c: u32,
+        }
+
+        fn check<T: codec::Encode + scale_info::TypeInfo + 'static>(
+            val: T,
+            expected: (u64, usize),
+        ) {
+            let (ty_id, types) = make_type::<T>();
+            let encoded = val.encode();
+            let res = decode_with_visitor(&mut &*encoded, ty_id, &types, SumVisitor).unwrap();
+            assert_eq!(res, expected);
+        }
+
+        check(vec![1u16, 2, 3], (6, 6));
+        check([1u32, 2, 3], (6, 12));
+        check(Foo { a: 1, b: 2, c: 3 }, (6, 7));
+        check((1u8, 2u64, 3u16), (6, 11));
+    }
+
     #[test]
     fn bailout_works() {
         let input = ("hello", "world");
@@ -1211,37 +1752,913 @@ mod test {
         assert_eq!(decoded, ("hello".to_string(), "world".to_string()));
     }
 
-    // A couple of tests to check that invalid input doesn't lead to panics
-    // when we attempt to decode it to certain types.
-    mod proptests {
-        use super::*;
-        use proptest::prelude::*;
+    #[test]
+    fn peeking_items_doesnt_consume_them() {
+        // Peek at the first item, and then decode all of the items.
+        struct PeekVisitor;
+        impl Visitor for PeekVisitor {
+            type Value<'scale, 'resolver> = (Value, Vec<Value>);
+            type Error = DecodeError;
+            type TypeResolver = PortableRegistry;
 
-        proptest! {
-            #[test]
-            fn invalid_strings_dont_panic(bytes in any::<Vec<u8>>()) {
-                let (id, types) = make_type::<String>();
-                let _ = decode_with_visitor(&mut &*bytes, id, &types, ValueVisitor::new());
+            fn visit_composite<'scale, 'resolver>(
+                self,
+                value: &mut Composite<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                let peeked = value.peek_item(ValueVisitor::new()).unwrap()?;
+                let mut vals = vec![];
+                while let Some(val) = value.decode_item(ValueVisitor::new()) {
+                    vals.push(val?);
+                }
+                Ok((peeked, vals))
+            }
+            fn visit_tuple<'scale, 'resolver>(
+                self,
+                value: &mut Tuple<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                let peeked = value.peek_item(ValueVisitor::new()).unwrap()?;
+                let mut vals = vec![];
+                while let Some(val) = value.decode_item(ValueVisitor::new()) {
+                    vals.push(val?);
+                }
+                Ok((peeked, vals))
+            }
+            fn visit_sequence<'scale, 'resolver>(
+                self,
+                value: &mut Sequence<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                let peeked = value.peek_item(ValueVisitor::new()).unwrap()?;
+                let mut vals = vec![];
+                while let Some(val) = value.decode_item(ValueVisitor::new()) {
+                    vals.push(val?);
+                }
+                Ok((peeked, vals))
             }
+        }
 
-            #[test]
-            fn invalid_bitvecs_dont_panic(bytes in any::<Vec<u8>>()) {
-                use bitvec::{
-                    vec::BitVec,
-                    order::{Lsb0, Msb0},
-                };
+        fn peek_and_decode<T: Encode + scale_info::TypeInfo + 'static>(
+            val: T,
+        ) -> (Value, Vec<Value>) {
+            let encoded = val.encode();
+            let (id, types) = make_type::<T>();
+            let bytes = &mut &*encoded;
+            let res = decode_with_visitor(bytes, id, &types, PeekVisitor).unwrap();
+            assert_eq!(bytes.len(), 0, "Decoding should consume all bytes");
+            res
+        }
 
-                let (id, types) = make_type::<BitVec<u8,Lsb0>>();
-                let _ = decode_with_visitor(&mut &*bytes, id, &types, ValueVisitor::new());
+        #[derive(Encode, scale_info::TypeInfo)]
+        struct Foo {
+            a: u8,
+            b: bool,
+        }
 
-                let (id, types) = make_type::<BitVec<u8,Msb0>>();
-                let _ = decode_with_visitor(&mut &*bytes, id, &types, ValueVisitor::new());
+        assert_eq!(
+            peek_and_decode(Foo { a: 1, b: true }),
+            (Value::U8(1), vec![Value::U8(1), Value::Bool(true)])
+        );
+        assert_eq!(
+            peek_and_decode((2u16, 3u8)),
+            (Value::U16(2), vec![Value::U16(2), Value::U8(3)])
+        );
+        assert_eq!(peek_and_decode(vec![4u8, 5]), (Value::U8(4), vec![Value::U8(4), Value::U8(5)]));
+    }
 
-                let (id, types) = make_type::<BitVec<u32,Lsb0>>();
-                let _ = decode_with_visitor(&mut &*bytes, id, &types, ValueVisitor::new());
+    #[test]
+    fn skipping_items_works() {
+        // Skip the given number of items, and then decode the rest.
+        struct SkipVisitor(usize);
+        impl Visitor for SkipVisitor {
+            type Value<'scale, 'resolver> = Vec<Value>;
+            type Error = DecodeError;
+            type TypeResolver = PortableRegistry;
 
-                let (id, types) = make_type::<BitVec<u32,Msb0>>();
+            fn visit_composite<'scale, 'resolver>(
+                self,
+                value: &mut Composite<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                value.skip_items(self.0)?;
+                let mut vals = vec![];
+                while let Some(val) = value.decode_item(ValueVisitor::new()) {
+                    vals.push(val?);
+                }
+                Ok(vals)
+            }
+            fn visit_sequence<'scale, 'resolver>(
+                self,
+                value: &mut Sequence<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                value.skip_items(self.0)?;
+                let mut vals = vec![];
+                while let Some(val) = value.decode_item(ValueVisitor::new()) {
+                    vals.push(val?);
+                }
+                Ok(vals)
+            }
+        }
+
+        fn skip_and_decode<T: Encode + scale_info::TypeInfo + 'static>(
+            val: T,
+            n: usize,
+        ) -> Vec<Value> {
+            let encoded = val.encode();
+            let (id, types) = make_type::<T>();
+            let bytes = &mut &*encoded;
+            let res = decode_with_visitor(bytes, id, &types, SkipVisitor(n)).unwrap();
+            assert_eq!(bytes.len(), 0, "Decoding should consume all bytes");
+            res
+        }
+
+        #[derive(Encode, scale_info::TypeInfo)]
+        enum Bar {
+            A,
+            B(String, u8),
+        }
+
+        #[derive(Encode, scale_info::TypeInfo)]
+        struct Foo {
+            a: u8,
+            b: String,
+            c: Vec<(u32, bool)>,
+            d: Option<Bar>,
+            e: codec::Compact<u64>,
+            f: [u16; 3],
+            g: Vec<Bar>,
+            h: bool,
+        }
+
+        let foo = || Foo {
+            a: 1,
+            b: "hello".to_string(),
+            c: vec![(1, true), (2, false)],
+            d: Some(Bar::B("hi".to_string(), 2)),
+            e: codec::Compact(12345),
+            f: [1, 2, 3],
+            g: vec![Bar::A, Bar::B("there".to_string(), 3)],
+            h: true,
+        };
+
+        for n in 0..=8 {
+            let decoded = skip_and_decode(foo(), n);
+            assert_eq!(decoded.len(), 8 - n);
+        }
+        assert_eq!(skip_and_decode(foo(), 7), vec![Value::Bool(true)]);
+        assert_eq!(skip_and_decode(foo(), 100), vec![]);
+
+        // Fixed size items in sequences are skipped over in one go:
+        assert_eq!(
+            skip_and_decode(vec![(1u32, 2u64), (3, 4), (5, 6)], 2),
+            vec![Value::Tuple(vec![Value::U32(5), Value::U64(6)])]
+        );
+        assert_eq!(
+            skip_and_decode(vec!["a".to_string(), "b".to_string(), "c".to_string()], 2),
+            vec![Value::Str("c".to_string())]
+        );
+    }
+
+    #[test]
+    fn iterating_composite_fields_only_validates_on_decode() {
+        // Hand back each field without decoding it.
+        struct FieldsVisitor;
+        impl Visitor for FieldsVisitor {
+            type Value<'scale, 'resolver> =
+                Vec<CompositeField<'scale, 'resolver, PortableRegistry>>;
+            type Error = DecodeError;
+            type TypeResolver = PortableRegistry;
+
+            fn visit_composite<'scale, 'resolver>(
+                self,
+                value: &mut Composite<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                value.collect()
+            }
+        }
+
+        #[derive(scale_info::TypeInfo)]
+        #[allow(dead_code)]
+        struct Foo {
+            a: u8,
+            b: String,
+            c: bool,
+        }
+
+        // The string isn't valid UTF8, but iterating only needs to find where it ends:
+        let encoded = [1u8, 8, 0xff, 0xfe, 1];
+        let (id, types) = make_type::<Foo>();
+        let bytes = &mut &encoded[..];
+        let fields = decode_with_visitor(bytes, id, &types, FieldsVisitor).unwrap();
+        assert_eq!(bytes.len(), 0, "Iterating should consume all bytes");
+
+        let field_bytes: Vec<&[u8]> = fields.iter().map(|f| f.bytes()).collect();
+        assert_eq!(field_bytes, vec![&[1u8][..], &[8, 0xff, 0xfe], &[1]]);
+
+        // Decoding the fields is where they are validated:
+        assert_eq!(fields[0].decode_as_type::<u8>().unwrap(), 1);
+        assert!(fields[1].decode_as_type::<String>().is_err());
+        assert!(fields[2].decode_as_type::<bool>().unwrap());
+    }
+
+    #[test]
+    fn values_can_be_detached_from_temporary_bytes_and_types() {
+        // Borrow field names from the types and field bytes from the input.
+        struct FieldsVisitor;
+        impl Visitor for FieldsVisitor {
+            type Value<'scale, 'resolver> = Vec<(&'resolver str, &'scale [u8])>;
+            type Error = DecodeError;
+            type TypeResolver = PortableRegistry;
+
+            fn visit_composite<'scale, 'resolver>(
+                self,
+                value: &mut Composite<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                value.map(|f| f.map(|f| (f.name().unwrap_or_default(), f.bytes()))).collect()
+            }
+        }
+        impl Detach for FieldsVisitor {
+            type Owned = Vec<(String, Vec<u8>)>;
+            fn detach(value: Self::Value<'_, '_>) -> Self::Owned {
+                value.into_iter().map(|(name, bytes)| (name.to_string(), bytes.to_vec())).collect()
+            }
+        }
+
+        #[derive(Encode, scale_info::TypeInfo)]
+        struct Foo {
+            a: u8,
+            b: String,
+        }
+
+        // Neither the bytes nor the types outlive this function:
+        fn decode_fields(foo: Foo) -> Vec<(String, Vec<u8>)> {
+            let (id, types) = make_type::<Foo>();
+            decode_with_visitor_owned(foo.encode(), id, &types, FieldsVisitor).unwrap()
+        }
+
+        let fields = decode_fields(Foo { a: 1, b: "hi".to_string() });
+        assert_eq!(fields, vec![("a".to_string(), vec![1]), ("b".to_string(), "hi".encode())]);
+    }
+
+    #[test]
+    fn variant_payload_bytes_can_be_captured() {
+        // Return the variant index along with the bytes of its fields.
+        struct PayloadVisitor;
+        impl Visitor for PayloadVisitor {
+            type Value<'scale, 'resolver> = (u8, &'scale [u8]);
+            type Error = DecodeError;
+            type TypeResolver = PortableRegistry;
+
+            fn visit_variant<'scale, 'resolver>(
+                self,
+                value: &mut Variant<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                assert_eq!(value.bytes_from_start()[0], value.index());
+                let start = value.bytes_from_fields_start();
+                value.skip_decoding()?;
+                let end = value.bytes_from_undecoded();
+                Ok((value.index(), &start[..start.len() - end.len()]))
+            }
+        }
+
+        #[derive(Encode, scale_info::TypeInfo)]
+        enum Foo {
+            A,
+            B(String, u16),
+        }
+
+        let (id, types) = make_type::<Foo>();
+        for (foo, index, payload) in
+            [(Foo::A, 0, vec![]), (Foo::B("hi".to_string(), 1), 1, ("hi", 1u16).encode())]
+        {
+            // Add some trailing bytes, which shouldn't be captured:
+            let mut encoded = foo.encode();
+            encoded.extend([1, 2, 3]);
+
+            let bytes = &mut &*encoded;
+            let res = decode_with_visitor(bytes, id, &types, PayloadVisitor).unwrap();
+            assert_eq!(res, (index, &*payload));
+            assert_eq!(*bytes, &[1, 2, 3]);
+        }
+    }
+
+    #[test]
+    fn other_variant_names_can_be_looked_up() {
+        struct OtherNamesVisitor;
+        impl Visitor for OtherNamesVisitor {
+            type Value<'scale, 'resolver> = Vec<&'resolver str>;
+            type Error = DecodeError;
+            type TypeResolver = PortableRegistry;
+
+            fn visit_variant<'scale, 'resolver>(
+                self,
+                value: &mut Variant<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                value.skip_decoding()?;
+                Ok(value.other_variant_names().collect())
+            }
+        }
+
+        #[derive(Encode, scale_info::TypeInfo)]
+        enum Foo {
+            A,
+            B(bool),
+            C { c: u8 },
+        }
+
+        let (id, types) = make_type::<Foo>();
+        let other_names = |foo: Foo| {
+            decode_with_visitor(&mut &*foo.encode(), id, &types, OtherNamesVisitor).unwrap()
+        };
+        assert_eq!(other_names(Foo::A), vec!["B", "C"]);
+        assert_eq!(other_names(Foo::B(true)), vec!["A", "C"]);
+        assert_eq!(other_names(Foo::C { c: 1 }), vec!["A", "B"]);
+    }
+
+    #[test]
+    fn composite_sub_tuples_and_compact_control() {
+        // Decode the middle two fields as a tuple, and the first as a compact value.
+        struct SubTupleVisitor;
+        impl Visitor for SubTupleVisitor {
+            type Value<'scale, 'resolver> = (u32, String, u16);
+            type Error = crate::Error;
+            type TypeResolver = PortableRegistry;
+
+            fn visit_composite<'scale, 'resolver>(
+                self,
+                value: &mut Composite<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                let mut tuple = value.sub_tuple(1..3)?;
+                assert_eq!(tuple.remaining(), 2);
+                let b = tuple.next().unwrap()?.decode_as_type()?;
+                let c = tuple.next().unwrap()?.decode_as_type()?;
+
+                // The composite itself hasn't moved along:
+                let a = value.next().unwrap()?.decode_as_type_maybe_compact(true)?;
+                value.skip_decoding()?;
+                Ok((a, b, c))
+            }
+        }
+
+        #[derive(Encode, scale_info::TypeInfo)]
+        struct Foo {
+            a: u32,
+            b: String,
+            c: u16,
+            d: bool,
+        }
+
+        // The bytes of `a` actually hold a compact encoded number:
+        let a = <u32 as codec::Decode>::decode(&mut &*(codec::Compact(1000u32), 0u16).encode())
+            .unwrap();
+        let foo = Foo { a, b: "hi".to_string(), c: 2, d: true };
+
+        let (id, types) = make_type::<Foo>();
+        let res = decode_with_visitor(&mut &*foo.encode(), id, &types, SubTupleVisitor).unwrap();
+        assert_eq!(res, (1000, "hi".to_string(), 2));
+    }
+
+    #[test]
+    fn items_can_be_decoded_using_visitor_factories() {
+        // Flatten nested sequences and tuples of numbers, recording how deep each one is.
+        #[derive(Clone, Copy)]
+        struct DepthVisitor(usize);
+        impl Visitor for DepthVisitor {
+            type Value<'scale, 'resolver> = Vec<(usize, u8)>;
+            type Error = DecodeError;
+            type TypeResolver = PortableRegistry;
+
+            fn visit_u8<'scale, 'resolver>(
+                self,
+                value: u8,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                Ok(vec![(self.0, value)])
+            }
+            fn visit_sequence<'scale, 'resolver>(
+                self,
+                value: &mut Sequence<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                let items: Vec<Vec<_>> = value.decode_items_using(&|| DepthVisitor(self.0 + 1))?;
+                Ok(items.concat())
+            }
+            fn visit_tuple<'scale, 'resolver>(
+                self,
+                value: &mut Tuple<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                let factory = || DepthVisitor(self.0 + 1);
+                let mut first = value.decode_item_with(&factory).unwrap_or(Ok(vec![]))?;
+                let rest: Vec<Vec<_>> = value.decode_items_using(&factory)?;
+                first.extend(rest.concat());
+                Ok(first)
+            }
+        }
+
+        let encoded = (1u8, vec![vec![2u8], vec![]], 3u8).encode();
+        let (id, types) = make_type::<(u8, Vec<Vec<u8>>, u8)>();
+        let res = decode_with_visitor(&mut &*encoded, id, &types, DepthVisitor(0)).unwrap();
+        assert_eq!(res, vec![(1, 1), (3, 2), (1, 3)]);
+
+        // Decoding stops at the first error:
+        let encoded = vec![1u8, 2].encode();
+        let (id, types) = make_type::<Vec<u8>>();
+        let err = decode_with_visitor(&mut &encoded[..2], id, &types, DepthVisitor(0));
+        assert!(matches!(err, Err(DecodeError::CodecError(_))));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_decode_items_works() {
+        // Skip the given number of items, and then decode the rest in parallel.
+        struct ParVisitor<T>(usize, core::marker::PhantomData<T>);
+        impl<T: crate::DecodeAsType + Send> Visitor for ParVisitor<T> {
+            type Value<'scale, 'resolver> = Vec<T>;
+            type Error = crate::Error;
+            type TypeResolver = PortableRegistry;
+
+            fn visit_sequence<'scale, 'resolver>(
+                self,
+                value: &mut Sequence<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                value.skip_items(self.0)?;
+                value.par_decode_items()
+            }
+        }
+
+        fn par_decode<T, Out>(val: T, n: usize) -> Result<Vec<Out>, crate::Error>
+        where
+            T: Encode + scale_info::TypeInfo + 'static,
+            Out: crate::DecodeAsType + Send,
+        {
+            let encoded = val.encode();
+            let (id, types) = make_type::<T>();
+            let bytes = &mut &*encoded;
+            let res =
+                decode_with_visitor(bytes, id, &types, ParVisitor(n, core::marker::PhantomData));
+            assert_eq!(bytes.len(), 0, "Decoding should consume all bytes");
+            res
+        }
+
+        let strings: Vec<String> = (0..1000).map(|n| n.to_string()).collect();
+        assert_eq!(par_decode::<_, String>(strings.clone(), 0).unwrap(), strings);
+        assert_eq!(par_decode::<_, String>(strings.clone(), 990).unwrap(), &strings[990..]);
+        assert_eq!(par_decode::<_, String>(strings, 2000).unwrap(), Vec::<String>::new());
+
+        let nums: Vec<u32> = (0..1000).collect();
+        let decoded = par_decode::<_, u64>(nums.clone(), 0).unwrap();
+        assert_eq!(decoded, nums.iter().map(|&n| n as u64).collect::<Vec<_>>());
+
+        // Errors report the index of the item that failed to decode:
+        let err = par_decode::<_, u8>(nums, 10).unwrap_err();
+        assert_eq!(err.context().path().to_string(), "[256]");
+    }
+
+    #[test]
+    fn encoded_size_of_works() {
+        fn assert_size<T: Encode + scale_info::TypeInfo + 'static>(val: T) {
+            let (id, types) = make_type::<T>();
+            let mut encoded = val.encode();
+            let len = encoded.len();
+            // Trailing bytes aren't counted:
+            encoded.extend_from_slice(&[1, 2, 3]);
+            assert_eq!(encoded_size_of(&encoded, id, &types).unwrap(), len);
+        }
+
+        #[derive(Encode, scale_info::TypeInfo)]
+        enum Foo {
+            A([u8; 32], codec::Compact<u128>),
+            B { c: Vec<Option<String>>, d: (bool, u16) },
+        }
+
+        assert_size(123u8);
+        assert_size([7u8; 32]);
+        assert_size(vec![[1u64; 4]; 10]);
+        assert_size("hello".to_string());
+        assert_size(Foo::A([1; 32], codec::Compact(12345)));
+        assert_size(Foo::B { c: vec![Some("hi".to_string()), None], d: (true, 1) });
+
+        // Not enough bytes:
+        let (id, types) = make_type::<[u8; 32]>();
+        assert!(encoded_size_of(&[0u8; 31], id, &types).is_err());
+    }
+
+    #[test]
+    fn containers_expose_item_type_ids_and_compactness() {
+        use crate::resolver::{InMemoryResolver, Primitive};
+
+        // Record the type ID and compactness of the items in whatever container we're given.
+        struct ItemInfoVisitor;
+        impl Visitor for ItemInfoVisitor {
+            type Value<'scale, 'resolver> = (u32, bool);
+            type Error = DecodeError;
+            type TypeResolver = InMemoryResolver;
+
+            fn visit_sequence<'scale, 'resolver>(
+                self,
+                value: &mut Sequence<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                value.skip_decoding()?;
+                Ok((*value.inner_type_id(), value.is_compact()))
+            }
+            fn visit_array<'scale, 'resolver>(
+                self,
+                value: &mut Array<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                value.skip_decoding()?;
+                Ok((*value.inner_type_id(), value.is_compact()))
+            }
+            fn visit_composite<'scale, 'resolver>(
+                self,
+                value: &mut Composite<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                let item = value.next().expect("one field")?;
+                assert_eq!(item.is_compact(), value.is_compact());
+                Ok((*item.type_id(), value.is_compact()))
+            }
+            fn visit_tuple<'scale, 'resolver>(
+                self,
+                value: &mut Tuple<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                let item = value.next().expect("one item")?;
+                assert_eq!(item.is_compact(), value.is_compact());
+                Ok((*item.type_id(), value.is_compact()))
+            }
+        }
+
+        let mut builder = InMemoryResolver::builder();
+        let u32_id = builder.add_primitive(Primitive::U32);
+        let seq_id = builder.add_sequence(u32_id);
+        let arr_id = builder.add_array(u32_id, 2);
+        let struct_id = builder.add_struct("Foo", [("a", u32_id)]);
+        let compact_struct_id = builder.add_compact(struct_id);
+        let tuple_id = builder.add_tuple([u32_id]);
+        let compact_tuple_id = builder.add_compact(tuple_id);
+        let types = builder.build();
+
+        let decode = |bytes: &[u8], type_id| {
+            decode_with_visitor(&mut &*bytes, type_id, &types, ItemInfoVisitor).unwrap()
+        };
+        assert_eq!(decode(&vec![1u32, 2].encode(), seq_id), (u32_id, false));
+        assert_eq!(decode(&[1u32, 2].encode(), arr_id), (u32_id, false));
+        assert_eq!(decode(&1u32.encode(), struct_id), (u32_id, false));
+        assert_eq!(decode(&codec::Compact(1u32).encode(), compact_struct_id), (u32_id, true));
+        assert_eq!(decode(&1u32.encode(), tuple_id), (u32_id, false));
+        assert_eq!(decode(&codec::Compact(1u32).encode(), compact_tuple_id), (u32_id, true));
+    }
+
+    #[test]
+    fn split_by_types_works() {
+        #[derive(Encode, scale_info::TypeInfo)]
+        struct Foo {
+            a: Vec<u16>,
+            b: Option<String>,
+        }
+
+        let mut types = scale_info::Registry::new();
+        let u32_id = types.register_type(&scale_info::MetaType::new::<u32>()).id;
+        let foo_id = types.register_type(&scale_info::MetaType::new::<Foo>()).id;
+        let compact_id =
+            types.register_type(&scale_info::MetaType::new::<codec::Compact<u64>>()).id;
+        let types: scale_info::PortableRegistry = types.into();
+
+        let foo = Foo { a: vec![1, 2, 3], b: Some("hi".to_string()) };
+        let values = [123u32.encode(), foo.encode(), codec::Compact(1000u64).encode()];
+        let mut encoded = values.concat();
+
+        let split = split_by_types(&encoded, &[u32_id, foo_id, compact_id], &types).unwrap();
+        assert_eq!(split, values.iter().map(|v| &**v).collect::<Vec<_>>());
+
+        // No type IDs means no values:
+        assert!(split_by_types(&encoded, &[], &types).unwrap().is_empty());
+
+        // Running out of bytes points to the value that couldn't be skipped over:
+        encoded.truncate(encoded.len() - 1);
+        let err = split_by_types(&encoded, &[u32_id, foo_id, compact_id], &types).unwrap_err();
+        assert_eq!(err.context().path().to_string(), "[2]");
+    }
+
+    #[test]
+    fn visitors_can_capture_opaque_values() {
+        use crate::resolver::UnhandledKind;
+        use scale_type_resolver::{Primitive, ResolvedTypeVisitor};
+
+        // Type 0 is of some kind that the resolver can't describe, type 1 is a u8, and
+        // type 2 is a composite of a u8 followed by type 0.
+        struct NewerResolver;
+        impl TypeResolver for NewerResolver {
+            type TypeId = u32;
+            type Error = core::convert::Infallible;
+
+            fn resolve_type<'this, V: ResolvedTypeVisitor<'this, TypeId = u32>>(
+                &'this self,
+                type_id: u32,
+                visitor: V,
+            ) -> Result<V::Value, Self::Error> {
+                Ok(match type_id {
+                    0 => visitor.visit_unhandled(UnhandledKind::Variant),
+                    1 => visitor.visit_primitive(Primitive::U8),
+                    2 => visitor.visit_composite(
+                        core::iter::empty(),
+                        [crate::Field::unnamed(1), crate::Field::unnamed(0)].into_iter(),
+                    ),
+                    _ => visitor.visit_not_found(),
+                })
+            }
+        }
+
+        // Hands back the bytes of each value, flattened.
+        struct BytesVisitor;
+        impl Visitor for BytesVisitor {
+            type Value<'scale, 'resolver> = Vec<u8>;
+            type Error = DecodeError;
+            type TypeResolver = NewerResolver;
+
+            fn visit_u8<'scale, 'resolver>(
+                self,
+                value: u8,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                Ok(vec![value])
+            }
+            fn visit_composite<'scale, 'resolver>(
+                self,
+                value: &mut Composite<'scale, 'resolver, NewerResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                let mut bytes = Vec::new();
+                while let Some(item) = value.decode_item(BytesVisitor) {
+                    bytes.extend(item?);
+                }
+                Ok(bytes)
+            }
+            fn visit_opaque<'scale, 'resolver>(
+                self,
+                value: &mut Opaque<'scale, 'resolver, NewerResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                assert_eq!(value.kind(), UnhandledKind::Variant);
+                // Opaque values here are a length byte followed by that many bytes:
+                let len = value.decode::<u8>()?;
+                Ok(value.take(len as usize)?.to_vec())
+            }
+        }
+
+        // Exactly the bytes that the visitor takes are moved past:
+        let cursor = &mut &[1u8, 2, 3, 4, 5, 6][..];
+        assert_eq!(decode_with_visitor(cursor, 2, &NewerResolver, BytesVisitor), Ok(vec![1, 3, 4]));
+        assert_eq!(*cursor, &[5, 6]);
+
+        // Running out of bytes is an error:
+        let err = decode_with_visitor(&mut &[1u8, 3, 4][..], 2, &NewerResolver, BytesVisitor);
+        assert_eq!(err, Err(DecodeError::NotEnoughInput(2)));
+
+        // Visitors return an error by default:
+        assert!(<u8 as crate::DecodeAsType>::decode_as_type(&mut &[1u8][..], 0, &NewerResolver)
+            .is_err());
+        // Types which aren't found at all are never handed over:
+        let err = decode_with_visitor(&mut &[1u8][..], 3, &NewerResolver, BytesVisitor);
+        assert!(matches!(err, Err(DecodeError::TypeIdNotFound(_))));
+    }
+
+    #[test]
+    fn values_of_unhandled_kinds_can_be_skipped() {
+        use crate::resolver::UnhandledKind;
+        use scale_type_resolver::{Primitive, ResolvedTypeVisitor};
+
+        // Type 0 is of some kind that the resolver can't describe, type 1 is a u8, type 2 is a
+        // composite of a u8 followed by type 0, type 3 is an unknown primitive, and type 4 is a
+        // tuple of type 3 followed by a u8.
+        struct NewerResolver;
+        impl TypeResolver for NewerResolver {
+            type TypeId = u32;
+            type Error = core::convert::Infallible;
+
+            fn resolve_type<'this, V: ResolvedTypeVisitor<'this, TypeId = u32>>(
+                &'this self,
+                type_id: u32,
+                visitor: V,
+            ) -> Result<V::Value, Self::Error> {
+                Ok(match type_id {
+                    0 => visitor.visit_unhandled(UnhandledKind::Variant),
+                    1 => visitor.visit_primitive(Primitive::U8),
+                    2 => visitor.visit_composite(
+                        core::iter::empty(),
+                        [crate::Field::unnamed(1), crate::Field::unnamed(0)].into_iter(),
+                    ),
+                    3 => visitor.visit_unhandled(UnhandledKind::Primitive),
+                    4 => visitor.visit_tuple([3, 1].into_iter()),
+                    _ => visitor.visit_not_found(),
+                })
+            }
+        }
+
+        // Hands back the bytes of each field of a composite, found by iterating over them.
+        struct FieldBytesVisitor;
+        impl Visitor for FieldBytesVisitor {
+            type Value<'scale, 'resolver> = Vec<&'scale [u8]>;
+            type Error = DecodeError;
+            type TypeResolver = NewerResolver;
+
+            fn visit_composite<'scale, 'resolver>(
+                self,
+                value: &mut Composite<'scale, 'resolver, NewerResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                value.map(|field| field.map(|f| f.bytes())).collect()
+            }
+        }
+
+        // Ignoring opaque values assumes that they run to the end of the input:
+        let encoded = [1u8, 2, 3];
+        assert_eq!(
+            decode_with_visitor(&mut &encoded[..], 2, &NewerResolver, FieldBytesVisitor),
+            Ok(vec![&[1u8][..], &[2, 3]])
+        );
+        let cursor = &mut &encoded[..];
+        decode_with_visitor(cursor, 2, &NewerResolver, IgnoreVisitor::new()).unwrap();
+        assert!(cursor.is_empty());
+        assert_eq!(encoded_size_of(&encoded, 2, &NewerResolver), Ok(3));
+
+        // Visitors which don't want values are still handed unknown primitives, since only
+        // they know how big they are:
+        struct SkipU16Visitor;
+        impl Visitor for SkipU16Visitor {
+            type Value<'scale, 'resolver> = ();
+            type Error = DecodeError;
+            type TypeResolver = NewerResolver;
+
+            const WANTS_VALUES: bool = false;
+
+            fn visit_unexpected<'scale, 'resolver>(
+                self,
+                _unexpected: Unexpected,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                Ok(())
+            }
+            fn visit_unknown_primitive<'scale, 'resolver>(
+                self,
+                value: &mut UnknownPrimitive<'scale, 'resolver, NewerResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                value.take(2)?;
+                Ok(())
+            }
+        }
+        let cursor = &mut &encoded[..];
+        decode_with_visitor(cursor, 3, &NewerResolver, SkipU16Visitor).unwrap();
+        assert_eq!(*cursor, &[3]);
+
+        // But nothing else can tell how big they are, so they can't be ignored:
+        let err = decode_with_visitor(&mut &encoded[..], 4, &NewerResolver, IgnoreVisitor::new());
+        assert!(matches!(err, Err(DecodeError::TypeIdNotFound(_))));
+    }
+
+    #[test]
+    fn visitors_not_wanting_values_skip_them() {
+        // Records the kind of value that it was told was skipped.
+        struct SkipVisitor;
+        impl Visitor for SkipVisitor {
+            type Value<'scale, 'resolver> = Unexpected;
+            type Error = DecodeError;
+            type TypeResolver = PortableRegistry;
+
+            const WANTS_VALUES: bool = false;
+
+            fn visit_unexpected<'scale, 'resolver>(
+                self,
+                unexpected: Unexpected,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                Ok(unexpected)
+            }
+            fn visit_sequence<'scale, 'resolver>(
+                self,
+                _value: &mut Sequence<'scale, 'resolver, Self::TypeResolver>,
+                _type_id: TypeIdFor<Self>,
+            ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+                panic!("values should not be visited")
+            }
+        }
+
+        fn skip<T: Encode + scale_info::TypeInfo + 'static>(value: T) -> Unexpected {
+            let (id, types) = make_type::<T>();
+            let encoded = (value, 1u8).encode();
+            let bytes = &mut &*encoded;
+            let unexpected = decode_with_visitor(bytes, id, &types, SkipVisitor).unwrap();
+            assert_eq!(*bytes, &[1], "value should be skipped over");
+            unexpected
+        }
+
+        assert_eq!(skip(vec![0u8; 1_000_000]), Unexpected::Sequence);
+        assert_eq!(skip(vec![vec!["a".to_string()]]), Unexpected::Sequence);
+        assert_eq!(skip(codec::Compact(123u32)), Unexpected::U32);
+        assert_eq!(skip(Some(true)), Unexpected::Variant);
+        assert_eq!(skip((1u8, 2u16)), Unexpected::Tuple);
+
+        // The bytes of skipped strings aren't validated, but running out of bytes is noticed:
+        let (id, types) = make_type::<String>();
+        let encoded = vec![2 << 2, 0xFF, 0xFF];
+        assert!(decode_with_visitor(&mut &*encoded, id, &types, IgnoreVisitor::new()).is_ok());
+        let err = decode_with_visitor(&mut &encoded[..2], id, &types, IgnoreVisitor::new());
+        assert_eq!(err, Err(DecodeError::NotEnoughInput(1)));
+    }
+
+    #[test]
+    fn skipping_deeply_nested_values_works() {
+        #[derive(scale_info::TypeInfo)]
+        #[allow(dead_code)]
+        struct Node {
+            next: Option<alloc::boxed::Box<Node>>,
+        }
+
+        // Encoding this directly would recurse, so build the bytes by hand; each
+        // `Some` is a 1 byte, and the final `None` a 0 byte.
+        let depth = 100_000;
+        let mut encoded = vec![1u8; depth];
+        encoded.push(0);
+
+        let (id, types) = make_type::<Node>();
+        let bytes = &mut &*encoded;
+        decode_with_visitor(bytes, id, &types, IgnoreVisitor::new()).unwrap();
+        assert_eq!(bytes.len(), 0, "Decoding should consume all bytes");
+
+        // Missing bytes are still noticed:
+        let bytes = &mut &encoded[..depth];
+        assert!(decode_with_visitor(bytes, id, &types, IgnoreVisitor::new()).is_err());
+    }
+
+    // A couple of tests to check that invalid input doesn't lead to panics
+    // when we attempt to decode it to certain types.
+    mod proptests {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn invalid_strings_dont_panic(bytes in any::<Vec<u8>>()) {
+                let (id, types) = make_type::<String>();
+                let _ = decode_with_visitor(&mut &*bytes, id, &types, ValueVisitor::new());
+            }
+
+            #[test]
+            fn invalid_bitvecs_dont_panic(bytes in any::<Vec<u8>>()) {
+                use bitvec::{
+                    vec::BitVec,
+                    order::{Lsb0, Msb0},
+                };
+
+                let (id, types) = make_type::<BitVec<u8,Lsb0>>();
+                let _ = decode_with_visitor(&mut &*bytes, id, &types, ValueVisitor::new());
+
+                let (id, types) = make_type::<BitVec<u8,Msb0>>();
+                let _ = decode_with_visitor(&mut &*bytes, id, &types, ValueVisitor::new());
+
+                let (id, types) = make_type::<BitVec<u32,Lsb0>>();
+                let _ = decode_with_visitor(&mut &*bytes, id, &types, ValueVisitor::new());
+
+                let (id, types) = make_type::<BitVec<u32,Msb0>>();
+                let _ = decode_with_visitor(&mut &*bytes, id, &types, ValueVisitor::new());
+
+                let (id, types) = make_type::<BitVec<u16,Lsb0>>();
+                let _ = decode_with_visitor(&mut &*bytes, id, &types, ValueVisitor::new());
+
+                let (id, types) = make_type::<BitVec<u16,Msb0>>();
+                let _ = decode_with_visitor(&mut &*bytes, id, &types, ValueVisitor::new());
+
+                let (id, types) = make_type::<BitVec<u64,Lsb0>>();
+                let _ = decode_with_visitor(&mut &*bytes, id, &types, ValueVisitor::new());
+
+                let (id, types) = make_type::<BitVec<u64,Msb0>>();
+                let _ = decode_with_visitor(&mut &*bytes, id, &types, ValueVisitor::new());
+            }
+
+            #[test]
+            fn invalid_nested_values_dont_panic(bytes in any::<Vec<u8>>()) {
+                #[derive(scale_info::TypeInfo)]
+                #[allow(dead_code)]
+                enum Foo {
+                    A(codec::Compact<u64>, char),
+                    B { c: Option<Option<Option<bool>>>, d: [u16; 3] },
+                    C(Vec<Foo>, String),
+                }
+
+                let (id, types) = make_type::<Foo>();
                 let _ = decode_with_visitor(&mut &*bytes, id, &types, ValueVisitor::new());
+                let _ = decode_with_visitor(&mut &*bytes, id, &types, IgnoreVisitor::new());
+                let _ = encoded_size_of(&bytes, id, &types);
             }
         }
     }
@@ -0,0 +1,129 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::visitor::{decode_with_visitor, DecodeError, TypeIdFor, Visitor};
+use crate::Error;
+use alloc::boxed::Box;
+
+/// Decode data according to the type ID and type resolver provided, like
+/// [`decode_with_visitor()`]. Visitors used with this return a [`PartialError`] when something
+/// goes wrong, which can carry whatever was successfully decoded before the error was hit, so
+/// that it isn't all thrown away. See [`crate::value::decode_value_partial()`] for a visitor
+/// which does this.
+///
+/// On error, the provided pointer to the data slice is not moved, and the returned error
+/// contains the byte offset (relative to the data given) of the value that failed to decode.
+pub fn decode_with_visitor_partial<'scale, 'resolver, V, P>(
+    data: &mut &'scale [u8],
+    ty_id: TypeIdFor<V>,
+    types: &'resolver V::TypeResolver,
+    visitor: V,
+) -> Result<V::Value<'scale, 'resolver>, PartialError<P>>
+where
+    V: Visitor<Error = PartialError<P>>,
+{
+    let original = *data;
+    decode_with_visitor(data, ty_id, types, visitor).map_err(|mut e| {
+        *data = original;
+        e.0.offset = original.len() - e.0.remaining.unwrap_or(original.len());
+        e
+    })
+}
+
+/// An error which carries the part of a value that was successfully decoded before the error
+/// was hit, if there was any. Visitors which return this should add whatever they've decoded
+/// so far to any error they see via [`PartialError::with_partial()`], and note where the error
+/// occurred via [`PartialError::at_remaining()`].
+#[derive(Debug)]
+pub struct PartialError<P>(Box<PartialErrorInner<P>>);
+
+// Boxed, since the partial value and error can be fairly large, and errors are the uncommon case.
+#[derive(Debug)]
+struct PartialErrorInner<P> {
+    partial: Option<P>,
+    error: Error,
+    // How many bytes were left in the input from the start of the value that failed to decode.
+    remaining: Option<usize>,
+    offset: usize,
+}
+
+impl<P> PartialError<P> {
+    /// Construct a new error with no partially decoded value.
+    pub fn new(error: impl Into<Error>) -> Self {
+        PartialError(Box::new(PartialErrorInner {
+            partial: None,
+            error: error.into(),
+            remaining: None,
+            offset: 0,
+        }))
+    }
+    /// Replace the partially decoded value.
+    pub fn with_partial(mut self, partial: P) -> Self {
+        self.0.partial = Some(partial);
+        self
+    }
+    /// Note where the value that failed to decode started, given the bytes from that point
+    /// onwards. Only the first (ie innermost) location given is kept.
+    pub fn at_remaining(mut self, remaining: &[u8]) -> Self {
+        self.0.remaining.get_or_insert(remaining.len());
+        self
+    }
+    /// Modify the underlying error, for instance to give it some context.
+    pub fn map_error(mut self, f: impl FnOnce(Error) -> Error) -> Self {
+        self.0.error = f(self.0.error);
+        self
+    }
+    /// The part of the value that was decoded before the error, if any.
+    pub fn partial(&self) -> Option<&P> {
+        self.0.partial.as_ref()
+    }
+    /// Take the part of the value that was decoded before the error, if any.
+    pub fn take_partial(&mut self) -> Option<P> {
+        self.0.partial.take()
+    }
+    /// The error that was hit.
+    pub fn error(&self) -> &Error {
+        &self.0.error
+    }
+    /// Convert this into the error that was hit.
+    pub fn into_error(self) -> Error {
+        self.0.error
+    }
+    /// The byte offset, relative to the start of the data given to
+    /// [`decode_with_visitor_partial()`], of the value that failed to decode.
+    pub fn offset(&self) -> usize {
+        self.0.offset
+    }
+}
+
+impl<P> From<DecodeError> for PartialError<P> {
+    fn from(err: DecodeError) -> Self {
+        PartialError::new(err)
+    }
+}
+
+impl<P> From<Error> for PartialError<P> {
+    fn from(err: Error) -> Self {
+        PartialError::new(err)
+    }
+}
+
+impl<P: core::fmt::Debug> core::fmt::Display for PartialError<P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} (at byte offset {})", self.0.error, self.0.offset)
+    }
+}
+
+impl<P: core::fmt::Debug> core::error::Error for PartialError<P> {}
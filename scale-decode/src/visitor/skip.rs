@@ -0,0 +1,631 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! A skip-only traversal, used to move past SCALE encoded values without handing anything to a
+//! [`crate::Visitor`]. Values are not validated beyond what is needed to find where they end (for
+//! instance, the bytes of strings aren't checked to be valid UTF8), and where the size of a value
+//! can be known from its type alone, we skip over it without looking at its bytes at all.
+//!
+//! Values of kinds that the type resolver can't describe (see [`UnhandledKind`]) are handed to an
+//! [`IgnoreVisitor`] instead, to skip over them in the same way as when decoding.
+
+use crate::visitor::{BitSequence, DecodeError, IgnoreVisitor, Unexpected};
+use alloc::{format, string::ToString, vec::Vec};
+use codec::{Compact, Decode};
+use scale_type_resolver::{
+    BitsOrderFormat, BitsStoreFormat, FieldIter, PathIter, Primitive, ResolvedTypeVisitor,
+    TypeResolver, UnhandledKind, VariantIter,
+};
+
+/// Move `data` past the SCALE encoded value described by the type ID given.
+pub(crate) fn skip_type<R: TypeResolver>(
+    data: &mut &[u8],
+    type_id: R::TypeId,
+    types: &R,
+    is_compact: bool,
+) -> Result<(), DecodeError> {
+    let mut stack = Stack::new();
+    stack.push(Work::One { type_id, is_compact });
+    skip_stack(data, types, stack)
+}
+
+/// Move `data` past the SCALE encoded value described by the type ID given, returning the kind of
+/// value that was skipped over. This is used to skip values for visitors which don't want them.
+/// `None` is returned, and nothing is skipped, if the type resolver can't describe the kind of
+/// value, so that the visitor can be handed it to decide how many bytes it takes up.
+pub(crate) fn skip_value<R: TypeResolver>(
+    data: &mut &[u8],
+    type_id: R::TypeId,
+    types: &R,
+    is_compact: bool,
+) -> Result<Option<Unexpected>, DecodeError> {
+    // Compact values are reported as whatever they wrap.
+    let mut kind_type_id = type_id.clone();
+    let kind = loop {
+        let kind = types
+            .resolve_type(kind_type_id.clone(), KindOf { type_id: kind_type_id })
+            .map_err(|e| DecodeError::TypeResolvingError(e.to_string()))??;
+        match kind {
+            Kind::Is(kind) => break kind,
+            Kind::Compact(inner) => kind_type_id = inner,
+            Kind::Unhandled => return Ok(None),
+        }
+    };
+    skip_type(data, type_id, types, is_compact)?;
+    Ok(Some(kind))
+}
+
+/// Return the number of bytes that the SCALE encoded value at the start of `data` occupies,
+/// given the type ID that describes it. This is cheaper than decoding the value with some
+/// [`crate::Visitor`]: nothing is done with the individual values, and values whose size is
+/// known from their type alone (such as byte arrays) are jumped over without looking at their
+/// bytes at all. As such, the bytes aren't validated beyond what's needed to
+/// find where the value ends.
+///
+/// This is useful for splitting apart a set of concatenated SCALE encoded values.
+pub fn encoded_size_of<R: TypeResolver>(
+    data: &[u8],
+    type_id: R::TypeId,
+    types: &R,
+) -> Result<usize, DecodeError> {
+    let mut rest = data;
+    skip_type(&mut rest, type_id, types, false)?;
+    Ok(data.len() - rest.len())
+}
+
+/// Split some concatenated SCALE encoded values into the bytes of each one, given the type IDs
+/// that describe each value in turn. Like [`encoded_size_of()`], this doesn't decode the values
+/// or validate their bytes beyond what's needed to find where each one ends.
+///
+/// Any bytes left over after the last value are ignored. If a value can't be skipped over, the
+/// error returned will point to its index in the list of type IDs.
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_decode::visitor::split_by_types;
+///
+/// let mut types = scale_info::Registry::new();
+/// let bool_id = types.register_type(&scale_info::MetaType::new::<bool>()).id;
+/// let str_id = types.register_type(&scale_info::MetaType::new::<String>()).id;
+/// let types: scale_info::PortableRegistry = types.into();
+///
+/// let encoded = (true, "hello", false).encode();
+/// let values = split_by_types(&encoded, &[bool_id, str_id, bool_id], &types).unwrap();
+///
+/// assert_eq!(values, vec![&encoded[0..1], &encoded[1..7], &encoded[7..8]]);
+/// ```
+pub fn split_by_types<'scale, 'a, R: TypeResolver>(
+    bytes: &'scale [u8],
+    type_ids: impl IntoIterator<Item = &'a R::TypeId>,
+    types: &R,
+) -> Result<Vec<&'scale [u8]>, crate::Error>
+where
+    R::TypeId: 'a,
+{
+    let mut rest = bytes;
+    type_ids
+        .into_iter()
+        .enumerate()
+        .map(|(idx, type_id)| {
+            let start = rest;
+            skip_type(&mut rest, type_id.clone(), types, false)
+                .map_err(|e| crate::Error::from(e).at_idx(idx))?;
+            Ok(&start[..start.len() - rest.len()])
+        })
+        .collect()
+}
+
+/// Move `data` past `len` SCALE encoded values of the type ID given.
+pub(crate) fn skip_types<R: TypeResolver>(
+    data: &mut &[u8],
+    type_id: R::TypeId,
+    types: &R,
+    len: usize,
+) -> Result<(), DecodeError> {
+    let mut stack = Stack::new();
+    push_many(data, types, &mut stack, type_id, len)?;
+    skip_stack(data, types, stack)
+}
+
+// Rather than recursing into nested types, we keep a stack of the things left to skip over. This
+// avoids the overhead of a bunch of nested calls, and means that deeply nested values can't
+// exhaust the call stack.
+type Stack<TypeId> = smallvec::SmallVec<[Work<TypeId>; 16]>;
+
+#[derive(Clone)]
+enum Work<TypeId> {
+    // Skip a single value of this type.
+    One { type_id: TypeId, is_compact: bool },
+    // Skip `len` values of this type, which don't all have the same size.
+    Many { type_id: TypeId, len: usize },
+}
+
+fn skip_stack<R: TypeResolver>(
+    data: &mut &[u8],
+    types: &R,
+    mut stack: Stack<R::TypeId>,
+) -> Result<(), DecodeError> {
+    while let Some(work) = stack.pop() {
+        skip_work(data, types, &mut stack, work)?;
+    }
+    Ok(())
+}
+
+// Do one piece of work, moving `data` past any bytes that it covers and pushing any nested values
+// to the stack. If we run out of bytes, nothing will have been pushed to the stack.
+fn skip_work<R: TypeResolver>(
+    data: &mut &[u8],
+    types: &R,
+    stack: &mut Stack<R::TypeId>,
+    work: Work<R::TypeId>,
+) -> Result<(), DecodeError> {
+    match work {
+        Work::One { type_id, is_compact } => {
+            let skipper = Skipper { data, type_id: type_id.clone(), types, is_compact, stack };
+            types
+                .resolve_type(type_id, skipper)
+                .map_err(|e| DecodeError::TypeResolvingError(e.to_string()))??;
+        }
+        Work::Many { type_id, len } => {
+            if len > 1 {
+                stack.push(Work::Many { type_id: type_id.clone(), len: len - 1 });
+            }
+            if len > 0 {
+                stack.push(Work::One { type_id, is_compact: false });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A skip-only traversal of some value which can be paused when it runs out of bytes, and carried
+/// on from the same place once more bytes have arrived, rather than starting again from the
+/// beginning of the value.
+pub(crate) struct ResumableSkip<TypeId> {
+    stack: Stack<TypeId>,
+    // How many bytes we've skipped over so far.
+    offset: usize,
+}
+
+impl<TypeId: Clone> ResumableSkip<TypeId> {
+    /// Prepare to skip over a value of the type given.
+    pub(crate) fn new(type_id: TypeId) -> Self {
+        let mut stack = Stack::new();
+        stack.push(Work::One { type_id, is_compact: false });
+        ResumableSkip { stack, offset: 0 }
+    }
+
+    /// Carry on skipping over the value, given every byte that we've seen so far (including those
+    /// handed over in previous calls). Once the end of the value has been found, we return how
+    /// many bytes it occupies. If we run out of bytes first, [`DecodeError::NotEnoughInput`] is
+    /// returned, and this can be called again with more bytes to carry on from where we stopped.
+    pub(crate) fn resume<R: TypeResolver<TypeId = TypeId>>(
+        &mut self,
+        data: &[u8],
+        types: &R,
+    ) -> Result<usize, DecodeError> {
+        let mut rest = &data[self.offset..];
+        while let Some(work) = self.stack.pop() {
+            let mut after = rest;
+            if let Err(e) = skip_work(&mut after, types, &mut self.stack, work.clone()) {
+                // Put the work back so that we can try it again with more bytes.
+                self.stack.push(work);
+                return Err(e);
+            }
+            self.offset += rest.len() - after.len();
+            rest = after;
+        }
+        Ok(self.offset)
+    }
+}
+
+// Skip over `len` values of the given type. If every value is the same size we can skip them
+// all at once, and otherwise we push them to the stack to be skipped one by one.
+fn push_many<R: TypeResolver>(
+    data: &mut &[u8],
+    types: &R,
+    stack: &mut Stack<R::TypeId>,
+    type_id: R::TypeId,
+    len: usize,
+) -> Result<(), DecodeError> {
+    if len == 0 {
+        return Ok(());
+    }
+    match fixed_size(type_id.clone(), types)? {
+        Some(size) => advance(data, size.saturating_mul(len)),
+        None => {
+            stack.push(Work::Many { type_id, len });
+            Ok(())
+        }
+    }
+}
+
+/// Return the encoded size of the type given, if it's the same for every value.
+pub(crate) fn fixed_size<R: TypeResolver>(
+    type_id: R::TypeId,
+    types: &R,
+) -> Result<Option<usize>, DecodeError> {
+    types
+        .resolve_type(type_id, FixedSizer { types })
+        .map_err(|e| DecodeError::TypeResolvingError(e.to_string()))?
+}
+
+fn advance(data: &mut &[u8], len: usize) -> Result<(), DecodeError> {
+    *data = data.get(len..).ok_or_else(|| DecodeError::NotEnoughInput(len - data.len()))?;
+    Ok(())
+}
+
+// Check that the compact encoded value at the start of `data` isn't cut short, so that running out
+// of bytes is reported as `NotEnoughInput`, holding the number of bytes still needed.
+fn ensure_compact(data: &[u8]) -> Result<(), DecodeError> {
+    let len = match data.first() {
+        None => 1,
+        Some(b) => match b & 0b11 {
+            0b00 => 1,
+            0b01 => 2,
+            0b10 => 4,
+            _ => (b >> 2) as usize + 5,
+        },
+    };
+    match len.checked_sub(data.len()) {
+        Some(needed) if needed > 0 => Err(DecodeError::NotEnoughInput(needed)),
+        _ => Ok(()),
+    }
+}
+
+fn primitive_size(primitive: Primitive) -> Option<usize> {
+    match primitive {
+        Primitive::Bool | Primitive::U8 | Primitive::I8 => Some(1),
+        Primitive::U16 | Primitive::I16 => Some(2),
+        Primitive::Char | Primitive::U32 | Primitive::I32 => Some(4),
+        Primitive::U64 | Primitive::I64 => Some(8),
+        Primitive::U128 | Primitive::I128 => Some(16),
+        Primitive::U256 | Primitive::I256 => Some(32),
+        Primitive::Str => None,
+    }
+}
+
+// Skip over the bytes of some resolved type, pushing any nested values to the stack to be
+// skipped next.
+struct Skipper<'a, 'b, 'resolver, R: TypeResolver> {
+    data: &'a mut &'b [u8],
+    type_id: R::TypeId,
+    types: &'resolver R,
+    is_compact: bool,
+    stack: &'a mut Stack<R::TypeId>,
+}
+
+impl<R: TypeResolver> Skipper<'_, '_, '_, R> {
+    // Push fields so that they are popped (and thus skipped) in order.
+    fn push_fields(
+        &mut self,
+        type_ids: impl ExactSizeIterator<Item = R::TypeId>,
+        is_compact: bool,
+    ) {
+        let start = self.stack.len();
+        self.stack.extend(type_ids.map(|type_id| Work::One { type_id, is_compact }));
+        self.stack[start..].reverse();
+    }
+}
+
+impl<'resolver, R: TypeResolver> ResolvedTypeVisitor<'resolver> for Skipper<'_, '_, 'resolver, R> {
+    type TypeId = R::TypeId;
+    type Value = Result<(), DecodeError>;
+
+    fn visit_unhandled(self, kind: UnhandledKind) -> Self::Value {
+        let type_id = self.type_id;
+        if kind == UnhandledKind::NotFound {
+            return Err(DecodeError::TypeIdNotFound(format!(
+                "Kind {kind:?} (type ID {type_id:?}) has not been properly handled"
+            )));
+        }
+        // We can't tell where the value ends from its type, so skip over it by decoding it
+        // instead, which hands it to the visitor to decide.
+        crate::visitor::decode_with_visitor_maybe_compact(
+            self.data,
+            type_id,
+            self.types,
+            IgnoreVisitor::<R>::new(),
+            self.is_compact,
+        )
+    }
+
+    fn visit_not_found(self) -> Self::Value {
+        let type_id = self.type_id;
+        Err(DecodeError::TypeIdNotFound(format!("{type_id:?}")))
+    }
+
+    fn visit_composite<Path, Fields>(mut self, _path: Path, fields: Fields) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+        Fields: FieldIter<'resolver, Self::TypeId>,
+    {
+        if self.is_compact && fields.len() != 1 {
+            return Err(DecodeError::CannotDecodeCompactIntoType);
+        }
+        let is_compact = self.is_compact;
+        self.push_fields(fields.map(|f| f.id), is_compact);
+        Ok(())
+    }
+
+    fn visit_variant<Path, Fields, Var>(mut self, _path: Path, variants: Var) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+        Fields: FieldIter<'resolver, Self::TypeId>,
+        Var: VariantIter<'resolver, Fields>,
+    {
+        if self.is_compact {
+            return Err(DecodeError::CannotDecodeCompactIntoType);
+        }
+        let index = *self.data.first().ok_or(DecodeError::NotEnoughInput(1))?;
+        *self.data = &self.data[1..];
+        let variant = variants
+            .into_iter()
+            .find(|v| v.index == index)
+            .ok_or(DecodeError::VariantNotFound(index))?;
+        self.push_fields(variant.fields.map(|f| f.id), false);
+        Ok(())
+    }
+
+    fn visit_sequence<Path>(self, _path: Path, inner_type_id: Self::TypeId) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+    {
+        if self.is_compact {
+            return Err(DecodeError::CannotDecodeCompactIntoType);
+        }
+        ensure_compact(self.data)?;
+        let len = <Compact<u64>>::decode(self.data)?.0 as usize;
+        push_many(self.data, self.types, self.stack, inner_type_id, len)
+    }
+
+    fn visit_array(self, inner_type_id: Self::TypeId, len: usize) -> Self::Value {
+        if self.is_compact {
+            return Err(DecodeError::CannotDecodeCompactIntoType);
+        }
+        push_many(self.data, self.types, self.stack, inner_type_id, len)
+    }
+
+    fn visit_tuple<TypeIds>(mut self, type_ids: TypeIds) -> Self::Value
+    where
+        TypeIds: ExactSizeIterator<Item = Self::TypeId>,
+    {
+        if self.is_compact && type_ids.len() != 1 {
+            return Err(DecodeError::CannotDecodeCompactIntoType);
+        }
+        let is_compact = self.is_compact;
+        self.push_fields(type_ids, is_compact);
+        Ok(())
+    }
+
+    fn visit_primitive(self, primitive: Primitive) -> Self::Value {
+        if self.is_compact {
+            ensure_compact(self.data)?;
+            return match primitive {
+                Primitive::U8 => Compact::<u8>::skip(self.data),
+                Primitive::U16 => Compact::<u16>::skip(self.data),
+                Primitive::U32 => Compact::<u32>::skip(self.data),
+                Primitive::U64 => Compact::<u64>::skip(self.data),
+                Primitive::U128 => Compact::<u128>::skip(self.data),
+                _ => return Err(DecodeError::CannotDecodeCompactIntoType),
+            }
+            .map_err(Into::into);
+        }
+        match primitive_size(primitive) {
+            Some(size) => advance(self.data, size),
+            None => {
+                ensure_compact(self.data)?;
+                let len = <Compact<u32>>::decode(self.data)?.0 as usize;
+                advance(self.data, len)
+            }
+        }
+    }
+
+    fn visit_compact(self, inner_type_id: Self::TypeId) -> Self::Value {
+        self.stack.push(Work::One { type_id: inner_type_id, is_compact: true });
+        Ok(())
+    }
+
+    fn visit_bit_sequence(
+        self,
+        store_format: BitsStoreFormat,
+        order_format: BitsOrderFormat,
+    ) -> Self::Value {
+        if self.is_compact {
+            return Err(DecodeError::CannotDecodeCompactIntoType);
+        }
+        // Make sure that running out of bytes is reported as such, before handing over to
+        // `BitSequence` to find where the bits end.
+        ensure_compact(self.data)?;
+        let mut bits_data = *self.data;
+        let bits_len = <Compact<u32>>::decode(&mut bits_data)?.0 as usize;
+        let store_bytes = match store_format {
+            BitsStoreFormat::U8 => 1,
+            BitsStoreFormat::U16 => 2,
+            BitsStoreFormat::U32 => 4,
+            BitsStoreFormat::U64 => 8,
+        };
+        let bytes_len = bits_len.div_ceil(store_bytes * 8) * store_bytes;
+        if bits_data.len() < bytes_len {
+            return Err(DecodeError::NotEnoughInput(bytes_len - bits_data.len()));
+        }
+
+        let format = scale_bits::Format::new(store_format, order_format);
+        *self.data = BitSequence::new(format, self.data).bytes_after()?;
+        Ok(())
+    }
+}
+
+// Work out the fixed size of some resolved type, if it has one.
+struct FixedSizer<'resolver, R: TypeResolver> {
+    types: &'resolver R,
+}
+
+impl<'resolver, R: TypeResolver> ResolvedTypeVisitor<'resolver> for FixedSizer<'resolver, R> {
+    type TypeId = R::TypeId;
+    type Value = Result<Option<usize>, DecodeError>;
+
+    fn visit_unhandled(self, _kind: UnhandledKind) -> Self::Value {
+        // Leave it to the skipper to report a proper error.
+        Ok(None)
+    }
+
+    fn visit_composite<Path, Fields>(self, _path: Path, fields: Fields) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+        Fields: FieldIter<'resolver, Self::TypeId>,
+    {
+        let mut size = 0usize;
+        for field in fields {
+            let Some(field_size) = fixed_size(field.id, self.types)? else { return Ok(None) };
+            size = size.saturating_add(field_size);
+        }
+        Ok(Some(size))
+    }
+
+    fn visit_array(self, inner_type_id: Self::TypeId, len: usize) -> Self::Value {
+        Ok(fixed_size(inner_type_id, self.types)?.map(|size| size.saturating_mul(len)))
+    }
+
+    fn visit_tuple<TypeIds>(self, type_ids: TypeIds) -> Self::Value
+    where
+        TypeIds: ExactSizeIterator<Item = Self::TypeId>,
+    {
+        let mut size = 0usize;
+        for type_id in type_ids {
+            let Some(field_size) = fixed_size(type_id, self.types)? else { return Ok(None) };
+            size = size.saturating_add(field_size);
+        }
+        Ok(Some(size))
+    }
+
+    fn visit_primitive(self, primitive: Primitive) -> Self::Value {
+        Ok(primitive_size(primitive))
+    }
+}
+
+// Work out what kind of value some resolved type describes, so that visitors which skip values
+// can be told what they skipped.
+struct KindOf<TypeId> {
+    type_id: TypeId,
+}
+
+enum Kind<TypeId> {
+    Is(Unexpected),
+    Compact(TypeId),
+    // The type resolver couldn't describe the type.
+    Unhandled,
+}
+
+impl<'resolver, TypeId: scale_type_resolver::TypeId + 'static> ResolvedTypeVisitor<'resolver>
+    for KindOf<TypeId>
+{
+    type TypeId = TypeId;
+    type Value = Result<Kind<TypeId>, DecodeError>;
+
+    fn visit_unhandled(self, kind: UnhandledKind) -> Self::Value {
+        let type_id = self.type_id;
+        if kind == UnhandledKind::NotFound {
+            return Err(DecodeError::TypeIdNotFound(format!(
+                "Kind {kind:?} (type ID {type_id:?}) has not been properly handled"
+            )));
+        }
+        Ok(Kind::Unhandled)
+    }
+
+    fn visit_not_found(self) -> Self::Value {
+        let type_id = self.type_id;
+        Err(DecodeError::TypeIdNotFound(format!("{type_id:?}")))
+    }
+
+    fn visit_composite<Path, Fields>(self, path: Path, fields: Fields) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+        Fields: FieldIter<'resolver, Self::TypeId>,
+    {
+        // Floats are described as composites; see the decoder.
+        #[cfg(feature = "float")]
+        if fields.len() == 1 {
+            let mut path = path;
+            match (path.next(), path.next()) {
+                (Some("f32"), None) => return Ok(Kind::Is(Unexpected::F32)),
+                (Some("f64"), None) => return Ok(Kind::Is(Unexpected::F64)),
+                _ => {}
+            }
+        }
+        #[cfg(not(feature = "float"))]
+        let _ = (path, fields);
+        Ok(Kind::Is(Unexpected::Composite))
+    }
+
+    fn visit_variant<Path, Fields, Var>(self, _path: Path, _variants: Var) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+        Fields: FieldIter<'resolver, Self::TypeId>,
+        Var: VariantIter<'resolver, Fields>,
+    {
+        Ok(Kind::Is(Unexpected::Variant))
+    }
+
+    fn visit_sequence<Path>(self, _path: Path, _inner_type_id: Self::TypeId) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+    {
+        Ok(Kind::Is(Unexpected::Sequence))
+    }
+
+    fn visit_array(self, _inner_type_id: Self::TypeId, _len: usize) -> Self::Value {
+        Ok(Kind::Is(Unexpected::Array))
+    }
+
+    fn visit_tuple<TypeIds>(self, _type_ids: TypeIds) -> Self::Value
+    where
+        TypeIds: ExactSizeIterator<Item = Self::TypeId>,
+    {
+        Ok(Kind::Is(Unexpected::Tuple))
+    }
+
+    fn visit_primitive(self, primitive: Primitive) -> Self::Value {
+        let kind = match primitive {
+            Primitive::Bool => Unexpected::Bool,
+            Primitive::Char => Unexpected::Char,
+            Primitive::Str => Unexpected::Str,
+            Primitive::U8 => Unexpected::U8,
+            Primitive::U16 => Unexpected::U16,
+            Primitive::U32 => Unexpected::U32,
+            Primitive::U64 => Unexpected::U64,
+            Primitive::U128 => Unexpected::U128,
+            Primitive::U256 => Unexpected::U256,
+            Primitive::I8 => Unexpected::I8,
+            Primitive::I16 => Unexpected::I16,
+            Primitive::I32 => Unexpected::I32,
+            Primitive::I64 => Unexpected::I64,
+            Primitive::I128 => Unexpected::I128,
+            Primitive::I256 => Unexpected::I256,
+        };
+        Ok(Kind::Is(kind))
+    }
+
+    fn visit_compact(self, inner_type_id: Self::TypeId) -> Self::Value {
+        Ok(Kind::Compact(inner_type_id))
+    }
+
+    fn visit_bit_sequence(
+        self,
+        _store_format: BitsStoreFormat,
+        _order_format: BitsOrderFormat,
+    ) -> Self::Value {
+        Ok(Kind::Is(Unexpected::Bitsequence))
+    }
+}
@@ -14,7 +14,10 @@
 // limitations under the License.
 
 use crate::{
-    visitor::{DecodeError, IgnoreVisitor, Visitor},
+    visitor::{
+        skip::{skip_type, skip_types},
+        DecodeError, IgnoreVisitor, Visitor,
+    },
     DecodeAsType,
 };
 use scale_type_resolver::TypeResolver;
@@ -39,11 +42,11 @@ impl<'scale, 'resolver, R: TypeResolver> Array<'scale, 'resolver, R> {
     }
     /// Skip over all bytes associated with this array. After calling this,
     /// [`Self::bytes_from_undecoded()`] will represent the bytes after this array.
+    ///
+    /// Like [`Self::skip_items()`], this doesn't validate the bytes being skipped over beyond
+    /// what's needed to find where they end.
     pub fn skip_decoding(&mut self) -> Result<(), DecodeError> {
-        while self.remaining > 0 {
-            self.decode_item(IgnoreVisitor::<R>::new()).transpose()?;
-        }
-        Ok(())
+        self.skip_items(self.remaining())
     }
     /// The bytes representing this array and anything following it.
     pub fn bytes_from_start(&self) -> &'scale [u8] {
@@ -54,6 +57,21 @@ impl<'scale, 'resolver, R: TypeResolver> Array<'scale, 'resolver, R> {
     pub fn bytes_from_undecoded(&self) -> &'scale [u8] {
         self.item_bytes
     }
+    /// The type resolver that this array is being decoded with. This can be used to look up more
+    /// information about types, for instance via [`crate::resolver::ResolverExt`].
+    pub fn types(&self) -> &'resolver R {
+        self.types
+    }
+    /// The type ID of each item in the array.
+    pub fn inner_type_id(&self) -> &R::TypeId {
+        &self.type_id
+    }
+    /// If the items in the array are compact encoded. Array items are never compact encoded, so
+    /// this always returns false, but it's provided for consistency with [`super::Composite`] and
+    /// [`super::Tuple`].
+    pub fn is_compact(&self) -> bool {
+        false
+    }
     /// The number of un-decoded items remaining in this array.
     pub fn remaining(&self) -> usize {
         self.remaining
@@ -62,6 +80,46 @@ impl<'scale, 'resolver, R: TypeResolver> Array<'scale, 'resolver, R> {
     pub fn is_empty(&self) -> bool {
         self.remaining == 0
     }
+    /// Skip over the next item without decoding it. This avoids looking at the bytes of the item
+    /// where its size is known from its type alone, and only hands values to a visitor (an
+    /// [`crate::visitor::IgnoreVisitor`]) if the type resolver can't describe them.
+    pub fn skip_item(&mut self) -> Option<Result<(), DecodeError>> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let b = &mut self.item_bytes;
+        let res = skip_type(b, self.type_id.clone(), self.types, false);
+        self.item_bytes = *b;
+        self.remaining -= 1;
+        Some(res)
+    }
+    /// Skip over the next `n` items (or however many remain, if fewer) without decoding them.
+    /// If the items all have the same encoded size, they are skipped over in one go.
+    pub fn skip_items(&mut self, n: usize) -> Result<(), DecodeError> {
+        let n = n.min(self.remaining);
+        let b = &mut self.item_bytes;
+        let res = skip_types(b, self.type_id.clone(), self.types, n);
+        self.item_bytes = *b;
+        self.remaining -= n;
+        res
+    }
+    /// Decode the next item from the array by providing a visitor to handle it, without moving
+    /// past it. A subsequent call to [`Self::decode_item()`] will decode the same item again.
+    pub fn peek_item<V: Visitor<TypeResolver = R>>(
+        &self,
+        visitor: V,
+    ) -> Option<Result<V::Value<'scale, 'resolver>, V::Error>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        Some(crate::visitor::decode_with_visitor(
+            &mut &*self.item_bytes,
+            self.type_id.clone(),
+            self.types,
+            visitor,
+        ))
+    }
     /// Decode an item from the array by providing a visitor to handle it.
     pub fn decode_item<V: Visitor<TypeResolver = R>>(
         &mut self,
@@ -129,6 +187,12 @@ impl<'scale, 'resolver, R: TypeResolver> ArrayItem<'scale, 'resolver, R> {
     pub fn type_id(&self) -> &R::TypeId {
         &self.type_id
     }
+    /// If the item is compact encoded. Array items are never compact encoded, so this always
+    /// returns false, but it's provided for consistency with [`super::CompositeField`] and
+    /// [`super::TupleField`].
+    pub fn is_compact(&self) -> bool {
+        false
+    }
     /// Decode this item using a visitor.
     pub fn decode_with_visitor<V: Visitor<TypeResolver = R>>(
         &self,
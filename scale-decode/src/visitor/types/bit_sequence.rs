@@ -13,7 +13,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::visitor::DecodeError;
+use crate::visitor::{DecodeError, InvalidBitSequence};
 use scale_bits::{
     scale::{decode_using_format_from, Decoder},
     Format,
@@ -33,6 +33,11 @@ impl<'scale> BitSequence<'scale> {
         BitSequence { format, bytes, bytes_after: None }
     }
 
+    /// The store and order formats that the bits are encoded with.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
     /// The bytes left in the input, starting from this bit sequence.
     pub fn bytes_from_start(&self) -> &'scale [u8] {
         self.bytes
@@ -45,23 +50,42 @@ impl<'scale> BitSequence<'scale> {
         if let Some(bytes_after) = self.bytes_after {
             Ok(bytes_after)
         } else {
-            let decoder = decode_using_format_from(self.bytes, self.format)?;
-            self.bytes.get(decoder.encoded_size()..).ok_or(DecodeError::NotEnoughInput)
+            self.decoder_and_bytes_after().map(|(_, bytes_after)| bytes_after)
         }
     }
 
     /// Return a decoder to decode the bits in this bit sequence.
     pub fn decode(&mut self) -> Result<Decoder<'scale>, DecodeError> {
-        let decoder = decode_using_format_from(self.bytes, self.format)?;
-        self.bytes_after =
-            Some(self.bytes.get(decoder.encoded_size()..).ok_or(DecodeError::NotEnoughInput)?);
+        let (decoder, bytes_after) = self.decoder_and_bytes_after()?;
+        self.bytes_after = Some(bytes_after);
         Ok(decoder)
     }
+
+    // We check that all of the bytes are present here, so that the returned decoder can't run
+    // out of bytes part way through.
+    fn decoder_and_bytes_after(&self) -> Result<(Decoder<'scale>, &'scale [u8]), DecodeError> {
+        let decoder =
+            decode_using_format_from(self.bytes, self.format).map_err(|_| self.invalid(None))?;
+        match self.bytes.get(decoder.encoded_size()..) {
+            Some(bytes_after) => Ok((decoder, bytes_after)),
+            None => Err(self.invalid(Some(decoder.len()))),
+        }
+    }
+
+    fn invalid(&self, bits_len: Option<usize>) -> DecodeError {
+        DecodeError::InvalidBitSequence(InvalidBitSequence {
+            store_format: self.format.store,
+            order_format: self.format.order,
+            bits_len,
+            available_bytes: self.bytes.len(),
+        })
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use alloc::string::ToString;
     use bitvec::{
         order::{Lsb0, Msb0},
         vec::BitVec,
@@ -112,6 +136,50 @@ mod test {
         assert_remaining_bytes_works(b, StoreFormat::U64, OrderFormat::Msb0);
     }
 
+    #[test]
+    fn truncated_input_reports_bit_sequence_details() {
+        let bits: BitVec<u32, Lsb0> = bits![1, 0, 1].iter().collect();
+        let bytes = bits.encode();
+        let format = Format::new(StoreFormat::U32, OrderFormat::Lsb0);
+
+        for missing in 1..=4 {
+            let truncated = &bytes[..bytes.len() - missing];
+            let expected = DecodeError::InvalidBitSequence(InvalidBitSequence {
+                store_format: StoreFormat::U32,
+                order_format: OrderFormat::Lsb0,
+                bits_len: Some(3),
+                available_bytes: bytes.len() - missing,
+            });
+            let err = BitSequence::new(format, truncated).bytes_after().unwrap_err();
+            assert_eq!(err, expected);
+            let err = BitSequence::new(format, truncated).decode().err().unwrap();
+            assert_eq!(err, expected);
+        }
+
+        // If the length itself can't be decoded, we don't know how many bits there are:
+        let err = BitSequence::new(format, &[]).bytes_after().unwrap_err();
+        assert_eq!(
+            err,
+            DecodeError::InvalidBitSequence(InvalidBitSequence {
+                store_format: StoreFormat::U32,
+                order_format: OrderFormat::Lsb0,
+                bits_len: None,
+                available_bytes: 0,
+            })
+        );
+        assert_eq!(
+            err.to_string(),
+            "Cannot decode bit sequence (store format U32, order format Lsb0) from 0 bytes: the length could not be decoded"
+        );
+
+        // These details are surfaced as their own kind of error:
+        let err = crate::Error::from(err);
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::InvalidBitSequence(InvalidBitSequence { bits_len: None, .. })
+        ));
+    }
+
     #[test]
     fn skipping_remaining_bytes_works() {
         assert_remaining_bytes_works_all(bits![]);
@@ -14,7 +14,8 @@
 // limitations under the License.
 
 use crate::{
-    visitor::{DecodeError, IgnoreVisitor, Visitor},
+    resolver::{FieldInfo, FieldInfoResolver},
+    visitor::{skip::skip_type, DecodeError, Visitor},
     DecodeAsType, FieldIter,
 };
 use scale_type_resolver::{Field, TypeResolver};
@@ -25,6 +26,7 @@ pub struct Composite<'scale, 'resolver, R: TypeResolver> {
     item_bytes: &'scale [u8],
     fields: smallvec::SmallVec<[Field<'resolver, R::TypeId>; 16]>,
     path: smallvec::SmallVec<[&'resolver str; 5]>,
+    parent: Option<FieldParent<R::TypeId>>,
     next_field_idx: usize,
     types: &'resolver R,
     is_compact: bool,
@@ -42,7 +44,32 @@ impl<'scale, 'resolver, R: TypeResolver> Composite<'scale, 'resolver, R> {
     ) -> Composite<'scale, 'resolver, R> {
         let path = smallvec::SmallVec::from_iter(path);
         let fields = smallvec::SmallVec::from_iter(fields);
-        Composite { path, bytes, item_bytes: bytes, fields, types, next_field_idx: 0, is_compact }
+        Composite {
+            path,
+            bytes,
+            item_bytes: bytes,
+            fields,
+            parent: None,
+            types,
+            next_field_idx: 0,
+            is_compact,
+        }
+    }
+    // Record the type (and variant) that these fields belong to, so that more information about
+    // each field can be looked up via a `FieldInfoResolver`.
+    pub(crate) fn with_parent(mut self, type_id: R::TypeId, variant_index: Option<u8>) -> Self {
+        self.parent = Some(FieldParent { type_id, variant_index });
+        self
+    }
+    /// Construct a [`Composite`] from its parts, in order to test how some [`Visitor`] handles it.
+    /// See [`crate::test_utils`].
+    #[cfg(feature = "test-utils")]
+    pub fn from_parts(
+        bytes: &'scale [u8],
+        fields: &mut dyn FieldIter<'resolver, R::TypeId>,
+        types: &'resolver R,
+    ) -> Composite<'scale, 'resolver, R> {
+        Composite::new(core::iter::empty(), bytes, fields, types, false)
     }
     /// Return the name of the composite type, if one was given.
     pub fn name(&self) -> Option<&'resolver str> {
@@ -54,11 +81,11 @@ impl<'scale, 'resolver, R: TypeResolver> Composite<'scale, 'resolver, R> {
     }
     /// Skip over all bytes associated with this composite type. After calling this,
     /// [`Self::bytes_from_undecoded()`] will represent the bytes after this composite type.
+    ///
+    /// Like [`Self::skip_items()`], this doesn't validate the bytes being skipped over beyond
+    /// what's needed to find where they end.
     pub fn skip_decoding(&mut self) -> Result<(), DecodeError> {
-        while let Some(res) = self.decode_item(IgnoreVisitor::<R>::new()) {
-            res?;
-        }
-        Ok(())
+        self.skip_items(self.remaining())
     }
     /// The bytes representing this composite type and anything following it.
     pub fn bytes_from_start(&self) -> &'scale [u8] {
@@ -69,6 +96,11 @@ impl<'scale, 'resolver, R: TypeResolver> Composite<'scale, 'resolver, R> {
     pub fn bytes_from_undecoded(&self) -> &'scale [u8] {
         self.item_bytes
     }
+    /// The type resolver that this composite is being decoded with. This can be used to look up more
+    /// information about types, for instance via [`crate::resolver::ResolverExt`].
+    pub fn types(&self) -> &'resolver R {
+        self.types
+    }
     /// The number of un-decoded items remaining in this composite type.
     pub fn remaining(&self) -> usize {
         self.fields.len() - self.next_field_idx
@@ -77,6 +109,11 @@ impl<'scale, 'resolver, R: TypeResolver> Composite<'scale, 'resolver, R> {
     pub fn fields(&self) -> &[Field<'resolver, R::TypeId>] {
         &self.fields
     }
+    /// If the fields in this composite are compact encoded, which is the case when the composite
+    /// is itself compact encoded and has a single field.
+    pub fn is_compact(&self) -> bool {
+        self.is_compact
+    }
     /// Return whether any of the fields are unnamed.
     pub fn has_unnamed_fields(&self) -> bool {
         self.fields.iter().any(|f| f.name.is_none())
@@ -91,17 +128,124 @@ impl<'scale, 'resolver, R: TypeResolver> Composite<'scale, 'resolver, R> {
             self.is_compact,
         )
     }
+    /// Return a [`super::Tuple`] over the given range of the remaining fields, where index 0 is
+    /// the next field to be decoded. Any fields before the range are skipped over to find where it
+    /// begins, but this composite isn't moved along. As with [`Self::as_tuple()`], the fields
+    /// are compact encoded if this composite is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range isn't within the remaining fields.
+    pub fn sub_tuple(
+        &self,
+        range: core::ops::Range<usize>,
+    ) -> Result<super::Tuple<'scale, 'resolver, R>, DecodeError> {
+        let remaining = &self.fields[self.next_field_idx..];
+        let fields = &remaining[range.clone()];
+        let mut bytes = self.item_bytes;
+        for field in &remaining[..range.start] {
+            skip_type(&mut bytes, field.id.clone(), self.types, self.is_compact)?;
+        }
+        Ok(super::Tuple::new(bytes, &mut fields.iter().cloned(), self.types, self.is_compact))
+    }
     /// Return the name of the next field to be decoded; `None` if either the field has no name,
     /// or there are no fields remaining.
     pub fn peek_name(&self) -> Option<&'resolver str> {
         self.fields.get(self.next_field_idx).and_then(|f| f.name)
     }
+    /// Skip over the next field without decoding it. This avoids looking at the bytes of the field
+    /// where its size is known from its type alone, and only hands values to a visitor (an
+    /// [`crate::visitor::IgnoreVisitor`]) if the type resolver can't describe them.
+    pub fn skip_item(&mut self) -> Option<Result<(), DecodeError>> {
+        self.skip_item_maybe_compact(self.is_compact)
+    }
+    fn skip_item_maybe_compact(&mut self, is_compact: bool) -> Option<Result<(), DecodeError>> {
+        let field = self.fields.get(self.next_field_idx)?;
+        let b = &mut &*self.item_bytes;
+        let res = skip_type(b, field.id.clone(), self.types, is_compact);
+
+        if res.is_ok() {
+            // Move our cursors forwards only if skipping was OK:
+            self.item_bytes = *b;
+            self.next_field_idx += 1;
+        } else {
+            // Otherwise, skip to end to prevent any future iterations:
+            self.next_field_idx = self.fields.len()
+        }
+
+        Some(res)
+    }
+    /// Skip over the next `n` fields (or however many remain, if fewer) without decoding them.
+    /// See [`Self::skip_item()`].
+    pub fn skip_items(&mut self, n: usize) -> Result<(), DecodeError> {
+        for _ in 0..n {
+            match self.skip_item() {
+                Some(res) => res?,
+                None => break,
+            }
+        }
+        Ok(())
+    }
+    /// Decode the next field by providing a visitor to handle it, without moving past it. A
+    /// subsequent call to [`Self::decode_item()`] will decode the same field again.
+    pub fn peek_item<V: Visitor<TypeResolver = R>>(
+        &self,
+        visitor: V,
+    ) -> Option<Result<V::Value<'scale, 'resolver>, V::Error>> {
+        let field = self.fields.get(self.next_field_idx)?;
+        Some(crate::visitor::decode_with_visitor_maybe_compact(
+            &mut &*self.item_bytes,
+            field.id.clone(),
+            self.types,
+            visitor,
+            self.is_compact,
+        ))
+    }
     /// Decode the next field in the composite type by providing a visitor to handle it. This is more
     /// efficient than iterating over the key/value pairs if you already know how you want to decode the
     /// values.
     pub fn decode_item<V: Visitor<TypeResolver = R>>(
         &mut self,
         visitor: V,
+    ) -> Option<Result<V::Value<'scale, 'resolver>, V::Error>> {
+        self.decode_item_maybe_compact(visitor, self.is_compact)
+    }
+    /// Return the next field, like iterating would, but treat it as compact encoded if `is_compact`
+    /// is true, even if the type information doesn't say so. This is useful when the type information
+    /// has been synthesized and lacks compact markers.
+    pub fn next_maybe_compact(
+        &mut self,
+        is_compact: bool,
+    ) -> Option<Result<CompositeField<'scale, 'resolver, R>, DecodeError>> {
+        let is_compact = self.is_compact || is_compact;
+        // Record details we need before we skip over the thing:
+        let index = self.next_field_idx;
+        let field = self.fields.get(index)?.clone();
+        let num_bytes_before = self.item_bytes.len();
+        let item_bytes = self.item_bytes;
+
+        // Now, skip over the item we're going to hand back. We only need to find where it ends
+        // here; it'll be properly decoded (and validated) if it's handed to a visitor later:
+        if let Err(e) = self.skip_item_maybe_compact(is_compact)? {
+            return Some(Err(e));
+        };
+
+        // How many bytes did we skip over? What bytes represent the thing we skipped?
+        let num_bytes_after = self.item_bytes.len();
+        let res_bytes = &item_bytes[..num_bytes_before - num_bytes_after];
+        Some(Ok(CompositeField {
+            bytes: res_bytes,
+            field,
+            parent: self.parent.clone(),
+            index,
+            types: self.types,
+            is_compact,
+        }))
+    }
+    fn decode_item_maybe_compact<V: Visitor<TypeResolver = R>>(
+        &mut self,
+        visitor: V,
+        is_compact: bool,
     ) -> Option<Result<V::Value<'scale, 'resolver>, V::Error>> {
         let field = self.fields.get(self.next_field_idx)?;
         let b = &mut &*self.item_bytes;
@@ -112,7 +256,7 @@ impl<'scale, 'resolver, R: TypeResolver> Composite<'scale, 'resolver, R> {
             field.id.clone(),
             self.types,
             visitor,
-            self.is_compact,
+            is_compact,
         );
 
         if res.is_ok() {
@@ -132,33 +276,24 @@ impl<'scale, 'resolver, R: TypeResolver> Composite<'scale, 'resolver, R> {
 impl<'scale, 'resolver, R: TypeResolver> Iterator for Composite<'scale, 'resolver, R> {
     type Item = Result<CompositeField<'scale, 'resolver, R>, DecodeError>;
     fn next(&mut self) -> Option<Self::Item> {
-        // Record details we need before we decode and skip over the thing:
-        let field = self.fields.get(self.next_field_idx)?.clone();
-        let num_bytes_before = self.item_bytes.len();
-        let item_bytes = self.item_bytes;
-
-        // Now, decode and skip over the item we're going to hand back:
-        if let Err(e) = self.decode_item(IgnoreVisitor::<R>::new())? {
-            return Some(Err(e));
-        };
-
-        // How many bytes did we skip over? What bytes represent the thing we decoded?
-        let num_bytes_after = self.item_bytes.len();
-        let res_bytes = &item_bytes[..num_bytes_before - num_bytes_after];
-        Some(Ok(CompositeField {
-            bytes: res_bytes,
-            field,
-            types: self.types,
-            is_compact: self.is_compact,
-        }))
+        self.next_maybe_compact(false)
     }
 }
 
+// The type, and possibly the variant of it, that some composite fields belong to.
+#[derive(Debug, Clone)]
+struct FieldParent<TypeId> {
+    type_id: TypeId,
+    variant_index: Option<u8>,
+}
+
 /// A single field in the composite type.
 #[derive(Debug)]
 pub struct CompositeField<'scale, 'resolver, R: TypeResolver> {
     bytes: &'scale [u8],
     field: Field<'resolver, R::TypeId>,
+    parent: Option<FieldParent<R::TypeId>>,
+    index: usize,
     types: &'resolver R,
     is_compact: bool,
 }
@@ -170,6 +305,8 @@ impl<'scale, 'resolver, R: TypeResolver> Clone for CompositeField<'scale, 'resol
             types: self.types,
             is_compact: self.is_compact,
             field: self.field.clone(),
+            parent: self.parent.clone(),
+            index: self.index,
         }
     }
 }
@@ -195,26 +332,56 @@ impl<'scale, 'resolver, R: TypeResolver> CompositeField<'scale, 'resolver, R> {
     pub fn decode_with_visitor<V: Visitor<TypeResolver = R>>(
         &self,
         visitor: V,
+    ) -> Result<V::Value<'scale, 'resolver>, V::Error> {
+        self.decode_with_visitor_maybe_compact(visitor, self.is_compact)
+    }
+    /// Decode this field using a visitor, treating it as compact encoded only if `is_compact` is
+    /// true, regardless of [`Self::is_compact()`]. The bytes of the field are those found when
+    /// moving past it, so this is only useful when the field is known to be encoded differently
+    /// from how its type describes it.
+    pub fn decode_with_visitor_maybe_compact<V: Visitor<TypeResolver = R>>(
+        &self,
+        visitor: V,
+        is_compact: bool,
     ) -> Result<V::Value<'scale, 'resolver>, V::Error> {
         crate::visitor::decode_with_visitor_maybe_compact(
             &mut &*self.bytes,
             self.field.id.clone(),
             self.types,
             visitor,
-            self.is_compact,
+            is_compact,
         )
     }
     /// Decode this field into a specific type via [`DecodeAsType`].
     pub fn decode_as_type<T: DecodeAsType>(&self) -> Result<T, crate::Error> {
+        self.decode_as_type_maybe_compact(self.is_compact)
+    }
+    /// Decode this field into a specific type via [`DecodeAsType`], treating it as compact encoded
+    /// only if `is_compact` is true. See [`Self::decode_with_visitor_maybe_compact()`].
+    pub fn decode_as_type_maybe_compact<T: DecodeAsType>(
+        &self,
+        is_compact: bool,
+    ) -> Result<T, crate::Error> {
         T::decode_as_type_maybe_compact(
             &mut &*self.bytes,
             self.field.id.clone(),
             self.types,
-            self.is_compact,
+            is_compact,
         )
     }
 }
 
+impl<'scale, 'resolver, R: FieldInfoResolver> CompositeField<'scale, 'resolver, R> {
+    /// Extra information about this field, such as its docs and the name of its type, if the
+    /// [`FieldInfoResolver`] is able to provide it. This is always `None` for fields which aren't
+    /// part of a composite or variant type, for instance those handed to
+    /// [`crate::visitor::decode_fields_with_visitor()`].
+    pub fn info(&self) -> Option<FieldInfo<'resolver>> {
+        let parent = self.parent.clone()?;
+        self.types.field_info(parent.type_id, parent.variant_index, self.index)
+    }
+}
+
 impl<'scale, 'resolver, R: TypeResolver> crate::visitor::DecodeItemIterator<'scale, 'resolver, R>
     for Composite<'scale, 'resolver, R>
 {
@@ -18,15 +18,19 @@
 mod array;
 mod bit_sequence;
 mod composite;
+mod opaque;
 mod sequence;
 mod str;
 mod tuple;
+mod unknown_primitive;
 mod variant;
 
 pub use self::str::Str;
-pub use array::Array;
+pub use array::{Array, ArrayItem};
 pub use bit_sequence::BitSequence;
-pub use composite::Composite;
-pub use sequence::Sequence;
-pub use tuple::Tuple;
+pub use composite::{Composite, CompositeField};
+pub use opaque::Opaque;
+pub use sequence::{Sequence, SequenceItem};
+pub use tuple::{Tuple, TupleField};
+pub use unknown_primitive::UnknownPrimitive;
 pub use variant::Variant;
@@ -0,0 +1,79 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::visitor::DecodeError;
+use codec::Decode;
+use scale_type_resolver::{TypeResolver, UnhandledKind};
+
+/// This represents a value of some kind that the [`TypeResolver`] couldn't describe, which it
+/// reports by calling [`scale_type_resolver::ResolvedTypeVisitor::visit_unhandled()`] with any
+/// [`UnhandledKind`] other than `Primitive` or `NotFound`.
+///
+/// Since we can't tell where such a value ends, it's up to the visitor to decide how many bytes
+/// it occupies; it should take them via [`Self::take()`], [`Self::take_rest()`] or
+/// [`Self::decode()`], and only these bytes are moved past once the visitor returns.
+pub struct Opaque<'scale, 'resolver, R: TypeResolver> {
+    bytes: &'scale [u8],
+    offset: usize,
+    kind: UnhandledKind,
+    types: &'resolver R,
+}
+
+impl<'scale, 'resolver, R: TypeResolver> Opaque<'scale, 'resolver, R> {
+    pub(crate) fn new(bytes: &'scale [u8], kind: UnhandledKind, types: &'resolver R) -> Self {
+        Opaque { bytes, offset: 0, kind, types }
+    }
+    /// The kind of value that the type resolver couldn't describe.
+    pub fn kind(&self) -> UnhandledKind {
+        self.kind
+    }
+    /// The bytes left in the input, starting from this value.
+    pub fn bytes_from_start(&self) -> &'scale [u8] {
+        self.bytes
+    }
+    /// The bytes that have not yet been taken, which includes anything following this value.
+    pub fn bytes_from_undecoded(&self) -> &'scale [u8] {
+        &self.bytes[self.offset..]
+    }
+    /// The type resolver that this value is being decoded with.
+    pub fn types(&self) -> &'resolver R {
+        self.types
+    }
+    /// Take the next `n` bytes as belonging to this value, failing if there aren't enough.
+    pub fn take(&mut self, n: usize) -> Result<&'scale [u8], DecodeError> {
+        let bytes = self.bytes_from_undecoded();
+        if bytes.len() < n {
+            return Err(DecodeError::NotEnoughInput(n - bytes.len()));
+        }
+        self.offset += n;
+        Ok(&bytes[..n])
+    }
+    /// Take every remaining byte as belonging to this value. This is useful when the value is
+    /// known to be the last thing in the input.
+    pub fn take_rest(&mut self) -> &'scale [u8] {
+        let bytes = self.bytes_from_undecoded();
+        self.offset = self.bytes.len();
+        bytes
+    }
+    /// Decode the next bytes into some type via its [`codec::Decode`] impl, taking any bytes that
+    /// were decoded as belonging to this value.
+    pub fn decode<T: Decode>(&mut self) -> Result<T, DecodeError> {
+        let bytes = &mut self.bytes_from_undecoded();
+        let len_before = bytes.len();
+        let value = T::decode(bytes)?;
+        self.offset += len_before - bytes.len();
+        Ok(value)
+    }
+}
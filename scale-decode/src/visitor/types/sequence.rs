@@ -21,6 +21,9 @@ use crate::{
 use codec::{Compact, Decode};
 use scale_type_resolver::TypeResolver;
 
+#[cfg(feature = "rayon")]
+use alloc::vec::Vec;
+
 /// This enables a visitor to decode items from a sequence type.
 pub struct Sequence<'scale, 'resolver, R: TypeResolver> {
     bytes: &'scale [u8],
@@ -57,10 +60,40 @@ impl<'scale, 'resolver, R: TypeResolver> Sequence<'scale, 'resolver, R> {
     pub fn bytes_from_undecoded(&self) -> &'scale [u8] {
         self.values.bytes_from_undecoded()
     }
+    /// The type resolver that this sequence is being decoded with. This can be used to look up more
+    /// information about types, for instance via [`crate::resolver::ResolverExt`].
+    pub fn types(&self) -> &'resolver R {
+        self.values.types()
+    }
+    /// The type ID of each item in the sequence.
+    pub fn inner_type_id(&self) -> &R::TypeId {
+        self.values.inner_type_id()
+    }
+    /// If the items in the sequence are compact encoded. See [`Array::is_compact()`].
+    pub fn is_compact(&self) -> bool {
+        self.values.is_compact()
+    }
     /// The number of un-decoded items remaining in this sequence.
     pub fn remaining(&self) -> usize {
         self.values.remaining()
     }
+    /// Skip over the next item without decoding it. See [`Array::skip_item()`].
+    pub fn skip_item(&mut self) -> Option<Result<(), DecodeError>> {
+        self.values.skip_item()
+    }
+    /// Skip over the next `n` items (or however many remain, if fewer) without decoding them.
+    /// See [`Array::skip_items()`].
+    pub fn skip_items(&mut self, n: usize) -> Result<(), DecodeError> {
+        self.values.skip_items(n)
+    }
+    /// Decode the next item from the sequence by providing a visitor to handle it, without moving
+    /// past it. A subsequent call to [`Self::decode_item()`] will decode the same item again.
+    pub fn peek_item<V: Visitor<TypeResolver = R>>(
+        &self,
+        visitor: V,
+    ) -> Option<Result<V::Value<'scale, 'resolver>, V::Error>> {
+        self.values.peek_item(visitor)
+    }
     /// Decode an item from the sequence by providing a visitor to handle it.
     pub fn decode_item<V: Visitor<TypeResolver = R>>(
         &mut self,
@@ -70,6 +103,52 @@ impl<'scale, 'resolver, R: TypeResolver> Sequence<'scale, 'resolver, R> {
     }
 }
 
+#[cfg(feature = "rayon")]
+impl<'scale, 'resolver, R> Sequence<'scale, 'resolver, R>
+where
+    R: TypeResolver + Sync,
+    R::TypeId: Send + Sync,
+{
+    /// Decode all of the remaining items in this sequence into `T`, spreading the work across
+    /// the [`rayon`] thread pool. This can be much faster than decoding each item in turn for
+    /// large sequences.
+    ///
+    /// To do this, we first skip over each of the items to find the bytes belonging to it, which
+    /// is generally much cheaper than decoding them, and then decode these bytes in parallel.
+    /// Either way, all of the remaining items will have been consumed once this returns.
+    pub fn par_decode_items<T: DecodeAsType + Send>(&mut self) -> Result<Vec<T>, crate::Error> {
+        use rayon::prelude::*;
+
+        // The length prefix was decoded fine when this sequence was created.
+        let len = <Compact<u64>>::decode(&mut &*self.bytes).expect("valid length; qed").0;
+        let start_idx = len as usize - self.remaining();
+
+        // Find the bytes for each item:
+        let mut item_bytes = Vec::with_capacity(self.remaining());
+        while self.remaining() > 0 {
+            let before = self.bytes_from_undecoded();
+            let res = self.skip_item().expect("items remain; qed");
+            res.map_err(|e| crate::Error::from(e).at_idx(start_idx + item_bytes.len()))?;
+            let after = self.bytes_from_undecoded();
+            item_bytes.push(&before[..before.len() - after.len()]);
+        }
+
+        // Decode the items in parallel. The results are collected before looking for errors so
+        // that the error from the first item to fail is the one returned.
+        let types = self.types();
+        let type_id = self.values.inner_type_id();
+        let results: Vec<Result<T, crate::Error>> = item_bytes
+            .par_iter()
+            .enumerate()
+            .map(|(idx, bytes)| {
+                T::decode_as_type(&mut &**bytes, type_id.clone(), types)
+                    .map_err(|e| e.at_idx(start_idx + idx))
+            })
+            .collect();
+        results.into_iter().collect()
+    }
+}
+
 // Iterating returns a representation of each field in the tuple type.
 impl<'scale, 'resolver, R: TypeResolver> Iterator for Sequence<'scale, 'resolver, R> {
     type Item = Result<SequenceItem<'scale, 'resolver, R>, DecodeError>;
@@ -105,6 +184,10 @@ impl<'scale, 'resolver, R: TypeResolver> SequenceItem<'scale, 'resolver, R> {
     pub fn type_id(&self) -> &R::TypeId {
         self.item.type_id()
     }
+    /// If the item is compact encoded. See [`ArrayItem::is_compact()`].
+    pub fn is_compact(&self) -> bool {
+        self.item.is_compact()
+    }
     /// Decode this item using a visitor.
     pub fn decode_with_visitor<V: Visitor<TypeResolver = R>>(
         &self,
@@ -14,7 +14,9 @@
 // limitations under the License.
 
 use crate::visitor::DecodeError;
+use alloc::{borrow::Cow, string::String};
 use codec::{Compact, CompactLen, Decode};
+use core::{cell::OnceCell, str::Utf8Error};
 
 /// This represents a string, but defers proper decoding of it until it's asked for,
 /// and avoids allocating.
@@ -22,6 +24,8 @@ pub struct Str<'scale> {
     len: usize,
     compact_len: usize,
     bytes: &'scale [u8],
+    // The result of UTF-8 validating the string, once we've done so.
+    validated: OnceCell<Result<&'scale str, Utf8Error>>,
 }
 
 impl<'scale> Str<'scale> {
@@ -35,29 +39,88 @@ impl<'scale> Str<'scale> {
         let len = compact_len.0 as usize;
         let compact_len = Compact::<u32>::compact_len(&compact_len.0);
 
-        Ok(Str { len, bytes, compact_len })
+        // Check up front that the string bytes are all there, so that we can hand them out freely.
+        if remaining_bytes.len() < len {
+            return Err(DecodeError::NotEnoughInput(len - remaining_bytes.len()));
+        }
+
+        Ok(Str { len, bytes, compact_len, validated: OnceCell::new() })
     }
-    /// The length of the string.
+    /// The length of the string in bytes.
     pub fn len(&self) -> usize {
         self.len
     }
+    /// The number of characters in the string, failing if the bytes could not be
+    /// properly utf8-decoded.
+    pub fn len_utf8(&self) -> Result<usize, DecodeError> {
+        Ok(self.as_str()?.chars().count())
+    }
     /// The bytes left in the input, starting from this string.
     pub fn bytes_from_start(&self) -> &'scale [u8] {
         self.bytes
     }
     /// The bytes remaining in the input after this string.
     pub fn bytes_after(&self) -> Result<&'scale [u8], DecodeError> {
-        self.bytes.get(self.compact_len + self.len..).ok_or(DecodeError::NotEnoughInput)
+        Ok(&self.bytes[self.compact_len + self.len..])
     }
     /// Is the string zero bytes long?
     pub fn is_empty(&self) -> bool {
         self.len == 0
     }
-    /// return a string, failing if the bytes could not be properly utf8-decoded.
+    /// The raw bytes of the string, which may or may not be valid utf8.
+    pub fn as_bytes(&self) -> &'scale [u8] {
+        &self.bytes[self.compact_len..self.compact_len + self.len]
+    }
+    /// return a string, failing if the bytes could not be properly utf8-decoded. The bytes
+    /// are only validated the first time this is called.
     pub fn as_str(&self) -> Result<&'scale str, DecodeError> {
-        let start = self.compact_len;
-        let end = start + self.len;
-        alloc::str::from_utf8(self.bytes.get(start..end).ok_or(DecodeError::NotEnoughInput)?)
-            .map_err(DecodeError::InvalidStr)
+        let bytes = self.as_bytes();
+        self.validated.get_or_init(|| core::str::from_utf8(bytes)).map_err(DecodeError::InvalidStr)
+    }
+    /// Return a string, replacing any invalid utf8 sequences with `U+FFFD REPLACEMENT CHARACTER`.
+    /// This only allocates if the bytes are not valid utf8.
+    pub fn to_string_lossy(&self) -> Cow<'scale, str> {
+        match self.as_str() {
+            Ok(s) => Cow::Borrowed(s),
+            Err(_) => Cow::Owned(String::from_utf8_lossy(self.as_bytes()).into_owned()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use codec::Encode;
+
+    #[test]
+    fn str_accessors_work() {
+        let encoded = ("héllo", 1u8).encode();
+        let s = Str::new(&encoded).unwrap();
+
+        assert_eq!(s.len(), 6);
+        assert_eq!(s.len_utf8().unwrap(), 5);
+        assert_eq!(s.as_bytes(), "héllo".as_bytes());
+        assert_eq!(s.as_str().unwrap(), "héllo");
+        assert_eq!(s.as_str().unwrap(), "héllo");
+        assert!(matches!(s.to_string_lossy(), Cow::Borrowed("héllo")));
+        assert_eq!(s.bytes_after().unwrap(), &[1]);
+    }
+
+    #[test]
+    fn invalid_utf8_can_be_decoded_lossily() {
+        let encoded = [0xffu8, b'a', 0xfe].to_vec().encode();
+        let s = Str::new(&encoded).unwrap();
+
+        assert!(matches!(s.as_str(), Err(DecodeError::InvalidStr(_))));
+        assert!(s.len_utf8().is_err());
+        assert_eq!(s.as_bytes(), &[0xff, b'a', 0xfe]);
+        assert_eq!(s.to_string_lossy(), "\u{FFFD}a\u{FFFD}");
+    }
+
+    #[test]
+    fn truncated_str_is_an_error() {
+        let encoded = "hello".encode();
+        let err = Str::new(&encoded[..4]).err().unwrap();
+        assert_eq!(err, DecodeError::NotEnoughInput(2));
     }
 }
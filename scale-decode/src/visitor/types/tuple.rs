@@ -14,7 +14,7 @@
 // limitations under the License.
 
 use crate::{
-    visitor::{DecodeError, IgnoreVisitor, Visitor},
+    visitor::{skip::skip_type, DecodeError, Visitor},
     DecodeAsType, FieldIter,
 };
 use scale_type_resolver::{Field, TypeResolver};
@@ -39,13 +39,23 @@ impl<'scale, 'resolver, R: TypeResolver> Tuple<'scale, 'resolver, R> {
         let fields = smallvec::SmallVec::from_iter(fields);
         Tuple { bytes, item_bytes: bytes, fields, types, next_field_idx: 0, is_compact }
     }
+    /// Construct a [`Tuple`] from its parts, in order to test how some [`Visitor`] handles it.
+    /// See [`crate::test_utils`].
+    #[cfg(feature = "test-utils")]
+    pub fn from_parts(
+        bytes: &'scale [u8],
+        fields: &mut dyn FieldIter<'resolver, R::TypeId>,
+        types: &'resolver R,
+    ) -> Tuple<'scale, 'resolver, R> {
+        Tuple::new(bytes, fields, types, false)
+    }
     /// Skip over all bytes associated with this tuple. After calling this,
     /// [`Self::bytes_from_undecoded()`] will represent the bytes after this tuple.
+    ///
+    /// Like [`Self::skip_items()`], this doesn't validate the bytes being skipped over beyond
+    /// what's needed to find where they end.
     pub fn skip_decoding(&mut self) -> Result<(), DecodeError> {
-        while let Some(res) = self.decode_item(IgnoreVisitor::<R>::new()) {
-            res?;
-        }
-        Ok(())
+        self.skip_items(self.remaining())
     }
     /// The bytes representing this tuple and anything following it.
     pub fn bytes_from_start(&self) -> &'scale [u8] {
@@ -56,14 +66,104 @@ impl<'scale, 'resolver, R: TypeResolver> Tuple<'scale, 'resolver, R> {
     pub fn bytes_from_undecoded(&self) -> &'scale [u8] {
         self.item_bytes
     }
+    /// The type resolver that this tuple is being decoded with. This can be used to look up more
+    /// information about types, for instance via [`crate::resolver::ResolverExt`].
+    pub fn types(&self) -> &'resolver R {
+        self.types
+    }
+    /// If the items in this tuple are compact encoded, which is the case when the tuple is itself
+    /// compact encoded and has a single item.
+    pub fn is_compact(&self) -> bool {
+        self.is_compact
+    }
     /// The number of un-decoded items remaining in the tuple.
     pub fn remaining(&self) -> usize {
-        self.fields.len()
+        self.fields.len() - self.next_field_idx
+    }
+    /// Skip over the next item without decoding it. This avoids looking at the bytes of the item
+    /// where its size is known from its type alone, and only hands values to a visitor (an
+    /// [`crate::visitor::IgnoreVisitor`]) if the type resolver can't describe them.
+    pub fn skip_item(&mut self) -> Option<Result<(), DecodeError>> {
+        self.skip_item_maybe_compact(self.is_compact)
+    }
+    fn skip_item_maybe_compact(&mut self, is_compact: bool) -> Option<Result<(), DecodeError>> {
+        let field = self.fields.get(self.next_field_idx)?;
+        let b = &mut &*self.item_bytes;
+        let res = skip_type(b, field.id.clone(), self.types, is_compact);
+
+        if res.is_ok() {
+            // Move our cursors forwards only if skipping was OK:
+            self.item_bytes = *b;
+            self.next_field_idx += 1;
+        } else {
+            // Otherwise, skip to end to prevent any future iterations:
+            self.next_field_idx = self.fields.len()
+        }
+
+        Some(res)
+    }
+    /// Skip over the next `n` items (or however many remain, if fewer) without decoding them.
+    /// See [`Self::skip_item()`].
+    pub fn skip_items(&mut self, n: usize) -> Result<(), DecodeError> {
+        for _ in 0..n {
+            match self.skip_item() {
+                Some(res) => res?,
+                None => break,
+            }
+        }
+        Ok(())
+    }
+    /// Decode the next item by providing a visitor to handle it, without moving past it. A
+    /// subsequent call to [`Self::decode_item()`] will decode the same item again.
+    pub fn peek_item<V: Visitor<TypeResolver = R>>(
+        &self,
+        visitor: V,
+    ) -> Option<Result<V::Value<'scale, 'resolver>, V::Error>> {
+        let field = self.fields.get(self.next_field_idx)?;
+        Some(crate::visitor::decode_with_visitor_maybe_compact(
+            &mut &*self.item_bytes,
+            field.id.clone(),
+            self.types,
+            visitor,
+            self.is_compact,
+        ))
     }
     /// Decode the next item from the tuple by providing a visitor to handle it.
     pub fn decode_item<V: Visitor<TypeResolver = R>>(
         &mut self,
         visitor: V,
+    ) -> Option<Result<V::Value<'scale, 'resolver>, V::Error>> {
+        self.decode_item_maybe_compact(visitor, self.is_compact)
+    }
+    /// Return the next item, like iterating would, but treat it as compact encoded if `is_compact`
+    /// is true, even if the type information doesn't say so. This is useful when the type information
+    /// has been synthesized and lacks compact markers.
+    pub fn next_maybe_compact(
+        &mut self,
+        is_compact: bool,
+    ) -> Option<Result<TupleField<'scale, 'resolver, R>, DecodeError>> {
+        let is_compact = self.is_compact || is_compact;
+        // Record details we need before we skip over the thing:
+        let field = self.fields.get(self.next_field_idx)?.clone();
+        let num_bytes_before = self.item_bytes.len();
+        let item_bytes = self.item_bytes;
+
+        // Now, skip over the item we're going to hand back. We only need to find where it ends
+        // here; it'll be properly decoded (and validated) if it's handed to a visitor later:
+        if let Err(e) = self.skip_item_maybe_compact(is_compact)? {
+            return Some(Err(e));
+        };
+
+        // How many bytes did we skip over? What bytes represent the thing we skipped?
+        let num_bytes_after = self.item_bytes.len();
+        let res_bytes = &item_bytes[..num_bytes_before - num_bytes_after];
+
+        Some(Ok(TupleField { bytes: res_bytes, type_id: field.id, types: self.types, is_compact }))
+    }
+    fn decode_item_maybe_compact<V: Visitor<TypeResolver = R>>(
+        &mut self,
+        visitor: V,
+        is_compact: bool,
     ) -> Option<Result<V::Value<'scale, 'resolver>, V::Error>> {
         let field = self.fields.get(self.next_field_idx)?;
         let b = &mut &*self.item_bytes;
@@ -73,7 +173,7 @@ impl<'scale, 'resolver, R: TypeResolver> Tuple<'scale, 'resolver, R> {
             field.id.clone(),
             self.types,
             visitor,
-            self.is_compact,
+            is_compact,
         );
 
         if res.is_ok() {
@@ -93,26 +193,7 @@ impl<'scale, 'resolver, R: TypeResolver> Tuple<'scale, 'resolver, R> {
 impl<'scale, 'resolver, R: TypeResolver> Iterator for Tuple<'scale, 'resolver, R> {
     type Item = Result<TupleField<'scale, 'resolver, R>, DecodeError>;
     fn next(&mut self) -> Option<Self::Item> {
-        // Record details we need before we decode and skip over the thing:
-        let field = self.fields.get(self.next_field_idx)?.clone();
-        let num_bytes_before = self.item_bytes.len();
-        let item_bytes = self.item_bytes;
-
-        // Now, decode and skip over the item we're going to hand back:
-        if let Err(e) = self.decode_item(IgnoreVisitor::<R>::new())? {
-            return Some(Err(e));
-        };
-
-        // How many bytes did we skip over? What bytes represent the thing we decoded?
-        let num_bytes_after = self.item_bytes.len();
-        let res_bytes = &item_bytes[..num_bytes_before - num_bytes_after];
-
-        Some(Ok(TupleField {
-            bytes: res_bytes,
-            type_id: field.id,
-            types: self.types,
-            is_compact: self.is_compact,
-        }))
+        self.next_maybe_compact(false)
     }
 }
 
@@ -143,20 +224,40 @@ impl<'scale, 'resolver, R: TypeResolver> TupleField<'scale, 'resolver, R> {
         &self,
         visitor: V,
     ) -> Result<V::Value<'scale, 'resolver>, V::Error> {
-        crate::visitor::decode_with_visitor(
+        self.decode_with_visitor_maybe_compact(visitor, self.is_compact)
+    }
+    /// Decode this field using a visitor, treating it as compact encoded only if `is_compact` is
+    /// true, regardless of [`Self::is_compact()`]. The bytes of the field are those found when
+    /// moving past it, so this is only useful when the field is known to be encoded differently
+    /// from how its type describes it.
+    pub fn decode_with_visitor_maybe_compact<V: Visitor<TypeResolver = R>>(
+        &self,
+        visitor: V,
+        is_compact: bool,
+    ) -> Result<V::Value<'scale, 'resolver>, V::Error> {
+        crate::visitor::decode_with_visitor_maybe_compact(
             &mut &*self.bytes,
             self.type_id.clone(),
             self.types,
             visitor,
+            is_compact,
         )
     }
     /// Decode this field into a specific type via [`DecodeAsType`].
     pub fn decode_as_type<T: DecodeAsType>(&self) -> Result<T, crate::Error> {
+        self.decode_as_type_maybe_compact(self.is_compact)
+    }
+    /// Decode this field into a specific type via [`DecodeAsType`], treating it as compact encoded
+    /// only if `is_compact` is true. See [`Self::decode_with_visitor_maybe_compact()`].
+    pub fn decode_as_type_maybe_compact<T: DecodeAsType>(
+        &self,
+        is_compact: bool,
+    ) -> Result<T, crate::Error> {
         T::decode_as_type_maybe_compact(
             &mut &*self.bytes,
             self.type_id.clone(),
             self.types,
-            self.is_compact,
+            is_compact,
         )
     }
 }
@@ -0,0 +1,163 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::visitor::DecodeError;
+use codec::Decode;
+use scale_type_resolver::TypeResolver;
+
+/// This represents a primitive type which the [`TypeResolver`] knows about, but which isn't one of
+/// the standard [`crate::resolver::Primitive`]s. A resolver reports one of these by calling
+/// [`scale_type_resolver::ResolvedTypeVisitor::visit_unhandled()`] with
+/// [`scale_type_resolver::UnhandledKind::Primitive`].
+///
+/// How many bytes such a primitive occupies is up to the visitor to decide; it should take them
+/// via [`Self::take()`] or [`Self::decode()`], and only these bytes are moved past once the
+/// visitor returns. Unknown primitives can't be skipped over, since their size isn't known.
+pub struct UnknownPrimitive<'scale, 'resolver, R: TypeResolver> {
+    bytes: &'scale [u8],
+    offset: usize,
+    types: &'resolver R,
+}
+
+impl<'scale, 'resolver, R: TypeResolver> UnknownPrimitive<'scale, 'resolver, R> {
+    pub(crate) fn new(bytes: &'scale [u8], types: &'resolver R) -> Self {
+        UnknownPrimitive { bytes, offset: 0, types }
+    }
+    /// The bytes left in the input, starting from this primitive.
+    pub fn bytes_from_start(&self) -> &'scale [u8] {
+        self.bytes
+    }
+    /// The bytes that have not yet been taken, which includes anything following this primitive.
+    pub fn bytes_from_undecoded(&self) -> &'scale [u8] {
+        &self.bytes[self.offset..]
+    }
+    /// The type resolver that this primitive is being decoded with. This can be used to look up
+    /// what kind of primitive the type ID refers to.
+    pub fn types(&self) -> &'resolver R {
+        self.types
+    }
+    /// Take the next `n` bytes as belonging to this primitive, failing if there aren't enough.
+    pub fn take(&mut self, n: usize) -> Result<&'scale [u8], DecodeError> {
+        let bytes = self.bytes_from_undecoded();
+        if bytes.len() < n {
+            return Err(DecodeError::NotEnoughInput(n - bytes.len()));
+        }
+        self.offset += n;
+        Ok(&bytes[..n])
+    }
+    /// Decode the next bytes into some type via its [`codec::Decode`] impl, taking any bytes that
+    /// were decoded as belonging to this primitive.
+    pub fn decode<T: Decode>(&mut self) -> Result<T, DecodeError> {
+        let bytes = &mut self.bytes_from_undecoded();
+        let len_before = bytes.len();
+        let value = T::decode(bytes)?;
+        self.offset += len_before - bytes.len();
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::visitor::{decode_with_visitor, TypeIdFor, Visitor};
+    use crate::Field;
+    use scale_type_resolver::{Primitive, ResolvedTypeVisitor, UnhandledKind};
+
+    // Type 0 is an f64, type 1 is a u8, and type 2 is a composite of both.
+    struct FloatResolver;
+    impl TypeResolver for FloatResolver {
+        type TypeId = u32;
+        type Error = core::convert::Infallible;
+
+        fn resolve_type<'this, V: ResolvedTypeVisitor<'this, TypeId = u32>>(
+            &'this self,
+            type_id: u32,
+            visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            Ok(match type_id {
+                0 => visitor.visit_unhandled(UnhandledKind::Primitive),
+                1 => visitor.visit_primitive(Primitive::U8),
+                2 => visitor.visit_composite(
+                    core::iter::empty(),
+                    [Field::named(0, "a"), Field::named(1, "b")].into_iter(),
+                ),
+                _ => visitor.visit_not_found(),
+            })
+        }
+    }
+
+    struct FloatVisitor;
+    impl Visitor for FloatVisitor {
+        type Value<'scale, 'resolver> = f64;
+        type Error = DecodeError;
+        type TypeResolver = FloatResolver;
+
+        fn visit_u8<'scale, 'resolver>(
+            self,
+            value: u8,
+            _type_id: TypeIdFor<Self>,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            Ok(value.into())
+        }
+        fn visit_composite<'scale, 'resolver>(
+            self,
+            value: &mut crate::visitor::types::Composite<'scale, 'resolver, FloatResolver>,
+            _type_id: TypeIdFor<Self>,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            let mut total = 0.0;
+            while let Some(n) = value.decode_item(FloatVisitor) {
+                total += n?;
+            }
+            Ok(total)
+        }
+        fn visit_unknown_primitive<'scale, 'resolver>(
+            self,
+            value: &mut UnknownPrimitive<'scale, 'resolver, FloatResolver>,
+            _type_id: TypeIdFor<Self>,
+        ) -> Result<Self::Value<'scale, 'resolver>, Self::Error> {
+            let bytes = value.take(8)?;
+            Ok(f64::from_le_bytes(bytes.try_into().expect("8 bytes taken")))
+        }
+    }
+
+    #[test]
+    fn unknown_primitives_can_be_decoded_by_visitors() {
+        let mut encoded = 1.5f64.to_le_bytes().to_vec();
+        encoded.extend([2, 3]);
+
+        let cursor = &mut &*encoded;
+        assert_eq!(decode_with_visitor(cursor, 0, &FloatResolver, FloatVisitor), Ok(1.5));
+        assert_eq!(*cursor, &[2, 3]);
+
+        let cursor = &mut &*encoded;
+        assert_eq!(decode_with_visitor(cursor, 2, &FloatResolver, FloatVisitor), Ok(3.5));
+        assert_eq!(*cursor, &[3]);
+
+        let err = decode_with_visitor(&mut &encoded[..4], 0, &FloatResolver, FloatVisitor);
+        assert_eq!(err, Err(DecodeError::NotEnoughInput(4)));
+    }
+
+    #[test]
+    fn unknown_primitives_are_unhandled_by_default() {
+        let encoded = 1.5f64.to_le_bytes();
+        let err = decode_with_visitor(
+            &mut &encoded[..],
+            0,
+            &FloatResolver,
+            crate::visitor::IgnoreVisitor::new(),
+        );
+        assert!(matches!(err, Err(DecodeError::TypeIdNotFound(_))));
+    }
+}
@@ -14,13 +14,18 @@
 // limitations under the License.
 
 use crate::visitor::{Composite, DecodeError};
-use scale_type_resolver::{FieldIter, TypeResolver, VariantIter};
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use scale_type_resolver::{
+    FieldIter, PathIter, ResolvedTypeVisitor, TypeResolver, UnhandledKind, VariantIter,
+};
 
 /// A representation of the a variant type.
 pub struct Variant<'scale, 'resolver, R: TypeResolver> {
     bytes: &'scale [u8],
     variant_name: &'resolver str,
     variant_index: u8,
+    type_id: Option<R::TypeId>,
     fields: Composite<'scale, 'resolver, R>,
 }
 
@@ -33,7 +38,7 @@ impl<'scale, 'resolver, R: TypeResolver> Variant<'scale, 'resolver, R> {
         mut variants: Variants,
         types: &'resolver R,
     ) -> Result<Variant<'scale, 'resolver, R>, DecodeError> {
-        let index = *bytes.first().ok_or(DecodeError::NotEnoughInput)?;
+        let index = *bytes.first().ok_or(DecodeError::NotEnoughInput(1))?;
         let item_bytes = &bytes[1..];
 
         // Does a variant exist with the index we're looking for?
@@ -49,7 +54,34 @@ impl<'scale, 'resolver, R: TypeResolver> Variant<'scale, 'resolver, R> {
             false,
         );
 
-        Ok(Variant { bytes, variant_index: index, variant_name: variant.name, fields })
+        Ok(Variant {
+            bytes,
+            variant_index: index,
+            variant_name: variant.name,
+            type_id: None,
+            fields,
+        })
+    }
+    // Record the variant type that we're decoding, so that more information about its fields can
+    // be looked up. See `Composite::with_parent()`.
+    pub(crate) fn with_parent(mut self, type_id: R::TypeId) -> Self {
+        self.fields = self.fields.with_parent(type_id.clone(), Some(self.variant_index));
+        self.type_id = Some(type_id);
+        self
+    }
+    /// Construct a [`Variant`] from its parts, in order to test how some [`crate::Visitor`]
+    /// handles it. The first byte given is the index of the variant to use. See
+    /// [`crate::test_utils`].
+    #[cfg(feature = "test-utils")]
+    pub fn from_parts<
+        Fields: FieldIter<'resolver, R::TypeId>,
+        Variants: VariantIter<'resolver, Fields>,
+    >(
+        bytes: &'scale [u8],
+        variants: Variants,
+        types: &'resolver R,
+    ) -> Result<Variant<'scale, 'resolver, R>, DecodeError> {
+        Variant::new(bytes, variants, types)
     }
 }
 
@@ -59,15 +91,40 @@ impl<'scale, 'resolver, R: TypeResolver> Variant<'scale, 'resolver, R> {
     pub fn skip_decoding(&mut self) -> Result<(), DecodeError> {
         self.fields.skip_decoding()
     }
-    /// The bytes representing this sequence and anything following it.
+    /// The bytes representing this variant (starting with the variant index) and anything
+    /// following it.
     pub fn bytes_from_start(&self) -> &'scale [u8] {
         self.bytes
     }
+    /// The bytes representing the fields of this variant (ie everything after the variant index)
+    /// and anything following it. Together with [`Self::bytes_from_undecoded()`], this can be used
+    /// to capture the raw bytes of the variant payload:
+    ///
+    /// ```rust
+    /// # use scale_decode::visitor::{types::Variant, DecodeError};
+    /// # use scale_decode::TypeResolver;
+    /// fn payload_bytes<'scale, R: TypeResolver>(
+    ///     value: &mut Variant<'scale, '_, R>,
+    /// ) -> Result<&'scale [u8], DecodeError> {
+    ///     let start = value.bytes_from_fields_start();
+    ///     value.skip_decoding()?;
+    ///     let end = value.bytes_from_undecoded();
+    ///     Ok(&start[..start.len() - end.len()])
+    /// }
+    /// ```
+    pub fn bytes_from_fields_start(&self) -> &'scale [u8] {
+        &self.bytes[1..]
+    }
     /// The bytes that have not yet been decoded in this variant (this never includes the
     /// variant index at the front) and anything following it.
     pub fn bytes_from_undecoded(&self) -> &'scale [u8] {
         self.fields.bytes_from_undecoded()
     }
+    /// The type resolver that this variant is being decoded with. This can be used to look up more
+    /// information about types, for instance via [`crate::resolver::ResolverExt`].
+    pub fn types(&self) -> &'resolver R {
+        self.fields.types()
+    }
     /// The name of the variant.
     pub fn name(&self) -> &'resolver str {
         self.variant_name
@@ -76,8 +133,47 @@ impl<'scale, 'resolver, R: TypeResolver> Variant<'scale, 'resolver, R> {
     pub fn index(&self) -> u8 {
         self.variant_index
     }
+    /// The names of the other variants that the variant type has, in the order that the type
+    /// resolver gives them. These aren't looked up until this is called, and so are cheap to
+    /// ignore when there are many variants, and are typically only needed to report an error.
+    ///
+    /// Nothing is returned if the variant type isn't known, which is the case when the variant is
+    /// constructed from its parts for testing.
+    pub fn other_variant_names(&self) -> impl Iterator<Item = &'resolver str> {
+        let mut names = match &self.type_id {
+            Some(type_id) => self
+                .types()
+                .resolve_type(type_id.clone(), VariantNamesVisitor(PhantomData))
+                .unwrap_or_default(),
+            None => Vec::new(),
+        };
+        names.retain(|name| *name != self.variant_name);
+        names.into_iter()
+    }
     /// Access the variant fields.
     pub fn fields(&mut self) -> &mut Composite<'scale, 'resolver, R> {
         &mut self.fields
     }
 }
+
+// Collect the names of each variant of whatever variant type is resolved.
+struct VariantNamesVisitor<'resolver, TypeId>(PhantomData<(&'resolver (), TypeId)>);
+
+impl<'resolver, TypeId: scale_type_resolver::TypeId + 'static> ResolvedTypeVisitor<'resolver>
+    for VariantNamesVisitor<'resolver, TypeId>
+{
+    type TypeId = TypeId;
+    type Value = Vec<&'resolver str>;
+
+    fn visit_unhandled(self, _kind: UnhandledKind) -> Self::Value {
+        Vec::new()
+    }
+    fn visit_variant<Path, Fields, Var>(self, _path: Path, variants: Var) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+        Fields: FieldIter<'resolver, Self::TypeId>,
+        Var: VariantIter<'resolver, Fields>,
+    {
+        variants.map(|v| v.name).collect()
+    }
+}
@@ -0,0 +1,78 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use scale_decode::{DecodeAsType, IntoVisitor};
+
+// Const params can come before type params.
+#[derive(DecodeAsType)]
+struct Arr<const N: usize, T>([T; N]);
+
+// Custom trait bounds work alongside const params.
+#[derive(DecodeAsType)]
+#[decode_as_type(trait_bounds = "T: IntoVisitor")]
+struct CustomBounds<const N: usize, T> {
+    items: [T; N],
+}
+
+// Arrays of generic containers, nested arrays and const expressions.
+#[derive(DecodeAsType)]
+struct Nested<T, const N: usize, const M: usize> {
+    items: [Arr<N, T>; M],
+    nested: [[T; N]; M],
+    fixed: [T; 2 + 2],
+    braced: Arr<{ N }, T>,
+}
+
+// Defaults on const and type params.
+#[derive(DecodeAsType)]
+enum Defaults<T = u8, const N: usize = 4> {
+    A([T; N]),
+    B { b: Arr<N, T> },
+}
+
+// Bounds given can mention the const params, and other const types are fine.
+#[derive(DecodeAsType)]
+#[decode_as_type(trait_bounds = "[T; N]: IntoVisitor")]
+struct BoundsOnArrays<'a, const N: usize, const B: bool, T> {
+    items: [T; N],
+    name: std::borrow::Cow<'a, str>,
+}
+
+// Transparent wrappers around const generic arrays.
+#[derive(DecodeAsType)]
+#[decode_as_type(transparent)]
+struct Transparent<T, const N: usize>([T; N]);
+
+// Where clauses on the type itself are kept.
+#[derive(DecodeAsType)]
+struct WithWhere<T, const N: usize>
+where
+    T: Clone,
+{
+    items: [T; N],
+}
+
+fn can_decode_as_type<T: DecodeAsType>() {}
+
+fn main() {
+    can_decode_as_type::<Arr<4, u64>>();
+    can_decode_as_type::<CustomBounds<2, bool>>();
+    can_decode_as_type::<Nested<u16, 2, 3>>();
+    can_decode_as_type::<Defaults>();
+    can_decode_as_type::<Defaults<u32, 8>>();
+    can_decode_as_type::<WithWhere<String, 1>>();
+    can_decode_as_type::<Transparent<u8, 32>>();
+    can_decode_as_type::<BoundsOnArrays<'static, 3, true, u8>>();
+}
@@ -0,0 +1,51 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// scale_decode is only reachable via some re-export here, as it might be if it was
+// re-exported from another crate.
+mod reexports {
+    pub use scale_decode as decoding;
+}
+
+use reexports::decoding::DecodeAsType;
+
+#[derive(DecodeAsType)]
+#[decode_as_type(crate_path = "reexports::decoding")]
+struct Foo<T> {
+    a: u8,
+    #[decode_as_type(as = "u32")]
+    b: u64,
+    #[decode_as_type(skip)]
+    c: Vec<T>,
+}
+
+#[derive(DecodeAsType)]
+#[decode_as_type(crate_path = "reexports::decoding")]
+enum Bar<T> {
+    A(Foo<T>),
+    B {
+        value: bool,
+        #[decode_as_type(skip)]
+        skipped: u8,
+    },
+    C,
+}
+
+fn can_decode_as_type<T: DecodeAsType>() {}
+
+fn main() {
+    can_decode_as_type::<Foo<u8>>();
+    can_decode_as_type::<Bar<String>>();
+}
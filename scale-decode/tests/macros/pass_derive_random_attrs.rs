@@ -27,6 +27,8 @@ struct Foo {
     value: u16,
     #[codec(index = 2)]
     type_id: bool,
+    #[codec(encoded_as = "u64", dumb_trait_bound(T: Foo))]
+    other: u32,
 }
 
 fn can_decode_as_type<T: DecodeAsType>() {}
@@ -0,0 +1,74 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-decode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// The generated code shouldn't rely on the names in the prelude, which may be shadowed.
+#![allow(dead_code)]
+
+use scale_decode::DecodeAsType;
+
+type Result<T> = core::result::Result<T, core::fmt::Error>;
+struct Option;
+enum Shadowed {
+    Some,
+    None,
+    Ok,
+    Err,
+}
+#[allow(unused_imports)]
+use Shadowed::*;
+
+fn check_foo(foo: &Foo) -> Result<()> {
+    if foo.a > foo.b as u8 {
+        return core::result::Result::Err(core::fmt::Error);
+    }
+    core::result::Result::Ok(())
+}
+
+#[derive(DecodeAsType)]
+#[decode_as_type(validate = "check_foo")]
+struct Foo {
+    #[decode_as_type(compact)]
+    a: u8,
+    #[decode_as_type(as = "u32")]
+    b: u64,
+}
+
+#[derive(DecodeAsType)]
+#[decode_as_type(transparent)]
+struct Wrapper(Foo);
+
+#[derive(DecodeAsType)]
+#[decode_as_type(tag = "type", content = "value")]
+enum Tagged {
+    A(Foo),
+    B { value: bool },
+    C,
+}
+
+#[derive(DecodeAsType)]
+#[decode_as_type(from_index, try_from_u8)]
+enum Fieldless {
+    A,
+    B,
+}
+
+fn can_decode_as_type<T: DecodeAsType>() {}
+
+fn main() {
+    can_decode_as_type::<Foo>();
+    can_decode_as_type::<Wrapper>();
+    can_decode_as_type::<Tagged>();
+    can_decode_as_type::<Fieldless>();
+}